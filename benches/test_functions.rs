@@ -0,0 +1,76 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use nelder_mead_optimizer::nalgebra::{DVector, SVector};
+use nelder_mead_optimizer::nelder_mead::NelderMead;
+use nelder_mead_optimizer::test_functions::{rosenbrock, TestFunction};
+
+fn optimizer() -> NelderMead {
+    NelderMead::builder()
+        .xatol(1e-8)
+        .fatol(1e-10)
+        .disable_no_improv_break()
+        .max_fun_evals(20_000)
+        .build()
+}
+
+/// Full runs of `minimize` on every test function, in two and ten
+/// dimensions for the functions taking any number of parameters.
+fn test_functions(c: &mut Criterion) {
+    let optimizer = optimizer();
+    let mut group = c.benchmark_group("minimize");
+    for function in TestFunction::ALL {
+        let dims = function.dimension().map_or(vec![2, 10], |dim| vec![dim]);
+        for dim in dims {
+            let start: DVector<f64> = function.start(dim);
+            group.bench_with_input(BenchmarkId::new(function.name(), dim), &start, |b, start| {
+                b.iter(|| optimizer.minimize(|x| function.evaluate(x), black_box(start.clone())))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// The same run of the four-dimensional Rosenbrock function through every
+/// interface of the solver.
+fn interfaces(c: &mut Criterion) {
+    let optimizer = optimizer();
+    let start: DVector<f64> = TestFunction::Rosenbrock.start(4);
+    let mut group = c.benchmark_group("rosenbrock_4");
+    group.bench_function("minimize", |b| {
+        b.iter(|| optimizer.minimize(rosenbrock, black_box(start.clone())))
+    });
+    group.bench_function("minimize_static", |b| {
+        let start = SVector::<f64, 4>::from_column_slice(start.as_slice());
+        let f = |x: &SVector<f64, 4>| rosenbrock(&DVector::from_column_slice(x.as_slice()));
+        b.iter(|| optimizer.minimize_static(f, black_box(start)))
+    });
+    group.bench_function("minimize_batch", |b| {
+        let f = |xs: &[DVector<f64>]| xs.iter().map(rosenbrock).collect();
+        b.iter(|| optimizer.minimize_batch(f, black_box(start.clone())))
+    });
+    group.bench_function("iter", |b| {
+        b.iter(|| {
+            let mut iter = optimizer.iter(rosenbrock, black_box(start.clone())).unwrap();
+            for state in iter.by_ref() {
+                state.unwrap();
+            }
+            iter.into_result()
+        })
+    });
+    group.bench_function("ask_tell", |b| {
+        b.iter(|| {
+            let mut run = optimizer.ask_tell(black_box(start.clone())).unwrap();
+            while run.termination_reason().is_none() {
+                for x in run.ask() {
+                    run.tell(&x, rosenbrock(&x)).unwrap();
+                }
+            }
+            run.result()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, test_functions, interfaces);
+criterion_main!(benches);
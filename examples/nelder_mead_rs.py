@@ -5,6 +5,8 @@ if __name__ == "__main__":
     def styblinski_tang_function(x):
         return 0.5 * sum([xi**4 - 16*xi**2 + 5*xi for xi in x])
 
-    print(nelder_mead(
+    result = nelder_mead(
         styblinski_tang_function, [0., 0.], 0.1, 10e-6, 10, 100, 1.0, 2.0, -0.5, 0.5)
-    )
\ No newline at end of file
+
+    print(result)
+    print(result.best_point, result.best_score)
@@ -0,0 +1,663 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::history::{History, HistoryEntry};
+use crate::logging;
+use crate::nelder_mead::{is_nan, total_cmp, Direction, NanHandling};
+use crate::objective::ObjectiveFunction;
+use crate::observer::{Observer, SharedObserver};
+use crate::progress::Budget;
+use crate::random::Rng;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationCriterion, TerminationState};
+
+/// Pure random search, sampling the box between the bounds uniformly and
+/// keeping the best sample, as a baseline for the other solvers.
+///
+/// Random search makes no assumption on the objective at all, so a solver
+/// that does not clearly beat it with the same number of evaluations is
+/// not suited to the problem, or is badly configured. Every evaluation is
+/// an iteration, and the run ends once `max_fun_evals` samples were
+/// drawn. The [`Observer`] and the [`TerminationCriterion`] are given the
+/// state of the run after every iteration from the `(n + 1)`-th on, in
+/// which the simplex is made of the `n + 1` best samples, and so is the
+/// history recorded with
+/// [`record_history`](RandomSearchBuilder::record_history). The same
+/// samples are returned as the `final_simplex` of the
+/// [`OptimizationResult`].
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::DVector;
+/// use nelder_mead_optimizer::baseline::RandomSearch;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::test_functions::rosenbrock;
+///
+/// let nelder_mead = NelderMead::builder().minimize(rosenbrock, DVector::from_element(4, 0.0))?;
+/// let random = RandomSearch::builder()
+///     .max_fun_evals(nelder_mead.evaluations)
+///     .seed(3)
+///     .minimize(rosenbrock, DVector::from_element(4, -2.0), DVector::from_element(4, 2.0))?;
+///
+/// assert_eq!(random.evaluations, nelder_mead.evaluations);
+/// assert!(nelder_mead.best_score * 100.0 < random.best_score);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct RandomSearch<T = f64> {
+    pub(crate) seed: Option<u64>,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) record_history: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for RandomSearch<T> {
+    fn default() -> Self {
+        RandomSearch {
+            seed: None,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            record_history: false,
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`RandomSearch`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `seed`                  | none                 |
+/// | `max_fun_evals`         | `1000 * n`           |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `record_history`        | false                |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct RandomSearchBuilder<T = f64> {
+    config: RandomSearch<T>,
+}
+
+impl<T: RealField + Copy> Default for RandomSearchBuilder<T> {
+    fn default() -> Self {
+        RandomSearchBuilder {
+            config: RandomSearch::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> RandomSearchBuilder<T> {
+    /// Seed of the random samples, making the run reproducible. Without a
+    /// seed every run draws different samples.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Number of samples to draw.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Record the best samples after every iteration in the `history` of
+    /// the result, see [`History`].
+    pub fn record_history(mut self, record_history: bool) -> Self {
+        self.config.record_history = record_history;
+        self
+    }
+
+    /// Additional stopping rule, checked after every iteration next to the
+    /// built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every iteration with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> RandomSearch<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`RandomSearch::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, lower, upper)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`RandomSearch::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, lower, upper)
+    }
+}
+
+impl<T: RealField + Copy> RandomSearch<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> RandomSearchBuilder<T> {
+        RandomSearchBuilder::default()
+    }
+
+    /// Finds the best sample (the highest one, depending on the configured
+    /// [`Direction`]) of the objective function within the bounds.
+    ///
+    /// Fails if the bounds are empty, of different lengths, not finite or
+    /// crossed, or if the objective function returns NaN under
+    /// [`NanHandling::Error`].
+    pub fn minimize<F>(
+        &self,
+        obj_fn: F,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        check_bounds(&lower, &upper)?;
+        let dim = lower.len();
+        let max_fun_evals = self.max_fun_evals.unwrap_or(1000 * dim as u64);
+        let mut rng = Rng::seeded(self.seed);
+        let samples = std::iter::repeat_with(|| {
+            DVector::from_fn(dim, |i, _| {
+                lower[i] + (upper[i] - lower[i]) * convert(rng.uniform())
+            })
+        });
+        let search = Search {
+            direction: self.direction,
+            nan_handling: self.nan_handling,
+            max_duration: self.max_duration,
+            record_history: self.record_history,
+            termination: &self.termination,
+            observer: &self.observer,
+            budget: Budget {
+                max_iter: max_fun_evals,
+                max_fun_evals: Some(max_fun_evals),
+                max_duration: self.max_duration,
+            },
+        };
+        search.run(
+            obj_fn,
+            dim,
+            samples.take(max_fun_evals.max(1) as usize),
+            TerminationReason::MaxFunEvals,
+        )
+    }
+
+    /// Same as [`RandomSearch::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), lower, upper)
+    }
+}
+
+/// Exhaustive search on a regular grid spanning the box between the
+/// bounds, as a baseline for the other solvers.
+///
+/// Every parameter takes `points` evenly spaced values, from its lower to
+/// its upper bound, or the middle of the bounds for a single point, so
+/// that the grid holds `points^n` points. They are evaluated in
+/// lexicographic order, the last parameter changing fastest, until the
+/// grid is done, which ends the run as converged, or `max_fun_evals` is
+/// spent. Every evaluation is an iteration. The [`Observer`] and the
+/// [`TerminationCriterion`] are given the state of the run after every
+/// iteration from the `(n + 1)`-th on, in which the simplex is made of the
+/// `n + 1` best grid points, and so is the history recorded with
+/// [`record_history`](GridSearchBuilder::record_history). The same points
+/// are returned as the `final_simplex` of the [`OptimizationResult`].
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::baseline::GridSearch;
+/// use nelder_mead_optimizer::result::TerminationReason;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 0.5).powi(2) + (x[1] + 1.0).abs()
+/// }
+/// let results = GridSearch::builder()
+///     .points(9)
+///     .record_history(true)
+///     .minimize(f, dvector![-1.0, -2.0], dvector![1.0, 2.0])?;
+///
+/// assert_eq!(results.evaluations, 81);
+/// assert_eq!(results.termination_reason, TerminationReason::Converged);
+/// assert_eq!(results.best_point, dvector![0.5, -1.0]);
+/// assert_eq!(results.history.unwrap().len(), 79);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct GridSearch<T = f64> {
+    pub(crate) points: usize,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) record_history: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for GridSearch<T> {
+    fn default() -> Self {
+        GridSearch {
+            points: 11,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            record_history: false,
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`GridSearch`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `points`                | 11                   |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `record_history`        | false                |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct GridSearchBuilder<T = f64> {
+    config: GridSearch<T>,
+}
+
+impl<T: RealField + Copy> Default for GridSearchBuilder<T> {
+    fn default() -> Self {
+        GridSearchBuilder {
+            config: GridSearch::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> GridSearchBuilder<T> {
+    /// Number of values of every parameter, at least one.
+    pub fn points(mut self, points: usize) -> Self {
+        self.config.points = points;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times,
+    /// before the grid is done.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Record the best grid points after every iteration in the `history`
+    /// of the result, see [`History`].
+    pub fn record_history(mut self, record_history: bool) -> Self {
+        self.config.record_history = record_history;
+        self
+    }
+
+    /// Additional stopping rule, checked after every iteration next to the
+    /// built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every iteration with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> GridSearch<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`GridSearch::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, lower, upper)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`GridSearch::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, lower, upper)
+    }
+}
+
+impl<T: RealField + Copy> GridSearch<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> GridSearchBuilder<T> {
+        GridSearchBuilder::default()
+    }
+
+    /// Finds the best grid point (the highest one, depending on the
+    /// configured [`Direction`]) of the objective function within the
+    /// bounds.
+    ///
+    /// Fails if the bounds are empty, of different lengths, not finite or
+    /// crossed, if `points` is zero, or if the objective function returns
+    /// NaN under [`NanHandling::Error`].
+    pub fn minimize<F>(
+        &self,
+        obj_fn: F,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        check_bounds(&lower, &upper)?;
+        if self.points == 0 {
+            return Err(NelderMeadError::InvalidGrid);
+        }
+        let dim = lower.len();
+        let size = (self.points as u64).saturating_pow(dim as u32);
+        let value = |i: usize, j: usize| {
+            if self.points == 1 {
+                (lower[i] + upper[i]) * convert(0.5)
+            } else {
+                let fraction: T = convert(j as f64 / (self.points - 1) as f64);
+                lower[i] + (upper[i] - lower[i]) * fraction
+            }
+        };
+        // indices of the next grid point, `None` once the grid is done
+        let mut indices = Some(vec![0; dim]);
+        let grid = std::iter::from_fn(|| {
+            let current = indices.clone()?;
+            let point = DVector::from_fn(dim, |i, _| value(i, current[i]));
+            indices = (0..dim)
+                .rev()
+                .find(|&i| current[i] + 1 < self.points)
+                .map(|i| {
+                    let mut next = current.clone();
+                    next[i] += 1;
+                    next[i + 1..].fill(0);
+                    next
+                });
+            Some(point)
+        });
+        let max_fun_evals = self
+            .max_fun_evals
+            .map_or(size, |limit| limit.clamp(1, size));
+        let search = Search {
+            direction: self.direction,
+            nan_handling: self.nan_handling,
+            max_duration: self.max_duration,
+            record_history: self.record_history,
+            termination: &self.termination,
+            observer: &self.observer,
+            budget: Budget {
+                max_iter: size,
+                max_fun_evals: self.max_fun_evals,
+                max_duration: self.max_duration,
+            },
+        };
+        let exhausted = if max_fun_evals < size {
+            TerminationReason::MaxFunEvals
+        } else {
+            TerminationReason::Converged
+        };
+        search.run(obj_fn, dim, grid.take(max_fun_evals as usize), exhausted)
+    }
+
+    /// Same as [`GridSearch::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), lower, upper)
+    }
+}
+
+/// Fails unless the bounds are non-empty, of the same length, finite and
+/// not crossed.
+fn check_bounds<T: RealField + Copy>(
+    lower: &DVector<T>,
+    upper: &DVector<T>,
+) -> Result<(), NelderMeadError> {
+    if lower.is_empty() {
+        return Err(NelderMeadError::InvalidSimplex);
+    }
+    if upper.len() != lower.len()
+        || lower
+            .iter()
+            .zip(upper.iter())
+            .any(|(l, u)| l > u || is_nan(*l) || is_nan(*u) || !l.is_finite() || !u.is_finite())
+    {
+        return Err(NelderMeadError::InvalidBounds);
+    }
+    Ok(())
+}
+
+/// Options shared by the baselines.
+struct Search<'a, T> {
+    direction: Direction,
+    nan_handling: NanHandling<T>,
+    max_duration: Option<Duration>,
+    record_history: bool,
+    termination: &'a Option<SharedCriterion<T>>,
+    observer: &'a Option<SharedObserver<T>>,
+    budget: Budget,
+}
+
+impl<T: RealField + Copy> Search<'_, T> {
+    /// Evaluates the `points` one by one, at least the first one, keeping
+    /// the `dim + 1` best, until they run out, which ends the run for the
+    /// reason `exhausted`, or the run is stopped otherwise.
+    fn run<F>(
+        &self,
+        mut obj_fn: F,
+        dim: usize,
+        mut points: impl Iterator<Item = DVector<T>>,
+        exhausted: TerminationReason,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let start = Instant::now();
+        let sign = self.direction.sign();
+        let mut best: Vec<(DVector<T>, T)> = Vec::with_capacity(dim + 2);
+        let mut entries = Vec::new();
+        let mut evaluations = 0;
+
+        let termination_reason = loop {
+            if evaluations > 0
+                && self
+                    .max_duration
+                    .is_some_and(|limit| start.elapsed() >= limit)
+            {
+                break TerminationReason::TimedOut;
+            }
+            let Some(x) = points.next() else {
+                break exhausted;
+            };
+            let score = self.nan_handling.score(obj_fn(&x), sign)?;
+            evaluations += 1;
+            let position = best.partition_point(|(_, kept)| total_cmp(*kept, score).is_le());
+            best.insert(position, (x, score));
+            best.truncate(dim + 1);
+            if best.len() <= dim {
+                continue;
+            }
+
+            let simplex = Simplex::from_pairs(&best);
+            let state = TerminationState {
+                iterations: evaluations,
+                evaluations,
+                elapsed: start.elapsed(),
+                simplex: &simplex,
+                sign,
+                last_move: None,
+                budget: self.budget,
+            };
+            logging::log_iteration(&state);
+            if self.record_history {
+                entries.push(HistoryEntry::new(&state, false));
+            }
+            if let Some(observer) = self.observer {
+                observer.0.observe(&state);
+            }
+            if let Some(criterion) = self.termination {
+                if criterion.0.should_stop(&state) {
+                    break TerminationReason::Criterion;
+                }
+            }
+        };
+
+        Ok(OptimizationResult::from_simplex(
+            best,
+            sign,
+            evaluations,
+            evaluations,
+            0,
+            termination_reason,
+            self.record_history.then_some(History { entries }),
+        ))
+    }
+}
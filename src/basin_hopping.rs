@@ -0,0 +1,520 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::logging;
+use crate::nelder_mead::{is_nan, total_cmp, Direction, NanHandling, NelderMead, StepSize};
+use crate::objective::ObjectiveFunction;
+use crate::observer::{Observer, SharedObserver};
+use crate::progress::Budget;
+use crate::random::Rng;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationCriterion, TerminationState};
+
+/// Distribution of the random perturbations of a [`BasinHopping`] run,
+/// scaled by the step along every parameter.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StepDistribution {
+    /// Uniform within `[-step, step]`.
+    #[default]
+    Uniform,
+    /// Normal, with the step as the standard deviation.
+    Normal,
+    /// Cauchy, with the step as the scale. Its heavy tails occasionally
+    /// jump far away from the current basin.
+    Cauchy,
+}
+
+impl StepDistribution {
+    /// Random deviate of unit scale.
+    pub(crate) fn sample(self, rng: &mut Rng) -> f64 {
+        match self {
+            StepDistribution::Uniform => 2.0 * rng.uniform() - 1.0,
+            StepDistribution::Normal => rng.normal(),
+            StepDistribution::Cauchy => (std::f64::consts::PI * (rng.uniform() - 0.5)).tan(),
+        }
+    }
+}
+
+/// Outcome of a [`BasinHopping`] run.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct BasinHoppingResult<T = f64> {
+    /// Best basin found: its local minimum and the final simplex of the
+    /// local search that reached it, with the hops as `iterations` and the
+    /// evaluations of every local search.
+    pub result: OptimizationResult<T>,
+    /// Distinct local minima found with their scores, ordered from the best
+    /// one.
+    pub minima: Vec<(DVector<T>, T)>,
+}
+
+/// Basin hopping, a global optimizer hopping from one local minimum to
+/// another, each found by a local Nelder-Mead search.
+///
+/// The run starts with a local search from `x_start`. Every hop then
+/// perturbs the current minimum by a random step drawn from the
+/// [`StepDistribution`], runs a local search from there, and moves to the
+/// minimum it finds when it scores better, and otherwise with the
+/// Metropolis probability `exp(-d / t)`, `d` being how much worse it scores
+/// and `t` the temperature. The temperature is in units of the objective
+/// function, and zero only accepts better minima. Perturbed points are
+/// clipped into the bounds, which also bound the local searches. The run
+/// is reproducible with [`BasinHoppingBuilder::seed`].
+///
+/// The run stops after `max_iter` hops, or once the best minimum did not
+/// improve for `no_improv_break` hops. The local searches share the
+/// evaluation and time budgets of the run. Runs are reported as a
+/// [`BasinHoppingResult`], with the local minima found, told apart by
+/// `minima_tol`. The [`Observer`] and the [`TerminationCriterion`] are given
+/// the final simplex of the best local search after every hop.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::DVector;
+/// use nelder_mead_optimizer::basin_hopping::BasinHopping;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::test_functions::rastrigin;
+///
+/// let x_start = DVector::from_element(2, 3.0);
+/// let hopping = BasinHopping::builder()
+///     .step(1.0)
+///     .temperature(2.0)
+///     .max_iter(200)
+///     .seed(4)
+///     .minimize(rastrigin, x_start.clone())?;
+/// let plain = NelderMead::builder().minimize(rastrigin, x_start)?;
+///
+/// assert!(hopping.result.best_score < 1e-4);
+/// assert!(plain.best_score > 1.0);
+/// assert!(hopping.minima.len() > 5);
+/// assert_eq!(hopping.minima[0].1, hopping.result.best_score);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct BasinHopping<T = f64> {
+    pub(crate) local: NelderMead<T>,
+    pub(crate) step: StepSize<T>,
+    pub(crate) step_distribution: StepDistribution,
+    pub(crate) temperature: T,
+    pub(crate) minima_tol: T,
+    pub(crate) seed: Option<u64>,
+    pub(crate) no_improv_break: Option<u64>,
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) bounds: Option<(DVector<T>, DVector<T>)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for BasinHopping<T> {
+    fn default() -> Self {
+        BasinHopping {
+            local: NelderMead::default(),
+            step: StepSize::Uniform(convert(0.5)),
+            step_distribution: StepDistribution::Uniform,
+            temperature: T::one(),
+            minima_tol: convert(1e-2),
+            seed: None,
+            no_improv_break: None,
+            max_iter: 100,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            bounds: None,
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`BasinHopping`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `local`                 | `NelderMead` default |
+/// | `step`                  | 0.5                  |
+/// | `step_distribution`     | `Uniform`            |
+/// | `temperature`           | 1                    |
+/// | `minima_tol`            | 1e-2                 |
+/// | `seed`                  | random               |
+/// | `no_improv_break`       | none                 |
+/// | `max_iter`              | 100                  |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `bounds`                | none                 |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct BasinHoppingBuilder<T = f64> {
+    config: BasinHopping<T>,
+}
+
+impl<T: RealField + Copy> Default for BasinHoppingBuilder<T> {
+    fn default() -> Self {
+        BasinHoppingBuilder {
+            config: BasinHopping::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> BasinHoppingBuilder<T> {
+    /// Nelder-Mead solver of the local searches. Its direction, NaN
+    /// handling and budgets are taken from this solver, and so are the
+    /// bounds unless it has its own.
+    pub fn local(mut self, local: NelderMead<T>) -> Self {
+        self.config.local = local;
+        self
+    }
+
+    /// Scale of the perturbations, either a single value used for every
+    /// dimension or a [`StepSize`]. It should be of the order of the
+    /// distance between neighbouring minima.
+    pub fn step(mut self, step: impl Into<StepSize<T>>) -> Self {
+        self.config.step = step.into();
+        self
+    }
+
+    /// Distribution of the perturbations.
+    pub fn step_distribution(mut self, step_distribution: StepDistribution) -> Self {
+        self.config.step_distribution = step_distribution;
+        self
+    }
+
+    /// Temperature of the Metropolis acceptance, not negative and finite.
+    /// Zero only accepts better minima.
+    pub fn temperature(mut self, temperature: T) -> Self {
+        self.config.temperature = temperature;
+        self
+    }
+
+    /// Distance along every parameter within which two local minima are
+    /// considered the same one, not negative.
+    pub fn minima_tol(mut self, minima_tol: T) -> Self {
+        self.config.minima_tol = minima_tol;
+        self
+    }
+
+    /// Seed of the random numbers, making the run reproducible. Without a
+    /// seed every run draws different numbers.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Stop once the best minimum did not improve for this many hops.
+    pub fn no_improv_break(mut self, no_improv_break: u64) -> Self {
+        self.config.no_improv_break = Some(no_improv_break);
+        self
+    }
+
+    /// Maximum number of hops.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times,
+    /// by all the local searches together.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Lower and upper bounds of every parameter. The starting point and
+    /// the perturbed points are clipped into them.
+    pub fn bounds(mut self, lower: DVector<T>, upper: DVector<T>) -> Self {
+        self.config.bounds = Some((lower, upper));
+        self
+    }
+
+    /// Additional stopping rule, checked after every hop next to the
+    /// built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every hop with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> BasinHopping<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`BasinHopping::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<BasinHoppingResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`BasinHopping::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<BasinHoppingResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, x_start)
+    }
+}
+
+impl<T: RealField + Copy> BasinHopping<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> BasinHoppingBuilder<T> {
+        BasinHoppingBuilder::default()
+    }
+
+    /// Finds the minimum (or maximum, depending on the configured
+    /// [`Direction`]) of the objective function, hopping between its local
+    /// minima from `x_start`.
+    ///
+    /// Fails if `x_start` is empty, if the temperature or `minima_tol` is
+    /// negative or not finite, if the step or the bounds do not fit
+    /// `x_start`, or if a local search fails like [`NelderMead::minimize`].
+    pub fn minimize<F>(
+        &self,
+        mut obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<BasinHoppingResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let dim = x_start.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        let valid = [self.temperature, self.minima_tol]
+            .iter()
+            .all(|value| T::zero() <= *value && value.is_finite());
+        if !valid {
+            return Err(NelderMeadError::InvalidBasinHopping);
+        }
+        self.step.check_dim(dim)?;
+        let mut x_start = x_start;
+        if let Some((lower, upper)) = &self.bounds {
+            if lower.len() != dim
+                || upper.len() != dim
+                || lower.iter().zip(upper.iter()).any(|(l, u)| l > u || is_nan(*l) || is_nan(*u))
+            {
+                return Err(NelderMeadError::InvalidBounds);
+            }
+            x_start = x_start.sup(lower).inf(upper);
+        }
+
+        let start = Instant::now();
+        let sign: T = self.direction.sign();
+        let mut rng = Rng::seeded(self.seed);
+        let mut evaluations = 0;
+        let mut best = self.search(&mut obj_fn, x_start, evaluations, start)?;
+        evaluations += best.evaluations;
+        let mut current = (best.best_point.clone(), sign * best.best_score);
+        let mut minima = vec![(best.best_point.clone(), best.best_score)];
+        let mut hops = 0;
+        let mut stalled = 0;
+
+        let termination_reason = loop {
+            if hops >= self.max_iter {
+                break TerminationReason::MaxIter;
+            }
+            if self.max_fun_evals.is_some_and(|limit| evaluations >= limit) {
+                break TerminationReason::MaxFunEvals;
+            }
+            if self.max_duration.is_some_and(|limit| start.elapsed() >= limit) {
+                break TerminationReason::TimedOut;
+            }
+            if self.no_improv_break.is_some_and(|limit| stalled >= limit) {
+                break TerminationReason::NoImprovement;
+            }
+
+            let x = DVector::from_fn(dim, |i, _| {
+                let deviate: T = convert(self.step_distribution.sample(&mut rng));
+                current.0[i] + self.step.offset(i, current.0[i]) * deviate
+            });
+            let x = match &self.bounds {
+                Some((lower, upper)) => x.sup(lower).inf(upper),
+                None => x,
+            };
+            let local = self.search(&mut obj_fn, x, evaluations, start)?;
+            evaluations += local.evaluations;
+            hops += 1;
+            self.record(&mut minima, &local);
+
+            let score: T = sign * local.best_score;
+            let worsening = score - current.1;
+            let u: T = convert(rng.uniform());
+            if worsening <= T::zero()
+                || (self.temperature > T::zero() && u < (-worsening / self.temperature).exp())
+            {
+                current = (local.best_point.clone(), score);
+            }
+            if score < sign * best.best_score {
+                best = local;
+                stalled = 0;
+            } else {
+                stalled += 1;
+            }
+
+            let simplex = Simplex::from_slices(
+                best.final_simplex.iter().map(|(x, score)| (x.as_slice(), sign * *score)),
+            );
+            let state = TerminationState {
+                iterations: hops,
+                evaluations,
+                elapsed: start.elapsed(),
+                simplex: &simplex,
+                sign,
+                last_move: None,
+                budget: Budget {
+                    max_iter: self.max_iter,
+                    max_fun_evals: self.max_fun_evals,
+                    max_duration: self.max_duration,
+                },
+            };
+            logging::log_iteration(&state);
+            if let Some(observer) = &self.observer {
+                observer.0.observe(&state);
+            }
+            if let Some(criterion) = &self.termination {
+                if criterion.0.should_stop(&state) {
+                    break TerminationReason::Criterion;
+                }
+            }
+        };
+
+        minima.sort_by(|a, b| total_cmp(sign * a.1, sign * b.1));
+        Ok(BasinHoppingResult {
+            result: OptimizationResult::from_simplex(
+                best.final_simplex,
+                T::one(),
+                hops,
+                evaluations,
+                0,
+                termination_reason,
+                None,
+            ),
+            minima,
+        })
+    }
+
+    /// Same as [`BasinHopping::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<BasinHoppingResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), x_start)
+    }
+
+    /// Local search from `x`, within what remains of the budgets after
+    /// `evaluations` since `start`.
+    fn search<F>(
+        &self,
+        obj_fn: &mut F,
+        x: DVector<T>,
+        evaluations: u64,
+        start: Instant,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let local = self.local.polishing(
+            self.direction,
+            self.nan_handling,
+            &self.bounds,
+            self.max_fun_evals.map(|limit| limit.saturating_sub(evaluations)),
+            self.max_duration.map(|limit| limit.saturating_sub(start.elapsed())),
+        );
+        local.minimize(obj_fn, x)
+    }
+
+    /// Adds the minimum found by `local` to `minima`, unless it is within
+    /// `minima_tol` of one of them, which it replaces when it scores better.
+    fn record(&self, minima: &mut Vec<(DVector<T>, T)>, local: &OptimizationResult<T>) {
+        let sign: T = self.direction.sign();
+        let same = minima
+            .iter_mut()
+            .find(|(x, _)| (x - &local.best_point).amax() <= self.minima_tol);
+        match same {
+            Some(minimum) => {
+                if sign * local.best_score < sign * minimum.1 {
+                    *minimum = (local.best_point.clone(), local.best_score);
+                }
+            }
+            None => minima.push((local.best_point.clone(), local.best_score)),
+        }
+    }
+}
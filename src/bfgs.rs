@@ -0,0 +1,182 @@
+use std::time::Instant;
+
+use nalgebra::{convert, DMatrix, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::nelder_mead::NelderMead;
+use crate::objective::Evaluator;
+use crate::result::OptimizationResult;
+
+/// Sufficient decrease required from a line search step, relative to the
+/// decrease predicted by the gradient.
+const ARMIJO: f64 = 1e-4;
+
+/// Maximum number of halvings of a line search step.
+const MAX_HALVINGS: usize = 30;
+
+/// Scores of the points seen by the polishing stage, with the number of
+/// evaluations it spent.
+struct Polish<'a, T, E> {
+    config: &'a NelderMead<T>,
+    obj_fn: E,
+    sign: T,
+    evaluations: u64,
+    budget: Option<u64>,
+    start: Instant,
+}
+
+impl<T: RealField + Copy, E: Evaluator<T>> Polish<'_, T, E> {
+    /// Whether `count` more evaluations fit in the evaluation budget and
+    /// the time is not up.
+    fn affords(&self, count: u64) -> bool {
+        let evaluations = self.budget.is_none_or(|limit| self.evaluations + count <= limit);
+        let time = self.config.max_duration.is_none_or(|limit| self.start.elapsed() < limit);
+        evaluations && time
+    }
+
+    /// Score of the point `x`, penalized like in the simplex run.
+    fn score(&mut self, x: &DVector<T>) -> Result<T, NelderMeadError> {
+        self.evaluations += 1;
+        let value = self.obj_fn.evaluate_point(x);
+        Ok(self.config.nan_handling.score(value, self.sign)? + self.config.penalty_of(x))
+    }
+
+    /// Central finite-difference gradient of the score at `x`, all `2n`
+    /// points evaluated together.
+    fn gradient(&mut self, x: &DVector<T>) -> Result<DVector<T>, NelderMeadError> {
+        let dim = x.len();
+        let relative = T::default_epsilon().powf(convert(1.0 / 3.0));
+        let mut points = Vec::with_capacity(2 * dim);
+        for i in 0..dim {
+            let h = relative * x[i].abs().max(T::one());
+            for offset in [h, -h] {
+                let mut point = x.clone();
+                point[i] += offset;
+                points.push(self.config.feasible(point));
+            }
+        }
+        self.evaluations += points.len() as u64;
+        let values = self.obj_fn.evaluate_points(&points);
+        let mut gradient = DVector::zeros(dim);
+        for i in 0..dim {
+            let (forward, backward) = (&points[2 * i], &points[2 * i + 1]);
+            let width = forward[i] - backward[i];
+            if width.is_zero() {
+                continue;
+            }
+            let high = self.config.nan_handling.score(values[2 * i], self.sign)?
+                + self.config.penalty_of(forward);
+            let low = self
+                .config
+                .nan_handling
+                .score(values[2 * i + 1], self.sign)?
+                + self.config.penalty_of(backward);
+            gradient[i] = (high - low) / width;
+        }
+        Ok(gradient)
+    }
+}
+
+impl<T: RealField + Copy> NelderMead<T> {
+    /// Polishes the best point of `result` with at most `max_iter`
+    /// iterations of BFGS on central finite-difference gradients, within
+    /// what remains of the budgets of the run started at `start`. The
+    /// first step is scaled to the size of the final simplex, and every
+    /// point is brought inside the bounds. A better point replaces the
+    /// worst vertex of the final simplex and becomes its best.
+    pub(crate) fn polish_bfgs<E>(
+        &self,
+        obj_fn: E,
+        mut result: OptimizationResult<T>,
+        max_iter: u64,
+        start: Instant,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        E: Evaluator<T>,
+    {
+        let sign: T = self.direction.sign();
+        let mut run = Polish {
+            config: self,
+            obj_fn,
+            sign,
+            evaluations: 0,
+            budget: self
+                .max_fun_evals
+                .map(|limit| limit.saturating_sub(result.evaluations)),
+            start,
+        };
+        let dim = result.best_point.len();
+        let mut x = result.best_point.clone();
+        let mut score = sign * result.best_score;
+        let size = result
+            .final_simplex
+            .iter()
+            .fold(T::zero(), |max, (vertex, _)| max.max((vertex - &x).norm()));
+        let mut iterations = 0;
+        if run.affords(2 * dim as u64) && score.is_finite() {
+            let mut gradient = run.gradient(&x)?;
+            let usable = |g: &DVector<T>| g.iter().all(|gi| gi.is_finite()) && !g.norm().is_zero();
+            let mut inverse = DMatrix::identity(dim, dim);
+            if usable(&gradient) && !size.is_zero() {
+                inverse *= size / gradient.norm();
+            }
+            while iterations < max_iter && usable(&gradient) {
+                let mut direction = -(&inverse * &gradient);
+                if direction.dot(&gradient) >= T::zero() {
+                    inverse = DMatrix::identity(dim, dim);
+                    direction = -&gradient;
+                }
+                // backtracking line search on the sufficient decrease
+                let mut t = T::one();
+                let mut accepted = None;
+                for _ in 0..MAX_HALVINGS {
+                    if !run.affords(1) {
+                        break;
+                    }
+                    let trial = self.feasible(&x + &direction * t);
+                    let step = &trial - &x;
+                    let slope = gradient.dot(&step);
+                    if slope >= T::zero() {
+                        t *= convert(0.5);
+                        continue;
+                    }
+                    let trial_score = run.score(&trial)?;
+                    if trial_score <= score + convert::<f64, T>(ARMIJO) * slope {
+                        accepted = Some((trial, trial_score, step));
+                        break;
+                    }
+                    t *= convert(0.5);
+                }
+                let Some((trial, trial_score, step)) = accepted else {
+                    break;
+                };
+                iterations += 1;
+                x = trial;
+                score = trial_score;
+                if !run.affords(2 * dim as u64) {
+                    break;
+                }
+                let next_gradient = run.gradient(&x)?;
+                let change = &next_gradient - &gradient;
+                let curvature = step.dot(&change);
+                if curvature > T::default_epsilon() * step.norm() * change.norm() {
+                    let rho = T::one() / curvature;
+                    let left = DMatrix::identity(dim, dim) - &step * change.transpose() * rho;
+                    inverse = &left * inverse * left.transpose() + &step * step.transpose() * rho;
+                }
+                gradient = next_gradient;
+            }
+        }
+
+        result.iterations += iterations;
+        result.evaluations += run.evaluations;
+        if score < sign * result.best_score {
+            result.final_simplex.pop();
+            result.final_simplex.insert(0, (x.clone(), sign * score));
+            result.best_point = x;
+            result.best_score = sign * score;
+            result.constraint_violation = self.violation(&result.best_point);
+        }
+        Ok(result)
+    }
+}
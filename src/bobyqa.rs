@@ -0,0 +1,833 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DMatrix, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::logging;
+use crate::nelder_mead::{is_nan, total_cmp, Direction, NanHandling};
+use crate::objective::ObjectiveFunction;
+use crate::observer::{Observer, SharedObserver};
+use crate::progress::Budget;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationCriterion, TerminationState};
+
+/// Trust-region solver modelling the objective by quadratic interpolation,
+/// after Powell's BOBYQA (Bound Optimization BY Quadratic Approximation).
+///
+/// The objective is interpolated by a quadratic on `interpolation_points`
+/// points, `2n + 1` by default, which is not enough to determine the `(n +
+/// 1)(n + 2) / 2` coefficients of a quadratic, so that every model takes
+/// the Hessian of the previous one and changes it as little as possible,
+/// in Frobenius norm, to interpolate the points. Every iteration moves to
+/// the minimum of the model within the trust region around the best point
+/// and within the bounds, found by truncated conjugate gradients, and
+/// swaps the new point into the interpolation set. The radius of the trust
+/// region follows the agreement of the model with the objective. When the
+/// model is poor because the points lie far away, one of them is moved
+/// closer instead, where it makes the interpolation best conditioned. The
+/// trust region never shrinks below `rho`, which decreases from
+/// `rho_begin` to `rho_end`. On smooth objectives the curvature learnt by
+/// the model typically saves an order of magnitude of evaluations over
+/// Nelder-Mead.
+///
+/// The initial points lie `rho_begin` away from `x_start` along every
+/// parameter, and the interpolation points always stay within the bounds,
+/// which must therefore be at least `2 rho_begin` wide. The run converges
+/// once `rho` reached `rho_end` and the model fails to improve on the best
+/// point. The solver shares the configuration of [`NelderMead`] where it
+/// applies, so that switching algorithms only means switching builders.
+/// The [`Observer`] and the [`TerminationCriterion`] are given the state of
+/// the run after every iteration, in which the simplex is made of the
+/// `n + 1` best interpolation points. The same points are returned as the
+/// `final_simplex` of the [`OptimizationResult`].
+///
+/// [`NelderMead`]: crate::nelder_mead::NelderMead
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::DVector;
+/// use nelder_mead_optimizer::bobyqa::Bobyqa;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+///
+/// // an ellipsoid whose axes span three orders of magnitude
+/// fn f(x: &DVector<f64>) -> f64 {
+///     let scale = |i: usize| 10f64.powi(i as i32 % 4);
+///     x.iter().enumerate().map(|(i, xi)| scale(i) * (xi - 1.0).powi(2)).sum()
+/// }
+/// let x_start = DVector::zeros(10);
+/// let bobyqa = Bobyqa::builder().rho_end(1e-8).minimize(f, x_start.clone())?;
+/// let plain = NelderMead::builder()
+///     .disable_no_improv_break()
+///     .xatol(1e-6)
+///     .fatol(1e-10)
+///     .max_iter(u64::MAX)
+///     .minimize(f, x_start)?;
+///
+/// assert!(bobyqa.best_score < 1e-12);
+/// assert!(bobyqa.evaluations * 10 < plain.evaluations);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct Bobyqa<T = f64> {
+    pub(crate) rho_begin: T,
+    pub(crate) rho_end: T,
+    pub(crate) interpolation_points: Option<usize>,
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) bounds: Option<(DVector<T>, DVector<T>)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for Bobyqa<T> {
+    fn default() -> Self {
+        Bobyqa {
+            rho_begin: convert(0.5),
+            rho_end: convert(1e-6),
+            interpolation_points: None,
+            max_iter: 1000,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            bounds: None,
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`Bobyqa`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `rho_begin`             | 0.5                  |
+/// | `rho_end`               | 1e-6                 |
+/// | `interpolation_points`  | 2 n + 1              |
+/// | `max_iter`              | 1000                 |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `bounds`                | none                 |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct BobyqaBuilder<T = f64> {
+    config: Bobyqa<T>,
+}
+
+impl<T: RealField + Copy> Default for BobyqaBuilder<T> {
+    fn default() -> Self {
+        BobyqaBuilder {
+            config: Bobyqa::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> BobyqaBuilder<T> {
+    /// Initial radius of the trust region, which is also the distance of
+    /// the initial interpolation points from the starting point. It should
+    /// be about a tenth of the expected distance to the solution.
+    pub fn rho_begin(mut self, rho_begin: T) -> Self {
+        self.config.rho_begin = rho_begin;
+        self
+    }
+
+    /// Final lower limit of the trust region radius, i.e. the accuracy
+    /// required on the parameters.
+    pub fn rho_end(mut self, rho_end: T) -> Self {
+        self.config.rho_end = rho_end;
+        self
+    }
+
+    /// Number of interpolation points of the quadratic models, within
+    /// `[n + 2, (n + 1)(n + 2) / 2]`. More points build better models from
+    /// the start, at the cost of more initial evaluations.
+    pub fn interpolation_points(mut self, interpolation_points: usize) -> Self {
+        self.config.interpolation_points = Some(interpolation_points);
+        self
+    }
+
+    /// Maximum number of iterations, each evaluating a single point, either
+    /// to reduce the objective or to keep the interpolation well posed.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Lower and upper bounds of every parameter, at least `2 rho_begin`
+    /// apart. The starting point is clipped into them, and every point
+    /// evaluated lies within them.
+    pub fn bounds(mut self, lower: DVector<T>, upper: DVector<T>) -> Self {
+        self.config.bounds = Some((lower, upper));
+        self
+    }
+
+    /// Additional stopping rule, checked after every iteration next to the
+    /// built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every iteration with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> Bobyqa<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Bobyqa::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Bobyqa::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, x_start)
+    }
+}
+
+/// Evaluations of a run, with the budgets they are bounded by.
+struct Run<'a, T, F> {
+    config: &'a Bobyqa<T>,
+    obj_fn: F,
+    sign: T,
+    evaluations: u64,
+    start: Instant,
+}
+
+impl<T: RealField + Copy, F: FnMut(&DVector<T>) -> T> Run<'_, T, F> {
+    /// Score of `x`, or `None` when the evaluation budget or the time ran
+    /// out.
+    fn score(&mut self, x: &DVector<T>) -> Result<Option<T>, NelderMeadError> {
+        if self.exhausted().is_some() {
+            return Ok(None);
+        }
+        self.evaluate(x).map(Some)
+    }
+
+    /// Score of `x`, regardless of the budgets.
+    fn evaluate(&mut self, x: &DVector<T>) -> Result<T, NelderMeadError> {
+        self.evaluations += 1;
+        let value = (self.obj_fn)(x);
+        self.config.nan_handling.score(value, self.sign)
+    }
+
+    /// Why the run has to stop before the next evaluation, if it does.
+    fn exhausted(&self) -> Option<TerminationReason> {
+        if self
+            .config
+            .max_fun_evals
+            .is_some_and(|limit| self.evaluations >= limit)
+        {
+            return Some(TerminationReason::MaxFunEvals);
+        }
+        if self
+            .config
+            .max_duration
+            .is_some_and(|limit| self.start.elapsed() >= limit)
+        {
+            return Some(TerminationReason::TimedOut);
+        }
+        None
+    }
+}
+
+/// Quadratic model `f(x_best) + g' s + s' H s / 2` of the objective at
+/// `x_best + s`, with the factorized interpolation system it was solved
+/// from, in coordinates scaled by `scale` around the best point.
+struct Model<T: RealField> {
+    gradient: DVector<T>,
+    hessian: DMatrix<T>,
+    system: nalgebra::linalg::LU<T, nalgebra::Dyn, nalgebra::Dyn>,
+    // displacements of the interpolation points from the best one, scaled
+    scaled: Vec<DVector<T>>,
+    scale: T,
+}
+
+impl<T: RealField + Copy> Model<T> {
+    /// Model interpolating the scores at `points` whose Hessian differs
+    /// the least from `hessian` in Frobenius norm, or `None` when the
+    /// points do not determine it.
+    fn interpolate(
+        points: &[(DVector<T>, T)],
+        best: usize,
+        hessian: &DMatrix<T>,
+        scale: T,
+    ) -> Option<Self> {
+        let (m, dim) = (points.len(), hessian.nrows());
+        let half: T = convert(0.5);
+        let x_best = &points[best].0;
+        let scaled: Vec<DVector<T>> = points.iter().map(|(x, _)| (x - x_best) / scale).collect();
+        let previous = hessian * (scale * scale);
+        // minimum Frobenius norm system of Powell (2004): the change of the
+        // Hessian is `sum_j lambda_j s_j s_j'`, with `sum_j lambda_j = 0`
+        // and `sum_j lambda_j s_j = 0`
+        let mut system = DMatrix::zeros(m + dim + 1, m + dim + 1);
+        let mut rhs = DVector::zeros(m + dim + 1);
+        for i in 0..m {
+            for j in 0..m {
+                system[(i, j)] = half * scaled[i].dot(&scaled[j]).powi(2);
+            }
+            system[(i, m)] = T::one();
+            system[(m, i)] = T::one();
+            for k in 0..dim {
+                system[(i, m + 1 + k)] = scaled[i][k];
+                system[(m + 1 + k, i)] = scaled[i][k];
+            }
+            rhs[i] = points[i].1 - points[best].1 - half * scaled[i].dot(&(&previous * &scaled[i]));
+        }
+        let system = system.lu();
+        let solution = system.solve(&rhs)?;
+        if solution.iter().any(|v| !v.is_finite()) {
+            return None;
+        }
+        let mut change = DMatrix::zeros(dim, dim);
+        for (j, s) in scaled.iter().enumerate() {
+            change.ger(solution[j], s, s, T::one());
+        }
+        Some(Model {
+            gradient: solution.rows(m + 1, dim) / scale,
+            hessian: (previous + change) / (scale * scale),
+            system,
+            scaled,
+            scale,
+        })
+    }
+
+    /// Decrease of the model from the best point to `x_best + s`.
+    fn decrease(&self, s: &DVector<T>) -> T {
+        let half: T = convert(0.5);
+        -(self.gradient.dot(s) + half * s.dot(&(&self.hessian * s)))
+    }
+
+    /// Values at `x_best + s` of the Lagrange functions of the
+    /// interpolation points, each equal to one at its point and zero at the
+    /// others.
+    fn lagrange_values(&self, s: &DVector<T>) -> DVector<T> {
+        let (m, dim) = (self.scaled.len(), s.len());
+        let half: T = convert(0.5);
+        let s = s / self.scale;
+        let mut w = DVector::zeros(m + dim + 1);
+        for (j, y) in self.scaled.iter().enumerate() {
+            w[j] = half * y.dot(&s).powi(2);
+        }
+        w[m] = T::one();
+        w.rows_mut(m + 1, dim).copy_from(&s);
+        // the system is symmetric, so its inverse applied to `w` holds the
+        // value of every Lagrange function
+        match self.system.solve(&w) {
+            Some(values) => values.rows(0, m).into_owned(),
+            None => DVector::zeros(m),
+        }
+    }
+
+    /// Gradient at the best point of the Lagrange function of the point
+    /// `t`.
+    fn lagrange_gradient(&self, t: usize) -> DVector<T> {
+        let (m, dim) = (self.scaled.len(), self.gradient.len());
+        let mut e = DVector::zeros(m + dim + 1);
+        e[t] = T::one();
+        match self.system.solve(&e) {
+            Some(coefficients) => coefficients.rows(m + 1, dim) / self.scale,
+            None => DVector::zeros(dim),
+        }
+    }
+}
+
+impl<T: RealField + Copy> Bobyqa<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> BobyqaBuilder<T> {
+        BobyqaBuilder::default()
+    }
+
+    /// Finds a local minimum (or maximum, depending on the configured
+    /// [`Direction`]) of the objective function, starting from `x_start`.
+    ///
+    /// Fails if `x_start` is empty, if the radii are not within
+    /// `0 < rho_end <= rho_begin`, if the number of interpolation points is
+    /// not within `[n + 2, (n + 1)(n + 2) / 2]`, if the bounds do not fit
+    /// `x_start` or are narrower than `2 rho_begin`, or if the objective
+    /// function returns NaN under [`NanHandling::Error`].
+    pub fn minimize<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let dim = x_start.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        let count = self.interpolation_points.unwrap_or(2 * dim + 1);
+        let radii = self.rho_begin.is_finite()
+            && T::zero() < self.rho_end
+            && self.rho_end <= self.rho_begin;
+        if !radii || count < dim + 2 || count > (dim + 1) * (dim + 2) / 2 {
+            return Err(NelderMeadError::InvalidTrustRegion);
+        }
+        let mut x = x_start;
+        if let Some((lower, upper)) = &self.bounds {
+            if lower.len() != dim
+                || upper.len() != dim
+                || lower
+                    .iter()
+                    .zip(upper.iter())
+                    .any(|(l, u)| l > u || is_nan(*l) || is_nan(*u))
+            {
+                return Err(NelderMeadError::InvalidBounds);
+            }
+            let two: T = convert(2.0);
+            if lower
+                .iter()
+                .zip(upper.iter())
+                .any(|(l, u)| *u - *l < two * self.rho_begin)
+            {
+                return Err(NelderMeadError::InvalidTrustRegion);
+            }
+            x = x.sup(lower).inf(upper);
+        }
+
+        let mut run = Run {
+            config: self,
+            obj_fn,
+            sign: self.direction.sign(),
+            evaluations: 0,
+            start: Instant::now(),
+        };
+        let budget = Budget {
+            max_iter: self.max_iter,
+            max_fun_evals: self.max_fun_evals,
+            max_duration: self.max_duration,
+        };
+        let half: T = convert(0.5);
+        let tenth: T = convert(0.1);
+        let two: T = convert(2.0);
+        let mut rho = self.rho_begin;
+        let mut delta = rho;
+        let mut hessian = DMatrix::zeros(dim, dim);
+        let score = run.evaluate(&x)?;
+        let mut points = vec![(x, score)];
+        let mut iterations = 0;
+
+        let termination_reason = 'run: loop {
+            if points.len() < count {
+                // (re)build the interpolation set around the best point,
+                // the only one left
+                let centre = points[0].0.clone();
+                for y in self.initial_points(&centre, rho, count).into_iter().skip(1) {
+                    let Some(score) = run.score(&y)? else {
+                        break 'run run.exhausted().unwrap_or(TerminationReason::MaxFunEvals);
+                    };
+                    points.push((y, score));
+                }
+                hessian.fill(T::zero());
+            }
+            if iterations >= self.max_iter {
+                break TerminationReason::MaxIter;
+            }
+            if let Some(reason) = run.exhausted() {
+                break reason;
+            }
+
+            let best = best_index(&points);
+            let Some(model) = Model::interpolate(&points, best, &hessian, rho) else {
+                points = vec![points.swap_remove(best)];
+                continue;
+            };
+            hessian.copy_from(&model.hessian);
+            let x_best = points[best].0.clone();
+            let s = self.trust_region_step(&model, &x_best, delta);
+            let length = s.norm();
+            let predicted = model.decrease(&s);
+
+            let mut poor = true;
+            if length >= half * rho && predicted > T::zero() {
+                let x_new = self.clip(&x_best + &s);
+                let Some(score) = run.score(&x_new)? else {
+                    break run.exhausted().unwrap_or(TerminationReason::MaxFunEvals);
+                };
+                let ratio = (points[best].1 - score) / predicted;
+                delta = if ratio <= tenth {
+                    half * delta
+                } else if ratio <= convert(0.7) {
+                    (half * delta).max(length)
+                } else {
+                    (half * delta).max(two * length)
+                };
+                if delta <= convert::<f64, T>(1.5) * rho {
+                    delta = rho;
+                }
+                // replace the point whose Lagrange function is the largest
+                // at the new point, weighted by its distance
+                let lagrange = model.lagrange_values(&s);
+                let centre = if score < points[best].1 {
+                    &x_new
+                } else {
+                    &x_best
+                };
+                let replaced = (0..points.len())
+                    .filter(|&j| score < points[best].1 || j != best)
+                    .map(|j| {
+                        let distance = (&points[j].0 - centre).norm() / delta;
+                        (j, lagrange[j].abs() * distance.powi(2).max(T::one()))
+                    })
+                    .max_by(|a, b| total_cmp(a.1, b.1))
+                    .map_or(0, |(j, _)| j);
+                points[replaced] = (x_new, score);
+                poor = ratio < tenth;
+            } else {
+                delta = (tenth * delta).max(rho);
+            }
+            iterations += 1;
+
+            if poor {
+                let best = best_index(&points);
+                let (far, distance) = points
+                    .iter()
+                    .enumerate()
+                    .map(|(j, (y, _))| (j, (y - &points[best].0).norm()))
+                    .max_by(|a, b| total_cmp(a.1, b.1))
+                    .unwrap_or((best, T::zero()));
+                let mut step = None;
+                if distance > two * delta {
+                    // move the farthest point closer, where its Lagrange
+                    // function is the largest
+                    match Model::interpolate(&points, best, &hessian, rho) {
+                        Some(model) => {
+                            step = self.geometry_step(&model, &points, best, far, delta);
+                        }
+                        None => points = vec![points.swap_remove(best)],
+                    }
+                }
+                if let Some(step) = step {
+                    let y = self.clip(&points[best].0 + step);
+                    let Some(score) = run.score(&y)? else {
+                        break run.exhausted().unwrap_or(TerminationReason::MaxFunEvals);
+                    };
+                    points[far] = (y, score);
+                } else if points.len() == count && delta <= rho {
+                    if rho <= self.rho_end {
+                        break TerminationReason::Converged;
+                    }
+                    let previous = rho;
+                    let ratio = rho / self.rho_end;
+                    rho = if ratio > convert(250.0) {
+                        tenth * rho
+                    } else if ratio > convert(16.0) {
+                        (rho * self.rho_end).sqrt()
+                    } else {
+                        self.rho_end
+                    };
+                    delta = (half * previous).max(rho);
+                }
+            }
+
+            let simplex = best_points(&points, dim + 1);
+            let state = TerminationState {
+                iterations,
+                evaluations: run.evaluations,
+                elapsed: run.start.elapsed(),
+                simplex: &simplex,
+                sign: run.sign,
+                last_move: None,
+                budget,
+            };
+            logging::log_iteration(&state);
+            if let Some(observer) = &self.observer {
+                observer.0.observe(&state);
+            }
+            if let Some(criterion) = &self.termination {
+                if criterion.0.should_stop(&state) {
+                    break TerminationReason::Criterion;
+                }
+            }
+        };
+
+        Ok(OptimizationResult::from_simplex(
+            best_points(&points, dim + 1).to_pairs(T::one()),
+            run.sign,
+            iterations,
+            run.evaluations,
+            0,
+            termination_reason,
+            None,
+        ))
+    }
+
+    /// Same as [`Bobyqa::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), x_start)
+    }
+
+    /// First `count` interpolation points around `x`: `x` itself, then
+    /// `x + rho e_i` and `x - rho e_i` for every parameter, then
+    /// `x + rho e_i + rho e_j` for pairs of parameters. Along a parameter
+    /// too close to a bound, both points lie on the other side.
+    fn initial_points(&self, x: &DVector<T>, rho: T, count: usize) -> Vec<DVector<T>> {
+        let dim = x.len();
+        let two: T = convert(2.0);
+        // first and second offsets along every parameter
+        let offsets: Vec<(T, T)> = (0..dim)
+            .map(|i| match &self.bounds {
+                Some((_, upper)) if x[i] + rho > upper[i] => (-rho, -two * rho),
+                Some((lower, _)) if x[i] - rho < lower[i] => (rho, two * rho),
+                _ => (rho, -rho),
+            })
+            .collect();
+        let mut points = vec![x.clone()];
+        for (i, &(first, _)) in offsets.iter().enumerate() {
+            let mut y = x.clone();
+            y[i] += first;
+            points.push(y);
+        }
+        for (i, &(_, second)) in offsets.iter().enumerate() {
+            let mut y = x.clone();
+            y[i] += second;
+            points.push(y);
+        }
+        'pairs: for gap in 1..dim {
+            for i in 0..dim - gap {
+                let mut y = x.clone();
+                y[i] += offsets[i].0;
+                y[i + gap] += offsets[i + gap].0;
+                if points.len() >= count {
+                    break 'pairs;
+                }
+                points.push(y);
+            }
+        }
+        points.truncate(count);
+        points
+    }
+
+    /// Approximate minimum of the model within the trust region of radius
+    /// `delta` around `x_best` and within the bounds, by conjugate gradients
+    /// truncated at the boundary of the trust region. A variable reaching a
+    /// bound is fixed there and the conjugate gradients start again on the
+    /// other ones.
+    fn trust_region_step(&self, model: &Model<T>, x_best: &DVector<T>, delta: T) -> DVector<T> {
+        let dim = x_best.len();
+        let mut s = DVector::zeros(dim);
+        let mut fixed = vec![false; dim];
+        if let Some((lower, upper)) = &self.bounds {
+            for i in 0..dim {
+                let g = model.gradient[i];
+                fixed[i] = (x_best[i] <= lower[i] && g > T::zero())
+                    || (x_best[i] >= upper[i] && g < T::zero());
+            }
+        }
+        let tolerance = convert::<f64, T>(1e-4) * model.gradient.norm_squared();
+        'restart: for _ in 0..=dim {
+            let mut r = -(&model.gradient + &model.hessian * &s);
+            for i in (0..dim).filter(|&i| fixed[i]) {
+                r[i] = T::zero();
+            }
+            let mut rr = r.norm_squared();
+            if rr <= tolerance {
+                break;
+            }
+            let mut d = r.clone();
+            for _ in 0..dim {
+                let hd = &model.hessian * &d;
+                let curvature = d.dot(&hd);
+                let (ss, sd, dd) = (s.norm_squared(), s.dot(&d), d.norm_squared());
+                let to_boundary =
+                    ((sd * sd + dd * (delta * delta - ss)).max(T::zero()).sqrt() - sd) / dd;
+                let mut to_bound = (to_boundary, None);
+                if let Some((lower, upper)) = &self.bounds {
+                    for i in (0..dim).filter(|&i| !fixed[i] && !d[i].is_zero()) {
+                        let limit = if d[i] > T::zero() { upper[i] } else { lower[i] };
+                        let alpha = ((limit - x_best[i] - s[i]) / d[i]).max(T::zero());
+                        if alpha < to_bound.0 {
+                            to_bound = (alpha, Some(i));
+                        }
+                    }
+                }
+                let alpha = if curvature > T::zero() {
+                    (rr / curvature).min(to_bound.0)
+                } else {
+                    to_bound.0
+                };
+                s.axpy(alpha, &d, T::one());
+                if alpha == to_bound.0 {
+                    match to_bound.1 {
+                        Some(i) => {
+                            fixed[i] = true;
+                            continue 'restart;
+                        }
+                        None => break 'restart,
+                    }
+                }
+                r.axpy(-alpha, &hd, T::one());
+                for i in (0..dim).filter(|&i| fixed[i]) {
+                    r[i] = T::zero();
+                }
+                let rr_next = r.norm_squared();
+                if rr_next <= tolerance {
+                    break 'restart;
+                }
+                d = &r + d * (rr_next / rr);
+                rr = rr_next;
+            }
+            break;
+        }
+        s
+    }
+
+    /// Step from `x_best`, within the trust region of radius `delta` and
+    /// the bounds, at which the Lagrange function of the point `t` is the
+    /// largest among the steps along its gradient and towards the other
+    /// points, or `None` when each of them lands on one of the points.
+    fn geometry_step(
+        &self,
+        model: &Model<T>,
+        points: &[(DVector<T>, T)],
+        best: usize,
+        t: usize,
+        delta: T,
+    ) -> Option<DVector<T>> {
+        let x_best = &points[best].0;
+        let gradient = model.lagrange_gradient(t);
+        let mut directions = vec![gradient];
+        directions.extend(
+            points
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != best && j != t)
+                .map(|(_, (y, _))| y - x_best),
+        );
+        let separation = convert::<f64, T>(0.1) * delta;
+        let mut chosen = None;
+        let mut largest = T::zero();
+        for direction in directions {
+            let length = direction.norm();
+            if length.is_zero() {
+                continue;
+            }
+            for sign in [T::one(), -T::one()] {
+                let step = self.clip(x_best + &direction * (sign * delta / length)) - x_best;
+                let y = x_best + &step;
+                let crowded = points
+                    .iter()
+                    .enumerate()
+                    .any(|(j, (z, _))| j != t && (z - &y).norm() < separation);
+                if crowded {
+                    continue;
+                }
+                let value = model.lagrange_values(&step)[t].abs();
+                if value > largest {
+                    largest = value;
+                    chosen = Some(step);
+                }
+            }
+        }
+        chosen
+    }
+
+    /// Copy of `x` clipped into the bounds.
+    fn clip(&self, x: DVector<T>) -> DVector<T> {
+        match &self.bounds {
+            Some((lower, upper)) => x.sup(lower).inf(upper),
+            None => x,
+        }
+    }
+}
+
+/// Index of the best of the points.
+fn best_index<T: RealField + Copy>(points: &[(DVector<T>, T)]) -> usize {
+    points
+        .iter()
+        .enumerate()
+        .min_by(|a, b| total_cmp(a.1 .1, b.1 .1))
+        .map_or(0, |(index, _)| index)
+}
+
+/// Simplex of the `count` best of the points, ordered from the best one.
+fn best_points<T: RealField + Copy>(points: &[(DVector<T>, T)], count: usize) -> Simplex<T> {
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|a, b| total_cmp(points[*a].1, points[*b].1));
+    order.truncate(count);
+    Simplex::from_slices(order.iter().map(|&k| (points[k].0.as_slice(), points[k].1)))
+}
@@ -0,0 +1,1541 @@
+#[cfg(feature = "checkpoint")]
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use nalgebra::{DMatrix, DVector, RealField};
+
+#[cfg(feature = "checkpoint")]
+use crate::checkpoint::{Checkpoint, CheckpointSaver};
+use crate::coefficients::Coefficients;
+use crate::constraints::{AugmentedLagrangian, Constraint, Penalty, PenaltySchedule, SharedConstraint};
+use crate::nelder_mead::{
+    BoundsHandling, ContractionMode, DegeneracyRestart, Direction, FailureHandling,
+    ImprovementMode, NanHandling, NelderMead, OrientedRestart, PlateauJitter, SimplexMethod,
+    StepSize,
+};
+use crate::objective::{FallibleScore, ObjectiveFunction};
+use crate::observer::{Callback, Observer, SharedCallback, SharedObserver};
+use crate::error::NelderMeadError;
+use crate::event::{EventListener, SharedEventListener};
+use crate::noise::{Aggregate, Replication};
+use crate::result::OptimizationResult;
+use crate::termination::{SharedCriterion, TerminationCriterion};
+use crate::transform::{ParameterTransform, SharedTransform, Transform};
+
+/// Builder for [`NelderMead`].
+///
+/// Every parameter starts from its default value, so only the ones that
+/// matter for a given problem have to be set.
+///
+/// | parameter                 | default              |
+/// |---------------------------|----------------------|
+/// | `step`                    | 0.1                  |
+/// | `no_improve_thr`          | 10e-6                |
+/// | `improvement_mode`        | `Absolute`           |
+/// | `no_improv_break`         | 10                   |
+/// | `max_iter`                | 1000                 |
+/// | `max_fun_evals`           | none                 |
+/// | `max_duration`            | none                 |
+/// | `termination`             | none                 |
+/// | `observer`                | none                 |
+/// | `callback`                | none                 |
+/// | `on_event`                | none                 |
+/// | `alpha`                   | 1.0                  |
+/// | `gamma`                   | 2.0                  |
+/// | `rho`                     | 0.5                  |
+/// | `sigma`                   | 0.5                  |
+/// | `adaptive`                | false                |
+/// | `contraction`             | `InsideOnly`         |
+/// | `method`                  | `NelderMead`         |
+/// | `greedy_expansion`        | false                |
+/// | `speculative_evaluation`  | false                |
+/// | `parallel_points`         | 1                    |
+/// | `cache_evaluations`       | none                 |
+/// | `memory_efficient`        | false                |
+/// | `xatol`                   | none                 |
+/// | `fatol`                   | none                 |
+/// | `target_value`            | none                 |
+/// | `direction`               | `Minimize`           |
+/// | `nan_handling`            | `Error`              |
+/// | `failure_handling`        | `Abort`              |
+/// | `bounds`                  | none                 |
+/// | `bounds_handling`         | `Clip`               |
+/// | `transform`               | `Identity`           |
+/// | `parameter_transform`     | none                 |
+/// | `unit_simplex`            | none                 |
+/// | `periodic`                | none                 |
+/// | `auto_scale`              | false                |
+/// | `constraint`              | none                 |
+/// | `penalty`                 | `Quadratic(10)`      |
+/// | `penalty_schedule`        | none                 |
+/// | `equality_constraint`     | none                 |
+/// | `augmented_lagrangian`    | `(10, 10, 20, 1e-6)` |
+/// | `linear_equality`         | none                 |
+/// | `record_history`          | false                |
+/// | `record_timing`           | false                |
+/// | `stop_on_interrupt`       | false                |
+/// | `restart_on_degeneracy`   | none                 |
+/// | `oriented_restart`        | none                 |
+/// | `bfgs_polish`             | none                 |
+/// | `estimate_covariance`     | false                |
+/// | `replicate`               | none                 |
+/// | `replicate_adaptive`      | none                 |
+/// | `reevaluate_best`         | none                 |
+/// | `noise_aware_termination` | none                 |
+/// | `plateau_jitter`          | none                 |
+#[derive(Debug, Clone)]
+pub struct NelderMeadBuilder<T = f64> {
+    config: NelderMead<T>,
+}
+
+impl<T: RealField + Copy> Default for NelderMeadBuilder<T> {
+    fn default() -> Self {
+        NelderMeadBuilder {
+            config: NelderMead::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> NelderMeadBuilder<T> {
+    /// Look-around radius in initial step, either a single value used for
+    /// every dimension or a [`StepSize`].
+    ///
+    /// The run fails with [`NelderMeadError::InvalidSimplex`] if a step is
+    /// zero or not finite, as the simplex would be degenerate.
+    pub fn step(mut self, step: impl Into<StepSize<T>>) -> Self {
+        self.config.step = step.into();
+        self
+    }
+
+    /// Threshold informing on no improvement.
+    pub fn no_improve_thr(mut self, no_improve_thr: T) -> Self {
+        self.config.no_improve_thr = no_improve_thr;
+        self
+    }
+
+    /// Whether `no_improve_thr` is absolute or relative to the magnitude
+    /// of the best score.
+    pub fn improvement_mode(mut self, improvement_mode: ImprovementMode) -> Self {
+        self.config.improvement_mode = improvement_mode;
+        self
+    }
+
+    /// Break after this many iterations with an improvement lower
+    /// than `no_improve_thr`.
+    pub fn no_improv_break(mut self, no_improv_break: u64) -> Self {
+        self.config.no_improv_break = Some(no_improv_break);
+        self
+    }
+
+    /// Disables the no-improvement criterion. It stops prematurely on
+    /// flat plateaus and late near sharp minima, so it is best replaced by
+    /// the [`xatol`](Self::xatol) and [`fatol`](Self::fatol) convergence
+    /// tests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    /// use nelder_mead_optimizer::result::TerminationReason;
+    ///
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     (x[0] - 1.0).powi(4) + (x[1] + 1.0).powi(4)
+    /// }
+    /// let results = NelderMead::builder()
+    ///     .disable_no_improv_break()
+    ///     .xatol(1e-6)
+    ///     .fatol(1e-12)
+    ///     .minimize(&f, dvector![0.0, 0.0])?;
+    ///
+    /// assert_eq!(results.termination_reason, TerminationReason::Converged);
+    /// assert!((results.best_point[0] - 1.0).abs() < 1e-4);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn disable_no_improv_break(mut self) -> Self {
+        self.config.no_improv_break = None;
+        self
+    }
+
+    /// Always break after this number of iterations.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Never evaluate the objective function more than this number of
+    /// times, counting the evaluations of every simplex operation. The
+    /// initial simplex is always evaluated in full.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once this much wall-clock time has elapsed, returning the best
+    /// point found so far. The limit is checked before every evaluation,
+    /// so a run can overshoot it by at most one evaluation.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Additional stopping rule, possibly combining several criteria,
+    /// checked at the start of every iteration next to the built-in ones.
+    /// See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked once per iteration with the state of the run, see
+    /// [`Observer`]. Use [`PrintProgress`] to print every iteration.
+    ///
+    /// [`PrintProgress`]: crate::observer::PrintProgress
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Hook invoked once per iteration that can stop the run early, see
+    /// [`Callback`].
+    pub fn callback<C>(mut self, callback: C) -> Self
+    where
+        C: Callback<T> + Send + Sync + 'static,
+    {
+        self.config.callback = Some(SharedCallback(Arc::new(callback)));
+        self
+    }
+
+    /// Hook invoked with every operation applied to the simplex and the
+    /// points involved, see [`EventListener`].
+    pub fn on_event<L>(mut self, listener: L) -> Self
+    where
+        L: EventListener<T> + Send + Sync + 'static,
+    {
+        self.config.event_listener = Some(SharedEventListener(Arc::new(listener)));
+        self
+    }
+
+    /// All four simplex coefficients at once, validated on construction
+    /// of [`Coefficients`].
+    pub fn coefficients(mut self, coefficients: Coefficients<T>) -> Self {
+        self.config.coefficients = coefficients;
+        self
+    }
+
+    /// Reflection step parameter. Coefficients set one by one are only
+    /// validated once the optimizer runs, which then fails with
+    /// [`NelderMeadError::InvalidCoefficients`].
+    pub fn alpha(mut self, alpha: T) -> Self {
+        self.config.coefficients.alpha = alpha;
+        self
+    }
+
+    /// Expansion step parameter.
+    pub fn gamma(mut self, gamma: T) -> Self {
+        self.config.coefficients.gamma = gamma;
+        self
+    }
+
+    /// Contraction step parameter, the contracted point lies at `rho`
+    /// times the distance from the centroid to the worst point.
+    pub fn rho(mut self, rho: T) -> Self {
+        self.config.coefficients.rho = rho;
+        self
+    }
+
+    /// Shrink step parameter.
+    pub fn sigma(mut self, sigma: T) -> Self {
+        self.config.coefficients.sigma = sigma;
+        self
+    }
+
+    /// Use the dimension dependent coefficients of
+    /// [`Coefficients::adaptive`] instead of the configured ones, like
+    /// `adaptive=True` in scipy. The standard coefficients degrade badly
+    /// above about 10 parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::DVector;
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     x.iter().enumerate().map(|(i, xi)| (i + 1) as f64 * (xi - 1.0).powi(2)).sum()
+    /// }
+    /// let x_start = DVector::zeros(30);
+    /// let builder = NelderMead::builder()
+    ///     .disable_no_improv_break()
+    ///     .max_iter(u64::MAX)
+    ///     .max_fun_evals(20_000);
+    ///
+    /// let standard = builder.clone().minimize(&f, x_start.clone())?;
+    /// let adaptive = builder.adaptive(true).minimize(&f, x_start)?;
+    ///
+    /// assert!(standard.best_score > 1e-3);
+    /// assert!(adaptive.best_score < 1e-8);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn adaptive(mut self, adaptive: bool) -> Self {
+        self.config.adaptive = adaptive;
+        self
+    }
+
+    /// Which contraction to perform, see [`ContractionMode`].
+    pub fn contraction(mut self, contraction: ContractionMode) -> Self {
+        self.config.contraction = contraction;
+        self
+    }
+
+    /// Simplex algorithm of the iterations, see [`SimplexMethod`].
+    ///
+    /// Under [`SimplexMethod::MultiDirectional`], every iteration reflects
+    /// the `n` vertices but the best one through the best one, expands
+    /// them if the best reflected point beats the best vertex, keeping the
+    /// expansion as the reflection is kept by Nelder-Mead, see
+    /// [`greedy_expansion`](Self::greedy_expansion), and otherwise shrinks
+    /// the simplex with `sigma`. The simplex keeps its shape, up to the
+    /// bounds, so that unlike Nelder-Mead it provably converges to a
+    /// stationary point of a smooth function, at the cost of `n` evaluations
+    /// per iteration or more. The `n` points of a step are independent and
+    /// evaluated as one batch, e.g. in parallel with
+    /// `NelderMead::minimize_parallel`. Observers see the steps as
+    /// [`Move::Reflection`], [`Move::Expansion`] and [`Move::Shrink`], and
+    /// listeners get one event per moved vertex, whose centroid is the
+    /// best vertex. `contraction`, `parallel_points` and speculative
+    /// evaluation do not apply, and an iteration for which the remaining
+    /// evaluation budget does not allow a whole batch ends the run.
+    ///
+    /// [`Move::Reflection`]: crate::observer::Move::Reflection
+    /// [`Move::Expansion`]: crate::observer::Move::Expansion
+    /// [`Move::Shrink`]: crate::observer::Move::Shrink
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::{NelderMead, SimplexMethod};
+    ///
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     x.iter().enumerate().map(|(i, xi)| (xi - i as f64).powi(2)).sum()
+    /// }
+    /// let mut batches = Vec::new();
+    /// let results = NelderMead::builder()
+    ///     .method(SimplexMethod::MultiDirectional)
+    ///     .xatol(1e-6)
+    ///     .disable_no_improv_break()
+    ///     .max_iter(10_000)
+    ///     .minimize_batch(
+    ///         |xs: &[DVector<f64>]| {
+    ///             batches.push(xs.len());
+    ///             xs.iter().map(f).collect()
+    ///         },
+    ///         DVector::zeros(4),
+    ///     )?;
+    ///
+    /// assert!(results.best_score < 1e-10);
+    /// // the initial simplex, then batches of every vertex but the best one
+    /// assert_eq!(batches[0], 5);
+    /// assert!(batches[1..].iter().all(|&size| size == 4));
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn method(mut self, method: SimplexMethod) -> Self {
+        self.config.method = method;
+        self
+    }
+
+    /// Accept the expanded point as soon as it beats the best vertex
+    /// (greedy expansion) instead of only when it beats the reflected
+    /// point (greedy minimization). Greedy expansion keeps the simplex
+    /// larger, which tends to help along elongated valleys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn rosenbrock(x: &DVector<f64>) -> f64 {
+    ///     (1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0].powi(2)).powi(2)
+    /// }
+    /// let results = NelderMead::builder()
+    ///     .greedy_expansion(true)
+    ///     .xatol(1e-8)
+    ///     .fatol(1e-8)
+    ///     .disable_no_improv_break()
+    ///     .minimize(&rosenbrock, dvector![-1.2, 1.0])?;
+    ///
+    /// assert!(results.best_score < 1e-8);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn greedy_expansion(mut self, greedy_expansion: bool) -> Self {
+        self.config.greedy_expansion = greedy_expansion;
+        self
+    }
+
+    /// Evaluate every trial point an iteration may need, the reflected,
+    /// expanded and contracted points, as one batch instead of one after
+    /// the other. The simplex takes the same path, at the cost of up to
+    /// three evaluations per iteration that turn out unused, which pays
+    /// off when the batch is evaluated in parallel, see
+    /// `NelderMead::minimize_parallel`, and the objective dominates the
+    /// run time. [`NelderMead::ask_tell`] asks for the whole batch at
+    /// once, while [`NelderMead::minimize_static`] always evaluates one
+    /// point at a time. An iteration for which the remaining evaluation
+    /// budget does not allow the whole batch proceeds one point at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+    /// }
+    /// let sequential = NelderMead::builder()
+    ///     .minimize(&f, dvector![0.0, 0.0])?;
+    /// let speculative = NelderMead::builder()
+    ///     .speculative_evaluation(true)
+    ///     .minimize(&f, dvector![0.0, 0.0])?;
+    ///
+    /// assert_eq!(speculative.final_simplex, sequential.final_simplex);
+    /// assert!(speculative.evaluations > sequential.evaluations);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn speculative_evaluation(mut self, speculative: bool) -> Self {
+        self.config.speculative = speculative;
+        self
+    }
+
+    /// Replace the `parallel_points` worst vertices per iteration instead
+    /// of only the worst one, the parallel variant of Lee and Wiswall
+    /// (2007). Each of them is reflected through the centroid of the other
+    /// vertices, then expanded or contracted as in a regular iteration,
+    /// and the simplex only shrinks when none of them improves. The
+    /// reflected points are evaluated as one batch, and so are the
+    /// expanded and contracted ones, so that with
+    /// `NelderMead::minimize_parallel` an iteration takes the time of
+    /// two evaluations on `parallel_points` cores, for problems with an
+    /// expensive objective and more cores than the single point variant
+    /// can use. Speculative evaluation is ignored when more than one
+    /// vertex is replaced.
+    ///
+    /// The run fails with [`NelderMeadError::InvalidParallelPoints`] if
+    /// `parallel_points` is zero or exceeds the dimension of the problem,
+    /// and [`NelderMead::minimize_static`] only supports one point. An
+    /// iteration for which the remaining evaluation budget does not allow
+    /// a whole batch ends the run.
+    ///
+    /// [`NelderMead::minimize_static`]: crate::nelder_mead::NelderMead::minimize_static
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     x.iter().enumerate().map(|(i, xi)| (xi - i as f64).powi(2)).sum()
+    /// }
+    /// let batches = std::cell::Cell::new(0);
+    /// let results = NelderMead::builder()
+    ///     .parallel_points(4)
+    ///     .xatol(1e-6)
+    ///     .disable_no_improv_break()
+    ///     .minimize_batch(
+    ///         |xs: &[DVector<f64>]| {
+    ///             batches.set(batches.get() + 1);
+    ///             xs.iter().map(f).collect()
+    ///         },
+    ///         DVector::zeros(8),
+    ///     )?;
+    ///
+    /// assert!(results.best_score < 1e-6);
+    /// assert!(batches.get() < results.evaluations / 2);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn parallel_points(mut self, parallel_points: usize) -> Self {
+        self.config.parallel_points = parallel_points;
+        self
+    }
+
+    /// Remember the objective value of every evaluated point and serve
+    /// points seen before from memory, which is common after shrink and
+    /// restart cycles or with integer parameters. Points whose parameters
+    /// round to the same multiples of `resolution` share their value, a
+    /// `resolution` of zero only serves identical points. The run fails
+    /// with [`NelderMeadError::InvalidCacheResolution`] if `resolution` is
+    /// negative or not finite.
+    ///
+    /// Served points count neither towards `evaluations` nor towards
+    /// `max_fun_evals`, the hits and misses of the cache are reported in
+    /// [`OptimizationResult::cache`]. The cache is not saved in
+    /// checkpoints, and [`NelderMead::ask_tell`] leaves caching to the
+    /// caller.
+    ///
+    /// [`NelderMead::ask_tell`]: crate::nelder_mead::NelderMead::ask_tell
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// // integer parameters, rounded by the objective
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     (x[0].round() - 3.0).powi(2) + (x[1].round() + 2.0).powi(2)
+    /// }
+    /// let plain = NelderMead::builder()
+    ///     .step(2.0)
+    ///     .minimize(&f, dvector![0.0, 0.0])?;
+    /// let cached = NelderMead::builder()
+    ///     .step(2.0)
+    ///     .cache_evaluations(1.0)
+    ///     .minimize(&f, dvector![0.0, 0.0])?;
+    ///
+    /// assert_eq!(cached.best_score, 0.0);
+    /// assert!(cached.evaluations < plain.evaluations);
+    /// assert!(cached.cache.unwrap().hits > 0);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn cache_evaluations(mut self, resolution: T) -> Self {
+        self.config.cache_resolution = Some(resolution);
+        self
+    }
+
+    /// Keep the memory and the work of an iteration close to the simplex
+    /// itself, for problems with hundreds or thousands of parameters,
+    /// whose `(n + 1) × n` simplex dominates the cost of a run, e.g. 8 MB
+    /// for a thousand `f64` parameters. The vertices of a shrink step are
+    /// evaluated one at a time instead of as one batch, so that no second
+    /// simplex is allocated for them, and the entries recorded with
+    /// [`record_history`](Self::record_history) leave out the best point,
+    /// whose copies grow with the number of iterations, and the simplex
+    /// volume, a determinant costing `O(n³)` per iteration. The run visits
+    /// the same points as in the default mode, except that a cache set
+    /// with [`cache_evaluations`](Self::cache_evaluations) may serve a
+    /// shrunk vertex from one evaluated earlier in the same shrink step.
+    ///
+    /// A practical setup for problems of about a thousand parameters:
+    ///
+    /// - [`adaptive`](Self::adaptive) coefficients, without which the
+    ///   simplex stalls in high dimension;
+    /// - a `max_iter` and a `max_fun_evals` in the hundreds of thousands,
+    ///   and no [`no_improv_break`](Self::no_improv_break) shorter than a
+    ///   few times the dimension, since an iteration only moves one vertex
+    ///   and rarely improves the best one;
+    /// - no `restart_on_degeneracy` and no `oriented_restart`, which check
+    ///   an `n × n` matrix every iteration, and observers, callbacks and
+    ///   termination criteria that only read scores and the best point
+    ///   rather than the [`health`] or the [`simplex_volume`];
+    /// - no bounds handling, parameter transform or automatic scaling that
+    ///   maps the parameters, which keeps a second copy of the simplex
+    ///   mapped to them.
+    ///
+    /// [`health`]: crate::termination::TerminationState::health
+    /// [`simplex_volume`]: crate::termination::TerminationState::simplex_volume
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::DVector;
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    /// use nelder_mead_optimizer::test_functions::sphere;
+    ///
+    /// let dim = 50;
+    /// let start = DVector::from_element(dim, 1.0);
+    /// let results = NelderMead::builder()
+    ///     .memory_efficient(true)
+    ///     .adaptive(true)
+    ///     .no_improv_break(10 * dim as u64)
+    ///     .max_iter(100_000)
+    ///     .max_fun_evals(20_000)
+    ///     .record_history(true)
+    ///     .minimize(sphere, start.clone())?;
+    /// let history = results.history.unwrap();
+    ///
+    /// assert!(results.best_score < 1e-3 * sphere(&start));
+    /// assert!(history.entries.iter().all(|entry| entry.best_point.is_empty()));
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn memory_efficient(mut self, memory_efficient: bool) -> Self {
+        self.config.memory_efficient = memory_efficient;
+        self
+    }
+
+    /// Stop once every vertex coordinate is within `xatol` of the best
+    /// vertex (and the scores within `fatol`, if set).
+    pub fn xatol(mut self, xatol: T) -> Self {
+        self.config.xatol = Some(xatol);
+        self
+    }
+
+    /// Stop once every vertex score is within `fatol` of the best score
+    /// (and the coordinates within `xatol`, if set).
+    pub fn fatol(mut self, fatol: T) -> Self {
+        self.config.fatol = Some(fatol);
+        self
+    }
+
+    /// Stop as soon as the best score is at or below `target_value` (at or
+    /// above when maximizing), e.g. when the optimum is known to be zero.
+    pub fn target_value(mut self, target_value: T) -> Self {
+        self.config.target_value = Some(target_value);
+        self
+    }
+
+    /// Whether the objective function is minimized or maximized.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// What to do when an objective function optimized with
+    /// [`try_minimize`](Self::try_minimize) fails.
+    pub fn failure_handling(mut self, failure_handling: FailureHandling) -> Self {
+        self.config.failure_handling = failure_handling;
+        self
+    }
+
+    /// Lower and upper bounds of every parameter, enforced as set with
+    /// [`bounds_handling`](Self::bounds_handling). Every evaluated point,
+    /// the initial simplex included, lies within them: its step is
+    /// reversed along a parameter when it would leave the bounds, and
+    /// shrunk when the bounds are too close on both sides.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     (x[0] - 0.02).powi(2) + (x[1] - 0.5).powi(2)
+    /// }
+    /// let (lower, upper) = (dvector![0.0, 0.0], dvector![0.05, 1.0]);
+    /// // the first parameter is confined to a box narrower than the step
+    /// let initial = NelderMead::builder()
+    ///     .step(0.1)
+    ///     .bounds(lower.clone(), upper.clone())
+    ///     .max_iter(0)
+    ///     .minimize(&f, dvector![0.0, 1.0])?;
+    ///
+    /// assert!(initial.final_simplex.iter().all(|(x, _)| {
+    ///     (0..2).all(|i| lower[i] <= x[i] && x[i] <= upper[i])
+    /// }));
+    /// assert!(initial.simplex_health().volume > 0.0);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn bounds(mut self, lower: DVector<T>, upper: DVector<T>) -> Self {
+        self.config.bounds = Some((lower, upper));
+        self
+    }
+
+    /// How points outside of the [`bounds`](Self::bounds) are brought
+    /// back inside.
+    pub fn bounds_handling(mut self, bounds_handling: BoundsHandling) -> Self {
+        self.config.bounds_handling = bounds_handling;
+        self
+    }
+
+    /// Optimizes the parameter at `index` through `transform`, see
+    /// [`Transform`]. Every parameter without a transform is optimized as
+    /// is.
+    pub fn transform(self, index: usize, transform: Transform) -> Self {
+        match transform {
+            Transform::Identity => self.clear_transform(index),
+            _ => self.parameter_transform(index, transform),
+        }
+    }
+
+    /// Optimizes the parameter at `index` through a custom
+    /// [`ParameterTransform`], replacing any transform set before.
+    pub fn parameter_transform<P>(mut self, index: usize, transform: P) -> Self
+    where
+        P: ParameterTransform<T> + Send + Sync + 'static,
+    {
+        let transforms = &mut self.config.parameter_transforms;
+        if transforms.len() <= index {
+            transforms.resize(index + 1, None);
+        }
+        transforms[index] = Some(SharedTransform(Arc::new(transform)));
+        self
+    }
+
+    /// Constrains the parameters at `indices` to the unit simplex, i.e. to
+    /// non-negative weights summing to one, by optimizing the logarithms
+    /// of the weights, mapped back with a softmax. The starting weights
+    /// are normalized and should be positive, as a weight close to zero
+    /// hardly moves on the log scale. Can be called once per group of
+    /// weights.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// // minimal variance mix of three assets, the optimum being
+    /// // proportional to (4, 2, 1)
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     x[0].powi(2) + 2.0 * x[1].powi(2) + 4.0 * x[2].powi(2)
+    /// }
+    /// let results = NelderMead::builder()
+    ///     .unit_simplex(0..3)
+    ///     .no_improve_thr(1e-12)
+    ///     .xatol(1e-8)
+    ///     .fatol(1e-12)
+    ///     .minimize(&f, dvector![1.0, 1.0, 1.0])?;
+    ///
+    /// assert!((results.best_point.sum() - 1.0).abs() < 1e-12);
+    /// assert!((results.best_point[0] - 4.0 / 7.0).abs() < 1e-4);
+    /// assert!((results.best_point[1] - 2.0 / 7.0).abs() < 1e-4);
+    /// assert!((results.best_point[2] - 1.0 / 7.0).abs() < 1e-4);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn unit_simplex(mut self, indices: impl IntoIterator<Item = usize>) -> Self {
+        self.config.unit_simplices.push(indices.into_iter().collect());
+        self
+    }
+
+    /// Declares the parameter at `index` periodic, e.g. an angle or a
+    /// phase, with the given `period`. The simplex moves freely across
+    /// periods, the objective function receives, and the result reports,
+    /// the parameter wrapped within `[0, period)`, and the simplex is
+    /// shifted back by whole periods as it drifts, so that its steps do
+    /// not wander off to huge equivalent values. The `xatol` distance is
+    /// measured the shorter way around the period.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::f64::consts::TAU;
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// // minimal at the angle 0.1 and at 2
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     1.0 - (x[0] - 0.1).cos() + (x[1] - 2.0).powi(2)
+    /// }
+    /// let results = NelderMead::builder()
+    ///     .periodic(0, TAU)
+    ///     .no_improve_thr(1e-12)
+    ///     .xatol(1e-8)
+    ///     .fatol(1e-12)
+    ///     .minimize(&f, dvector![100.0, 0.0])?;
+    ///
+    /// assert!((results.best_point[0] - 0.1).abs() < 1e-4);
+    /// assert!((results.best_point[1] - 2.0).abs() < 1e-4);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn periodic(mut self, index: usize, period: T) -> Self {
+        let periods = &mut self.config.periods;
+        if periods.len() <= index {
+            periods.resize(index + 1, None);
+        }
+        periods[index] = Some(period);
+        self
+    }
+
+    /// Rescale every parameter without a transform by its magnitude at
+    /// the start, so that the simplex moves in a space where all of them
+    /// are of order one, and apply the [`step`](Self::step) relative to
+    /// the magnitude of every parameter. Parameters at zero keep their
+    /// scale.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// // minimal at (3e-6, 2e6)
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     (x[0] / 1e-6 - 3.0).powi(2) + (x[1] / 1e6 - 2.0).powi(2)
+    /// }
+    /// let unscaled = NelderMead::builder()
+    ///     .minimize(&f, dvector![1e-6, 1e6])?;
+    /// let results = NelderMead::builder()
+    ///     .auto_scale(true)
+    ///     .no_improve_thr(1e-12)
+    ///     .fatol(1e-12)
+    ///     .minimize(&f, dvector![1e-6, 1e6])?;
+    ///
+    /// assert!(unscaled.best_score > 1.0);
+    /// assert!((results.best_point[0] / 3e-6 - 1.0).abs() < 1e-4);
+    /// assert!((results.best_point[1] / 2e6 - 1.0).abs() < 1e-4);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn auto_scale(mut self, auto_scale: bool) -> Self {
+        self.config.auto_scale = auto_scale;
+        self
+    }
+
+    /// Removes the transform of the parameter at `index`.
+    fn clear_transform(mut self, index: usize) -> Self {
+        if let Some(transform) = self.config.parameter_transforms.get_mut(index) {
+            *transform = None;
+        }
+        self
+    }
+
+    /// Adds the inequality constraint `g(x) <= 0`, enforced by adding the
+    /// [`penalty`](Self::penalty) to the score of the points violating
+    /// it. See [`Constraint`] for an example.
+    pub fn constraint<C>(mut self, constraint: C) -> Self
+    where
+        C: Constraint<T> + Send + Sync + 'static,
+    {
+        self.config.constraints.push(SharedConstraint(Arc::new(constraint)));
+        self
+    }
+
+    /// Penalty of the points violating the constraints.
+    pub fn penalty(mut self, penalty: Penalty<T>) -> Self {
+        self.config.penalty = penalty;
+        self
+    }
+
+    /// Once a run ends with a constraint violated by more than
+    /// `tolerance`, multiply the penalty weight by `growth` and run again
+    /// from its final simplex, up to `max_rounds` runs in total. Only the
+    /// `minimize` family of methods runs more than once.
+    pub fn penalty_schedule(mut self, growth: T, max_rounds: u64, tolerance: T) -> Self {
+        self.config.penalty_schedule = Some(PenaltySchedule {
+            growth,
+            max_rounds,
+            tolerance,
+        });
+        self
+    }
+
+    /// Adds the equality constraint `h(x) = 0`, enforced by the
+    /// [`augmented_lagrangian`](Self::augmented_lagrangian) term added to
+    /// the score of every point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     x[0].powi(2) + 2.0 * x[1].powi(2)
+    /// }
+    /// // x + y = 3, the constrained optimum is (2, 1)
+    /// let results = NelderMead::builder()
+    ///     .equality_constraint(|x: &DVector<f64>| x[0] + x[1] - 3.0)
+    ///     .xatol(1e-8)
+    ///     .fatol(1e-10)
+    ///     .minimize(&f, dvector![0.0, 0.0])?;
+    ///
+    /// assert!((results.best_point[0] - 2.0).abs() < 1e-4);
+    /// assert!((results.best_point[1] - 1.0).abs() < 1e-4);
+    /// assert!(results.constraint_violation.unwrap() <= 1e-6);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn equality_constraint<C>(mut self, constraint: C) -> Self
+    where
+        C: Constraint<T> + Send + Sync + 'static,
+    {
+        self.config.equality_constraints.push(SharedConstraint(Arc::new(constraint)));
+        self
+    }
+
+    /// Augmented Lagrangian of the equality constraints, adding
+    /// `sum(lambda_j * h_j(x) + weight / 2 * h_j(x)^2)` to the score with
+    /// the multipliers `lambda_j` starting at zero. Once a run ends with an
+    /// equality constraint violated by more than `tolerance`, update the
+    /// multipliers, multiply `weight` by `growth` unless the violation
+    /// dropped to a quarter of the previous one and run again from the
+    /// final simplex, up to `max_rounds` runs in total. Only the `minimize`
+    /// family of methods runs more than once.
+    pub fn augmented_lagrangian(mut self, weight: T, growth: T, max_rounds: u64, tolerance: T) -> Self {
+        self.config.augmented_lagrangian = AugmentedLagrangian {
+            weight,
+            growth,
+            max_rounds,
+            tolerance,
+            multipliers: Vec::new(),
+        };
+        self
+    }
+
+    /// Adds the linear equality constraints `a * x = b`, replacing any set
+    /// before, which are eliminated rather than penalized: the simplex
+    /// moves in the `k` coordinates of the null space of `a`, `k` being the
+    /// dimension of the points minus the rank of `a`, and every evaluated
+    /// point satisfies the constraints up to rounding. The starting point
+    /// is projected onto them and the [`step`](Self::step) applies to the
+    /// coordinates. The observer, the callback, the termination criterion,
+    /// the event listener and the checkpoints see the coordinates, while
+    /// the result and its history report points.
+    ///
+    /// Only [`minimize`](Self::minimize) and
+    /// [`minimize_from_simplex`](Self::minimize_from_simplex), and the
+    /// methods built on them, support them, and options applying to single
+    /// parameters such as bounds cannot be combined with them, see
+    /// [`NelderMeadError::InvalidLinearEquality`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dmatrix, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     (x[0] - 1.0).powi(2) + (x[1] - 2.0).powi(2) + (x[2] - 3.0).powi(2)
+    /// }
+    /// // x + y + z = 3, the constrained optimum is (0, 1, 2)
+    /// let results = NelderMead::builder()
+    ///     .linear_equality(dmatrix![1.0, 1.0, 1.0], dvector![3.0])
+    ///     .xatol(1e-8)
+    ///     .fatol(1e-12)
+    ///     .minimize(&f, dvector![0.0, 0.0, 0.0])?;
+    ///
+    /// assert_eq!(results.final_simplex.len(), 3);
+    /// assert!((results.best_point.sum() - 3.0).abs() < 1e-12);
+    /// assert!((results.best_point - dvector![0.0, 1.0, 2.0]).amax() < 1e-4);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn linear_equality(mut self, a: DMatrix<T>, b: DVector<T>) -> Self {
+        self.config.linear_equality = Some((a, b));
+        self
+    }
+
+    /// Store the state of every iteration in the `history` of the result,
+    /// see [`History`](crate::history::History).
+    pub fn record_history(mut self, record_history: bool) -> Self {
+        self.config.record_history = record_history;
+        self
+    }
+
+    /// Measure how long the calls to the objective function and the
+    /// iterations take, reported in the `timing` of the result, see
+    /// [`TimingStats`](crate::timing::TimingStats). The clock is read
+    /// around every call to the objective, which is negligible next to
+    /// all but the cheapest objectives.
+    ///
+    /// [`NelderMead::ask_tell`] leaves timing to the caller, who evaluates
+    /// the points.
+    ///
+    /// [`NelderMead::ask_tell`]: crate::nelder_mead::NelderMead::ask_tell
+    pub fn record_timing(mut self, record_timing: bool) -> Self {
+        self.config.record_timing = record_timing;
+        self
+    }
+
+    /// Stop at the end of the current iteration once
+    /// [`request_stop`](crate::interrupt::request_stop) is called, e.g. on
+    /// Ctrl-C with the `ctrlc` feature, which then installs its handler.
+    pub fn stop_on_interrupt(mut self, stop_on_interrupt: bool) -> Self {
+        #[cfg(feature = "ctrlc")]
+        if stop_on_interrupt {
+            crate::interrupt::install_handler();
+        }
+        self.config.stop_on_interrupt = stop_on_interrupt;
+        self
+    }
+
+    /// Rebuild the simplex around its best vertex, using `step`, whenever
+    /// its condition number exceeds `max_condition`, at most
+    /// `max_restarts` times. A collapsed simplex only explores a subspace
+    /// and otherwise stalls, see [`SimplexHealth`]. Restarts of both kinds
+    /// count towards the `max_restarts` of each, see
+    /// [`oriented_restart`](Self::oriented_restart).
+    ///
+    /// [`SimplexHealth`]: crate::diagnostics::SimplexHealth
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+    /// }
+    /// // the vertices lie on a line the simplex can never leave
+    /// let collapsed = vec![dvector![0.0, 0.0], dvector![1.0, 1.0], dvector![2.0, 2.0]];
+    ///
+    /// let stalled = NelderMead::builder()
+    ///     .minimize_from_simplex(&f, collapsed.clone())?;
+    /// let restarted = NelderMead::builder()
+    ///     .restart_on_degeneracy(1e8, 3)
+    ///     .minimize_from_simplex(&f, collapsed)?;
+    ///
+    /// assert!(stalled.best_score > 1.0);
+    /// assert!(restarted.restarts >= 1);
+    /// assert!(restarted.best_score < 1e-4);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn restart_on_degeneracy(mut self, max_condition: T, max_restarts: u64) -> Self {
+        self.config.degeneracy_restart = Some(DegeneracyRestart { max_condition, max_restarts });
+        self
+    }
+
+    /// Oriented restart of Kelley (1999): whenever the mean score of the
+    /// simplex decreases by less than `alpha` times the squared norm of
+    /// the simplex gradient of the previous iteration, the simplex is
+    /// rebuilt around its best vertex along the negative simplex gradient,
+    /// at most `max_restarts` times. Kelley uses `alpha = 1e-4`.
+    ///
+    /// This detects the stagnation of the simplex on a non-stationary
+    /// point, as on badly scaled objectives. Restarts of both kinds count
+    /// towards the `max_restarts` of each.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// // McKinnon (1998), the simplex converges to the non-stationary
+    /// // point (0, 0) while the minimum is at (0, -0.5)
+    /// fn mckinnon(x: &DVector<f64>) -> f64 {
+    ///     let scale = if x[0] <= 0.0 { 360.0 } else { 6.0 };
+    ///     scale * x[0].abs().powi(2) + x[1] + x[1].powi(2)
+    /// }
+    /// let sqrt33 = 33f64.sqrt();
+    /// let simplex = vec![
+    ///     dvector![0.0, 0.0],
+    ///     dvector![1.0, 1.0],
+    ///     dvector![(1.0 + sqrt33) / 8.0, (1.0 - sqrt33) / 8.0],
+    /// ];
+    ///
+    /// let stalled = NelderMead::builder()
+    ///     .disable_no_improv_break()
+    ///     .max_iter(200)
+    ///     .minimize_from_simplex(&mckinnon, simplex.clone())?;
+    /// let restarted = NelderMead::builder()
+    ///     .disable_no_improv_break()
+    ///     .max_iter(200)
+    ///     .oriented_restart(1e-4, 10)
+    ///     .minimize_from_simplex(&mckinnon, simplex)?;
+    ///
+    /// assert!(stalled.best_score > -1e-6);
+    /// assert!(restarted.restarts >= 1);
+    /// assert!((restarted.best_score + 0.25).abs() < 1e-6);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn oriented_restart(mut self, alpha: T, max_restarts: u64) -> Self {
+        self.config.oriented_restart = Some(OrientedRestart { alpha, max_restarts });
+        self
+    }
+
+    /// Polish the solution with at most `max_iter` iterations of BFGS,
+    /// estimating the gradients by central finite differences, `2n`
+    /// evaluations each. On a smooth objective this sharpens the answer of
+    /// the simplex by several digits for a few evaluations more.
+    ///
+    /// The polishing starts from the best vertex once the run converged or
+    /// ran out of iterations, with a first step of the size of the final
+    /// simplex, within what remains of `max_fun_evals` and
+    /// `max_duration`. It minimizes the same penalized score, keeps every
+    /// point within the bounds and counts its iterations and evaluations
+    /// towards those of the result. An improvement takes the place of the
+    /// worst vertex of the `final_simplex`, at its front. The observer,
+    /// the hooks, the cache and the history only see the simplex run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    /// use nelder_mead_optimizer::test_functions::rosenbrock;
+    ///
+    /// let plain = NelderMead::builder().minimize(rosenbrock, dvector![-1.2, 1.0])?;
+    /// let polished = NelderMead::builder()
+    ///     .bfgs_polish(50)
+    ///     .minimize(rosenbrock, dvector![-1.2, 1.0])?;
+    ///
+    /// assert!(plain.best_score > 1e-8);
+    /// assert!(polished.best_score < 1e-12);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn bfgs_polish(mut self, max_iter: u64) -> Self {
+        self.config.bfgs_polish = Some(max_iter);
+        self
+    }
+
+    /// Estimate the Hessian of the objective at the solution by central
+    /// finite differences, `2n² + 1` evaluations, and from it the
+    /// covariance and standard errors of the parameters, see
+    /// [`HessianEstimate`]. For a negative log-likelihood these are those
+    /// of the maximum likelihood estimates.
+    ///
+    /// The estimate is made once the run converged or ran out of
+    /// iterations, after any [`bfgs_polish`](Self::bfgs_polish), if its
+    /// evaluations fit in what remains of `max_fun_evals` and
+    /// `max_duration`. They count towards those of the result. The points
+    /// are not brought inside the bounds, so a solution on a bound gets
+    /// no estimate if the objective is not finite beyond it.
+    ///
+    /// [`HessianEstimate`]: crate::hessian::HessianEstimate
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// // negative log-likelihood of a normal sample in its mean and log
+    /// // standard deviation
+    /// let sample = [4.1, 5.3, 3.8, 6.0, 4.9, 5.6, 4.4, 5.1];
+    /// let nll = |x: &DVector<f64>| {
+    ///     let (mean, log_sd) = (x[0], x[1]);
+    ///     sample.iter().map(|y| log_sd + 0.5 * ((y - mean) / log_sd.exp()).powi(2)).sum::<f64>()
+    /// };
+    /// let results = NelderMead::builder()
+    ///     .bfgs_polish(20)
+    ///     .estimate_covariance(true)
+    ///     .minimize(nll, dvector![4.0, 0.0])?;
+    ///
+    /// // the standard error of the mean is the standard deviation of the
+    /// // sample over the square root of its size
+    /// let estimate = results.hessian.unwrap();
+    /// let sd = results.best_point[1].exp();
+    /// let errors = estimate.standard_errors.unwrap();
+    /// assert!((errors[0] - sd / 8f64.sqrt()).abs() < 1e-4);
+    /// assert!((errors[1] - 0.5f64.sqrt() / 8f64.sqrt()).abs() < 1e-4);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn estimate_covariance(mut self, estimate_covariance: bool) -> Self {
+        self.config.estimate_covariance = estimate_covariance;
+        self
+    }
+
+    /// Evaluate every point `count` times and score it with the
+    /// `aggregate` of its values, for objectives such as stochastic
+    /// simulations that return a different value on every call. A point
+    /// evaluated again pools the new values with the previous ones.
+    ///
+    /// The evaluations of the result and `max_fun_evals` count the
+    /// points, each of them costing `count` calls of the objective. The
+    /// replicates of a batch of points are evaluated as one batch. The
+    /// [`standard_error`] of the best score is recorded in the history.
+    /// The run fails with [`NelderMeadError::InvalidReplication`] if
+    /// `count` is zero, or if it is driven one iteration or one point at a
+    /// time, with [`iter`](Self::iter) or [`ask_tell`](Self::ask_tell),
+    /// which call the objective as given.
+    ///
+    /// [`standard_error`]: crate::history::HistoryEntry::standard_error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    /// use nelder_mead_optimizer::noise::Aggregate;
+    ///
+    /// // uniform noise in [-0.05, 0.05) from a linear congruential generator
+    /// let mut state = 7u64;
+    /// let mut noisy = move |x: &DVector<f64>| {
+    ///     state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    ///     let noise = (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+    ///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2) + 0.1 * noise
+    /// };
+    /// let results = NelderMead::builder()
+    ///     .step(1.0)
+    ///     .replicate(16, Aggregate::Mean)
+    ///     .record_history(true)
+    ///     .minimize(&mut noisy, dvector![0.0, 0.0])?;
+    /// let last = results.history.unwrap().entries.pop().unwrap();
+    ///
+    /// assert!((results.best_point[0] - 1.0).abs() < 0.2);
+    /// assert!((results.best_point[1] + 1.0).abs() < 0.2);
+    /// assert!(last.standard_error.unwrap() < 0.02);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn replicate(mut self, count: usize, aggregate: Aggregate) -> Self {
+        self.config.replication = Some(Replication {
+            count,
+            max_count: count,
+            target_error: None,
+            aggregate,
+        });
+        self
+    }
+
+    /// Evaluate every point at least `min_count` times, then again until
+    /// the standard error of the `aggregate` of its values falls to
+    /// `target_error` or the evaluation drew `max_count` values, see
+    /// [`replicate`](Self::replicate). The points are sampled more where
+    /// the objective is noisier, and the further values of a batch of
+    /// points are drawn one per point and round.
+    ///
+    /// The run fails with [`NelderMeadError::InvalidReplication`] if
+    /// `min_count` is zero or above `max_count`, or if `target_error` is
+    /// not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::cell::Cell;
+    ///
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    /// use nelder_mead_optimizer::noise::Aggregate;
+    ///
+    /// let calls = Cell::new(0u64);
+    /// let state = Cell::new(7u64);
+    /// let noisy = |x: &DVector<f64>| {
+    ///     calls.set(calls.get() + 1);
+    ///     state.set(state.get().wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407));
+    ///     let noise = (state.get() >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+    ///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2) + 0.1 * noise
+    /// };
+    /// let results = NelderMead::builder()
+    ///     .step(1.0)
+    ///     .replicate_adaptive(4, 64, 0.005, Aggregate::Median)
+    ///     .minimize(&noisy, dvector![0.0, 0.0])?;
+    ///
+    /// assert!((results.best_point[0] - 1.0).abs() < 0.2);
+    /// assert!(calls.get() >= 4 * results.evaluations);
+    /// assert!(calls.get() <= 64 * results.evaluations);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn replicate_adaptive(
+        mut self,
+        min_count: usize,
+        max_count: usize,
+        target_error: T,
+        aggregate: Aggregate,
+    ) -> Self {
+        self.config.replication = Some(Replication {
+            count: min_count,
+            max_count,
+            target_error: Some(target_error),
+            aggregate,
+        });
+        self
+    }
+
+    /// Evaluate the best vertex again every `every` iterations, or only
+    /// before stopping when `every` is zero, and rank it by its new score.
+    /// With a noisy objective the best vertex is often just a lucky draw,
+    /// which the simplex otherwise keeps forever.
+    ///
+    /// A run meeting its tolerances, its target value, its termination
+    /// criterion or its no-improvement rule only stops if the best vertex
+    /// is still the best one once evaluated again, and carries on
+    /// otherwise. The new score of the best vertex is also the new
+    /// reference of the no-improvement rule. With
+    /// [`replicate`](Self::replicate), the new values are pooled with the
+    /// previous ones, so the score of the best vertex gets more precise
+    /// with every evaluation. The cache is bypassed, and the evaluations
+    /// count towards `max_fun_evals`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// // the fifth evaluation is a lucky draw, far below the true value
+    /// fn outlier() -> impl FnMut(&DVector<f64>) -> f64 {
+    ///     let mut calls = 0;
+    ///     move |x| {
+    ///         calls += 1;
+    ///         let fluke = if calls == 5 { -100.0 } else { 0.0 };
+    ///         (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2) + fluke
+    ///     }
+    /// }
+    /// let fooled = NelderMead::builder()
+    ///     .minimize(outlier(), dvector![0.0, 0.0])?;
+    /// let results = NelderMead::builder()
+    ///     .reevaluate_best(0)
+    ///     .minimize(outlier(), dvector![0.0, 0.0])?;
+    ///
+    /// assert!(fooled.best_score < -90.0);
+    /// assert!(results.best_score >= 0.0);
+    /// assert!((results.best_point[0] - 1.0).abs() < 1e-2);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn reevaluate_best(mut self, every: u64) -> Self {
+        self.config.reevaluation = Some(every);
+        self
+    }
+
+    /// Stop once the scores of the best and the worst vertex cannot be told
+    /// apart from the noise of the objective, i.e. once their difference
+    /// is below `critical` times its standard error, `√(s_b² + s_w²)` from
+    /// the standard errors of both scores as in Welch's test. A `critical`
+    /// value of 2 stands for a significance level of about 5% once the
+    /// points are evaluated a few dozen times.
+    ///
+    /// The standard errors come from [`replicate`](Self::replicate), so
+    /// the test only applies with at least two replicates per point. The
+    /// run then stops on the test with [`TerminationReason::Converged`],
+    /// and neither the tolerances nor the no-improvement rule stop it
+    /// while the difference is significant. This avoids both stopping on
+    /// a lucky draw and running on as the simplex chases the noise. The
+    /// initial step should change the objective by more than its noise.
+    ///
+    /// The run fails with [`NelderMeadError::InvalidNoiseTest`] if
+    /// `critical` is not positive and finite.
+    ///
+    /// [`TerminationReason::Converged`]: crate::result::TerminationReason::Converged
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    /// use nelder_mead_optimizer::noise::Aggregate;
+    /// use nelder_mead_optimizer::result::TerminationReason;
+    ///
+    /// // uniform noise in [-0.05, 0.05) from a linear congruential generator
+    /// fn noisy() -> impl FnMut(&DVector<f64>) -> f64 {
+    ///     let mut state = 7u64;
+    ///     move |x| {
+    ///         state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    ///         let noise = (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+    ///         (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2) + 0.1 * noise
+    ///     }
+    /// }
+    /// let chasing = NelderMead::builder()
+    ///     .step(0.5)
+    ///     .replicate(16, Aggregate::Mean)
+    ///     .disable_no_improv_break()
+    ///     .minimize(noisy(), dvector![-2.0, 3.0])?;
+    /// let results = NelderMead::builder()
+    ///     .step(0.5)
+    ///     .replicate(16, Aggregate::Mean)
+    ///     .disable_no_improv_break()
+    ///     .noise_aware_termination(2.0)
+    ///     .minimize(noisy(), dvector![-2.0, 3.0])?;
+    ///
+    /// assert_eq!(chasing.termination_reason, TerminationReason::MaxIter);
+    /// assert_eq!(results.termination_reason, TerminationReason::Converged);
+    /// assert!(results.evaluations < chasing.evaluations / 10);
+    /// assert!((results.best_point[0] - 1.0).abs() < 0.2);
+    /// assert!((results.best_point[1] + 1.0).abs() < 0.2);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn noise_aware_termination(mut self, critical: T) -> Self {
+        self.config.noise_test = Some(critical);
+        self
+    }
+
+    /// Jitter the reflected point while all the vertices score the same,
+    /// adding to every coordinate a normal deviate of standard deviation
+    /// `magnitude` times the length of the reflection. The deviates are
+    /// drawn from `seed` and the iteration, so runs are reproducible.
+    ///
+    /// On a plateau of the objective, the deterministic simplex rejects
+    /// every trial point and shrinks until the no-improvement rule stops
+    /// it. With jitter, the vertex reflected is drawn at random, and a
+    /// reflected point scoring the same as the vertices replaces it
+    /// instead of shrinking the simplex, so that the simplex wanders
+    /// across the plateau until it finds lower ground. This applies to
+    /// the reflection of a single worst vertex, and the no-improvement
+    /// rule should allow enough iterations for the walk.
+    ///
+    /// The run fails with [`NelderMeadError::InvalidPlateauJitter`] if
+    /// `magnitude` is not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// // flat outside a well of radius 2 around the origin
+    /// fn well(x: &DVector<f64>) -> f64 {
+    ///     (x.norm_squared() - 4.0).min(0.0)
+    /// }
+    ///
+    /// let stuck = NelderMead::builder()
+    ///     .step(0.5)
+    ///     .minimize(well, dvector![3.0, 0.5])?;
+    /// let results = NelderMead::builder()
+    ///     .step(0.5)
+    ///     .no_improv_break(100)
+    ///     .plateau_jitter(0.3, 0)
+    ///     .minimize(well, dvector![3.0, 0.5])?;
+    ///
+    /// assert_eq!(stuck.best_score, 0.0);
+    /// assert!((results.best_score + 4.0).abs() < 1e-6);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn plateau_jitter(mut self, magnitude: T, seed: u64) -> Self {
+        self.config.plateau_jitter = Some(PlateauJitter { magnitude, seed });
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> NelderMead<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the optimizer,
+    /// see [`NelderMead::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the optimizer,
+    /// see [`NelderMead::minimize_from_simplex`].
+    pub fn minimize_from_simplex<F>(
+        self,
+        obj_fn: F,
+        simplex: Vec<DVector<T>>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize_from_simplex(obj_fn, simplex)
+    }
+
+    /// Finishes the configuration and immediately runs the optimizer,
+    /// see [`NelderMead::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the optimizer,
+    /// see [`NelderMead::try_minimize`].
+    pub fn try_minimize<F, R>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> R,
+        R: FallibleScore<T>,
+    {
+        self.build().try_minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the optimizer,
+    /// see [`NelderMead::minimize_slice`].
+    pub fn minimize_slice<F>(
+        self,
+        obj_fn: F,
+        x_start: &[T],
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&[T]) -> T,
+    {
+        self.build().minimize_slice(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the optimizer,
+    /// see [`NelderMead::minimize_batch`].
+    pub fn minimize_batch<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&[DVector<T>]) -> Vec<T>,
+    {
+        self.build().minimize_batch(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the optimizer,
+    /// see [`NelderMead::minimize_seeded`].
+    pub fn minimize_seeded<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+        seed: u64,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>, u64) -> T,
+    {
+        self.build().minimize_seeded(obj_fn, x_start, seed)
+    }
+
+    /// Finishes the configuration and immediately runs the optimizer,
+    /// see [`NelderMead::minimize_parallel`].
+    #[cfg(feature = "rayon")]
+    pub fn minimize_parallel<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        T: Send + Sync,
+        F: Fn(&DVector<T>) -> T + Sync,
+    {
+        self.build().minimize_parallel(obj_fn, x_start)
+    }
+}
+
+#[cfg(feature = "checkpoint")]
+impl<T> NelderMeadBuilder<T>
+where
+    T: RealField + Copy + serde::Serialize + serde::de::DeserializeOwned,
+{
+    /// Save a [`Checkpoint`] of the run to the JSON file at `path` every
+    /// `every` iterations, to be resumed with
+    /// [`NelderMead::resume_from_checkpoint`] after the run was preempted.
+    /// A failure to save stops the run with
+    /// [`NelderMeadError::Checkpoint`]. Only the runs driven by a
+    /// [`NelderMeadIter`](crate::nelder_mead_iter::NelderMeadIter), such as
+    /// [`NelderMead::minimize`], are checkpointed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::checkpoint::Checkpoint;
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+    /// }
+    /// let path = std::env::temp_dir().join("nelder_mead_checkpoint_doctest.json");
+    /// let optimizer = NelderMead::builder()
+    ///     .checkpoint_file(&path, 5)
+    ///     .build();
+    /// let results = optimizer.minimize(&f, dvector![0.0, 0.0])?;
+    ///
+    /// // after a preemption, the run continues from the last checkpoint
+    /// let checkpoint = Checkpoint::load(&path)?;
+    /// let resumed = optimizer.resume_from_checkpoint(&f, checkpoint)?.run()?;
+    ///
+    /// assert_eq!(resumed.best_point, results.best_point);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn checkpoint_file<P: Into<PathBuf>>(mut self, path: P, every: u64) -> Self {
+        let path = path.into();
+        self.config.checkpoint = Some(CheckpointSaver {
+            every: every.max(1),
+            save: Arc::new(move |checkpoint: &Checkpoint<T>| checkpoint.save(&path)),
+        });
+        self
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use nalgebra::RealField;
+
+/// How often the evaluation cache configured with
+/// [`NelderMeadBuilder::cache_evaluations`] served a point from memory.
+///
+/// [`NelderMeadBuilder::cache_evaluations`]: crate::builder::NelderMeadBuilder::cache_evaluations
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+///
+/// // the objective only depends on the rounded parameters
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0].round() - 3.0).powi(2) + (x[1].round() + 2.0).powi(2)
+/// }
+/// let results = NelderMead::builder()
+///     .step(2.0)
+///     .cache_evaluations(1.0)
+///     .minimize(&f, dvector![0.0, 0.0])?;
+/// let stats = results.cache.unwrap();
+///
+/// assert_eq!(stats.misses, results.evaluations);
+/// assert!(stats.hit_rate() > 0.5);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheStats {
+    /// Number of points served from the cache.
+    pub hits: u64,
+    /// Number of points evaluated by the objective function, and stored.
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Share of the points that were served from the cache, zero if no
+    /// point was looked up.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            lookups => self.hits as f64 / lookups as f64,
+        }
+    }
+}
+
+/// Objective values by point, the coordinates being rounded to multiples
+/// of `resolution`, or taken as is when it is zero.
+#[derive(Debug, Clone)]
+pub(crate) struct EvaluationCache<T> {
+    resolution: T,
+    values: HashMap<Vec<u64>, T>,
+    pub(crate) stats: CacheStats,
+}
+
+impl<T: RealField + Copy> EvaluationCache<T> {
+    pub(crate) fn new(resolution: T) -> Self {
+        EvaluationCache {
+            resolution,
+            values: HashMap::new(),
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Value stored for the point `x`, counted as a hit, or `None`,
+    /// counted as a miss.
+    pub(crate) fn get(&mut self, x: &[T]) -> Option<T> {
+        let value = self.values.get(&self.key(x)).copied();
+        match value {
+            Some(_) => self.stats.hits += 1,
+            None => self.stats.misses += 1,
+        }
+        value
+    }
+
+    /// Stores the value of the point `x`, keeping the first value of a
+    /// rounded point.
+    pub(crate) fn insert(&mut self, x: &[T], value: T) {
+        self.values.entry(self.key(x)).or_insert(value);
+    }
+
+    fn key(&self, x: &[T]) -> Vec<u64> {
+        point_key(x, self.resolution)
+    }
+}
+
+/// Key of the point `x`, its coordinates rounded to multiples of
+/// `resolution`, or taken as is when it is zero.
+pub(crate) fn point_key<T: RealField + Copy>(x: &[T], resolution: T) -> Vec<u64> {
+    x.iter()
+        .map(|xi| {
+            let xi = if resolution > T::zero() { (*xi / resolution).round() } else { *xi };
+            let xi: f64 = xi.to_subset().unwrap_or(f64::NAN);
+            // adding zero turns -0 into 0, so that both share a key
+            (xi + 0.0).to_bits()
+        })
+        .collect()
+}
@@ -0,0 +1,195 @@
+#[cfg(feature = "checkpoint")]
+use std::fs::{self, File};
+#[cfg(feature = "checkpoint")]
+use std::io::{BufReader, BufWriter, Write};
+use std::fmt;
+#[cfg(feature = "checkpoint")]
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use nalgebra::{DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::history::HistoryEntry;
+use crate::nelder_mead::NelderMead;
+use crate::nelder_mead_iter::NelderMeadIter;
+use crate::observer::Move;
+use crate::result::TerminationReason;
+
+/// State of a [`NelderMeadIter`] run, taken with
+/// [`NelderMeadIter::checkpoint`], from which the run continues where it
+/// stopped with [`NelderMead::resume_from_checkpoint`].
+///
+/// The configuration is not part of the checkpoint, the run has to be
+/// resumed with the same one. Neither are the evaluation cache and the
+/// timing statistics, see [`NelderMead::resume_from_checkpoint`]. With
+/// the `serde` feature the checkpoint can be serialized, the `checkpoint`
+/// feature adds `Checkpoint::save` and `Checkpoint::load` to keep it in a
+/// JSON file, as well as periodic saving with
+/// `NelderMeadBuilder::checkpoint_file`.
+///
+/// Scores are stored in the direction of the objective, i.e. they are not
+/// negated when maximizing. When the bounds are enforced by a transform,
+/// see [`BoundsHandling`], or parameters are transformed, see
+/// [`Transform`], or rescaled, the vertices are stored in the internal
+/// coordinates.
+///
+/// [`BoundsHandling`]: crate::nelder_mead::BoundsHandling
+/// [`Transform`]: crate::transform::Transform
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// }
+/// let optimizer = NelderMead::builder().build();
+///
+/// // the run is preempted after 10 iterations
+/// let mut iter = optimizer.iter(&f, dvector![0.0, 0.0])?;
+/// for state in iter.by_ref().take(10) {
+///     state?;
+/// }
+/// let checkpoint = iter.checkpoint();
+///
+/// let resumed = optimizer.resume_from_checkpoint(&f, checkpoint)?.run()?;
+/// let uninterrupted = optimizer.minimize(&f, dvector![0.0, 0.0])?;
+///
+/// assert_eq!(resumed, uninterrupted);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct Checkpoint<T = f64> {
+    /// Vertices of the simplex with their scores, ordered from the best one.
+    pub simplex: Vec<(DVector<T>, T)>,
+    /// Number of iterations completed so far.
+    pub iterations: u64,
+    /// Number of objective function evaluations so far.
+    pub evaluations: u64,
+    /// Number of times the simplex was rebuilt so far.
+    pub restarts: u64,
+    /// Mean score and squared simplex gradient norm of the previous
+    /// simplex, for the sufficient decrease test of the oriented restart.
+    pub decrease_reference: Option<(T, T)>,
+    /// Wall-clock time spent in the run so far, counted against
+    /// `max_duration` once resumed.
+    pub elapsed: Duration,
+    /// Best score the no-improvement criterion compares against.
+    pub prev_best: T,
+    /// Number of consecutive iterations without improvement.
+    pub no_improv: u64,
+    /// Operation performed by the last iteration.
+    pub last_move: Option<Move>,
+    /// Why the run stopped, `None` if it was still going.
+    pub termination_reason: Option<TerminationReason>,
+    /// Iterations recorded so far, when `record_history` is enabled.
+    pub history: Vec<HistoryEntry<T>>,
+    /// Scales of the internal coordinates under automatic scaling, see
+    /// [`NelderMeadBuilder::auto_scale`].
+    ///
+    /// [`NelderMeadBuilder::auto_scale`]: crate::builder::NelderMeadBuilder::auto_scale
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub scales: Option<DVector<T>>,
+    /// Running sum of the vertices the centroid is computed from, `None`
+    /// when it is recomputed at the next iteration.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub vertex_sum: Option<DVector<T>>,
+}
+
+impl<T: RealField + Copy> NelderMead<T> {
+    /// Continues a run from a [`Checkpoint`] taken with this
+    /// configuration, see [`NelderMeadIter::run`] to run it to the end.
+    ///
+    /// The evaluation cache and the timing statistics are not saved in the
+    /// checkpoint: the resumed run starts with an empty cache under
+    /// [`NelderMeadBuilder::cache_evaluations`], so points evaluated before
+    /// the checkpoint may be evaluated again, and under
+    /// [`NelderMeadBuilder::record_timing`] its timing only covers the work
+    /// done since it was resumed. The wall-clock time counted against
+    /// `max_duration` is kept.
+    ///
+    /// Fails with [`NelderMeadError::InvalidSimplex`] if the simplex of the
+    /// checkpoint does not contain `n + 1` vertices of the same dimension
+    /// `n >= 1`.
+    ///
+    /// [`NelderMeadBuilder::cache_evaluations`]: crate::builder::NelderMeadBuilder::cache_evaluations
+    /// [`NelderMeadBuilder::record_timing`]: crate::builder::NelderMeadBuilder::record_timing
+    pub fn resume_from_checkpoint<F>(
+        &self,
+        obj_fn: F,
+        checkpoint: Checkpoint<T>,
+    ) -> Result<NelderMeadIter<T, F>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        NelderMeadIter::resume(self.clone(), obj_fn, checkpoint)
+    }
+}
+
+#[cfg(feature = "checkpoint")]
+impl<T> Checkpoint<T>
+where
+    T: RealField + Copy + serde::Serialize + serde::de::DeserializeOwned,
+{
+    /// Writes the checkpoint to a JSON file at `path`. The file is written
+    /// next to `path` first and then renamed, so a run preempted while
+    /// saving never leaves a truncated checkpoint behind.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), NelderMeadError> {
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+
+        let mut writer = BufWriter::new(File::create(&tmp).map_err(checkpoint_error)?);
+        serde_json::to_writer(&mut writer, self).map_err(checkpoint_error)?;
+        writer.flush().map_err(checkpoint_error)?;
+        drop(writer);
+        fs::rename(&tmp, path).map_err(checkpoint_error)
+    }
+
+    /// Reads a checkpoint written by [`Checkpoint::save`].
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, NelderMeadError> {
+        let reader = BufReader::new(File::open(path).map_err(checkpoint_error)?);
+        serde_json::from_reader(reader).map_err(checkpoint_error)
+    }
+}
+
+#[cfg(feature = "checkpoint")]
+fn checkpoint_error<E: fmt::Display>(err: E) -> NelderMeadError {
+    NelderMeadError::Checkpoint(err.to_string())
+}
+
+/// Function saving a checkpoint.
+pub(crate) type SaveFn<T> = dyn Fn(&Checkpoint<T>) -> Result<(), NelderMeadError> + Send + Sync;
+
+/// Periodic saving of the checkpoint, stored in the optimizer
+/// configuration.
+#[derive(Clone)]
+pub(crate) struct CheckpointSaver<T> {
+    pub(crate) every: u64,
+    pub(crate) save: Arc<SaveFn<T>>,
+}
+
+impl<T> fmt::Debug for CheckpointSaver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CheckpointSaver {{ every: {} }}", self.every)
+    }
+}
+
+impl<T> PartialEq for CheckpointSaver<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.every == other.every && Arc::ptr_eq(&self.save, &other.save)
+    }
+}
@@ -0,0 +1,585 @@
+use std::collections::VecDeque;
+use std::iter;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DMatrix, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::logging;
+use crate::nelder_mead::{is_nan, total_cmp, Direction, NanHandling, StepSize};
+use crate::objective::ObjectiveFunction;
+use crate::observer::{Observer, SharedObserver};
+use crate::progress::Budget;
+use crate::random::Rng;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationCriterion, TerminationState};
+
+/// Hansen's CMA-ES (Covariance Matrix Adaptation Evolution Strategy), which
+/// samples every generation from a multivariate normal distribution and
+/// adapts its mean, step size and covariance matrix to the best samples.
+///
+/// The covariance matrix learns the scaling and the correlations of the
+/// parameters, so that ill-conditioned and non-separable problems are as
+/// easy as a sphere once it is adapted, while the sampling keeps looking
+/// beyond the nearest local minimum of multi-modal problems, where the
+/// simplex of Nelder-Mead gets stuck. With `max_restarts`, a run which
+/// converged starts again from `x_start` with twice the population (IPOP),
+/// which finds the global minimum of many multi-modal problems. The run is
+/// reproducible with [`CmaEsBuilder::seed`].
+///
+/// The run converges once the standard deviation of the distribution along
+/// every parameter is below `xtol`, or once the scores of a generation and
+/// the best scores of the recent generations all lie within `ftol`. Runs
+/// are reported as an [`OptimizationResult`], whose `iterations` are the
+/// generations of every restart and whose `final_simplex` holds the best
+/// point found and the last `n` other points sampled. The [`Observer`] and
+/// the [`TerminationCriterion`] are given the same vertices after every
+/// generation.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::DVector;
+/// use nelder_mead_optimizer::cma_es::CmaEs;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::test_functions::rastrigin;
+///
+/// let x_start = DVector::from_element(5, 3.0);
+/// let cma_es = CmaEs::builder()
+///     .step(2.0)
+///     .max_restarts(4)
+///     .seed(7)
+///     .minimize(rastrigin, x_start.clone())?;
+/// let plain = NelderMead::builder().minimize(rastrigin, x_start)?;
+///
+/// assert!(cma_es.best_score < 1e-8);
+/// assert!(plain.best_score > 1.0);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct CmaEs<T = f64> {
+    pub(crate) step: StepSize<T>,
+    pub(crate) population_size: Option<usize>,
+    pub(crate) max_restarts: u64,
+    pub(crate) seed: Option<u64>,
+    pub(crate) xtol: T,
+    pub(crate) ftol: T,
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) bounds: Option<(DVector<T>, DVector<T>)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for CmaEs<T> {
+    fn default() -> Self {
+        CmaEs {
+            step: StepSize::Uniform(convert(0.3)),
+            population_size: None,
+            max_restarts: 0,
+            seed: None,
+            xtol: convert(1e-11),
+            ftol: convert(1e-12),
+            max_iter: 10_000,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            bounds: None,
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`CmaEs`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `step`                  | 0.3                  |
+/// | `population_size`       | 4 + 3 ln(n)          |
+/// | `max_restarts`          | 0                    |
+/// | `seed`                  | random               |
+/// | `xtol`                  | 1e-11                |
+/// | `ftol`                  | 1e-12                |
+/// | `max_iter`              | 10000                |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `bounds`                | none                 |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct CmaEsBuilder<T = f64> {
+    config: CmaEs<T>,
+}
+
+impl<T: RealField + Copy> Default for CmaEsBuilder<T> {
+    fn default() -> Self {
+        CmaEsBuilder {
+            config: CmaEs::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> CmaEsBuilder<T> {
+    /// Initial standard deviation of the distribution along every
+    /// parameter, either a single value used for every dimension or a
+    /// [`StepSize`]. About a third of the region expected to hold the
+    /// minimum is a good choice.
+    pub fn step(mut self, step: impl Into<StepSize<T>>) -> Self {
+        self.config.step = step.into();
+        self
+    }
+
+    /// Number of points sampled per generation, at least 2. Larger
+    /// populations search multi-modal problems more globally, at the cost
+    /// of more evaluations per generation.
+    pub fn population_size(mut self, population_size: usize) -> Self {
+        self.config.population_size = Some(population_size);
+        self
+    }
+
+    /// Maximum number of restarts after the run converged, each from
+    /// `x_start` with twice the population of the previous one.
+    pub fn max_restarts(mut self, max_restarts: u64) -> Self {
+        self.config.max_restarts = max_restarts;
+        self
+    }
+
+    /// Seed of the random numbers, making the run reproducible. Without a
+    /// seed every run draws different numbers.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Standard deviation of the distribution below which the run
+    /// converges.
+    pub fn xtol(mut self, xtol: T) -> Self {
+        self.config.xtol = xtol;
+        self
+    }
+
+    /// Spread of the recent scores below which the run converges.
+    pub fn ftol(mut self, ftol: T) -> Self {
+        self.config.ftol = ftol;
+        self
+    }
+
+    /// Maximum number of generations, summed over the restarts.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Lower and upper bounds of every parameter. The starting point and
+    /// every sample are clipped into them, like under
+    /// [`BoundsHandling::Clip`].
+    ///
+    /// [`BoundsHandling::Clip`]: crate::nelder_mead::BoundsHandling::Clip
+    pub fn bounds(mut self, lower: DVector<T>, upper: DVector<T>) -> Self {
+        self.config.bounds = Some((lower, upper));
+        self
+    }
+
+    /// Additional stopping rule, checked after every generation next to
+    /// the built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every generation with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> CmaEs<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`CmaEs::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`CmaEs::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, x_start)
+    }
+}
+
+/// Evaluations of a run, with the budgets they are bounded by, and the
+/// points reported in its state.
+struct Run<'a, T, F> {
+    config: &'a CmaEs<T>,
+    obj_fn: F,
+    sign: T,
+    evaluations: u64,
+    iterations: u64,
+    start: Instant,
+    // best point found, and the last `n` other points sampled
+    best: (DVector<T>, T),
+    recent: VecDeque<(DVector<T>, T)>,
+}
+
+impl<T: RealField + Copy, F: FnMut(&DVector<T>) -> T> Run<'_, T, F> {
+    /// Score of `x`, or `None` when the evaluation budget or the time ran
+    /// out.
+    fn score(&mut self, x: &DVector<T>) -> Result<Option<T>, NelderMeadError> {
+        if self.exhausted().is_some() {
+            return Ok(None);
+        }
+        let score = self.evaluate(x)?;
+        let mut point = (x.clone(), score);
+        if score < self.best.1 {
+            // the previous best point becomes the latest one sampled
+            point = std::mem::replace(&mut self.best, point);
+        }
+        self.recent.pop_front();
+        self.recent.push_back(point);
+        Ok(Some(score))
+    }
+
+    /// Score of `x`, regardless of the budgets.
+    fn evaluate(&mut self, x: &DVector<T>) -> Result<T, NelderMeadError> {
+        self.evaluations += 1;
+        let value = (self.obj_fn)(x);
+        self.config.nan_handling.score(value, self.sign)
+    }
+
+    /// Why the run has to stop before the next evaluation, if it does.
+    fn exhausted(&self) -> Option<TerminationReason> {
+        if self.config.max_fun_evals.is_some_and(|limit| self.evaluations >= limit) {
+            return Some(TerminationReason::MaxFunEvals);
+        }
+        if self.config.max_duration.is_some_and(|limit| self.start.elapsed() >= limit) {
+            return Some(TerminationReason::TimedOut);
+        }
+        None
+    }
+
+    /// Simplex of the best point and the last points sampled, ordered from
+    /// the best one.
+    fn simplex(&self) -> Simplex<T> {
+        let points = iter::once(&self.best).chain(&self.recent);
+        let mut simplex = Simplex::from_slices(points.map(|(x, score)| (x.as_slice(), *score)));
+        simplex.sort();
+        simplex
+    }
+}
+
+impl<T: RealField + Copy> CmaEs<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> CmaEsBuilder<T> {
+        CmaEsBuilder::default()
+    }
+
+    /// Finds the minimum (or maximum, depending on the configured
+    /// [`Direction`]) of the objective function, sampling around
+    /// `x_start`.
+    ///
+    /// Fails if `x_start` is empty, if `xtol` or `ftol` is not positive and
+    /// finite, if the population is smaller than 2, if the step or the
+    /// bounds do not fit `x_start`, or if the objective function returns
+    /// NaN under [`NanHandling::Error`].
+    pub fn minimize<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let dim = x_start.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        if ![self.xtol, self.ftol].iter().all(|tol| T::zero() < *tol && tol.is_finite()) {
+            return Err(NelderMeadError::InvalidTolerances);
+        }
+        if self.population_size.is_some_and(|size| size < 2) {
+            return Err(NelderMeadError::InvalidPopulation);
+        }
+        self.step.check_dim(dim)?;
+        if let Some((lower, upper)) = &self.bounds {
+            if lower.len() != dim
+                || upper.len() != dim
+                || lower.iter().zip(upper.iter()).any(|(l, u)| l > u || is_nan(*l) || is_nan(*u))
+            {
+                return Err(NelderMeadError::InvalidBounds);
+            }
+        }
+
+        let x_start = self.clip(x_start);
+        let mut run = Run {
+            config: self,
+            obj_fn,
+            sign: self.direction.sign(),
+            evaluations: 0,
+            iterations: 0,
+            start: Instant::now(),
+            best: (x_start.clone(), T::zero()),
+            recent: VecDeque::new(),
+        };
+        let score = run.evaluate(&x_start)?;
+        run.best.1 = score;
+        run.recent = iter::repeat_n((x_start.clone(), score), dim).collect();
+        let mut rng = Rng::seeded(self.seed);
+        let mut population = self
+            .population_size
+            .unwrap_or(4 + (3.0 * (dim as f64).ln()).floor() as usize);
+        let mut restarts = 0;
+
+        let termination_reason = loop {
+            let reason = self.search(&mut run, &mut rng, &x_start, population)?;
+            if reason != TerminationReason::Converged || restarts >= self.max_restarts {
+                break reason;
+            }
+            restarts += 1;
+            population *= 2;
+        };
+
+        Ok(OptimizationResult::from_simplex(
+            run.simplex().to_pairs(T::one()),
+            run.sign,
+            run.iterations,
+            run.evaluations,
+            restarts,
+            termination_reason,
+            None,
+        ))
+    }
+
+    /// Same as [`CmaEs::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), x_start)
+    }
+
+    /// Runs the evolution strategy from `x_start` with `population` points
+    /// per generation, until it converged or a stopping rule applies.
+    fn search<F>(
+        &self,
+        run: &mut Run<'_, T, F>,
+        rng: &mut Rng,
+        x_start: &DVector<T>,
+        population: usize,
+    ) -> Result<TerminationReason, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let dim = x_start.len();
+        let n: T = convert(dim as f64);
+        let one = T::one();
+        let two: T = convert(2.0);
+
+        // weights of the best half of the population, from the best one
+        let parents = population / 2;
+        let log_half: T = convert(((population + 1) as f64 / 2.0).ln());
+        let mut weights: Vec<T> = (1..=parents).map(|i| log_half - convert::<f64, T>((i as f64).ln())).collect();
+        let total = weights.iter().fold(T::zero(), |sum, w| sum + *w);
+        weights.iter_mut().for_each(|w| *w /= total);
+        let mu_eff = one / weights.iter().fold(T::zero(), |sum, w| sum + *w * *w);
+
+        // learning rates of the step size, the evolution paths and the
+        // covariance matrix
+        let c_sigma = (mu_eff + two) / (n + mu_eff + convert(5.0));
+        let d_sigma = one + two * T::zero().max(((mu_eff - one) / (n + one)).sqrt() - one) + c_sigma;
+        let c_c = (convert::<f64, T>(4.0) + mu_eff / n) / (n + convert(4.0) + two * mu_eff / n);
+        let c_1 = two / ((n + convert(1.3)).powi(2) + mu_eff);
+        let c_mu = (one - c_1).min(two * (mu_eff - two + one / mu_eff) / ((n + two).powi(2) + mu_eff));
+        // expected length of a standard normal vector
+        let chi_n = n.sqrt() * (one - one / (convert::<f64, T>(4.0) * n) + one / (convert::<f64, T>(21.0) * n * n));
+
+        // the initial steps are folded into the covariance matrix, so that
+        // the step size starts at 1
+        let steps = DVector::from_fn(dim, |i, _| self.step.offset(i, x_start[i]).abs());
+        let mut mean = x_start.clone();
+        let mut sigma = one;
+        let mut covariance = DMatrix::from_diagonal(&steps.map(|s| s * s));
+        let mut basis = DMatrix::identity(dim, dim);
+        let mut scales = steps;
+        let mut path_sigma = DVector::zeros(dim);
+        let mut path_c = DVector::zeros(dim);
+        let history_len = 10 + (30 * dim).div_ceil(population);
+        let mut history: VecDeque<T> = VecDeque::with_capacity(history_len);
+        let mut generation = 0;
+
+        loop {
+            if run.iterations >= self.max_iter {
+                return Ok(TerminationReason::MaxIter);
+            }
+            if let Some(reason) = run.exhausted() {
+                return Ok(reason);
+            }
+
+            let mut samples = Vec::with_capacity(population);
+            for _ in 0..population {
+                let z = DVector::from_fn(dim, |_, _| convert::<f64, T>(rng.normal()));
+                let x = self.clip(&mean + &basis * scales.component_mul(&z) * sigma);
+                let Some(score) = run.score(&x)? else {
+                    return Ok(run.exhausted().unwrap_or(TerminationReason::MaxFunEvals));
+                };
+                samples.push(((&x - &mean) / sigma, score));
+            }
+            run.iterations += 1;
+            generation += 1;
+            samples.sort_by(|a, b| total_cmp(a.1, b.1));
+
+            // move the mean to the weighted best samples
+            let step = samples
+                .iter()
+                .zip(&weights)
+                .fold(DVector::zeros(dim), |sum: DVector<T>, ((y, _), w)| sum + y * *w);
+            mean += &step * sigma;
+
+            // evolution paths, the first one whitened by C^(-1/2)
+            let whitened = &basis * (basis.tr_mul(&step).component_div(&scales));
+            path_sigma = &path_sigma * (one - c_sigma) + whitened * (c_sigma * (two - c_sigma) * mu_eff).sqrt();
+            let decay = one - (one - c_sigma).powi(2 * generation);
+            let stalled = path_sigma.norm() / decay.sqrt() >= (convert::<f64, T>(1.4) + two / (n + one)) * chi_n;
+            let h_sigma = if stalled { T::zero() } else { one };
+            path_c = &path_c * (one - c_c) + &step * (h_sigma * (c_c * (two - c_c) * mu_eff).sqrt());
+
+            // rank-one and rank-mu updates of the covariance matrix
+            let rank_one = &path_c * path_c.transpose() + &covariance * ((one - h_sigma) * c_c * (two - c_c));
+            let rank_mu = samples
+                .iter()
+                .zip(&weights)
+                .fold(DMatrix::zeros(dim, dim), |sum: DMatrix<T>, ((y, _), w)| sum + y * y.transpose() * *w);
+            covariance = &covariance * (one - c_1 - c_mu) + rank_one * c_1 + rank_mu * c_mu;
+            covariance = (&covariance + covariance.transpose()) * convert::<f64, T>(0.5);
+            sigma *= ((c_sigma / d_sigma) * (path_sigma.norm() / chi_n - one)).exp();
+
+            let eigen = covariance.clone().symmetric_eigen();
+            let floor = T::default_epsilon() * eigen.eigenvalues.amax();
+            scales = eigen.eigenvalues.map(|value| value.max(floor).sqrt());
+            basis = eigen.eigenvectors;
+
+            let simplex = run.simplex();
+            let state = TerminationState {
+                iterations: run.iterations,
+                evaluations: run.evaluations,
+                elapsed: run.start.elapsed(),
+                simplex: &simplex,
+                sign: run.sign,
+                last_move: None,
+                budget: Budget {
+                    max_iter: self.max_iter,
+                    max_fun_evals: self.max_fun_evals,
+                    max_duration: self.max_duration,
+                },
+            };
+            logging::log_iteration(&state);
+            if let Some(observer) = &self.observer {
+                observer.0.observe(&state);
+            }
+            if let Some(criterion) = &self.termination {
+                if criterion.0.should_stop(&state) {
+                    return Ok(TerminationReason::Criterion);
+                }
+            }
+
+            // converged once the distribution or the scores are flat
+            let deviation = (0..dim).fold(T::zero(), |max, i| max.max(covariance[(i, i)].sqrt())) * sigma;
+            if history.len() == history_len {
+                history.pop_front();
+            }
+            history.push_back(samples[0].1);
+            let spread = |scores: &mut dyn Iterator<Item = T>| {
+                let (low, high) = scores.fold((samples[0].1, samples[0].1), |(low, high), s| (low.min(s), high.max(s)));
+                high - low
+            };
+            let flat = history.len() == history_len
+                && spread(&mut samples.iter().map(|(_, score)| *score)) < self.ftol
+                && spread(&mut history.iter().copied()) < self.ftol;
+            if deviation < self.xtol || flat {
+                return Ok(TerminationReason::Converged);
+            }
+        }
+    }
+
+    /// Copy of `x` clipped into the bounds.
+    fn clip(&self, x: DVector<T>) -> DVector<T> {
+        match &self.bounds {
+            Some((lower, upper)) => x.sup(lower).inf(upper),
+            None => x,
+        }
+    }
+}
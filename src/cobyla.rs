@@ -0,0 +1,848 @@
+use std::iter;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DMatrix, DVector, RealField};
+
+use crate::constraints::{Constraint, SharedConstraint};
+use crate::error::NelderMeadError;
+use crate::logging;
+use crate::nelder_mead::{is_nan, Direction, NanHandling};
+use crate::objective::ObjectiveFunction;
+use crate::observer::{Observer, SharedObserver};
+use crate::progress::Budget;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationCriterion, TerminationState};
+
+/// Powell's COBYLA (Constrained Optimization BY Linear Approximations), a
+/// derivative-free solver for problems with inequality constraints
+/// `g(x) <= 0`, equality constraints `h(x) = 0` and bounds.
+///
+/// It interpolates the objective and every constraint linearly on a
+/// simplex of `n + 1` points and moves to the point minimizing the linear
+/// models within a trust region of radius `rho`, which shrinks from
+/// `rho_begin` to `rho_end`. Unlike penalties added to the Nelder-Mead
+/// score, the constraints are modelled individually, so that the solver
+/// follows the boundary of the feasible region instead of bouncing on it.
+/// Steps are accepted according to the merit function
+/// `f(x) + mu * max(0, max_i g_i(x))`, `mu` growing as needed.
+///
+/// Bounds and equality constraints are handled as pairs of inequality
+/// constraints, so points slightly outside of them can be evaluated while
+/// the models are built. Runs are reported as an [`OptimizationResult`],
+/// whose `final_simplex` holds the interpolation points ordered by merit
+/// with their objective values and whose `constraint_violation` includes
+/// the bounds. The [`Observer`] and the [`TerminationCriterion`] are given
+/// the state of the run after every iteration, in which the simplex is
+/// made of the interpolation points in the same order.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::cobyla::Cobyla;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 2.0).powi(2) + (x[1] - 2.0).powi(2)
+/// }
+/// // inside of the unit disk, the constrained optimum is (1, 1) / sqrt(2)
+/// let results = Cobyla::builder()
+///     .constraint(|x: &DVector<f64>| x[0].powi(2) + x[1].powi(2) - 1.0)
+///     .rho_begin(0.5)
+///     .rho_end(1e-8)
+///     .minimize(&f, dvector![0.0, 0.0])?;
+///
+/// let optimum = std::f64::consts::FRAC_1_SQRT_2;
+/// assert!((results.best_point[0] - optimum).abs() < 1e-6);
+/// assert!((results.best_point[1] - optimum).abs() < 1e-6);
+/// assert!(results.constraint_violation.unwrap() < 1e-8);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct Cobyla<T = f64> {
+    pub(crate) rho_begin: T,
+    pub(crate) rho_end: T,
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) bounds: Option<(DVector<T>, DVector<T>)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) constraints: Vec<SharedConstraint<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) equality_constraints: Vec<SharedConstraint<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for Cobyla<T> {
+    fn default() -> Self {
+        Cobyla {
+            rho_begin: convert(0.5),
+            rho_end: convert(1e-6),
+            max_iter: 1000,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            bounds: None,
+            constraints: Vec::new(),
+            equality_constraints: Vec::new(),
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`Cobyla`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `rho_begin`             | 0.5                  |
+/// | `rho_end`               | 1e-6                 |
+/// | `max_iter`              | 1000                 |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `bounds`                | none                 |
+/// | `constraint`            | none                 |
+/// | `equality_constraint`   | none                 |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct CobylaBuilder<T = f64> {
+    config: Cobyla<T>,
+}
+
+impl<T: RealField + Copy> Default for CobylaBuilder<T> {
+    fn default() -> Self {
+        CobylaBuilder {
+            config: Cobyla::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> CobylaBuilder<T> {
+    /// Initial radius of the trust region, which is also the distance of
+    /// the initial interpolation points from the starting point. It should
+    /// be about a tenth of the expected distance to the solution.
+    pub fn rho_begin(mut self, rho_begin: T) -> Self {
+        self.config.rho_begin = rho_begin;
+        self
+    }
+
+    /// Final radius of the trust region, i.e. the accuracy required on
+    /// the parameters. The run converges once the trust region cannot
+    /// shrink any further.
+    pub fn rho_end(mut self, rho_end: T) -> Self {
+        self.config.rho_end = rho_end;
+        self
+    }
+
+    /// Maximum number of iterations, each moving a single point of the
+    /// simplex, either to reduce the merit or to keep the simplex well
+    /// shaped.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Lower and upper bounds of every parameter, handled as linear
+    /// constraints. Infinite bounds are ignored.
+    pub fn bounds(mut self, lower: DVector<T>, upper: DVector<T>) -> Self {
+        self.config.bounds = Some((lower, upper));
+        self
+    }
+
+    /// Adds the inequality constraint `g(x) <= 0`, see [`Constraint`].
+    pub fn constraint<C>(mut self, constraint: C) -> Self
+    where
+        C: Constraint<T> + Send + Sync + 'static,
+    {
+        self.config.constraints.push(SharedConstraint(Arc::new(constraint)));
+        self
+    }
+
+    /// Adds the equality constraint `h(x) = 0`, see [`Constraint`].
+    pub fn equality_constraint<C>(mut self, constraint: C) -> Self
+    where
+        C: Constraint<T> + Send + Sync + 'static,
+    {
+        self.config.equality_constraints.push(SharedConstraint(Arc::new(constraint)));
+        self
+    }
+
+    /// Additional stopping rule, checked after every iteration next to the
+    /// built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every iteration with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> Cobyla<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Cobyla::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Cobyla::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, x_start)
+    }
+}
+
+/// Interpolation point with its score, minimized, and the values of the
+/// constraints, all in the form `c(x) <= 0`.
+#[derive(Debug, Clone)]
+struct Vertex<T> {
+    x: DVector<T>,
+    score: T,
+    constraints: DVector<T>,
+}
+
+impl<T: RealField + Copy> Vertex<T> {
+    /// Largest constraint violation, zero at a feasible point.
+    fn violation(&self) -> T {
+        self.constraints.iter().fold(T::zero(), |max, c| max.max(*c))
+    }
+
+    /// Merit of the point under the penalty parameter `mu`.
+    fn merit(&self, mu: T) -> T {
+        self.score + mu * self.violation()
+    }
+}
+
+/// Objective function and budget of a run.
+struct Run<'a, T, F> {
+    config: &'a Cobyla<T>,
+    obj_fn: F,
+    sign: T,
+    start: Instant,
+    evaluations: u64,
+}
+
+impl<T, F> Run<'_, T, F>
+where
+    T: RealField + Copy,
+    F: FnMut(&DVector<T>) -> T,
+{
+    /// Reason to stop before the next evaluation, if the budget is spent.
+    fn out_of_budget(&self) -> Option<TerminationReason> {
+        if self.config.max_fun_evals.is_some_and(|limit| self.evaluations >= limit) {
+            Some(TerminationReason::MaxFunEvals)
+        } else if self.config.max_duration.is_some_and(|limit| self.start.elapsed() >= limit) {
+            Some(TerminationReason::TimedOut)
+        } else {
+            None
+        }
+    }
+
+    /// Evaluates the objective function and the constraints at `x`.
+    fn evaluate(&mut self, x: DVector<T>) -> Result<Vertex<T>, NelderMeadError> {
+        self.evaluations += 1;
+        let score = self.config.nan_handling.score((self.obj_fn)(&x), self.sign)?;
+        let constraints = self.config.constraint_values(&x);
+        Ok(Vertex { x, score, constraints })
+    }
+
+    /// Evaluates the vertices `x_0 + rho * e_j` of the simplex around
+    /// `x_0`, stopping early when the budget is spent.
+    fn simplex_around(
+        &mut self,
+        best: Vertex<T>,
+        rho: T,
+    ) -> Result<(Vec<Vertex<T>>, Option<TerminationReason>), NelderMeadError> {
+        let dim = best.x.len();
+        let mut vertices = vec![best];
+        for j in 0..dim {
+            if let Some(reason) = self.out_of_budget() {
+                return Ok((vertices, Some(reason)));
+            }
+            let mut x = vertices[0].x.clone();
+            x[j] += rho;
+            vertices.push(self.evaluate(x)?);
+        }
+        Ok((vertices, None))
+    }
+}
+
+impl<T: RealField + Copy> Cobyla<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> CobylaBuilder<T> {
+        CobylaBuilder::default()
+    }
+
+    /// Values of the constraints at `x` in the form `c(x) <= 0`: the
+    /// inequality constraints, both signs of the equality constraints and
+    /// the finite bounds.
+    fn constraint_values(&self, x: &DVector<T>) -> DVector<T> {
+        let mut values: Vec<T> = self.constraints.iter().map(|g| g.0.evaluate(x)).collect();
+        for h in &self.equality_constraints {
+            let value = h.0.evaluate(x);
+            values.push(value);
+            values.push(-value);
+        }
+        if let Some((lower, upper)) = &self.bounds {
+            for (i, xi) in x.iter().enumerate() {
+                if lower[i].is_finite() {
+                    values.push(lower[i] - *xi);
+                }
+                if upper[i].is_finite() {
+                    values.push(*xi - upper[i]);
+                }
+            }
+        }
+        DVector::from_vec(values)
+    }
+
+    /// Finds a local minimum (or maximum, depending on the configured
+    /// [`Direction`]) of the objective function subject to the
+    /// constraints, starting from `x_start`.
+    ///
+    /// Fails if `x_start` is empty, if `rho_begin` is not positive and
+    /// finite or `rho_end` not within `(0, rho_begin]`, if the bounds do
+    /// not fit `x_start`, or if the objective function returns NaN under
+    /// [`NanHandling::Error`].
+    pub fn minimize<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let dim = x_start.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        let radii = self.rho_begin.is_finite() && T::zero() < self.rho_end && self.rho_end <= self.rho_begin;
+        if !radii {
+            return Err(NelderMeadError::InvalidTrustRegion);
+        }
+        if let Some((lower, upper)) = &self.bounds {
+            if lower.len() != dim
+                || upper.len() != dim
+                || lower.iter().zip(upper.iter()).any(|(l, u)| l > u || is_nan(*l) || is_nan(*u))
+            {
+                return Err(NelderMeadError::InvalidBounds);
+            }
+        }
+
+        let budget = Budget {
+            max_iter: self.max_iter,
+            max_fun_evals: self.max_fun_evals,
+            max_duration: self.max_duration,
+        };
+        let mut run = Run {
+            config: self,
+            obj_fn,
+            sign: self.direction.sign(),
+            start: Instant::now(),
+            evaluations: 0,
+        };
+        let alpha: T = convert(0.25);
+        let beta: T = convert(2.1);
+        let gamma: T = convert(0.5);
+        let half: T = convert(0.5);
+        let tenth: T = convert(0.1);
+
+        let mut rho = self.rho_begin;
+        let mut mu = T::zero();
+        let mut iterations = 0;
+        // iterations the observer and the termination criterion were given
+        let mut observed = 0;
+        let mut restarts = 0;
+        // whether the last trust region step was short or poor
+        let mut stalled = false;
+        // whether the last trust region step reduced the merit well
+        let mut succeeded = false;
+
+        let best = run.evaluate(x_start)?;
+        let (mut vertices, stopped) = run.simplex_around(best, rho)?;
+        let termination_reason = match stopped {
+            Some(reason) => reason,
+            None => loop {
+                if iterations > observed {
+                    observed = iterations;
+                    let simplex = merit_simplex(&vertices, mu);
+                    let state = TerminationState {
+                        iterations,
+                        evaluations: run.evaluations,
+                        elapsed: run.start.elapsed(),
+                        simplex: &simplex,
+                        sign: run.sign,
+                        last_move: None,
+                        budget,
+                    };
+                    logging::log_iteration(&state);
+                    if let Some(observer) = &self.observer {
+                        observer.0.observe(&state);
+                    }
+                    if let Some(criterion) = &self.termination {
+                        if criterion.0.should_stop(&state) {
+                            break TerminationReason::Criterion;
+                        }
+                    }
+                }
+                if iterations >= self.max_iter {
+                    break TerminationReason::MaxIter;
+                }
+                if let Some(reason) = run.out_of_budget() {
+                    break reason;
+                }
+                order(&mut vertices, mu);
+
+                let models = Models::new(&vertices).filter(Models::is_finite);
+                let models = match models {
+                    Some(models) => models,
+                    None => {
+                        // degenerate simplex or infinite scores, rebuilt
+                        // around the best point with a smaller radius
+                        if rho <= self.rho_end {
+                            break TerminationReason::Converged;
+                        }
+                        rho = reduced(rho, self.rho_end);
+                        let best = vertices.swap_remove(0);
+                        let (rebuilt, stopped) = run.simplex_around(best, rho)?;
+                        vertices = rebuilt;
+                        restarts += 1;
+                        iterations += 1;
+                        stalled = false;
+                        succeeded = false;
+                        match stopped {
+                            Some(reason) => break reason,
+                            None => continue,
+                        }
+                    }
+                };
+                let acceptable = (0..dim).all(|j| {
+                    models.face_distance(j) >= alpha * rho && models.edges.column(j).norm() <= beta * rho
+                });
+
+                if !acceptable && !succeeded {
+                    // replaces the vertex spoiling the geometry by a point
+                    // at a distance `gamma * rho` from the opposite face
+                    let far = (0..dim).max_by(|&i, &j| {
+                        let (i, j) = (models.edges.column(i).norm(), models.edges.column(j).norm());
+                        i.partial_cmp(&j).unwrap_or(std::cmp::Ordering::Equal)
+                    });
+                    let j = match far.filter(|&j| models.edges.column(j).norm() > beta * rho) {
+                        Some(j) => j,
+                        None => (0..dim)
+                            .min_by(|&i, &j| {
+                                let (i, j) = (models.face_distance(i), models.face_distance(j));
+                                i.partial_cmp(&j).unwrap_or(std::cmp::Ordering::Equal)
+                            })
+                            .expect("the simplex has a vertex besides the best one"),
+                    };
+                    let normal = models.inverse.row(j).transpose();
+                    let step = normal.clone() * (gamma * rho / normal.norm());
+                    let step = if models.merit(&step, mu) <= models.merit(&-&step, mu) {
+                        step
+                    } else {
+                        -step
+                    };
+                    let mut vertex = run.evaluate(&vertices[0].x + &step)?;
+                    if !vertex.score.is_finite() && run.out_of_budget().is_none() {
+                        vertex = run.evaluate(&vertices[0].x - step)?;
+                    }
+                    vertices[j + 1] = vertex;
+                    iterations += 1;
+                    stalled = false;
+                    continue;
+                }
+                succeeded = false;
+                if stalled {
+                    stalled = false;
+                    if rho <= self.rho_end {
+                        break TerminationReason::Converged;
+                    }
+                    rho = reduced(rho, self.rho_end);
+                    continue;
+                }
+
+                let step = models.trust_step(rho);
+                if step.norm() < half * rho {
+                    stalled = true;
+                    continue;
+                }
+                // the penalty parameter must make the predicted reduction
+                // of the merit positive
+                let best = &vertices[0];
+                let violation = best.violation();
+                let objective_decrease = -models.objective.dot(&step);
+                let violation_decrease = violation - models.violation(&step);
+                if violation_decrease > T::zero() {
+                    let threshold = -objective_decrease / violation_decrease;
+                    if mu < convert::<f64, T>(1.5) * threshold {
+                        mu = threshold + threshold;
+                        if best_index(&vertices, mu) != 0 {
+                            continue;
+                        }
+                    }
+                }
+                let predicted = objective_decrease + mu * violation_decrease;
+
+                let x = &vertices[0].x + &step;
+                let vertex = run.evaluate(x)?;
+                iterations += 1;
+                let actual = vertices[0].merit(mu) - vertex.merit(mu);
+                let ratio = if predicted > T::zero() { actual / predicted } else { -T::one() };
+
+                // Powell's choice of the vertex replaced by the new point:
+                // the one of largest coefficient in the step, beyond one
+                // unless the merit improved, or else the farthest one
+                // whose replacement keeps the simplex acceptable
+                let coefficients = &models.inverse * &step;
+                let improved = actual > T::zero();
+                let mut replaced = None;
+                let mut largest = if improved { T::zero() } else { T::one() };
+                let mut farthest = convert::<f64, T>(1.1) * rho;
+                let mut far = None;
+                for (j, coefficient) in coefficients.iter().enumerate() {
+                    if coefficient.abs() > largest {
+                        largest = coefficient.abs();
+                        replaced = Some(j);
+                    }
+                    let distance = models.face_distance(j);
+                    let face_distance = coefficient.abs() * distance;
+                    if face_distance >= alpha * rho || face_distance >= distance {
+                        let edge = models.edges.column(j);
+                        let length = if improved { (&step - edge).norm() } else { edge.norm() };
+                        if length > farthest {
+                            farthest = length;
+                            far = Some(j);
+                        }
+                    }
+                }
+                // a point of infinite score would spoil the models
+                if let Some(j) = far.or(replaced).filter(|_| vertex.score.is_finite()) {
+                    vertices[j + 1] = vertex;
+                }
+                if ratio < tenth {
+                    stalled = true;
+                } else {
+                    succeeded = true;
+                }
+            },
+        };
+
+        order(&mut vertices, mu);
+        let mut rest = vertices.split_off(1);
+        rest.sort_by(|a, b| a.merit(mu).partial_cmp(&b.merit(mu)).unwrap_or(std::cmp::Ordering::Equal));
+        vertices.append(&mut rest);
+        let violation = (!vertices[0].constraints.is_empty()).then(|| vertices[0].violation());
+        let simplex = vertices.into_iter().map(|vertex| (vertex.x, vertex.score)).collect();
+        let mut result = OptimizationResult::from_simplex(
+            simplex,
+            run.sign,
+            iterations,
+            run.evaluations,
+            restarts,
+            termination_reason,
+            None,
+        );
+        result.constraint_violation = violation;
+        Ok(result)
+    }
+
+    /// Same as [`Cobyla::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), x_start)
+    }
+}
+
+/// Trust region radius after a reduction, which ends exactly at `rho_end`.
+fn reduced<T: RealField + Copy>(rho: T, rho_end: T) -> T {
+    let rho = rho * convert(0.5);
+    if rho <= rho_end * convert(1.5) {
+        rho_end
+    } else {
+        rho
+    }
+}
+
+/// Index of the vertex of lowest merit under `mu`, ties going to the
+/// lower violation.
+fn best_index<T: RealField + Copy>(vertices: &[Vertex<T>], mu: T) -> usize {
+    let mut best = 0;
+    for (j, vertex) in vertices.iter().enumerate().skip(1) {
+        let (merit, best_merit) = (vertex.merit(mu), vertices[best].merit(mu));
+        if merit < best_merit || (merit == best_merit && vertex.violation() < vertices[best].violation()) {
+            best = j;
+        }
+    }
+    best
+}
+
+/// Moves the vertex of lowest merit under `mu` first.
+fn order<T: RealField + Copy>(vertices: &mut [Vertex<T>], mu: T) {
+    let best = best_index(vertices, mu);
+    vertices.swap(0, best);
+}
+
+/// Simplex of the interpolation points, ordered by merit under `mu` like
+/// the `final_simplex` of the result.
+fn merit_simplex<T: RealField + Copy>(vertices: &[Vertex<T>], mu: T) -> Simplex<T> {
+    let best = best_index(vertices, mu);
+    let mut rest: Vec<&Vertex<T>> = vertices
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| *j != best)
+        .map(|(_, vertex)| vertex)
+        .collect();
+    rest.sort_by(|a, b| a.merit(mu).partial_cmp(&b.merit(mu)).unwrap_or(std::cmp::Ordering::Equal));
+    Simplex::from_slices(iter::once(&vertices[best]).chain(rest).map(|vertex| (vertex.x.as_slice(), vertex.score)))
+}
+
+/// Linear interpolation of the objective and the constraints on the
+/// simplex, around its first vertex.
+struct Models<T> {
+    /// Edges `x_j - x_0` as columns.
+    edges: DMatrix<T>,
+    /// Inverse of `edges`.
+    inverse: DMatrix<T>,
+    /// Gradient of the objective.
+    objective: DVector<T>,
+    /// Constraint values at `x_0`.
+    values: DVector<T>,
+    /// Gradients of the constraints as rows.
+    gradients: DMatrix<T>,
+}
+
+impl<T: RealField + Copy> Models<T> {
+    /// Models of the simplex, `None` if it is degenerate.
+    fn new(vertices: &[Vertex<T>]) -> Option<Self> {
+        let best = &vertices[0];
+        let dim = best.x.len();
+        let edges = DMatrix::from_fn(dim, dim, |i, j| vertices[j + 1].x[i] - best.x[i]);
+        let inverse = edges.clone().try_inverse()?;
+        let scores = DVector::from_fn(dim, |j, _| vertices[j + 1].score - best.score);
+        let objective = inverse.tr_mul(&scores);
+        let differences = DMatrix::from_fn(dim, best.constraints.len(), |j, i| {
+            vertices[j + 1].constraints[i] - best.constraints[i]
+        });
+        let gradients = differences.tr_mul(&inverse);
+        Some(Models {
+            edges,
+            inverse,
+            objective,
+            values: best.constraints.clone(),
+            gradients,
+        })
+    }
+
+    fn is_finite(&self) -> bool {
+        self.inverse.iter().all(|v| v.is_finite())
+            && self.objective.iter().all(|v| v.is_finite())
+            && self.gradients.iter().all(|v| v.is_finite())
+            && self.values.iter().all(|v| v.is_finite())
+    }
+
+    /// Distance of the `j`-th vertex after the best one from the face of
+    /// the simplex opposite to it.
+    fn face_distance(&self, j: usize) -> T {
+        T::one() / self.inverse.row(j).norm()
+    }
+
+    /// Largest violation of the linearized constraints at the step `d`.
+    fn violation(&self, d: &DVector<T>) -> T {
+        (&self.values + &self.gradients * d)
+            .iter()
+            .fold(T::zero(), |max, c| max.max(*c))
+    }
+
+    /// Linearized merit under `mu` at the step `d`, up to a constant.
+    fn merit(&self, d: &DVector<T>, mu: T) -> T {
+        self.objective.dot(d) + mu * self.violation(d)
+    }
+
+    /// Step within the trust region of radius `rho` minimizing the largest
+    /// violation of the linearized constraints, then the linearized
+    /// objective without increasing that violation. Like Powell's, the
+    /// search ends where it reaches the boundary of the trust region.
+    fn trust_step(&self, rho: T) -> DVector<T> {
+        let dim = self.objective.len();
+        let count = self.values.len();
+        let violation = self.violation(&DVector::zeros(dim));
+        let (d, violation) = if violation > T::zero() {
+            // over (d, t): minimize t subject to c + G d <= t and t >= 0
+            let rows = DMatrix::from_fn(count + 1, dim + 1, |i, j| match (i < count, j < dim) {
+                (true, true) => self.gradients[(i, j)],
+                (true, false) => -T::one(),
+                (false, true) => T::zero(),
+                (false, false) => -T::one(),
+            });
+            let limits = DVector::from_fn(count + 1, |i, _| if i < count { -self.values[i] } else { T::zero() });
+            let mut start = DVector::zeros(dim + 1);
+            start[dim] = violation;
+            let mut direction = DVector::zeros(dim + 1);
+            direction[dim] = T::one();
+            let (z, boundary) = active_set(&direction, &rows, &limits, start, rho, dim);
+            let d = z.rows(0, dim).into_owned();
+            if boundary {
+                return d;
+            }
+            (d, z[dim].max(T::zero()))
+        } else {
+            (DVector::zeros(dim), T::zero())
+        };
+        let limits = DVector::from_fn(count, |i, _| violation - self.values[i]);
+        active_set(&self.objective, &self.gradients, &limits, d, rho, dim).0
+    }
+}
+
+/// Active set descent of `c . z` subject to `rows * z <= limits` from the
+/// feasible point `start`, the first `ball` coordinates of `z` staying
+/// within a distance `rho` of the origin. Returns the final point and
+/// whether it stopped on the boundary of that ball.
+fn active_set<T: RealField + Copy>(
+    c: &DVector<T>,
+    rows: &DMatrix<T>,
+    limits: &DVector<T>,
+    start: DVector<T>,
+    rho: T,
+    ball: usize,
+) -> (DVector<T>, bool) {
+    let tiny = T::default_epsilon().sqrt();
+    let mut z = start;
+    let mut active: Vec<usize> = Vec::new();
+    for _ in 0..2 * (rows.nrows() + z.len()) + 10 {
+        // steepest descent projected onto the active constraints
+        let normals = DMatrix::from_fn(active.len(), z.len(), |i, j| rows[(active[i], j)]);
+        let multipliers = match (&normals * normals.transpose()).cholesky() {
+            Some(cholesky) => cholesky.solve(&(&normals * c)),
+            None if active.is_empty() => DVector::zeros(0),
+            None => return (z, false),
+        };
+        let direction = -(c - normals.tr_mul(&multipliers));
+        if direction.norm() <= tiny * c.norm() {
+            // a positive multiplier belongs to a constraint the descent
+            // can leave, without any the point is optimal
+            let leaving = multipliers
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| **m > T::zero())
+                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal));
+            match leaving {
+                Some((i, _)) => {
+                    active.remove(i);
+                    continue;
+                }
+                None => return (z, false),
+            }
+        }
+
+        let position = z.rows(0, ball);
+        let heading = direction.rows(0, ball);
+        let speed = heading.norm_squared();
+        let mut length = if speed > T::zero() {
+            let along = position.dot(&heading);
+            let room = (rho * rho - position.norm_squared()).max(T::zero());
+            (-along + (along * along + speed * room).sqrt()) / speed
+        } else {
+            T::max_value().unwrap_or_else(|| convert(f64::MAX))
+        };
+        let mut blocking = None;
+        for i in (0..rows.nrows()).filter(|i| !active.contains(i)) {
+            let row = rows.row(i);
+            let rate = row.dot(&direction.transpose());
+            if rate > tiny * row.norm() * direction.norm() {
+                let slack = (limits[i] - row.dot(&z.transpose())).max(T::zero());
+                if slack / rate < length {
+                    length = slack / rate;
+                    blocking = Some(i);
+                }
+            }
+        }
+        if blocking.is_none() && speed.is_zero() {
+            return (z, false);
+        }
+        z += direction * length;
+        match blocking {
+            Some(i) => active.push(i),
+            None => return (z, true),
+        }
+    }
+    (z, false)
+}
@@ -0,0 +1,167 @@
+use std::error::Error;
+use std::fmt;
+
+use nalgebra::{convert, RealField};
+
+/// Coefficients of the simplex operations.
+///
+/// With `x0` the centroid of all vertices but the worst one `xw`:
+///
+/// * reflection: `x0 + alpha * (x0 - xw)`
+/// * expansion: `x0 + gamma * (x0 - xw)`
+/// * contraction: `x0 + rho * (xw - x0)`
+/// * shrink: `x1 + sigma * (x - x1)` for every vertex `x` but the best
+///   one `x1`, towards it
+///
+/// # Examples
+///
+/// ```
+/// use nelder_mead_optimizer::coefficients::{CoefficientError, Coefficients};
+///
+/// assert!(Coefficients::new(1.0, 2.0, 0.5, 0.5).is_ok());
+/// assert_eq!(
+///     Coefficients::new(1.0, 2.0, -0.5, 0.5),
+///     Err(CoefficientError::Rho(-0.5))
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Coefficients<T = f64> {
+    pub(crate) alpha: T,
+    pub(crate) gamma: T,
+    pub(crate) rho: T,
+    pub(crate) sigma: T,
+}
+
+impl<T: RealField + Copy> Coefficients<T> {
+    /// Returns the coefficients if they satisfy `alpha > 0`, `gamma > 1`,
+    /// `0 < rho < 1` and `0 < sigma < 1`, or an error naming the first
+    /// coefficient out of range.
+    pub fn new(alpha: T, gamma: T, rho: T, sigma: T) -> Result<Self, CoefficientError> {
+        let coefficients = Coefficients { alpha, gamma, rho, sigma };
+        coefficients.validate()?;
+        Ok(coefficients)
+    }
+
+    /// Checks the ranges described in [`Coefficients::new`].
+    pub fn validate(&self) -> Result<(), CoefficientError> {
+        let zero = T::zero();
+        let one = T::one();
+        // written so that NaN fails every check
+        let alpha_ok = self.alpha > zero;
+        let gamma_ok = self.gamma > one;
+        let rho_ok = self.rho > zero && self.rho < one;
+        let sigma_ok = self.sigma > zero && self.sigma < one;
+        if !alpha_ok {
+            return Err(CoefficientError::Alpha(to_f64(self.alpha)));
+        }
+        if !gamma_ok {
+            return Err(CoefficientError::Gamma(to_f64(self.gamma)));
+        }
+        if !rho_ok {
+            return Err(CoefficientError::Rho(to_f64(self.rho)));
+        }
+        if !sigma_ok {
+            return Err(CoefficientError::Sigma(to_f64(self.sigma)));
+        }
+        Ok(())
+    }
+
+    /// Dimension dependent coefficients of Gao and Han (2012),
+    /// `alpha = 1`, `gamma = 1 + 2/n`, `rho = 0.75 - 1/(2n)` and
+    /// `sigma = 1 - 1/n` for `n` parameters, which keep the simplex from
+    /// degrading in high dimensions. They coincide with the standard ones
+    /// for `n = 2`, which are also used for `n = 1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nelder_mead_optimizer::coefficients::Coefficients;
+    ///
+    /// let coefficients = Coefficients::<f64>::adaptive(10);
+    ///
+    /// assert_eq!(coefficients.gamma(), 1.2);
+    /// assert_eq!(coefficients.sigma(), 0.9);
+    /// assert_eq!(Coefficients::<f64>::adaptive(2), Coefficients::default());
+    /// ```
+    pub fn adaptive(dim: usize) -> Self {
+        if dim < 2 {
+            return Coefficients::default();
+        }
+        let n: T = convert(dim as f64);
+        let one = T::one();
+        let two: T = convert(2.0);
+        Coefficients {
+            alpha: one,
+            gamma: one + two / n,
+            rho: convert::<f64, T>(0.75) - one / (two * n),
+            sigma: one - one / n,
+        }
+    }
+
+    /// Reflection coefficient.
+    pub fn alpha(&self) -> T {
+        self.alpha
+    }
+
+    /// Expansion coefficient.
+    pub fn gamma(&self) -> T {
+        self.gamma
+    }
+
+    /// Contraction coefficient.
+    pub fn rho(&self) -> T {
+        self.rho
+    }
+
+    /// Shrink coefficient.
+    pub fn sigma(&self) -> T {
+        self.sigma
+    }
+}
+
+impl<T: RealField + Copy> Default for Coefficients<T> {
+    /// The standard `alpha = 1`, `gamma = 2`, `rho = 0.5`, `sigma = 0.5`.
+    fn default() -> Self {
+        Coefficients {
+            alpha: T::one(),
+            gamma: convert(2.0),
+            rho: convert(0.5),
+            sigma: convert(0.5),
+        }
+    }
+}
+
+fn to_f64<T: RealField>(value: T) -> f64 {
+    value.to_subset().unwrap_or(f64::NAN)
+}
+
+/// Coefficient outside of its valid range, holding the rejected value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoefficientError {
+    Alpha(f64),
+    Gamma(f64),
+    Rho(f64),
+    Sigma(f64),
+}
+
+impl fmt::Display for CoefficientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoefficientError::Alpha(v) => {
+                write!(f, "reflection coefficient alpha must be positive, got {}", v)
+            }
+            CoefficientError::Gamma(v) => {
+                write!(f, "expansion coefficient gamma must be greater than 1, got {}", v)
+            }
+            CoefficientError::Rho(v) => {
+                write!(f, "contraction coefficient rho must be in (0, 1), got {}", v)
+            }
+            CoefficientError::Sigma(v) => {
+                write!(f, "shrink coefficient sigma must be in (0, 1), got {}", v)
+            }
+        }
+    }
+}
+
+impl Error for CoefficientError {}
@@ -0,0 +1,535 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+
+use nalgebra::{convert, DMatrix, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::nelder_mead::NelderMead;
+use crate::nelder_mead_iter::NelderMeadIter;
+use crate::noise::Replicated;
+use crate::objective::{ByRef, Evaluator};
+use crate::result::{OptimizationResult, TerminationReason};
+
+/// Inequality constraint `g(x) <= 0`, added to the optimizer with
+/// [`NelderMeadBuilder::constraint`] and enforced by a [`Penalty`].
+///
+/// It is implemented for every closure taking a `&DVector<f64>` and
+/// returning `g(x)`, and can be implemented on custom types carrying the
+/// data the constraint depends on.
+///
+/// [`NelderMeadBuilder::constraint`]: crate::builder::NelderMeadBuilder::constraint
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::constraints::Penalty;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 2.0).powi(2) + (x[1] - 2.0).powi(2)
+/// }
+/// // x + y <= 2, the constrained optimum is (1, 1)
+/// let results = NelderMead::builder()
+///     .constraint(|x: &DVector<f64>| x[0] + x[1] - 2.0)
+///     .penalty(Penalty::Quadratic(10.0))
+///     .penalty_schedule(10.0, 6, 1e-4)
+///     .xatol(1e-8)
+///     .fatol(1e-10)
+///     .minimize(&f, dvector![0.0, 0.0])?;
+///
+/// assert!((results.best_point[0] - 1.0).abs() < 1e-3);
+/// assert!((results.best_point[1] - 1.0).abs() < 1e-3);
+/// assert!(results.constraint_violation.unwrap() <= 1e-4);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+pub trait Constraint<T = f64> {
+    /// Returns `g(x)`, the constraint is satisfied when it is not positive.
+    fn evaluate(&self, x: &DVector<T>) -> T;
+}
+
+impl<T, F> Constraint<T> for F
+where
+    F: Fn(&DVector<T>) -> T,
+{
+    fn evaluate(&self, x: &DVector<T>) -> T {
+        self(x)
+    }
+}
+
+/// Constraint stored in the optimizer configuration.
+#[derive(Clone)]
+pub(crate) struct SharedConstraint<T>(pub(crate) Arc<dyn Constraint<T> + Send + Sync>);
+
+impl<T> fmt::Debug for SharedConstraint<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Constraint")
+    }
+}
+
+impl<T> PartialEq for SharedConstraint<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Penalty added to the score of a point for every violated
+/// [`Constraint`], with its weight.
+///
+/// Scores handed to the hooks and reported in the result include the
+/// penalty, which is subtracted from the objective when maximizing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Penalty<T = f64> {
+    /// `weight * sum(max(0, g_i(x))^2)`, smooth but only reaching the
+    /// feasible region as the weight grows.
+    Quadratic(T),
+    /// `weight * sum(max(0, g_i(x)))`, the exact (l1) penalty, which is
+    /// exact for a weight above the largest Lagrange multiplier but makes
+    /// the objective nonsmooth on the boundary.
+    Exact(T),
+}
+
+impl<T: RealField + Copy> Default for Penalty<T> {
+    fn default() -> Self {
+        Penalty::Quadratic(convert(10.0))
+    }
+}
+
+impl<T: RealField + Copy> Penalty<T> {
+    /// The same penalty with its weight multiplied by `factor`.
+    fn scaled(self, factor: T) -> Self {
+        match self {
+            Penalty::Quadratic(weight) => Penalty::Quadratic(weight * factor),
+            Penalty::Exact(weight) => Penalty::Exact(weight * factor),
+        }
+    }
+
+    /// Penalty of a point whose constraints evaluate to `values`.
+    fn apply(self, values: impl Iterator<Item = T>) -> T {
+        let violations = values.map(|g| g.max(T::zero()));
+        match self {
+            Penalty::Quadratic(weight) => weight * violations.fold(T::zero(), |sum, v| sum + v * v),
+            Penalty::Exact(weight) => weight * violations.fold(T::zero(), |sum, v| sum + v),
+        }
+    }
+}
+
+/// Augmented Lagrangian of the equality constraints, configured with
+/// [`NelderMeadBuilder::augmented_lagrangian`].
+///
+/// The score of a point is increased by
+/// `sum(lambda_j * h_j(x) + weight / 2 * h_j(x)^2)`, the multipliers
+/// `lambda_j` being updated between successive runs.
+///
+/// [`NelderMeadBuilder::augmented_lagrangian`]: crate::builder::NelderMeadBuilder::augmented_lagrangian
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct AugmentedLagrangian<T> {
+    pub(crate) weight: T,
+    pub(crate) growth: T,
+    pub(crate) max_rounds: u64,
+    pub(crate) tolerance: T,
+    pub(crate) multipliers: Vec<T>,
+}
+
+impl<T: RealField + Copy> Default for AugmentedLagrangian<T> {
+    fn default() -> Self {
+        AugmentedLagrangian {
+            weight: convert(10.0),
+            growth: convert(10.0),
+            max_rounds: 20,
+            tolerance: convert(1e-6),
+            multipliers: Vec::new(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> AugmentedLagrangian<T> {
+    /// Augmented Lagrangian term of a point whose equality constraints
+    /// evaluate to `values`.
+    fn apply(&self, values: impl Iterator<Item = T>) -> T {
+        let half: T = convert(0.5);
+        values.enumerate().fold(T::zero(), |sum, (j, h)| {
+            let multiplier = self.multipliers.get(j).copied().unwrap_or_else(T::zero);
+            sum + multiplier * h + half * self.weight * h * h
+        })
+    }
+
+    /// First-order update `lambda_j += weight * h_j(x)` of the multipliers
+    /// from the equality constraints `values` at the solution of a run.
+    fn update(&mut self, values: &[T]) {
+        self.multipliers.resize(values.len(), T::zero());
+        for (multiplier, &h) in self.multipliers.iter_mut().zip(values) {
+            *multiplier += self.weight * h;
+        }
+    }
+}
+
+/// Growth of the penalty weight between successive runs, configured with
+/// [`NelderMeadBuilder::penalty_schedule`].
+///
+/// [`NelderMeadBuilder::penalty_schedule`]: crate::builder::NelderMeadBuilder::penalty_schedule
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct PenaltySchedule<T> {
+    pub(crate) growth: T,
+    pub(crate) max_rounds: u64,
+    pub(crate) tolerance: T,
+}
+
+/// Affine subspace `x = origin + basis * z` of the points satisfying the
+/// linear equality constraints `A x = b`, the columns of `basis` being an
+/// orthonormal basis of the null space of `A`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct NullSpace<T> {
+    origin: DVector<T>,
+    basis: DMatrix<T>,
+}
+
+impl<T: RealField + Copy> NullSpace<T> {
+    /// Null space of the constraints `A x = b` on points of dimension
+    /// `dim`, from a singular value decomposition of `A`. Fails if `A`
+    /// and `b` do not fit together or the points, or if the system has no
+    /// solution.
+    fn new(a: &DMatrix<T>, b: &DVector<T>, dim: usize) -> Result<Self, NelderMeadError> {
+        if a.ncols() != dim || a.nrows() != b.len() {
+            return Err(NelderMeadError::InvalidLinearEquality);
+        }
+        // zero rows padding A to a square matrix yield every right
+        // singular vector, not only the ones of the thin decomposition
+        let rows = a.nrows().max(dim);
+        let mut padded = DMatrix::zeros(rows, dim);
+        padded.rows_mut(0, a.nrows()).copy_from(a);
+        let mut rhs = DVector::zeros(rows);
+        rhs.rows_mut(0, b.len()).copy_from(b);
+
+        let svd = padded.svd(true, true);
+        let largest = svd.singular_values.iter().fold(T::zero(), |max, s| max.max(*s));
+        let tolerance = largest * convert(rows as f64) * T::default_epsilon();
+        let origin = svd
+            .solve(&rhs, tolerance)
+            .map_err(|_| NelderMeadError::InvalidLinearEquality)?;
+        let residual = (a * &origin - b).amax();
+        if !residual.is_finite() || residual > T::default_epsilon().sqrt() * (T::one() + b.amax()) {
+            return Err(NelderMeadError::InvalidLinearEquality);
+        }
+
+        let v_t = svd.v_t.as_ref().expect("right singular vectors are computed");
+        let null: Vec<usize> = (0..dim).filter(|&i| svd.singular_values[i] <= tolerance).collect();
+        let basis = DMatrix::from_fn(dim, null.len(), |row, column| v_t[(null[column], row)]);
+        Ok(NullSpace { origin, basis })
+    }
+
+    /// Number of null-space coordinates.
+    fn dim(&self) -> usize {
+        self.basis.ncols()
+    }
+
+    /// Point of the null-space coordinates `z`.
+    fn to_full(&self, z: &DVector<T>) -> DVector<T> {
+        &self.origin + &self.basis * z
+    }
+
+    /// Null-space coordinates of the projection of the point `x` onto the
+    /// subspace.
+    fn to_reduced(&self, x: &DVector<T>) -> DVector<T> {
+        self.basis.tr_mul(&(x - &self.origin))
+    }
+
+    /// The constraint `g` on the null-space coordinates.
+    fn constraint(self: &Arc<Self>, g: &SharedConstraint<T>) -> SharedConstraint<T> {
+        let (space, g) = (Arc::clone(self), Arc::clone(&g.0));
+        SharedConstraint(Arc::new(move |z: &DVector<T>| g.evaluate(&space.to_full(z))))
+    }
+
+    /// Maps the points of a result on the null-space coordinates back.
+    fn to_full_result(&self, mut result: OptimizationResult<T>) -> OptimizationResult<T> {
+        result.best_point = self.to_full(&result.best_point);
+        for (x, _) in result.final_simplex.iter_mut() {
+            *x = self.to_full(x);
+        }
+        if let Some(history) = &mut result.history {
+            // entries recorded in the memory-efficient mode have no point
+            for entry in history.entries.iter_mut().filter(|entry| !entry.best_point.is_empty()) {
+                entry.best_point = self.to_full(&entry.best_point);
+            }
+        }
+        result
+    }
+}
+
+/// Objective on the null-space coordinates, evaluated at their points.
+struct InNullSpace<'a, T, E> {
+    obj_fn: E,
+    space: &'a NullSpace<T>,
+}
+
+impl<T: RealField + Copy, E: Evaluator<T>> Evaluator<T> for InNullSpace<'_, T, E> {
+    fn evaluate_point(&mut self, z: &DVector<T>) -> T {
+        self.obj_fn.evaluate_point(&self.space.to_full(z))
+    }
+
+    fn evaluate_points(&mut self, zs: &[DVector<T>]) -> Vec<T> {
+        let xs: Vec<DVector<T>> = zs.iter().map(|z| self.space.to_full(z)).collect();
+        self.obj_fn.evaluate_points(&xs)
+    }
+
+    fn evaluates_batches(&self) -> bool {
+        self.obj_fn.evaluates_batches()
+    }
+}
+
+impl<T: RealField + Copy> NelderMead<T> {
+    /// Configuration of the run on the null-space coordinates of the
+    /// linear equality constraints on points of dimension `dim`, with the
+    /// null space. The other constraints are evaluated at the points of
+    /// the coordinates. Fails if options applying to single parameters are
+    /// set, as the coordinates mix all of them, or if the constraints
+    /// leave no coordinate to optimize.
+    fn reduced(&self, dim: usize) -> Result<(Self, Arc<NullSpace<T>>), NelderMeadError> {
+        let (a, b) = self.linear_equality.as_ref().ok_or(NelderMeadError::InvalidLinearEquality)?;
+        if self.bounds.is_some()
+            || self.parameter_transforms.iter().any(Option::is_some)
+            || !self.unit_simplices.is_empty()
+            || self.periods.iter().any(Option::is_some)
+            || self.auto_scale
+        {
+            return Err(NelderMeadError::InvalidLinearEquality);
+        }
+        let space = Arc::new(NullSpace::new(a, b, dim)?);
+        if space.dim() == 0 {
+            return Err(NelderMeadError::InvalidLinearEquality);
+        }
+        let config = NelderMead {
+            linear_equality: None,
+            constraints: self.constraints.iter().map(|g| space.constraint(g)).collect(),
+            equality_constraints: self.equality_constraints.iter().map(|h| space.constraint(h)).collect(),
+            ..self.clone()
+        };
+        Ok((config, space))
+    }
+
+    /// Runs the optimizer on the null-space coordinates of the linear
+    /// equality constraints, starting around the projection of `x_start`
+    /// onto the points satisfying them.
+    pub(crate) fn minimize_reduced<E>(
+        &self,
+        obj_fn: E,
+        x_start: &DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        E: Evaluator<T>,
+    {
+        let (config, space) = self.reduced(x_start.len())?;
+        let simplex = config.initial_simplex(space.to_reduced(x_start))?;
+        let result = config.minimize_penalized(InNullSpace { obj_fn, space: &space }, simplex)?;
+        Ok(space.to_full_result(result))
+    }
+
+    /// Same as [`NelderMead::minimize_reduced`], starting from the
+    /// projection of a simplex of `k + 1` vertices, `k` being the number of
+    /// null-space coordinates.
+    pub(crate) fn minimize_from_simplex_reduced<E>(
+        &self,
+        obj_fn: E,
+        simplex: &[DVector<T>],
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        E: Evaluator<T>,
+    {
+        let dim = simplex.first().map_or(0, DVector::len);
+        let (config, space) = self.reduced(dim)?;
+        if simplex.len() != space.dim() + 1 || simplex.iter().any(|x| x.len() != dim) {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        let simplex = simplex.iter().map(|x| space.to_reduced(x)).collect();
+        let result = config.minimize_penalized(InNullSpace { obj_fn, space: &space }, simplex)?;
+        Ok(space.to_full_result(result))
+    }
+
+    /// Whether any inequality or equality constraint is set.
+    pub(crate) fn constrained(&self) -> bool {
+        !self.constraints.is_empty() || !self.equality_constraints.is_empty()
+    }
+
+    /// Penalty of the parameters `x` for the violated inequality
+    /// constraints plus the augmented Lagrangian term of the equality
+    /// constraints, zero without constraints.
+    pub(crate) fn penalty_of(&self, x: &DVector<T>) -> T {
+        let mut penalty = T::zero();
+        if !self.constraints.is_empty() {
+            penalty += self.penalty.apply(self.constraints.iter().map(|g| g.0.evaluate(x)));
+        }
+        if !self.equality_constraints.is_empty() {
+            penalty += self
+                .augmented_lagrangian
+                .apply(self.equality_constraints.iter().map(|h| h.0.evaluate(x)));
+        }
+        penalty
+    }
+
+    /// Largest inequality constraint violation `max(0, g_i(x))` of the
+    /// parameters `x`, `None` without inequality constraints.
+    fn inequality_violation(&self, x: &DVector<T>) -> Option<T> {
+        self.constraints
+            .iter()
+            .map(|g| g.0.evaluate(x).max(T::zero()))
+            .reduce(|a, b| a.max(b))
+    }
+
+    /// Largest constraint violation of the parameters `x`, `max(0, g_i(x))`
+    /// for the inequality and `|h_j(x)|` for the equality constraints,
+    /// `None` without constraints.
+    pub(crate) fn violation(&self, x: &DVector<T>) -> Option<T> {
+        self.inequality_violation(x)
+            .into_iter()
+            .chain(self.equality_constraints.iter().map(|h| h.0.evaluate(x).abs()))
+            .reduce(|a, b| a.max(b))
+    }
+
+    /// Runs the optimizer from `simplex`, see
+    /// [`NelderMead::penalized_runs`], evaluating every point several
+    /// times when configured with [`NelderMeadBuilder::replicate`].
+    ///
+    /// [`NelderMeadBuilder::replicate`]: crate::builder::NelderMeadBuilder::replicate
+    pub(crate) fn minimize_penalized<E>(
+        &self,
+        obj_fn: E,
+        simplex: Vec<DVector<T>>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        E: Evaluator<T>,
+    {
+        match self.replication {
+            Some(replication) => {
+                replication.check()?;
+                // the runs see the replicated objective as a plain one
+                let config = NelderMead { replication: None, ..self.clone() };
+                config.penalized_runs(Replicated::new(obj_fn, replication), simplex)
+            }
+            None => self.penalized_runs(obj_fn, simplex),
+        }
+    }
+
+    /// Runs the optimizer from `simplex`, then again from its final simplex
+    /// as long as the constraints are violated beyond their tolerance,
+    /// with a growing penalty weight for the inequality constraints and
+    /// updated multipliers for the equality constraints. The budgets and the
+    /// evaluation cache apply to all runs together.
+    fn penalized_runs<E>(
+        &self,
+        mut obj_fn: E,
+        simplex: Vec<DVector<T>>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        E: Evaluator<T>,
+    {
+        let start = Instant::now();
+        let (mut result, mut cache) = NelderMeadIter::new(self.clone(), ByRef(&mut obj_fn), simplex)?
+            .run_with_cache()?;
+        let schedule = self.penalty_schedule.filter(|_| !self.constraints.is_empty());
+        if schedule.is_none() && self.equality_constraints.is_empty() {
+            return self.polished(obj_fn, result, start);
+        }
+
+        let mut config = self.clone();
+        let mut previous_violation = None;
+        for round in 1.. {
+            let settled = matches!(
+                result.termination_reason,
+                TerminationReason::NoImprovement | TerminationReason::Converged
+            );
+            let tighten = schedule.is_some_and(|schedule| {
+                round < schedule.max_rounds
+                    && config
+                        .inequality_violation(&result.best_point)
+                        .is_some_and(|v| v > schedule.tolerance)
+            });
+            let values: Vec<T> = config
+                .equality_constraints
+                .iter()
+                .map(|h| h.0.evaluate(&result.best_point))
+                .collect();
+            let violation = values.iter().fold(T::zero(), |max, h| max.max(h.abs()));
+            let lagrangian = &mut config.augmented_lagrangian;
+            let update = round < lagrangian.max_rounds && violation > lagrangian.tolerance;
+            if !settled || !(tighten || update) {
+                break;
+            }
+
+            if let Some(schedule) = schedule.filter(|_| tighten) {
+                config.penalty = config.penalty.scaled(schedule.growth);
+            }
+            if update {
+                lagrangian.update(&values);
+                // The weight only grows when the violation did not shrink
+                // fast enough under the previous multipliers.
+                let quarter: T = convert(0.25);
+                if previous_violation.is_some_and(|previous| violation > quarter * previous) {
+                    lagrangian.weight *= lagrangian.growth;
+                }
+                previous_violation = Some(violation);
+            }
+            config.max_iter = self.max_iter.saturating_sub(result.iterations);
+            config.max_fun_evals = self.max_fun_evals.map(|limit| limit.saturating_sub(result.evaluations));
+            config.max_duration = self.max_duration.map(|limit| limit.saturating_sub(start.elapsed()));
+            let simplex = result.final_simplex.iter().map(|(x, _)| x.clone()).collect();
+            let next_run = NelderMeadIter::with_cache(config.clone(), ByRef(&mut obj_fn), simplex, cache)?;
+            let (mut next, next_cache) = next_run.run_with_cache()?;
+            cache = next_cache;
+
+            next.iterations += result.iterations;
+            next.evaluations += result.evaluations;
+            next.restarts += result.restarts;
+            if let (Some(history), Some(previous)) = (&mut next.history, result.history) {
+                for entry in history.entries.iter_mut() {
+                    entry.iteration += result.iterations;
+                    entry.evaluations += result.evaluations;
+                }
+                history.entries.splice(0..0, previous.entries);
+            }
+            if let (Some(timing), Some(previous)) = (&mut next.timing, result.timing) {
+                timing.merge(&previous);
+            }
+            result = next;
+        }
+        self.polished(obj_fn, result, start)
+    }
+
+    /// `result` polished by BFGS when configured with
+    /// [`NelderMeadBuilder::bfgs_polish`], then with the Hessian at its
+    /// best point when configured with
+    /// [`NelderMeadBuilder::estimate_covariance`], unless the run ran out
+    /// of its budgets or was stopped from the outside.
+    ///
+    /// [`NelderMeadBuilder::bfgs_polish`]: crate::builder::NelderMeadBuilder::bfgs_polish
+    /// [`NelderMeadBuilder::estimate_covariance`]: crate::builder::NelderMeadBuilder::estimate_covariance
+    fn polished<E>(
+        &self,
+        mut obj_fn: E,
+        result: OptimizationResult<T>,
+        start: Instant,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        E: Evaluator<T>,
+    {
+        let settled = !matches!(
+            result.termination_reason,
+            TerminationReason::MaxFunEvals
+                | TerminationReason::TimedOut
+                | TerminationReason::StoppedByCallback
+                | TerminationReason::StoppedByCaller
+                | TerminationReason::Interrupted
+        );
+        let result = match self.bfgs_polish.filter(|_| settled) {
+            Some(max_iter) => self.polish_bfgs(ByRef(&mut obj_fn), result, max_iter, start)?,
+            None => result,
+        };
+        if self.estimate_covariance && settled {
+            return Ok(self.estimate_hessian(obj_fn, result, start));
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,563 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::logging;
+use crate::nelder_mead::{is_nan, total_cmp, Direction, NanHandling, StepSize};
+#[cfg(feature = "rayon")]
+use crate::objective::Parallel;
+use crate::objective::{Batch, ByRef, Evaluator, ObjectiveFunction};
+use crate::observer::{Observer, SharedObserver};
+use crate::progress::Budget;
+use crate::random::Rng;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationCriterion, TerminationState};
+
+/// Cross-entropy method, a global optimizer sampling every generation from
+/// a normal distribution fitted to the best samples of the previous one.
+///
+/// Every generation draws `population_size` points from a normal
+/// distribution with independent parameters, evaluates them together as
+/// one batch, and moves the mean and the standard deviations of the
+/// distribution towards those of the elite: the `elite_fraction` best
+/// points of the generation, by the `smoothing` share of the difference.
+/// The distribution starts at `x_start`, with the step as its standard
+/// deviations, or half the width of the bounds with bounds. Samples are
+/// clipped into the bounds. Since the points of a generation are
+/// independent, [`CrossEntropy::minimize_batch`] and
+/// `CrossEntropy::minimize_parallel` evaluate a whole generation with a
+/// single call. The run is reproducible with [`CrossEntropyBuilder::seed`].
+///
+/// The run converges once every standard deviation is below `xtol`, or
+/// once the scores of the elite lie within `ftol`, relative to the best
+/// score or absolutely below 1. Runs are reported as an
+/// [`OptimizationResult`], whose `iterations` are the generations and
+/// whose `final_simplex` holds the `n + 1` best points sampled. The
+/// [`Observer`] and the [`TerminationCriterion`] are given the same points
+/// after every generation. `x_start` is always evaluated, the last
+/// generation only as far as `max_fun_evals` allows.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::DVector;
+/// use nelder_mead_optimizer::cross_entropy::CrossEntropy;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::test_functions::ackley;
+///
+/// let x_start = DVector::from_element(3, 2.5);
+/// let (lower, upper) = (DVector::from_element(3, -5.0), DVector::from_element(3, 5.0));
+/// let mut calls = 0;
+/// let cross_entropy = CrossEntropy::builder()
+///     .bounds(lower, upper)
+///     .population_size(100)
+///     .seed(5)
+///     .minimize_batch(
+///         |xs: &[DVector<f64>]| {
+///             calls += 1;
+///             xs.iter().map(ackley).collect()
+///         },
+///         x_start.clone(),
+///     )?;
+/// let plain = NelderMead::builder().minimize(ackley, x_start)?;
+///
+/// assert!(cross_entropy.best_score < 1e-6);
+/// assert!(plain.best_score > 1.0);
+/// assert_eq!(calls, cross_entropy.iterations + 1);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct CrossEntropy<T = f64> {
+    pub(crate) step: StepSize<T>,
+    pub(crate) population_size: Option<usize>,
+    pub(crate) elite_fraction: T,
+    pub(crate) smoothing: T,
+    pub(crate) seed: Option<u64>,
+    pub(crate) xtol: T,
+    pub(crate) ftol: T,
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) bounds: Option<(DVector<T>, DVector<T>)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for CrossEntropy<T> {
+    fn default() -> Self {
+        CrossEntropy {
+            step: StepSize::Uniform(convert(1.0)),
+            population_size: None,
+            elite_fraction: convert(0.1),
+            smoothing: convert(0.7),
+            seed: None,
+            xtol: convert(1e-8),
+            ftol: convert(1e-10),
+            max_iter: 1000,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            bounds: None,
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`CrossEntropy`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `step`                  | 1                    |
+/// | `population_size`       | max(10 n, 50)        |
+/// | `elite_fraction`        | 0.1                  |
+/// | `smoothing`             | 0.7                  |
+/// | `seed`                  | random               |
+/// | `xtol`                  | 1e-8                 |
+/// | `ftol`                  | 1e-10                |
+/// | `max_iter`              | 1000                 |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `bounds`                | none                 |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct CrossEntropyBuilder<T = f64> {
+    config: CrossEntropy<T>,
+}
+
+impl<T: RealField + Copy> Default for CrossEntropyBuilder<T> {
+    fn default() -> Self {
+        CrossEntropyBuilder {
+            config: CrossEntropy::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> CrossEntropyBuilder<T> {
+    /// Initial standard deviation of the samples around `x_start`, either a
+    /// single value used for every dimension or a [`StepSize`]. Ignored
+    /// with bounds, whose half-width is used instead.
+    pub fn step(mut self, step: impl Into<StepSize<T>>) -> Self {
+        self.config.step = step.into();
+        self
+    }
+
+    /// Number of points sampled every generation, at least 2.
+    pub fn population_size(mut self, population_size: usize) -> Self {
+        self.config.population_size = Some(population_size);
+        self
+    }
+
+    /// Share of the best points of a generation the distribution is fitted
+    /// to, within `(0, 1]`. The elite holds at least one point.
+    pub fn elite_fraction(mut self, elite_fraction: T) -> Self {
+        self.config.elite_fraction = elite_fraction;
+        self
+    }
+
+    /// Share of the way the mean and the standard deviations move towards
+    /// those of the elite every generation, within `(0, 1]`. Lower values
+    /// shrink the distribution more slowly, and explore more.
+    pub fn smoothing(mut self, smoothing: T) -> Self {
+        self.config.smoothing = smoothing;
+        self
+    }
+
+    /// Seed of the random numbers, making the run reproducible. Without a
+    /// seed every run draws different numbers.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Standard deviation along every parameter below which the run
+    /// converges.
+    pub fn xtol(mut self, xtol: T) -> Self {
+        self.config.xtol = xtol;
+        self
+    }
+
+    /// Spread of the scores of the elite below which the run converges,
+    /// relative to the best score, absolute below 1.
+    pub fn ftol(mut self, ftol: T) -> Self {
+        self.config.ftol = ftol;
+        self
+    }
+
+    /// Maximum number of generations.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Lower and upper bounds of every parameter, whose half-width is the
+    /// initial standard deviation. The starting point and every sample are
+    /// clipped into them.
+    pub fn bounds(mut self, lower: DVector<T>, upper: DVector<T>) -> Self {
+        self.config.bounds = Some((lower, upper));
+        self
+    }
+
+    /// Additional stopping rule, checked after every generation next to
+    /// the built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every generation with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> CrossEntropy<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`CrossEntropy::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`CrossEntropy::minimize_batch`].
+    pub fn minimize_batch<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&[DVector<T>]) -> Vec<T>,
+    {
+        self.build().minimize_batch(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`CrossEntropy::minimize_parallel`].
+    #[cfg(feature = "rayon")]
+    pub fn minimize_parallel<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        T: Send + Sync,
+        F: Fn(&DVector<T>) -> T + Sync,
+    {
+        self.build().minimize_parallel(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`CrossEntropy::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, x_start)
+    }
+}
+
+impl<T: RealField + Copy> CrossEntropy<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> CrossEntropyBuilder<T> {
+        CrossEntropyBuilder::default()
+    }
+
+    /// Finds the minimum (or maximum, depending on the configured
+    /// [`Direction`]) of the objective function, within the bounds or
+    /// around `x_start`.
+    ///
+    /// Fails if `x_start` is empty, if the population has fewer than two
+    /// points, if the elite fraction or the smoothing is not within
+    /// `(0, 1]`, if `xtol` or `ftol` is not positive and finite, if the step
+    /// or the bounds do not fit `x_start`, or if the objective function
+    /// returns NaN under [`NanHandling::Error`].
+    pub fn minimize<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.minimize_with(obj_fn, x_start)
+    }
+
+    /// Same as [`CrossEntropy::minimize`], but optimizes a batch objective,
+    /// taking several points at once and returning their values in the
+    /// same order. Every generation is evaluated with a single call, after
+    /// a first call evaluating `x_start`.
+    ///
+    /// Fails with [`NelderMeadError::InvalidBatch`] if the objective does
+    /// not return one value per point.
+    pub fn minimize_batch<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&[DVector<T>]) -> Vec<T>,
+    {
+        let mut batch = Batch { obj_fn, mismatch: false };
+        self.minimize_with(ByRef(&mut batch), x_start)
+    }
+
+    /// Same as [`CrossEntropy::minimize`], but evaluates the points of
+    /// every generation in parallel on the rayon thread pool. The result is
+    /// the same as with [`CrossEntropy::minimize`] for the same seed.
+    #[cfg(feature = "rayon")]
+    pub fn minimize_parallel<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        T: Send + Sync,
+        F: Fn(&DVector<T>) -> T + Sync,
+    {
+        self.minimize_with(Parallel(obj_fn), x_start)
+    }
+
+    /// Same as [`CrossEntropy::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), x_start)
+    }
+
+    /// Same as [`CrossEntropy::minimize`], with the objective evaluated by
+    /// `evaluator`.
+    fn minimize_with<E>(
+        &self,
+        mut evaluator: E,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        E: Evaluator<T>,
+    {
+        let dim = x_start.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        let size = self.population_size.unwrap_or((10 * dim).max(50));
+        if size < 2 {
+            return Err(NelderMeadError::InvalidPopulation);
+        }
+        let fractions = [self.elite_fraction, self.smoothing]
+            .iter()
+            .all(|share| T::zero() < *share && *share <= T::one());
+        if !fractions {
+            return Err(NelderMeadError::InvalidCrossEntropy);
+        }
+        if ![self.xtol, self.ftol].iter().all(|tol| T::zero() < *tol && tol.is_finite()) {
+            return Err(NelderMeadError::InvalidTolerances);
+        }
+        self.step.check_dim(dim)?;
+        let mut x_start = x_start;
+        let half: T = convert(0.5);
+        let mut deviation = DVector::from_fn(dim, |i, _| self.step.offset(i, x_start[i]).abs());
+        if let Some((lower, upper)) = &self.bounds {
+            if lower.len() != dim
+                || upper.len() != dim
+                || lower.iter().zip(upper.iter()).any(|(l, u)| l > u || is_nan(*l) || is_nan(*u))
+            {
+                return Err(NelderMeadError::InvalidBounds);
+            }
+            x_start = x_start.sup(lower).inf(upper);
+            deviation = (upper - lower) * half;
+        }
+        let elite_share = self.elite_fraction * convert(size as f64);
+        let elite_size = (1..size).find(|&k| elite_share <= convert(k as f64)).unwrap_or(size);
+
+        let start = Instant::now();
+        let sign = self.direction.sign();
+        let mut rng = Rng::seeded(self.seed);
+        let mut evaluations = 0;
+        let scores = self.evaluate(&mut evaluator, std::slice::from_ref(&x_start), &mut evaluations, sign, false)?;
+        let mut mean = x_start.clone();
+        let mut record = vec![(x_start, scores[0])];
+        let mut iterations = 0;
+
+        let termination_reason = loop {
+            if iterations >= self.max_iter {
+                break TerminationReason::MaxIter;
+            }
+            if self.max_fun_evals.is_some_and(|limit| evaluations >= limit) {
+                break TerminationReason::MaxFunEvals;
+            }
+            if self.max_duration.is_some_and(|limit| start.elapsed() >= limit) {
+                break TerminationReason::TimedOut;
+            }
+
+            let samples: Vec<DVector<T>> = (0..size)
+                .map(|_| {
+                    let x = DVector::from_fn(dim, |i, _| mean[i] + deviation[i] * convert(rng.normal()));
+                    match &self.bounds {
+                        Some((lower, upper)) => x.sup(lower).inf(upper),
+                        None => x,
+                    }
+                })
+                .collect();
+            let scores = self.evaluate(&mut evaluator, &samples, &mut evaluations, sign, true)?;
+            let evaluated = scores.len();
+            let mut generation: Vec<(DVector<T>, T)> = samples.into_iter().zip(scores).collect();
+            generation.sort_by(|a, b| total_cmp(a.1, b.1));
+            iterations += 1;
+
+            let elite = &generation[..elite_size.min(evaluated)];
+            let count: T = convert(elite.len() as f64);
+            let elite_mean = elite.iter().fold(DVector::zeros(dim), |sum, (x, _)| sum + x) / count;
+            let elite_deviation = elite
+                .iter()
+                .fold(DVector::zeros(dim), |sum, (x, _)| sum + (x - &elite_mean).map(|d| d * d))
+                .map(|variance: T| (variance / count).sqrt());
+            mean += (elite_mean - &mean) * self.smoothing;
+            deviation += (elite_deviation - &deviation) * self.smoothing;
+            let (best, worst) = (elite[0].1, elite[elite.len() - 1].1);
+            record.extend(generation.into_iter().take(dim + 1));
+            record.sort_by(|a, b| total_cmp(a.1, b.1));
+            record.truncate(dim + 1);
+            if evaluated < size {
+                break TerminationReason::MaxFunEvals;
+            }
+
+            let simplex = Simplex::from_pairs(&record);
+            let state = TerminationState {
+                iterations,
+                evaluations,
+                elapsed: start.elapsed(),
+                simplex: &simplex,
+                sign,
+                last_move: None,
+                budget: Budget {
+                    max_iter: self.max_iter,
+                    max_fun_evals: self.max_fun_evals,
+                    max_duration: self.max_duration,
+                },
+            };
+            logging::log_iteration(&state);
+            if let Some(observer) = &self.observer {
+                observer.0.observe(&state);
+            }
+            if let Some(criterion) = &self.termination {
+                if criterion.0.should_stop(&state) {
+                    break TerminationReason::Criterion;
+                }
+            }
+
+            if deviation.amax() < self.xtol || worst - best <= self.ftol * best.abs().max(T::one()) {
+                break TerminationReason::Converged;
+            }
+        };
+
+        Ok(OptimizationResult::from_simplex(
+            record,
+            sign,
+            iterations,
+            evaluations,
+            0,
+            termination_reason,
+            None,
+        ))
+    }
+
+    /// Scores of `points`, evaluated as one batch. With `limited`, only the
+    /// points within the remaining evaluation budget are evaluated.
+    fn evaluate<E>(
+        &self,
+        evaluator: &mut E,
+        points: &[DVector<T>],
+        evaluations: &mut u64,
+        sign: T,
+        limited: bool,
+    ) -> Result<Vec<T>, NelderMeadError>
+    where
+        E: Evaluator<T>,
+    {
+        let count = match self.max_fun_evals {
+            Some(limit) if limited => points.len().min(limit.saturating_sub(*evaluations) as usize),
+            _ => points.len(),
+        };
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let values = evaluator.evaluate_points(&points[..count]);
+        if values.len() != count {
+            return Err(NelderMeadError::InvalidBatch);
+        }
+        *evaluations += count as u64;
+        values.into_iter().map(|value| self.nan_handling.score(value, sign)).collect()
+    }
+}
@@ -0,0 +1,124 @@
+use nalgebra::{convert, DMatrix, DVector, RealField};
+
+use crate::nelder_mead::is_nan;
+use crate::simplex::Simplex;
+
+/// Shape of a simplex, telling whether it still spans the whole parameter
+/// space or has collapsed into a subspace, in which case a "converged"
+/// answer is unreliable.
+///
+/// Reported every iteration by [`TerminationState::health`] and for the
+/// final simplex by [`OptimizationResult::simplex_health`].
+///
+/// [`TerminationState::health`]: crate::termination::TerminationState::health
+/// [`OptimizationResult::simplex_health`]: crate::result::OptimizationResult::simplex_health
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// }
+/// let results = NelderMead::builder().minimize(&f, dvector![0.0, 0.0])?;
+/// let health = results.simplex_health();
+///
+/// assert!(health.volume > 0.0);
+/// assert!(!health.is_degenerate(1e8));
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimplexHealth<T = f64> {
+    /// Volume of the simplex, `|det(x_1 - x_0, ..., x_n - x_0)| / n!`.
+    pub volume: T,
+    /// Ratio of the longest to the shortest edge between any two vertices,
+    /// infinite when two vertices coincide.
+    pub edge_ratio: T,
+    /// Ratio of the largest to the smallest singular value of the edges
+    /// leaving the best vertex. It grows without bound as the simplex
+    /// flattens and is infinite once it spans a subspace only.
+    pub condition_number: T,
+}
+
+impl<T: RealField + Copy> SimplexHealth<T> {
+    /// Computes the diagnostics of a simplex ordered from its best vertex.
+    pub(crate) fn new(simplex: &Simplex<T>) -> Self {
+        let edges = edge_matrix(simplex);
+        let singular_values = edges.clone().singular_values();
+
+        let mut longest = T::zero();
+        let mut shortest = None::<T>;
+        for i in 0..simplex.len() {
+            for j in i + 1..simplex.len() {
+                let length = (simplex.column(i) - simplex.column(j)).norm();
+                longest = longest.max(length);
+                shortest = Some(shortest.map_or(length, |s| s.min(length)));
+            }
+        }
+
+        SimplexHealth {
+            volume: volume(&edges),
+            edge_ratio: longest / shortest.unwrap_or(T::one()),
+            condition_number: singular_values.max() / singular_values.min(),
+        }
+    }
+
+    /// Whether the simplex has collapsed, i.e. its condition number
+    /// exceeds `max_condition` or is not a number.
+    pub fn is_degenerate(&self, max_condition: T) -> bool {
+        self.condition_number > max_condition || is_nan(self.condition_number)
+    }
+}
+
+/// Response of the objective to a change of one parameter around the best
+/// point of a run, a row of the table returned by
+/// [`OptimizationResult::sensitivity`].
+///
+/// [`OptimizationResult::sensitivity`]: crate::result::OptimizationResult::sensitivity
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sensitivity<T = f64> {
+    /// Index of the perturbed parameter.
+    pub parameter: usize,
+    /// Change `δ` applied to the parameter, in both directions.
+    pub delta: T,
+    /// Objective value with the parameter decreased by `delta`.
+    pub below: T,
+    /// Objective value with the parameter increased by `delta`.
+    pub above: T,
+    /// Central difference `(above - below) / 2δ`, the partial derivative
+    /// of the objective, close to zero at an interior optimum.
+    pub slope: T,
+    /// Second difference `(above - 2 f(x) + below) / δ²`, the curvature of
+    /// the objective along the parameter. A small one marks a parameter
+    /// the objective hardly determines.
+    pub curvature: T,
+}
+
+/// Edges leaving the best vertex, one per column.
+pub(crate) fn edge_matrix<T: RealField + Copy>(simplex: &Simplex<T>) -> DMatrix<T> {
+    let best = simplex.vertex(0);
+    let n = best.len();
+    DMatrix::from_fn(n, n, |i, j| simplex.vertex(j + 1)[i] - best[i])
+}
+
+/// Simplex gradient `V^-T d` of a simplex ordered from its best vertex,
+/// `V` holding the edges leaving the best vertex and `d` the score
+/// differences to it. `None` when the simplex is degenerate.
+pub(crate) fn simplex_gradient<T: RealField + Copy>(simplex: &Simplex<T>) -> Option<DVector<T>> {
+    let best = simplex.score(0);
+    let differences = DVector::from_iterator(
+        simplex.len() - 1,
+        simplex.scores()[1..].iter().map(|score| *score - best),
+    );
+    edge_matrix(simplex).transpose().lu().solve(&differences)
+}
+
+/// Volume of the simplex spanned by the columns of `edges`.
+pub(crate) fn volume<T: RealField + Copy>(edges: &DMatrix<T>) -> T {
+    let factorial: T = convert((1..=edges.ncols()).map(|k| k as f64).product::<f64>());
+    edges.clone().determinant().abs() / factorial
+}
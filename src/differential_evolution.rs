@@ -0,0 +1,623 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::logging;
+use crate::nelder_mead::{is_nan, total_cmp, Direction, NanHandling, NelderMead, StepSize};
+use crate::objective::ObjectiveFunction;
+use crate::observer::{Observer, SharedObserver};
+use crate::progress::Budget;
+use crate::random::Rng;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationCriterion, TerminationState};
+
+/// How the mutant of a population member is built from the other members,
+/// `F` being the [`mutation`] factor.
+///
+/// [`mutation`]: DifferentialEvolutionBuilder::mutation
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MutationStrategy {
+    /// `x_r1 + F (x_r2 - x_r3)`, from three random members. Explores the
+    /// most, and suits multi-modal problems.
+    #[default]
+    Rand1,
+    /// `x_best + F (x_r1 - x_r2)`, around the best member. Converges faster,
+    /// at the risk of converging early.
+    Best1,
+    /// `x_i + F (x_best - x_i) + F (x_r1 - x_r2)`, between the member and the
+    /// best one.
+    CurrentToBest1,
+    /// `x_r1 + F (x_r2 - x_r3 + x_r4 - x_r5)`, from five random members.
+    Rand2,
+    /// `x_best + F (x_r1 - x_r2 + x_r3 - x_r4)`, around the best member.
+    Best2,
+}
+
+impl MutationStrategy {
+    /// Number of random members mixed into a mutant.
+    pub(crate) fn members(self) -> usize {
+        match self {
+            MutationStrategy::Best1 | MutationStrategy::CurrentToBest1 => 2,
+            MutationStrategy::Rand1 => 3,
+            MutationStrategy::Best2 => 4,
+            MutationStrategy::Rand2 => 5,
+        }
+    }
+}
+
+/// Storn and Price's differential evolution, a population-based global
+/// optimizer.
+///
+/// Every generation, each member of the population is challenged by a trial
+/// point: a mutant built from other members by the [`MutationStrategy`],
+/// whose coordinates are each taken with the probability `crossover`, the
+/// other ones from the member. The trial point replaces the member as soon
+/// as it scores at least as well. The initial population is drawn
+/// uniformly within the bounds, or within the step around `x_start`
+/// without bounds, and includes `x_start` itself. Mutant coordinates beyond
+/// the bounds are drawn again uniformly within them. The run is
+/// reproducible with [`DifferentialEvolutionBuilder::seed`].
+///
+/// The run converges once the scores of the population lie within `ftol`,
+/// relative to the best score, or absolutely below 1. With
+/// [`polish`](DifferentialEvolutionBuilder::polish), the best member is
+/// then refined by a local Nelder-Mead search, within the bounds and the
+/// remaining budget. Runs are reported as an [`OptimizationResult`],
+/// whose `iterations` are the generations followed by the iterations of
+/// the polishing, and whose `final_simplex` holds the `n + 1` best members
+/// of the population, or the final simplex of the polishing when it
+/// improved on them. The [`Observer`] and the [`TerminationCriterion`] are
+/// given the same members after every generation.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::DVector;
+/// use nelder_mead_optimizer::differential_evolution::{DifferentialEvolution, MutationStrategy};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::test_functions::rastrigin;
+///
+/// let x_start = DVector::from_element(4, 3.0);
+/// let (lower, upper) = (DVector::from_element(4, -5.12), DVector::from_element(4, 5.12));
+/// let de = DifferentialEvolution::builder()
+///     .bounds(lower, upper)
+///     .strategy(MutationStrategy::Rand1)
+///     .ftol(1e-6)
+///     .seed(3)
+///     .polish(
+///         NelderMead::builder()
+///             .disable_no_improv_break()
+///             .xatol(1e-10)
+///             .fatol(1e-14)
+///             .build(),
+///     )
+///     .minimize(rastrigin, x_start.clone())?;
+/// let plain = NelderMead::builder().minimize(rastrigin, x_start)?;
+///
+/// assert!(de.best_score < 1e-10);
+/// assert!(plain.best_score > 1.0);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct DifferentialEvolution<T = f64> {
+    pub(crate) step: StepSize<T>,
+    pub(crate) population_size: Option<usize>,
+    pub(crate) strategy: MutationStrategy,
+    pub(crate) mutation: T,
+    pub(crate) crossover: T,
+    pub(crate) seed: Option<u64>,
+    pub(crate) ftol: T,
+    pub(crate) polish: Option<NelderMead<T>>,
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) bounds: Option<(DVector<T>, DVector<T>)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for DifferentialEvolution<T> {
+    fn default() -> Self {
+        DifferentialEvolution {
+            step: StepSize::Uniform(convert(1.0)),
+            population_size: None,
+            strategy: MutationStrategy::Rand1,
+            mutation: convert(0.8),
+            crossover: convert(0.9),
+            seed: None,
+            ftol: convert(1e-8),
+            polish: None,
+            max_iter: 1000,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            bounds: None,
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`DifferentialEvolution`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `step`                  | 1                    |
+/// | `population_size`       | 10 n                 |
+/// | `strategy`              | `Rand1`              |
+/// | `mutation`              | 0.8                  |
+/// | `crossover`             | 0.9                  |
+/// | `seed`                  | random               |
+/// | `ftol`                  | 1e-8                 |
+/// | `polish`                | none                 |
+/// | `max_iter`              | 1000                 |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `bounds`                | none                 |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct DifferentialEvolutionBuilder<T = f64> {
+    config: DifferentialEvolution<T>,
+}
+
+impl<T: RealField + Copy> Default for DifferentialEvolutionBuilder<T> {
+    fn default() -> Self {
+        DifferentialEvolutionBuilder {
+            config: DifferentialEvolution::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> DifferentialEvolutionBuilder<T> {
+    /// Half-width of the region around `x_start` the initial population is
+    /// drawn from, either a single value used for every dimension or a
+    /// [`StepSize`]. Ignored with bounds, within which the population is
+    /// drawn instead.
+    pub fn step(mut self, step: impl Into<StepSize<T>>) -> Self {
+        self.config.step = step.into();
+        self
+    }
+
+    /// Number of members of the population, at least `n + 1` and more than
+    /// the random members mixed by the strategy.
+    pub fn population_size(mut self, population_size: usize) -> Self {
+        self.config.population_size = Some(population_size);
+        self
+    }
+
+    /// How the mutants are built, see [`MutationStrategy`].
+    pub fn strategy(mut self, strategy: MutationStrategy) -> Self {
+        self.config.strategy = strategy;
+        self
+    }
+
+    /// Factor `F` scaling the differences of members in the mutants,
+    /// within `(0, 2]`.
+    pub fn mutation(mut self, mutation: T) -> Self {
+        self.config.mutation = mutation;
+        self
+    }
+
+    /// Probability of taking every coordinate of the trial point from the
+    /// mutant, within `[0, 1]`. One coordinate is always taken from it.
+    pub fn crossover(mut self, crossover: T) -> Self {
+        self.config.crossover = crossover;
+        self
+    }
+
+    /// Seed of the random numbers, making the run reproducible. Without a
+    /// seed every run draws different numbers.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Spread of the scores of the population below which the run
+    /// converges, relative to the best score, absolute below 1.
+    pub fn ftol(mut self, ftol: T) -> Self {
+        self.config.ftol = ftol;
+        self
+    }
+
+    /// Nelder-Mead solver refining the best member once the population
+    /// converged or ran out of iterations. Its direction, NaN handling and
+    /// budgets are taken from this solver, and so are the bounds unless it
+    /// has its own.
+    pub fn polish(mut self, polish: NelderMead<T>) -> Self {
+        self.config.polish = Some(polish);
+        self
+    }
+
+    /// Maximum number of generations.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Lower and upper bounds of every parameter, within which the initial
+    /// population is drawn. The starting point is clipped into them.
+    pub fn bounds(mut self, lower: DVector<T>, upper: DVector<T>) -> Self {
+        self.config.bounds = Some((lower, upper));
+        self
+    }
+
+    /// Additional stopping rule, checked after every generation next to
+    /// the built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every generation with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> DifferentialEvolution<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`DifferentialEvolution::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`DifferentialEvolution::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, x_start)
+    }
+}
+
+/// Evaluations of a run, with the budgets they are bounded by.
+struct Run<'a, T, F> {
+    config: &'a DifferentialEvolution<T>,
+    obj_fn: F,
+    sign: T,
+    evaluations: u64,
+    start: Instant,
+}
+
+impl<T: RealField + Copy, F: FnMut(&DVector<T>) -> T> Run<'_, T, F> {
+    /// Score of `x`, or `None` when the evaluation budget or the time ran
+    /// out.
+    fn score(&mut self, x: &DVector<T>) -> Result<Option<T>, NelderMeadError> {
+        if self.exhausted().is_some() {
+            return Ok(None);
+        }
+        self.evaluate(x).map(Some)
+    }
+
+    /// Score of `x`, regardless of the budgets.
+    fn evaluate(&mut self, x: &DVector<T>) -> Result<T, NelderMeadError> {
+        self.evaluations += 1;
+        let value = (self.obj_fn)(x);
+        self.config.nan_handling.score(value, self.sign)
+    }
+
+    /// Why the run has to stop before the next evaluation, if it does.
+    fn exhausted(&self) -> Option<TerminationReason> {
+        if self.config.max_fun_evals.is_some_and(|limit| self.evaluations >= limit) {
+            return Some(TerminationReason::MaxFunEvals);
+        }
+        if self.config.max_duration.is_some_and(|limit| self.start.elapsed() >= limit) {
+            return Some(TerminationReason::TimedOut);
+        }
+        None
+    }
+}
+
+impl<T: RealField + Copy> DifferentialEvolution<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> DifferentialEvolutionBuilder<T> {
+        DifferentialEvolutionBuilder::default()
+    }
+
+    /// Finds the minimum (or maximum, depending on the configured
+    /// [`Direction`]) of the objective function, within the bounds or
+    /// around `x_start`.
+    ///
+    /// Fails if `x_start` is empty, if the population has fewer than `n + 1`
+    /// members or no more than the strategy mixes, if the mutation factor is not within `(0, 2]`, the
+    /// crossover probability not within `[0, 1]` or `ftol` not positive and
+    /// finite, if the step or the bounds do not fit `x_start`, or if the
+    /// objective function returns NaN under [`NanHandling::Error`]. The
+    /// polishing fails like [`NelderMead::minimize`].
+    pub fn minimize<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let dim = x_start.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        let size = self.population_size.unwrap_or(10 * dim);
+        if size <= dim || size <= self.strategy.members() {
+            return Err(NelderMeadError::InvalidPopulation);
+        }
+        let factors = T::zero() < self.mutation
+            && self.mutation <= convert(2.0)
+            && T::zero() <= self.crossover
+            && self.crossover <= T::one();
+        if !factors {
+            return Err(NelderMeadError::InvalidDifferentialEvolution);
+        }
+        if !(T::zero() < self.ftol && self.ftol.is_finite()) {
+            return Err(NelderMeadError::InvalidTolerances);
+        }
+        self.step.check_dim(dim)?;
+        let mut x_start = x_start;
+        if let Some((lower, upper)) = &self.bounds {
+            if lower.len() != dim
+                || upper.len() != dim
+                || lower.iter().zip(upper.iter()).any(|(l, u)| l > u || is_nan(*l) || is_nan(*u))
+            {
+                return Err(NelderMeadError::InvalidBounds);
+            }
+            x_start = x_start.sup(lower).inf(upper);
+        }
+
+        let mut run = Run {
+            config: self,
+            obj_fn,
+            sign: self.direction.sign(),
+            evaluations: 0,
+            start: Instant::now(),
+        };
+        let mut rng = Rng::seeded(self.seed);
+        let score = run.evaluate(&x_start)?;
+        let mut population = vec![(x_start.clone(), score)];
+        let mut iterations = 0;
+
+        let termination_reason = 'run: {
+            while population.len() < size {
+                let member = DVector::from_fn(dim, |i, _| self.draw(&mut rng, &x_start, i));
+                let Some(score) = run.score(&member)? else {
+                    break 'run run.exhausted().unwrap_or(TerminationReason::MaxFunEvals);
+                };
+                population.push((member, score));
+            }
+
+            loop {
+                if iterations >= self.max_iter {
+                    break 'run TerminationReason::MaxIter;
+                }
+                if let Some(reason) = run.exhausted() {
+                    break 'run reason;
+                }
+
+                for i in 0..size {
+                    let trial = self.trial(&mut rng, &population, i, &x_start);
+                    let Some(score) = run.score(&trial)? else {
+                        break 'run run.exhausted().unwrap_or(TerminationReason::MaxFunEvals);
+                    };
+                    if score <= population[i].1 {
+                        population[i] = (trial, score);
+                    }
+                }
+                iterations += 1;
+
+                let simplex = best_members(&population, dim + 1);
+                let state = TerminationState {
+                    iterations,
+                    evaluations: run.evaluations,
+                    elapsed: run.start.elapsed(),
+                    simplex: &simplex,
+                    sign: run.sign,
+                    last_move: None,
+                    budget: Budget {
+                        max_iter: self.max_iter,
+                        max_fun_evals: self.max_fun_evals,
+                        max_duration: self.max_duration,
+                    },
+                };
+                logging::log_iteration(&state);
+                if let Some(observer) = &self.observer {
+                    observer.0.observe(&state);
+                }
+                if let Some(criterion) = &self.termination {
+                    if criterion.0.should_stop(&state) {
+                        break 'run TerminationReason::Criterion;
+                    }
+                }
+
+                let (best, worst) = population
+                    .iter()
+                    .fold((population[0].1, population[0].1), |(low, high), (_, s)| (low.min(*s), high.max(*s)));
+                if worst - best <= self.ftol * best.abs().max(T::one()) {
+                    break 'run TerminationReason::Converged;
+                }
+            }
+        };
+
+        let mut final_simplex = best_members(&population, dim + 1).to_pairs(run.sign);
+        let best = final_simplex[0].clone();
+        let polishing = !matches!(
+            termination_reason,
+            TerminationReason::MaxFunEvals | TerminationReason::TimedOut
+        );
+        if let Some(polish) = self.polish.as_ref().filter(|_| polishing) {
+            let local = polish.polishing(
+                self.direction,
+                self.nan_handling,
+                &self.bounds,
+                self.max_fun_evals.map(|limit| limit.saturating_sub(run.evaluations)),
+                self.max_duration.map(|limit| limit.saturating_sub(run.start.elapsed())),
+            );
+            let polished = local.minimize(&mut run.obj_fn, best.0)?;
+            run.evaluations += polished.evaluations;
+            iterations += polished.iterations;
+            if run.sign * polished.best_score < run.sign * best.1 {
+                final_simplex = polished.final_simplex;
+            }
+        }
+
+        Ok(OptimizationResult::from_simplex(
+            final_simplex,
+            T::one(),
+            iterations,
+            run.evaluations,
+            0,
+            termination_reason,
+            None,
+        ))
+    }
+
+    /// Same as [`DifferentialEvolution::minimize`], but optimizes a
+    /// reusable [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), x_start)
+    }
+
+    /// Coordinate `i` of a random point, uniform within the bounds or
+    /// within the step around `x_start`.
+    fn draw(&self, rng: &mut Rng, x_start: &DVector<T>, i: usize) -> T {
+        let u: T = convert(rng.uniform());
+        match &self.bounds {
+            Some((lower, upper)) => lower[i] + u * (upper[i] - lower[i]),
+            None => {
+                let step = self.step.offset(i, x_start[i]);
+                x_start[i] + (u + u - T::one()) * step
+            }
+        }
+    }
+
+    /// Trial point challenging the member `i` of the population.
+    fn trial(&self, rng: &mut Rng, population: &[(DVector<T>, T)], i: usize, x_start: &DVector<T>) -> DVector<T> {
+        let size = population.len();
+        // distinct random members other than `i`
+        let mut picked: Vec<usize> = Vec::with_capacity(5);
+        while picked.len() < self.strategy.members() {
+            let r = rng.below(size);
+            if r != i && !picked.contains(&r) {
+                picked.push(r);
+            }
+        }
+        let x = |k: usize| &population[picked[k]].0;
+        let best = population
+            .iter()
+            .enumerate()
+            .min_by(|a, b| total_cmp(a.1 .1, b.1 .1))
+            .map_or(0, |(index, _)| index);
+        let x_best = &population[best].0;
+        let f = self.mutation;
+        let mutant = match self.strategy {
+            MutationStrategy::Rand1 => x(0) + (x(1) - x(2)) * f,
+            MutationStrategy::Best1 => x_best + (x(0) - x(1)) * f,
+            MutationStrategy::CurrentToBest1 => {
+                let current = &population[i].0;
+                current + (x_best - current) * f + (x(0) - x(1)) * f
+            }
+            MutationStrategy::Rand2 => x(0) + (x(1) - x(2) + x(3) - x(4)) * f,
+            MutationStrategy::Best2 => x_best + (x(0) - x(1) + x(2) - x(3)) * f,
+        };
+
+        let dim = mutant.len();
+        let forced = rng.below(dim);
+        let mut trial = population[i].0.clone();
+        for j in 0..dim {
+            if j == forced || convert::<f64, T>(rng.uniform()) < self.crossover {
+                trial[j] = mutant[j];
+            }
+        }
+        if let Some((lower, upper)) = &self.bounds {
+            for j in 0..dim {
+                if trial[j] < lower[j] || trial[j] > upper[j] {
+                    trial[j] = self.draw(rng, x_start, j);
+                }
+            }
+        }
+        trial
+    }
+}
+
+/// Simplex of the `count` best members of the population, ordered from the
+/// best one.
+fn best_members<T: RealField + Copy>(population: &[(DVector<T>, T)], count: usize) -> Simplex<T> {
+    let mut order: Vec<usize> = (0..population.len()).collect();
+    order.sort_by(|a, b| total_cmp(population[*a].1, population[*b].1));
+    order.truncate(count);
+    let members = order.iter().map(|&k| (population[k].0.as_slice(), population[k].1));
+    Simplex::from_slices(members)
+}
@@ -0,0 +1,538 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::logging;
+use crate::nelder_mead::{is_nan, total_cmp, Direction, NanHandling, NelderMead};
+use crate::objective::ObjectiveFunction;
+use crate::observer::{Observer, SharedObserver};
+use crate::progress::Budget;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationCriterion, TerminationState};
+
+/// Jones' DIRECT (dividing rectangles), a deterministic global optimizer
+/// for bound-constrained problems.
+///
+/// The bounds are scaled to the unit cube, which is divided into ever
+/// smaller hyperrectangles, each evaluated at its center. Every iteration
+/// divides the potentially optimal rectangles: those scoring best for some
+/// Lipschitz constant `K > 0` among rectangles of all sizes, and improving
+/// on the best score by at least `epsilon` of it for that constant. A
+/// rectangle is trisected along its longest sides, first along those whose
+/// new centers score best. Large rectangles keep being divided, so the
+/// search never stops exploring, while small ones around good scores
+/// refine the best point.
+///
+/// The run converges once the longest side of the rectangle holding the
+/// best center is below `xtol`, relative to the width of the bounds, and
+/// stops early rather than leave a rectangle half divided when
+/// `max_fun_evals` would be exceeded. With
+/// [`refine`](DirectBuilder::refine), the centers of the `refine_count`
+/// best rectangles are then each refined by a local Nelder-Mead search,
+/// within the bounds and the remaining budget. Runs are reported as an
+/// [`OptimizationResult`], whose `iterations` are the iterations of DIRECT
+/// followed by those of the local searches, and whose `final_simplex`
+/// holds the `n + 1` best centers, or the final simplex of the best local
+/// search when it improved on them. The [`Observer`] and the
+/// [`TerminationCriterion`] are given the same centers after every
+/// iteration.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::DVector;
+/// use nelder_mead_optimizer::direct::Direct;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::test_functions::rastrigin;
+///
+/// let (lower, upper) = (DVector::from_element(2, -4.0), DVector::from_element(2, 5.0));
+/// let direct = Direct::builder()
+///     .max_fun_evals(2000)
+///     .refine(
+///         NelderMead::builder()
+///             .disable_no_improv_break()
+///             .xatol(1e-10)
+///             .fatol(1e-14)
+///             .build(),
+///     )
+///     .minimize(rastrigin, lower, upper)?;
+/// let plain = NelderMead::builder().minimize(rastrigin, DVector::from_element(2, 0.5))?;
+///
+/// assert!(direct.best_score < 1e-10);
+/// assert!(direct.evaluations <= 2000);
+/// assert!(plain.best_score > 1.0);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct Direct<T = f64> {
+    pub(crate) epsilon: T,
+    pub(crate) xtol: T,
+    pub(crate) refine: Option<NelderMead<T>>,
+    pub(crate) refine_count: usize,
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for Direct<T> {
+    fn default() -> Self {
+        Direct {
+            epsilon: convert(1e-4),
+            xtol: convert(1e-6),
+            refine: None,
+            refine_count: 1,
+            max_iter: 1000,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`Direct`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `epsilon`               | 1e-4                 |
+/// | `xtol`                  | 1e-6                 |
+/// | `refine`                | none                 |
+/// | `refine_count`          | 1                    |
+/// | `max_iter`              | 1000                 |
+/// | `max_fun_evals`         | 1000 n               |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct DirectBuilder<T = f64> {
+    config: Direct<T>,
+}
+
+impl<T: RealField + Copy> Default for DirectBuilder<T> {
+    fn default() -> Self {
+        DirectBuilder {
+            config: Direct::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> DirectBuilder<T> {
+    /// Improvement on the best score, relative to it, a rectangle must be
+    /// able to reach to be divided, not negative. Larger values spend more
+    /// of the evaluations on large rectangles, exploring more globally.
+    pub fn epsilon(mut self, epsilon: T) -> Self {
+        self.config.epsilon = epsilon;
+        self
+    }
+
+    /// Longest side of the rectangle holding the best center below which
+    /// the run converges, relative to the width of the bounds.
+    pub fn xtol(mut self, xtol: T) -> Self {
+        self.config.xtol = xtol;
+        self
+    }
+
+    /// Nelder-Mead solver refining the best centers once the run converged
+    /// or ran out of iterations. Its direction, NaN handling and budgets
+    /// are taken from this solver, and so are the bounds unless it has its
+    /// own.
+    pub fn refine(mut self, refine: NelderMead<T>) -> Self {
+        self.config.refine = Some(refine);
+        self
+    }
+
+    /// Number of best rectangles whose centers are refined, one after the
+    /// other, by the [`refine`](DirectBuilder::refine) solver.
+    pub fn refine_count(mut self, refine_count: usize) -> Self {
+        self.config.refine_count = refine_count;
+        self
+    }
+
+    /// Maximum number of iterations.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Additional stopping rule, checked after every iteration next to the
+    /// built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every iteration with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> Direct<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Direct::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, lower, upper)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Direct::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, lower, upper)
+    }
+}
+
+/// Hyperrectangle of the unit cube, with the score of its center.
+struct Rectangle<T> {
+    center: DVector<T>,
+    // side `i` is `3^-levels[i]` long
+    levels: Vec<u32>,
+    score: T,
+}
+
+impl<T: RealField + Copy> Rectangle<T> {
+    /// Level of the longest sides, and their number.
+    fn longest(&self) -> (u32, usize) {
+        let level = self.levels.iter().copied().min().unwrap_or(0);
+        (level, self.levels.iter().filter(|&&l| l == level).count())
+    }
+}
+
+impl<T: RealField + Copy> Direct<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> DirectBuilder<T> {
+        DirectBuilder::default()
+    }
+
+    /// Finds the minimum (or maximum, depending on the configured
+    /// [`Direction`]) of the objective function within the bounds.
+    ///
+    /// Fails if the bounds are empty, of different lengths, not finite or
+    /// crossed, if `epsilon` is negative or `xtol` not positive, either not
+    /// finite, or if the objective function returns NaN under
+    /// [`NanHandling::Error`]. The refinement fails like
+    /// [`NelderMead::minimize`].
+    pub fn minimize<F>(
+        &self,
+        mut obj_fn: F,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let dim = lower.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        if upper.len() != dim
+            || lower
+                .iter()
+                .zip(upper.iter())
+                .any(|(l, u)| l > u || is_nan(*l) || is_nan(*u) || !l.is_finite() || !u.is_finite())
+        {
+            return Err(NelderMeadError::InvalidBounds);
+        }
+        let tolerances = T::zero() <= self.epsilon
+            && self.epsilon.is_finite()
+            && T::zero() < self.xtol
+            && self.xtol.is_finite();
+        if !tolerances {
+            return Err(NelderMeadError::InvalidTolerances);
+        }
+
+        let start = Instant::now();
+        let sign = self.direction.sign();
+        let max_fun_evals = self.max_fun_evals.unwrap_or(1000 * dim as u64);
+        let width = &upper - &lower;
+        let to_point = |center: &DVector<T>| &lower + center.component_mul(&width);
+        let mut evaluations = 0;
+        let mut evaluate = |center: &DVector<T>, evaluations: &mut u64| {
+            *evaluations += 1;
+            self.nan_handling.score(obj_fn(&to_point(center)), sign)
+        };
+
+        let center = DVector::from_element(dim, convert(0.5));
+        let score = evaluate(&center, &mut evaluations)?;
+        let mut rectangles = vec![Rectangle {
+            center,
+            levels: vec![0; dim],
+            score,
+        }];
+        let mut best = 0;
+        let mut iterations = 0;
+        let third: T = convert(1.0 / 3.0);
+
+        let termination_reason = 'run: loop {
+            if iterations >= self.max_iter {
+                break TerminationReason::MaxIter;
+            }
+
+            for j in self.potentially_optimal(&rectangles, best) {
+                let (level, longest) = rectangles[j].longest();
+                if evaluations + 2 * longest as u64 > max_fun_evals {
+                    break 'run TerminationReason::MaxFunEvals;
+                }
+                if self.max_duration.is_some_and(|limit| start.elapsed() >= limit) {
+                    break 'run TerminationReason::TimedOut;
+                }
+
+                let delta = third.powi(level as i32 + 1);
+                let mut splits = Vec::with_capacity(longest);
+                for i in (0..dim).filter(|&i| rectangles[j].levels[i] == level) {
+                    let mut pair = Vec::with_capacity(2);
+                    for offset in [-delta, delta] {
+                        let mut center = rectangles[j].center.clone();
+                        center[i] += offset;
+                        let score = evaluate(&center, &mut evaluations)?;
+                        pair.push((center, score));
+                    }
+                    splits.push((i, pair));
+                }
+                // divide first along the sides whose new centers score best
+                splits.sort_by(|a, b| {
+                    total_cmp(a.1[0].1.min(a.1[1].1), b.1[0].1.min(b.1[1].1)).then(a.0.cmp(&b.0))
+                });
+                for (i, pair) in splits {
+                    rectangles[j].levels[i] += 1;
+                    for (center, score) in pair {
+                        let levels = rectangles[j].levels.clone();
+                        rectangles.push(Rectangle { center, levels, score });
+                        if score < rectangles[best].score {
+                            best = rectangles.len() - 1;
+                        }
+                    }
+                }
+            }
+            iterations += 1;
+
+            let simplex = best_centers(&rectangles, dim + 1, to_point);
+            let state = TerminationState {
+                iterations,
+                evaluations,
+                elapsed: start.elapsed(),
+                simplex: &simplex,
+                sign,
+                last_move: None,
+                budget: Budget {
+                    max_iter: self.max_iter,
+                    max_fun_evals: Some(max_fun_evals),
+                    max_duration: self.max_duration,
+                },
+            };
+            logging::log_iteration(&state);
+            if let Some(observer) = &self.observer {
+                observer.0.observe(&state);
+            }
+            if let Some(criterion) = &self.termination {
+                if criterion.0.should_stop(&state) {
+                    break TerminationReason::Criterion;
+                }
+            }
+
+            let (level, _) = rectangles[best].longest();
+            if third.powi(level as i32) < self.xtol {
+                break TerminationReason::Converged;
+            }
+        };
+
+        let mut final_simplex = best_centers(&rectangles, dim + 1, to_point).to_pairs(sign);
+        let refining = !matches!(
+            termination_reason,
+            TerminationReason::MaxFunEvals | TerminationReason::TimedOut
+        );
+        if let Some(refine) = self.refine.as_ref().filter(|_| refining) {
+            let bounds = Some((lower.clone(), upper.clone()));
+            let mut best_score = sign * final_simplex[0].1;
+            let starts = best_centers(&rectangles, self.refine_count, to_point);
+            for k in 0..starts.len() {
+                let remaining = max_fun_evals.saturating_sub(evaluations);
+                if remaining == 0 || self.max_duration.is_some_and(|limit| start.elapsed() >= limit) {
+                    break;
+                }
+                let local = refine.polishing(
+                    self.direction,
+                    self.nan_handling,
+                    &bounds,
+                    Some(remaining),
+                    self.max_duration.map(|limit| limit.saturating_sub(start.elapsed())),
+                );
+                let refined = local.minimize(&mut obj_fn, starts.point(k))?;
+                evaluations += refined.evaluations;
+                iterations += refined.iterations;
+                if sign * refined.best_score < best_score {
+                    best_score = sign * refined.best_score;
+                    final_simplex = refined.final_simplex;
+                }
+            }
+        }
+
+        Ok(OptimizationResult::from_simplex(
+            final_simplex,
+            T::one(),
+            iterations,
+            evaluations,
+            0,
+            termination_reason,
+            None,
+        ))
+    }
+
+    /// Same as [`Direct::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), lower, upper)
+    }
+
+    /// Indices of the potentially optimal rectangles, `best` being the
+    /// index of the best one.
+    fn potentially_optimal(&self, rectangles: &[Rectangle<T>], best: usize) -> Vec<usize> {
+        // best rectangle of every size, ordered from the smallest size
+        let mut sizes: BTreeMap<(Reverse<u32>, usize), usize> = BTreeMap::new();
+        for (k, rectangle) in rectangles.iter().enumerate() {
+            let (level, longest) = rectangle.longest();
+            sizes
+                .entry((Reverse(level), longest))
+                .and_modify(|j| {
+                    if rectangle.score < rectangles[*j].score {
+                        *j = k;
+                    }
+                })
+                .or_insert(k);
+        }
+        let dim = rectangles[best].levels.len();
+        let ninth: T = convert(1.0 / 9.0);
+        let candidates: Vec<(T, T, usize)> = sizes
+            .into_iter()
+            .map(|((Reverse(level), longest), k)| {
+                // half the diagonal, with `longest` sides of `3^-level` and
+                // the others of a third of it
+                let side = ninth.powi(level as i32);
+                let squares = side * convert(longest as f64) + side * ninth * convert((dim - longest) as f64);
+                (squares.sqrt() * convert(0.5), rectangles[k].score, k)
+            })
+            .collect();
+
+        let f_min = rectangles[best].score;
+        let threshold = f_min - self.epsilon * f_min.abs();
+        let mut selected = Vec::new();
+        for (j, &(d_j, f_j, k)) in candidates.iter().enumerate() {
+            // range of the Lipschitz constants for which `j` scores best
+            let low = candidates[..j]
+                .iter()
+                .fold(T::zero(), |low, &(d, f, _)| low.max((f_j - f) / (d_j - d)));
+            let high = candidates[j + 1..]
+                .iter()
+                .map(|&(d, f, _)| (f - f_j) / (d - d_j))
+                .fold(None, |high: Option<T>, slope| Some(high.map_or(slope, |h| h.min(slope))));
+            let optimal = match high {
+                None => true,
+                Some(high) => T::zero() < high && low <= high && f_j - high * d_j <= threshold,
+            };
+            if optimal {
+                selected.push(k);
+            }
+        }
+        selected
+    }
+}
+
+/// Simplex of the centers of the `count` best rectangles, mapped to the
+/// bounds by `to_point`, ordered from the best one.
+fn best_centers<T, P>(rectangles: &[Rectangle<T>], count: usize, to_point: P) -> Simplex<T>
+where
+    T: RealField + Copy,
+    P: Fn(&DVector<T>) -> DVector<T>,
+{
+    let mut order: Vec<usize> = (0..rectangles.len()).collect();
+    order.sort_by(|a, b| total_cmp(rectangles[*a].score, rectangles[*b].score));
+    order.truncate(count);
+    let points: Vec<(DVector<T>, T)> = order
+        .iter()
+        .map(|&k| (to_point(&rectangles[k].center), rectangles[k].score))
+        .collect();
+    Simplex::from_pairs(&points)
+}
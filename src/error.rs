@@ -0,0 +1,321 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::coefficients::CoefficientError;
+
+/// Error returned when an optimization run cannot be carried out.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::error::NelderMeadError;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 1.0).sqrt() + x[1].powi(2)
+/// }
+/// let results = NelderMead::builder().minimize(&f, dvector![0.0, 0.0]);
+///
+/// assert_eq!(results, Err(NelderMeadError::NanScore));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum NelderMeadError {
+    /// A simplex coefficient is out of its valid range.
+    InvalidCoefficients(CoefficientError),
+    /// A per-dimension step does not have the dimension of the starting
+    /// point.
+    StepDimensionMismatch { expected: usize, found: usize },
+    /// The initial simplex does not contain `n + 1` vertices of the same
+    /// dimension `n >= 1`, or the step building it is zero or not finite.
+    InvalidSimplex,
+    /// The bounds do not have the dimension of the starting point, a
+    /// lower bound exceeds its upper bound, a bound lies outside of the
+    /// domain of the parameter transform, the bounds of a weight of a
+    /// unit simplex do not contain `[0, 1]`, or the bounds of a periodic
+    /// parameter do not contain `[0, period]`.
+    InvalidBounds,
+    /// A parameter transform is set beyond the dimension of the starting
+    /// point, a vertex of the initial simplex lies outside of the domain
+    /// of its parameter transforms, or a unit simplex has fewer than two
+    /// weights, a weight beyond the dimension of the starting point, in
+    /// another unit simplex or with a parameter transform, or a period is
+    /// not positive and finite, is set beyond the dimension of the starting
+    /// point or on a parameter with a transform.
+    InvalidTransform,
+    /// The linear equality constraints `A x = b` do not fit together or
+    /// the starting point, have no solution or a single one, are set
+    /// together with bounds, parameter transforms, unit simplices,
+    /// periodic parameters or automatic scaling, or are set on a solver
+    /// other than `minimize` and `minimize_from_simplex`.
+    InvalidLinearEquality,
+    /// The initial trust region radius is not positive and finite, or the
+    /// final one is not within `(0, rho_begin]`. A [`Bobyqa`] solver also
+    /// needs between `n + 2` and `(n + 1)(n + 2) / 2` interpolation points
+    /// and bounds at least `2 * rho_begin` wide.
+    ///
+    /// [`Bobyqa`]: crate::bobyqa::Bobyqa
+    InvalidTrustRegion,
+    /// The subspace sizes of a [`Subplex`] solver are not within
+    /// `1 <= min <= max`, or its factors `psi` and `omega` are not within
+    /// `(0, 1)`.
+    ///
+    /// [`Subplex`]: crate::subplex::Subplex
+    InvalidSubplex,
+    /// The step reduction factor of a [`HookeJeeves`] solver is not within
+    /// `(0, 1)`, or the final step of a [`HookeJeeves`] or the final poll
+    /// size of a [`Mads`] solver is not positive and finite.
+    ///
+    /// [`HookeJeeves`]: crate::hooke_jeeves::HookeJeeves
+    /// [`Mads`]: crate::mads::Mads
+    InvalidPatternSearch,
+    /// The tolerances `xtol` or `ftol` of a [`Powell`], [`CmaEs`],
+    /// [`DifferentialEvolution`], [`ParticleSwarm`] or [`Direct`] solver,
+    /// or the cluster tolerances of a [`MultiStart`] driver, are not
+    /// positive and finite, or the `epsilon` of a [`Direct`] solver is
+    /// negative or not finite.
+    ///
+    /// [`Powell`]: crate::powell::Powell
+    /// [`CmaEs`]: crate::cma_es::CmaEs
+    /// [`DifferentialEvolution`]: crate::differential_evolution::DifferentialEvolution
+    /// [`ParticleSwarm`]: crate::particle_swarm::ParticleSwarm
+    /// [`Direct`]: crate::direct::Direct
+    /// [`MultiStart`]: crate::multi_start::MultiStart
+    InvalidTolerances,
+    /// The population of a [`CmaEs`] solver has fewer than two points, the
+    /// population of a [`DifferentialEvolution`] solver fewer than `n + 1`
+    /// members or no more than its strategy mixes, the swarm of a
+    /// [`ParticleSwarm`] fewer than `n + 1` particles, the population of a
+    /// [`CrossEntropy`] solver fewer than two points, or a [`MultiStart`]
+    /// driver has no starts or more than its evaluation budget affords,
+    /// at `n + 1` evaluations for the initial simplex of each.
+    ///
+    /// [`CmaEs`]: crate::cma_es::CmaEs
+    /// [`CrossEntropy`]: crate::cross_entropy::CrossEntropy
+    /// [`DifferentialEvolution`]: crate::differential_evolution::DifferentialEvolution
+    /// [`ParticleSwarm`]: crate::particle_swarm::ParticleSwarm
+    /// [`MultiStart`]: crate::multi_start::MultiStart
+    InvalidPopulation,
+    /// The mutation factor of a [`DifferentialEvolution`] solver is not
+    /// within `(0, 2]`, or its crossover probability not within `[0, 1]`.
+    ///
+    /// [`DifferentialEvolution`]: crate::differential_evolution::DifferentialEvolution
+    InvalidDifferentialEvolution,
+    /// The inertia of a [`ParticleSwarm`] is not within `[0, 1)`, or one of
+    /// its cognitive and social weights is negative or infinite.
+    ///
+    /// [`ParticleSwarm`]: crate::particle_swarm::ParticleSwarm
+    InvalidParticleSwarm,
+    /// The elite fraction or the smoothing of a [`CrossEntropy`] solver is
+    /// not within `(0, 1]`.
+    ///
+    /// [`CrossEntropy`]: crate::cross_entropy::CrossEntropy
+    InvalidCrossEntropy,
+    /// The step, the gain or the perturbation of a [`Spsa`] solver is not
+    /// positive and finite, its stability constant negative or infinite,
+    /// one of its exponents not positive, or it averages no perturbation.
+    ///
+    /// [`Spsa`]: crate::spsa::Spsa
+    InvalidSpsa,
+    /// The initial temperature of a [`SimulatedAnnealing`] solver is not
+    /// positive and finite, its minimum temperature not positive and below
+    /// it, its exponential cooling rate not within `(0, 1)`, or its chain
+    /// empty.
+    ///
+    /// [`SimulatedAnnealing`]: crate::simulated_annealing::SimulatedAnnealing
+    InvalidAnnealing,
+    /// The temperature or the minima tolerance of a [`BasinHopping`] solver
+    /// is negative or not finite.
+    ///
+    /// [`BasinHopping`]: crate::basin_hopping::BasinHopping
+    InvalidBasinHopping,
+    /// A [`Sobol`] sequence was asked for zero dimensions or more than
+    /// [`Sobol::MAX_DIMENSION`].
+    ///
+    /// [`Sobol`]: crate::sobol::Sobol
+    /// [`Sobol::MAX_DIMENSION`]: crate::sobol::Sobol::MAX_DIMENSION
+    InvalidSampling,
+    /// The number of points along every parameter of a [`GridSearch`] is
+    /// zero.
+    ///
+    /// [`GridSearch`]: crate::baseline::GridSearch
+    InvalidGrid,
+    /// The grid of [`NelderMead::profile_likelihood`] has no value on
+    /// either side of the fit, its widths do not have the dimension of the
+    /// fit or are not positive and finite, or the threshold is not
+    /// positive and finite.
+    ///
+    /// [`NelderMead::profile_likelihood`]: crate::nelder_mead::NelderMead::profile_likelihood
+    InvalidProfile,
+    /// The resolution of the evaluation cache, see
+    /// [`NelderMeadBuilder::cache_evaluations`], is negative or not finite.
+    ///
+    /// [`NelderMeadBuilder::cache_evaluations`]: crate::builder::NelderMeadBuilder::cache_evaluations
+    InvalidCacheResolution,
+    /// The number of vertices replaced per iteration, see
+    /// [`NelderMeadBuilder::parallel_points`], is zero or exceeds the
+    /// dimension of the problem, or is above one for
+    /// [`NelderMead::minimize_static`], which evaluates one point at a time.
+    ///
+    /// [`NelderMeadBuilder::parallel_points`]: crate::builder::NelderMeadBuilder::parallel_points
+    /// [`NelderMead::minimize_static`]: crate::nelder_mead::NelderMead::minimize_static
+    InvalidParallelPoints,
+    /// The magnitude of the jitter of the reflected point, see
+    /// [`NelderMeadBuilder::plateau_jitter`], is not positive and finite.
+    ///
+    /// [`NelderMeadBuilder::plateau_jitter`]: crate::builder::NelderMeadBuilder::plateau_jitter
+    InvalidPlateauJitter,
+    /// The number of replicated evaluations of every point, see
+    /// [`NelderMeadBuilder::replicate`], is zero, its adaptive maximum is
+    /// below its minimum, or the target standard error is not positive
+    /// and finite. Replication is also only supported by the solvers
+    /// running to completion, such as [`NelderMead::minimize`], and not by
+    /// [`NelderMead::iter`], [`NelderMead::resume_from_checkpoint`],
+    /// [`NelderMead::ask_tell`] or [`NelderMead::minimize_static`].
+    ///
+    /// [`NelderMead::minimize`]: crate::nelder_mead::NelderMead::minimize
+    /// [`NelderMead::iter`]: crate::nelder_mead::NelderMead::iter
+    /// [`NelderMead::resume_from_checkpoint`]: crate::nelder_mead::NelderMead::resume_from_checkpoint
+    /// [`NelderMead::ask_tell`]: crate::nelder_mead::NelderMead::ask_tell
+    /// [`NelderMead::minimize_static`]: crate::nelder_mead::NelderMead::minimize_static
+    ///
+    /// [`NelderMeadBuilder::replicate`]: crate::builder::NelderMeadBuilder::replicate
+    InvalidReplication,
+    /// The critical value of the noise-aware termination test, see
+    /// [`NelderMeadBuilder::noise_aware_termination`], is not positive and
+    /// finite.
+    ///
+    /// [`NelderMeadBuilder::noise_aware_termination`]: crate::builder::NelderMeadBuilder::noise_aware_termination
+    InvalidNoiseTest,
+    /// The objective function returned NaN, so the vertices cannot be
+    /// ordered. See [`NanHandling`] for the alternatives.
+    ///
+    /// [`NanHandling`]: crate::nelder_mead::NanHandling
+    NanScore,
+    /// A point was told to a [`NelderMeadAskTell`] run without being asked
+    /// for, or twice.
+    ///
+    /// [`NelderMeadAskTell`]: crate::nelder_mead_ask_tell::NelderMeadAskTell
+    UnexpectedPoint,
+    /// A batch objective, see [`NelderMead::minimize_batch`],
+    /// [`ParticleSwarm::minimize_batch`] and
+    /// [`CrossEntropy::minimize_batch`], returned a number of values other
+    /// than the number of points it was given.
+    ///
+    /// [`NelderMead::minimize_batch`]: crate::nelder_mead::NelderMead::minimize_batch
+    /// [`ParticleSwarm::minimize_batch`]: crate::particle_swarm::ParticleSwarm::minimize_batch
+    /// [`CrossEntropy::minimize_batch`]: crate::cross_entropy::CrossEntropy::minimize_batch
+    InvalidBatch,
+    /// The objective function failed to evaluate a point under
+    /// [`FailureHandling::Abort`], with the description of the failure.
+    ///
+    /// [`FailureHandling::Abort`]: crate::nelder_mead::FailureHandling::Abort
+    Objective(String),
+    /// A checkpoint file could not be written or read.
+    Checkpoint(String),
+    /// A plot could not be rendered.
+    Plot(String),
+}
+
+impl fmt::Display for NelderMeadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NelderMeadError::InvalidCoefficients(err) => {
+                write!(f, "invalid coefficients: {}", err)
+            }
+            NelderMeadError::StepDimensionMismatch { expected, found } => write!(
+                f,
+                "per-dimension step has {} elements, the starting point has {}",
+                found, expected
+            ),
+            NelderMeadError::InvalidSimplex => {
+                f.write_str("simplex must contain n + 1 vertices of dimension n >= 1")
+            }
+            NelderMeadError::InvalidBounds => {
+                f.write_str("bounds must have the dimension of the point and lower <= upper")
+            }
+            NelderMeadError::InvalidTransform => {
+                f.write_str("point must lie in the domain of its parameter transforms")
+            }
+            NelderMeadError::InvalidLinearEquality => {
+                f.write_str("linear equality constraints must be consistent and fit the point")
+            }
+            NelderMeadError::InvalidTrustRegion => {
+                f.write_str("trust region radii must satisfy 0 < rho_end <= rho_begin")
+            }
+            NelderMeadError::InvalidSubplex => {
+                f.write_str("subspace sizes must satisfy 1 <= min <= max and psi, omega lie in (0, 1)")
+            }
+            NelderMeadError::InvalidPatternSearch => {
+                f.write_str("step reduction must lie in (0, 1) and the final step be positive")
+            }
+            NelderMeadError::InvalidTolerances => f.write_str("tolerances must be positive and finite"),
+            NelderMeadError::InvalidPopulation => f.write_str("population is too small for the solver"),
+            NelderMeadError::InvalidDifferentialEvolution => {
+                f.write_str("mutation must lie in (0, 2] and crossover in [0, 1]")
+            }
+            NelderMeadError::InvalidParticleSwarm => {
+                f.write_str("inertia must lie in [0, 1) and the weights be non-negative")
+            }
+            NelderMeadError::InvalidCrossEntropy => {
+                f.write_str("elite fraction and smoothing must lie in (0, 1]")
+            }
+            NelderMeadError::InvalidSpsa => {
+                f.write_str("SPSA gains must be positive and finite, with at least one perturbation")
+            }
+            NelderMeadError::InvalidAnnealing => {
+                f.write_str("temperatures must be positive and decreasing, and the chain not empty")
+            }
+            NelderMeadError::InvalidBasinHopping => {
+                f.write_str("temperature and minima tolerance must be non-negative and finite")
+            }
+            NelderMeadError::InvalidSampling => {
+                f.write_str("Sobol sequence dimension must lie in [1, 40]")
+            }
+            NelderMeadError::InvalidGrid => f.write_str("grids need at least one point along every parameter"),
+            NelderMeadError::InvalidCacheResolution => {
+                f.write_str("cache resolution must be non-negative and finite")
+            }
+            NelderMeadError::InvalidProfile => {
+                f.write_str("profile grids need values on both sides, positive widths and a positive threshold")
+            }
+            NelderMeadError::InvalidParallelPoints => {
+                f.write_str("parallel points must be between 1 and the dimension of the problem")
+            }
+            NelderMeadError::InvalidPlateauJitter => {
+                f.write_str("plateau jitter magnitude must be positive and finite")
+            }
+            NelderMeadError::InvalidReplication => {
+                f.write_str("replicates must be at least one per point and the target error positive")
+            }
+            NelderMeadError::InvalidNoiseTest => {
+                f.write_str("noise test critical value must be positive and finite")
+            }
+            NelderMeadError::NanScore => f.write_str("objective function returned NaN"),
+            NelderMeadError::UnexpectedPoint => {
+                f.write_str("point was not asked for or was already told")
+            }
+            NelderMeadError::InvalidBatch => {
+                f.write_str("batch objective must return one value per point")
+            }
+            NelderMeadError::Objective(err) => write!(f, "objective function failed: {}", err),
+            NelderMeadError::Checkpoint(err) => write!(f, "checkpoint failed: {}", err),
+            NelderMeadError::Plot(err) => write!(f, "plot failed: {}", err),
+        }
+    }
+}
+
+impl Error for NelderMeadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NelderMeadError::InvalidCoefficients(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<CoefficientError> for NelderMeadError {
+    fn from(err: CoefficientError) -> Self {
+        NelderMeadError::InvalidCoefficients(err)
+    }
+}
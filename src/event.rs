@@ -0,0 +1,194 @@
+use std::fmt;
+use std::sync::Arc;
+
+use nalgebra::DVector;
+
+use crate::observer::Move;
+
+/// Simplex operation performed by an iteration, together with the points
+/// involved, e.g. to animate the path of the simplex.
+///
+/// Events are reported to the [`EventListener`] configured with
+/// [`NelderMeadBuilder::on_event`] as soon as the operation is applied.
+/// An iteration interrupted by `max_fun_evals` or `max_duration` emits
+/// no event, an iteration replacing several vertices, see
+/// [`NelderMeadBuilder::parallel_points`], emits one event per replaced
+/// vertex, whose centroid is the one of the vertices that are kept.
+/// Scores are reported in the direction of the objective, i.e. they are
+/// not negated when maximizing.
+///
+/// [`NelderMeadBuilder::on_event`]: crate::builder::NelderMeadBuilder::on_event
+/// [`NelderMeadBuilder::parallel_points`]: crate::builder::NelderMeadBuilder::parallel_points
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub enum Event<T = f64> {
+    /// The worst vertex was replaced by its reflection through the
+    /// centroid of the others.
+    Reflection {
+        /// Centroid of all vertices but the worst one.
+        centroid: DVector<T>,
+        /// The worst vertex that was replaced.
+        replaced: DVector<T>,
+        /// The reflected point.
+        point: DVector<T>,
+        /// Score of the reflected point.
+        score: T,
+    },
+    /// The worst vertex was replaced by the expanded reflection.
+    Expansion {
+        /// Centroid of all vertices but the worst one.
+        centroid: DVector<T>,
+        /// The worst vertex that was replaced.
+        replaced: DVector<T>,
+        /// The reflected point the expansion improved on.
+        reflected: DVector<T>,
+        /// The expanded point.
+        point: DVector<T>,
+        /// Score of the expanded point.
+        score: T,
+    },
+    /// The worst vertex was moved towards the centroid.
+    Contraction {
+        /// Centroid of all vertices but the worst one.
+        centroid: DVector<T>,
+        /// The worst vertex that was replaced.
+        replaced: DVector<T>,
+        /// The contracted point.
+        point: DVector<T>,
+        /// Score of the contracted point.
+        score: T,
+    },
+    /// The worst vertex was replaced by the reflected point moved towards
+    /// the centroid.
+    OutsideContraction {
+        /// Centroid of all vertices but the worst one.
+        centroid: DVector<T>,
+        /// The worst vertex that was replaced.
+        replaced: DVector<T>,
+        /// The reflected point that was contracted.
+        reflected: DVector<T>,
+        /// The contracted point.
+        point: DVector<T>,
+        /// Score of the contracted point.
+        score: T,
+    },
+    /// Every vertex but the best one was moved towards the best one.
+    Shrink {
+        /// The best vertex the simplex shrank towards.
+        best: DVector<T>,
+        /// Vertices of the shrunk simplex with their scores, starting with
+        /// the best vertex, which is kept as is.
+        vertices: Vec<(DVector<T>, T)>,
+    },
+    /// The simplex had collapsed or stagnated and was rebuilt around the
+    /// best vertex.
+    Restart {
+        /// Vertices of the rebuilt simplex with their scores, starting
+        /// with the best vertex it was built around.
+        vertices: Vec<(DVector<T>, T)>,
+    },
+}
+
+impl<T> Event<T> {
+    /// The [`Move`] this event reports.
+    pub fn movement(&self) -> Move {
+        match self {
+            Event::Reflection { .. } => Move::Reflection,
+            Event::Expansion { .. } => Move::Expansion,
+            Event::Contraction { .. } => Move::Contraction,
+            Event::OutsideContraction { .. } => Move::OutsideContraction,
+            Event::Shrink { .. } => Move::Shrink,
+            Event::Restart { .. } => Move::Restart,
+        }
+    }
+
+    /// Applies `f` to every point of the event.
+    pub(crate) fn points_mut(&mut self, mut f: impl FnMut(&mut DVector<T>)) {
+        match self {
+            Event::Reflection { centroid, replaced, point, .. }
+            | Event::Contraction { centroid, replaced, point, .. } => {
+                f(centroid);
+                f(replaced);
+                f(point);
+            }
+            Event::Expansion { centroid, replaced, reflected, point, .. }
+            | Event::OutsideContraction { centroid, replaced, reflected, point, .. } => {
+                f(centroid);
+                f(replaced);
+                f(reflected);
+                f(point);
+            }
+            Event::Shrink { best, vertices } => {
+                f(best);
+                vertices.iter_mut().for_each(|(x, _)| f(x));
+            }
+            Event::Restart { vertices } => vertices.iter_mut().for_each(|(x, _)| f(x)),
+        }
+    }
+}
+
+/// Hook invoked with an [`Event`] every time the simplex moves.
+///
+/// It is implemented for every closure taking an [`Event`]; closures
+/// collecting events need interior mutability since the listener is
+/// shared with the configuration.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+///
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::event::Event;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// }
+/// let events = Arc::new(Mutex::new(Vec::new()));
+/// let trace = events.clone();
+/// let results = NelderMead::builder()
+///     .on_event(move |event: &Event| trace.lock().unwrap().push(event.clone()))
+///     .minimize(&f, dvector![0.0, 0.0])?;
+///
+/// let events = events.lock().unwrap();
+/// assert!(!events.is_empty());
+/// assert!(events.iter().any(|event| matches!(event, Event::Reflection { .. })));
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+pub trait EventListener<T = f64> {
+    /// Called with every operation applied to the simplex.
+    fn on_event(&self, event: &Event<T>);
+}
+
+impl<T, F> EventListener<T> for F
+where
+    F: Fn(&Event<T>),
+{
+    fn on_event(&self, event: &Event<T>) {
+        self(event)
+    }
+}
+
+/// Event listener stored in the optimizer configuration.
+#[derive(Clone)]
+pub(crate) struct SharedEventListener<T>(pub(crate) Arc<dyn EventListener<T> + Send + Sync>);
+
+impl<T> fmt::Debug for SharedEventListener<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EventListener")
+    }
+}
+
+impl<T> PartialEq for SharedEventListener<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
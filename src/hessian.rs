@@ -0,0 +1,132 @@
+use std::time::Instant;
+
+use nalgebra::{convert, DMatrix, DVector, RealField};
+
+use crate::nelder_mead::NelderMead;
+use crate::objective::Evaluator;
+use crate::result::OptimizationResult;
+
+/// Central finite-difference Hessian of the objective at the best point
+/// of a run, with the covariance and standard errors it implies, as
+/// estimated with [`NelderMeadBuilder::estimate_covariance`].
+///
+/// For a negative log-likelihood, or a log-likelihood being maximized,
+/// the covariance is the asymptotic covariance of the maximum likelihood
+/// estimates. For a sum of squared residuals, it is to be scaled by twice
+/// the residual variance.
+///
+/// [`NelderMeadBuilder::estimate_covariance`]: crate::builder::NelderMeadBuilder::estimate_covariance
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct HessianEstimate<T = f64> {
+    /// Second derivatives of the objective at the best point.
+    pub hessian: DMatrix<T>,
+    /// Inverse of the Hessian, negated when maximizing, `None` unless it
+    /// is positive definite, as at a strict optimum.
+    pub covariance: Option<DMatrix<T>>,
+    /// Square roots of the diagonal of the covariance.
+    pub standard_errors: Option<DVector<T>>,
+}
+
+impl<T: RealField + Copy> HessianEstimate<T> {
+    /// Estimate from the Hessian `hessian` of an objective minimized when
+    /// `sign` is one and maximized when it is minus one.
+    fn new(hessian: DMatrix<T>, sign: T) -> Self {
+        let covariance = (&hessian * sign).cholesky().map(|cholesky| cholesky.inverse());
+        let standard_errors = covariance
+            .as_ref()
+            .map(|covariance| covariance.diagonal().map(|variance| variance.sqrt()));
+        HessianEstimate {
+            hessian,
+            covariance,
+            standard_errors,
+        }
+    }
+}
+
+impl<T: RealField + Copy> NelderMead<T> {
+    /// Estimates the Hessian at the best point of `result` by central
+    /// differences, with steps `ε^(1/4) · max(|x_i|, 1)`, all `2n² + 1`
+    /// points evaluated together. Nothing is estimated if they do not fit
+    /// in what remains of the budgets of the run started at `start`, or if
+    /// the objective is not finite at any of them.
+    pub(crate) fn estimate_hessian<E>(
+        &self,
+        mut obj_fn: E,
+        mut result: OptimizationResult<T>,
+        start: Instant,
+    ) -> OptimizationResult<T>
+    where
+        E: Evaluator<T>,
+    {
+        let x = &result.best_point;
+        let dim = x.len();
+        let count = 2 * dim * dim + 1;
+        let evaluations = self
+            .max_fun_evals
+            .is_none_or(|limit| result.evaluations + count as u64 <= limit);
+        let time = self.max_duration.is_none_or(|limit| start.elapsed() < limit);
+        if !(evaluations && time) {
+            return result;
+        }
+
+        let relative = T::default_epsilon().powf(convert(0.25));
+        let steps: Vec<T> = x.iter().map(|xi| relative * xi.abs().max(T::one())).collect();
+        let shifted = |offsets: &[(usize, T)]| {
+            let mut point = x.clone();
+            for &(i, offset) in offsets {
+                point[i] += offset;
+            }
+            point
+        };
+        // the point itself, the pairs of points along every axis, then the
+        // four corners of every pair of axes
+        let mut points = Vec::with_capacity(count);
+        points.push(x.clone());
+        for (i, &h) in steps.iter().enumerate() {
+            points.push(shifted(&[(i, h)]));
+            points.push(shifted(&[(i, -h)]));
+        }
+        for i in 0..dim {
+            for j in 0..i {
+                let (hi, hj) = (steps[i], steps[j]);
+                for (si, sj) in [(hi, hj), (hi, -hj), (-hi, hj), (-hi, -hj)] {
+                    points.push(shifted(&[(i, si), (j, sj)]));
+                }
+            }
+        }
+        let values = obj_fn.evaluate_points(&points);
+        result.evaluations += points.len() as u64;
+        if values.len() != points.len() || values.iter().any(|value| !value.is_finite()) {
+            return result;
+        }
+
+        let mut hessian = DMatrix::zeros(dim, dim);
+        let two: T = convert(2.0);
+        let four: T = convert(4.0);
+        for i in 0..dim {
+            let h = steps[i];
+            hessian[(i, i)] = (values[1 + 2 * i] - two * values[0] + values[2 + 2 * i]) / (h * h);
+        }
+        let mut corners = values[1 + 2 * dim..].chunks(4);
+        for i in 0..dim {
+            for j in 0..i {
+                let Some(&[pp, pm, mp, mm]) = corners.next() else {
+                    return result;
+                };
+                let value = (pp - pm - mp + mm) / (four * steps[i] * steps[j]);
+                hessian[(i, j)] = value;
+                hessian[(j, i)] = value;
+            }
+        }
+        result.hessian = Some(HessianEstimate::new(hessian, self.direction.sign()));
+        result
+    }
+}
@@ -0,0 +1,196 @@
+#[cfg(feature = "csv")]
+use std::fs::File;
+#[cfg(feature = "csv")]
+use std::io::{self, BufWriter, Write};
+#[cfg(feature = "csv")]
+use std::path::Path;
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::observer::Move;
+use crate::termination::TerminationState;
+
+/// State of the simplex at the start of an iteration, as recorded with
+/// `record_history`.
+///
+/// Scores are reported in the direction of the objective, i.e. they are
+/// not negated when maximizing.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct HistoryEntry<T = f64> {
+    /// Number of iterations completed so far.
+    pub iteration: u64,
+    /// Number of objective function evaluations so far.
+    pub evaluations: u64,
+    /// Best vertex of the simplex, empty when recorded in the
+    /// [`memory_efficient`] mode.
+    ///
+    /// [`memory_efficient`]: crate::builder::NelderMeadBuilder::memory_efficient
+    pub best_point: DVector<T>,
+    /// Score of `best_point`.
+    pub best_score: T,
+    /// Volume of the simplex, see [`TerminationState::simplex_volume`],
+    /// NaN when recorded in the [`memory_efficient`] mode.
+    ///
+    /// [`memory_efficient`]: crate::builder::NelderMeadBuilder::memory_efficient
+    pub simplex_volume: T,
+    /// Operation performed by the previous iteration, `None` for the
+    /// initial simplex.
+    pub last_move: Option<Move>,
+    /// Standard error of `best_score` when every point is evaluated
+    /// several times, see [`replicate`], `None` otherwise.
+    ///
+    /// [`replicate`]: crate::builder::NelderMeadBuilder::replicate
+    pub standard_error: Option<T>,
+}
+
+impl<T: RealField + Copy> HistoryEntry<T> {
+    /// Entry of the state, without the best point and the volume when
+    /// `compact`, nor the standard error of its score.
+    pub(crate) fn new(state: &TerminationState<T>, compact: bool) -> Self {
+        HistoryEntry {
+            iteration: state.iterations(),
+            evaluations: state.evaluations(),
+            best_point: if compact { DVector::zeros(0) } else { state.best_point().into_owned() },
+            best_score: state.best_score(),
+            simplex_volume: if compact { convert(f64::NAN) } else { state.simplex_volume() },
+            last_move: state.last_move(),
+            standard_error: None,
+        }
+    }
+}
+
+/// Trace of a run, one entry per iteration from the initial simplex to
+/// the final one, e.g. to plot convergence curves after the run.
+///
+/// Like the [`Observer`], a run stopped by `max_fun_evals` or
+/// `max_duration` in the middle of an iteration misses the last entry.
+///
+/// [`Observer`]: crate::observer::Observer
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// }
+/// let results = NelderMead::builder()
+///     .record_history(true)
+///     .minimize(&f, dvector![0.0, 0.0])?;
+/// let history = results.history.unwrap();
+///
+/// assert_eq!(history.entries[0].last_move, None);
+/// assert!(history.entries.windows(2).all(|w| w[1].best_score <= w[0].best_score));
+/// assert_eq!(history.entries.last().unwrap().best_score, results.best_score);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct History<T = f64> {
+    /// Recorded iterations, in order.
+    pub entries: Vec<HistoryEntry<T>>,
+}
+
+impl<T> History<T> {
+    /// Number of recorded iterations.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no iteration was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Best score of every recorded iteration, in order.
+    pub fn best_scores(&self) -> impl Iterator<Item = &T> + '_ {
+        self.entries.iter().map(|entry| &entry.best_score)
+    }
+}
+
+#[cfg(feature = "csv")]
+impl<T: RealField + Copy> History<T> {
+    /// Writes the history to a CSV file at `path`, see
+    /// [`History::write_csv`].
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_csv(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Writes the history as CSV, one row per iteration with the columns
+    /// `iteration`, `evaluations`, `best_score`, `simplex_volume`,
+    /// `last_move` and `x0`, `x1`, ... for the coordinates of the best
+    /// point. `last_move` is empty for the initial simplex.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+    /// }
+    /// let results = NelderMead::builder()
+    ///     .record_history(true)
+    ///     .minimize(&f, dvector![0.0, 0.0])?;
+    /// let history = results.history.unwrap();
+    ///
+    /// let mut csv = Vec::new();
+    /// history.write_csv(&mut csv)?;
+    /// let csv = String::from_utf8(csv)?;
+    /// let mut lines = csv.lines();
+    ///
+    /// assert_eq!(
+    ///     lines.next(),
+    ///     Some("iteration,evaluations,best_score,simplex_volume,last_move,x0,x1")
+    /// );
+    /// assert!(lines.next().unwrap().starts_with("0,3,"));
+    /// assert_eq!(csv.lines().count(), history.len() + 1);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let dim = self.entries.first().map_or(0, |entry| entry.best_point.len());
+        write!(writer, "iteration,evaluations,best_score,simplex_volume,last_move")?;
+        for i in 0..dim {
+            write!(writer, ",x{}", i)?;
+        }
+        writeln!(writer)?;
+
+        for entry in &self.entries {
+            let last_move = entry.last_move.map(|m| format!("{:?}", m)).unwrap_or_default();
+            write!(
+                writer,
+                "{},{},{},{},{}",
+                entry.iteration,
+                entry.evaluations,
+                entry.best_score,
+                entry.simplex_volume,
+                last_move
+            )?;
+            for xi in entry.best_point.iter() {
+                write!(writer, ",{}", xi)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
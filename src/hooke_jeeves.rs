@@ -0,0 +1,485 @@
+use std::iter;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::logging;
+use crate::nelder_mead::{is_nan, Direction, NanHandling, StepSize};
+use crate::objective::ObjectiveFunction;
+use crate::observer::{Observer, SharedObserver};
+use crate::progress::Budget;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationCriterion, TerminationState};
+
+/// Hooke and Jeeves' pattern search, which probes the objective function
+/// along the coordinate axes and follows the direction of the successful
+/// probes.
+///
+/// Every iteration is an exploratory move: each coordinate in turn is
+/// stepped up, then down, and any improvement is kept. After a successful
+/// exploration the search jumps as far again in the same direction, a
+/// pattern move, and explores around the new point, which it keeps only if
+/// it improved on the last base point. When an exploration around the base
+/// point finds no improvement, all the steps are multiplied by the step
+/// reduction. The search only compares scores, never builds a model of the
+/// objective and keeps probing on a full grid of steps, so it copes with
+/// noisy or discontinuous objectives on which the simplex of Nelder-Mead
+/// collapses.
+///
+/// The run converges once every step fell below `xtol`. The [`Observer`]
+/// and the [`TerminationCriterion`] are given the state of the run after
+/// every iteration, in which the simplex is made of the base point and the
+/// last point tried along every coordinate. The same vertices are returned
+/// as the `final_simplex` of the [`OptimizationResult`].
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::hooke_jeeves::HookeJeeves;
+///
+/// // a quadratic bowl on a staircase of plateaus
+/// fn f(x: &DVector<f64>) -> f64 {
+///     let bowl = (x[0] - 1.0).powi(2) + 4.0 * (x[1] + 2.0).powi(2) + (x[2] - 0.5).powi(2);
+///     (bowl * 100.0).floor() / 100.0
+/// }
+/// let results = HookeJeeves::builder()
+///     .step(0.5)
+///     .xtol(1e-4)
+///     .minimize(f, dvector![-3.0, 3.0, 4.0])?;
+///
+/// assert!(results.best_score < 0.01);
+/// assert!((&results.best_point - dvector![1.0, -2.0, 0.5]).amax() < 0.1);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct HookeJeeves<T = f64> {
+    pub(crate) step: StepSize<T>,
+    pub(crate) step_reduction: T,
+    pub(crate) xtol: T,
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) bounds: Option<(DVector<T>, DVector<T>)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for HookeJeeves<T> {
+    fn default() -> Self {
+        HookeJeeves {
+            step: StepSize::Uniform(convert(0.1)),
+            step_reduction: convert(0.5),
+            xtol: convert(1e-8),
+            max_iter: 10_000,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            bounds: None,
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`HookeJeeves`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `step`                  | 0.1                  |
+/// | `step_reduction`        | 0.5                  |
+/// | `xtol`                  | 1e-8                 |
+/// | `max_iter`              | 10000                |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `bounds`                | none                 |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct HookeJeevesBuilder<T = f64> {
+    config: HookeJeeves<T>,
+}
+
+impl<T: RealField + Copy> Default for HookeJeevesBuilder<T> {
+    fn default() -> Self {
+        HookeJeevesBuilder {
+            config: HookeJeeves::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> HookeJeevesBuilder<T> {
+    /// Initial step along every parameter, either a single value used for
+    /// every dimension or a [`StepSize`].
+    pub fn step(mut self, step: impl Into<StepSize<T>>) -> Self {
+        self.config.step = step.into();
+        self
+    }
+
+    /// Factor applied to the steps after an exploration around the base
+    /// point found no improvement, within `(0, 1)`.
+    pub fn step_reduction(mut self, step_reduction: T) -> Self {
+        self.config.step_reduction = step_reduction;
+        self
+    }
+
+    /// Step below which the search along a parameter is done. The run
+    /// converges once every step is below it.
+    pub fn xtol(mut self, xtol: T) -> Self {
+        self.config.xtol = xtol;
+        self
+    }
+
+    /// Maximum number of exploratory moves.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Lower and upper bounds of every parameter. The starting point and
+    /// every probe are clipped into them, like under
+    /// [`BoundsHandling::Clip`].
+    ///
+    /// [`BoundsHandling::Clip`]: crate::nelder_mead::BoundsHandling::Clip
+    pub fn bounds(mut self, lower: DVector<T>, upper: DVector<T>) -> Self {
+        self.config.bounds = Some((lower, upper));
+        self
+    }
+
+    /// Additional stopping rule, checked after every iteration next to the
+    /// built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every iteration with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> HookeJeeves<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`HookeJeeves::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`HookeJeeves::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, x_start)
+    }
+}
+
+/// Evaluations of a run, with the budgets they are bounded by.
+struct Run<'a, T, F> {
+    config: &'a HookeJeeves<T>,
+    obj_fn: F,
+    sign: T,
+    evaluations: u64,
+    start: Instant,
+}
+
+impl<T: RealField + Copy, F: FnMut(&DVector<T>) -> T> Run<'_, T, F> {
+    /// Score of `x`, or `None` when the evaluation budget or the time ran
+    /// out.
+    fn score(&mut self, x: &DVector<T>) -> Result<Option<T>, NelderMeadError> {
+        if self.exhausted().is_some() {
+            return Ok(None);
+        }
+        self.evaluate(x).map(Some)
+    }
+
+    /// Score of `x`, regardless of the budgets.
+    fn evaluate(&mut self, x: &DVector<T>) -> Result<T, NelderMeadError> {
+        self.evaluations += 1;
+        let value = (self.obj_fn)(x);
+        self.config.nan_handling.score(value, self.sign)
+    }
+
+    /// Why the run has to stop before the next evaluation, if it does.
+    fn exhausted(&self) -> Option<TerminationReason> {
+        if self.config.max_fun_evals.is_some_and(|limit| self.evaluations >= limit) {
+            return Some(TerminationReason::MaxFunEvals);
+        }
+        if self.config.max_duration.is_some_and(|limit| self.start.elapsed() >= limit) {
+            return Some(TerminationReason::TimedOut);
+        }
+        None
+    }
+}
+
+impl<T: RealField + Copy> HookeJeeves<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> HookeJeevesBuilder<T> {
+        HookeJeevesBuilder::default()
+    }
+
+    /// Finds a local minimum (or maximum, depending on the configured
+    /// [`Direction`]) of the objective function, starting from `x_start`.
+    ///
+    /// Fails if `x_start` is empty, if the step reduction is not within
+    /// `(0, 1)` or `xtol` is not positive and finite, if the step or the
+    /// bounds do not fit `x_start`, or if the objective function returns
+    /// NaN under [`NanHandling::Error`].
+    pub fn minimize<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let dim = x_start.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        let valid = [self.step_reduction, self.xtol].iter().all(|f| T::zero() < *f)
+            && self.step_reduction < T::one()
+            && self.xtol.is_finite();
+        if !valid {
+            return Err(NelderMeadError::InvalidPatternSearch);
+        }
+        self.step.check_dim(dim)?;
+        if let Some((lower, upper)) = &self.bounds {
+            if lower.len() != dim
+                || upper.len() != dim
+                || lower.iter().zip(upper.iter()).any(|(l, u)| l > u || is_nan(*l) || is_nan(*u))
+            {
+                return Err(NelderMeadError::InvalidBounds);
+            }
+        }
+
+        let mut run = Run {
+            config: self,
+            obj_fn,
+            sign: self.direction.sign(),
+            evaluations: 0,
+            start: Instant::now(),
+        };
+        let budget = Budget {
+            max_iter: self.max_iter,
+            max_fun_evals: self.max_fun_evals,
+            max_duration: self.max_duration,
+        };
+        let mut base = self.clip(x_start);
+        let mut base_score = run.evaluate(&base)?;
+        let mut steps = DVector::from_fn(dim, |i, _| self.step.offset(i, base[i]).abs());
+        let mut probes = vec![(base.clone(), base_score); dim];
+        // point reached by the last pattern move, explored next
+        let mut pattern: Option<DVector<T>> = None;
+        let mut iterations = 0;
+
+        let termination_reason = loop {
+            if iterations >= self.max_iter {
+                break TerminationReason::MaxIter;
+            }
+            if let Some(reason) = run.exhausted() {
+                break reason;
+            }
+
+            let from_pattern = pattern.is_some();
+            let (mut x, mut score) = match pattern.take() {
+                Some(point) => match run.score(&point)? {
+                    Some(score) => (point, score),
+                    None => continue,
+                },
+                None => (base.clone(), base_score),
+            };
+            let completed = self.explore(&mut run, &mut x, &mut score, &steps, &mut probes)?;
+            iterations += 1;
+            if score < base_score {
+                let mut next = &x * convert::<f64, T>(2.0) - &base;
+                if let Some((lower, upper)) = &self.bounds {
+                    next = next.sup(lower).inf(upper);
+                }
+                pattern = Some(next);
+                base = x;
+                base_score = score;
+            } else if !from_pattern {
+                steps *= self.step_reduction;
+            }
+            if !completed {
+                continue;
+            }
+
+            let simplex = pattern_simplex(&base, base_score, &probes);
+            let state = TerminationState {
+                iterations,
+                evaluations: run.evaluations,
+                elapsed: run.start.elapsed(),
+                simplex: &simplex,
+                sign: run.sign,
+                last_move: None,
+                budget,
+            };
+            logging::log_iteration(&state);
+            if let Some(observer) = &self.observer {
+                observer.0.observe(&state);
+            }
+            if let Some(criterion) = &self.termination {
+                if criterion.0.should_stop(&state) {
+                    break TerminationReason::Criterion;
+                }
+            }
+            if steps.iter().all(|step| *step < self.xtol) {
+                break TerminationReason::Converged;
+            }
+        };
+
+        Ok(OptimizationResult::from_simplex(
+            pattern_simplex(&base, base_score, &probes).to_pairs(T::one()),
+            run.sign,
+            iterations,
+            run.evaluations,
+            0,
+            termination_reason,
+            None,
+        ))
+    }
+
+    /// Same as [`HookeJeeves::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), x_start)
+    }
+
+    /// Exploratory move from `x`, whose score is `score`: steps every
+    /// coordinate in turn up, then down, keeping any improvement, and
+    /// records the last point tried along each coordinate in `probes`.
+    /// Returns `false` when the budget ran out before the move was done.
+    fn explore<F>(
+        &self,
+        run: &mut Run<'_, T, F>,
+        x: &mut DVector<T>,
+        score: &mut T,
+        steps: &DVector<T>,
+        probes: &mut [(DVector<T>, T)],
+    ) -> Result<bool, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        for i in 0..x.len() {
+            let origin = x[i];
+            for offset in [steps[i], -steps[i]] {
+                let mut value = origin + offset;
+                if let Some((lower, upper)) = &self.bounds {
+                    value = value.max(lower[i]).min(upper[i]);
+                }
+                if value == origin {
+                    continue;
+                }
+                let mut probe = x.clone();
+                probe[i] = value;
+                let Some(probe_score) = run.score(&probe)? else {
+                    return Ok(false);
+                };
+                let improved = probe_score < *score;
+                probes[i] = (probe.clone(), probe_score);
+                if improved {
+                    *x = probe;
+                    *score = probe_score;
+                    break;
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Copy of `x` clipped into the bounds.
+    fn clip(&self, mut x: DVector<T>) -> DVector<T> {
+        if let Some((lower, upper)) = &self.bounds {
+            for i in 0..x.len() {
+                x[i] = x[i].max(lower[i]).min(upper[i]);
+            }
+        }
+        x
+    }
+}
+
+/// Simplex of the base point and the last point tried along every
+/// coordinate, ordered from the best one.
+fn pattern_simplex<T: RealField + Copy>(base: &DVector<T>, base_score: T, probes: &[(DVector<T>, T)]) -> Simplex<T> {
+    let probes = probes.iter().map(|(x, score)| (x.as_slice(), *score));
+    let mut simplex = Simplex::from_slices(iter::once((base.as_slice(), base_score)).chain(probes));
+    simplex.sort();
+    simplex
+}
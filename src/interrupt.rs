@@ -0,0 +1,61 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set by [`request_stop`], consumed by the next iteration of a run with
+/// `stop_on_interrupt` enabled.
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Asks the runs with `stop_on_interrupt` enabled to stop at the end of
+/// their current iteration, returning the best point found so far with
+/// [`TerminationReason::Interrupted`].
+///
+/// It only sets a flag, so it can be called from a signal handler or
+/// another thread. With the `ctrlc` feature it is called on Ctrl-C.
+///
+/// [`TerminationReason::Interrupted`]: crate::result::TerminationReason::Interrupted
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::interrupt;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::result::TerminationReason;
+///
+/// let mut calls = 0;
+/// let f = |x: &DVector<f64>| {
+///     calls += 1;
+///     if calls == 20 {
+///         // e.g. a shutdown requested by another part of the application
+///         interrupt::request_stop();
+///     }
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// };
+/// let results = NelderMead::builder()
+///     .stop_on_interrupt(true)
+///     .minimize(f, dvector![0.0, 0.0])?;
+///
+/// assert_eq!(results.termination_reason, TerminationReason::Interrupted);
+/// assert!(results.evaluations <= 22);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+pub fn request_stop() {
+    STOP_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Whether a stop was requested, clearing the request so that it only
+/// stops a single run.
+pub(crate) fn take_stop_request() -> bool {
+    STOP_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+/// Installs a Ctrl-C handler calling [`request_stop`], once per process.
+/// An application that already handles Ctrl-C keeps its own handler and
+/// should call [`request_stop`] from it.
+#[cfg(feature = "ctrlc")]
+pub(crate) fn install_handler() {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| {
+        // fails only when another handler is installed, see above
+        let _ = ctrlc::set_handler(request_stop);
+    });
+}
@@ -1,5 +1,60 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
 
+/// Re-export of the linear algebra crate used in the public API, so that
+/// callers do not need to depend on a matching nalgebra version.
+#[cfg(feature = "nalgebra")]
+pub use nalgebra;
+
+pub mod baseline;
+pub mod basin_hopping;
+mod bfgs;
+pub mod bobyqa;
+pub mod builder;
+pub mod cache;
+pub mod checkpoint;
+pub mod cma_es;
+pub mod cobyla;
+pub mod coefficients;
+pub mod constraints;
+pub mod cross_entropy;
+pub mod diagnostics;
+pub mod differential_evolution;
+pub mod direct;
+pub mod error;
+pub mod event;
+pub mod hessian;
+pub mod history;
+pub mod hooke_jeeves;
+pub mod interrupt;
+mod logging;
+pub mod mads;
+pub mod multi_start;
+#[cfg(feature = "ndarray")]
+pub mod ndarray_backend;
 pub mod nelder_mead;
+pub mod nelder_mead_ask_tell;
+pub mod nelder_mead_iter;
+pub mod nelder_mead_static;
 pub mod nelder_mead_wrp;
+pub mod noise;
+pub mod objective;
+pub mod observer;
+pub mod params;
+pub mod particle_swarm;
+#[cfg(feature = "plotters")]
+pub mod plot;
+pub mod powell;
+pub mod profile;
+pub mod progress;
+mod random;
+pub mod result;
+mod simplex;
+pub mod simulated_annealing;
+pub mod sobol;
+pub mod spsa;
+pub mod subplex;
+pub mod termination;
+pub mod test_functions;
+pub mod timing;
+pub mod transform;
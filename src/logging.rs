@@ -0,0 +1,50 @@
+use nalgebra::RealField;
+
+use crate::result::TerminationReason;
+use crate::termination::TerminationState;
+
+/// Whether [`log_iteration`] would emit anything, so that callers can skip
+/// building its state.
+pub(crate) fn iteration_enabled() -> bool {
+    #[cfg(feature = "log")]
+    return log::log_enabled!(log::Level::Debug);
+    #[cfg(not(feature = "log"))]
+    return false;
+}
+
+/// Logs the best score of the iteration at `debug` level and its vertices
+/// at `trace` level.
+pub(crate) fn log_iteration<T: RealField + Copy>(state: &TerminationState<T>) {
+    #[cfg(feature = "log")]
+    {
+        log::debug!(
+            "iter {}, evals {}, best so far: {}",
+            state.iterations(),
+            state.evaluations(),
+            state.best_score()
+        );
+        if log::log_enabled!(log::Level::Trace) {
+            for (x, score) in state.vertices() {
+                let coords: Vec<String> = x.iter().map(|xi| xi.to_string()).collect();
+                log::trace!("  [{}] -> {}", coords.join(", "), score);
+            }
+        }
+    }
+}
+
+/// Logs the end of a run at `info` level.
+pub(crate) fn log_termination<T: RealField + Copy>(
+    reason: TerminationReason,
+    iterations: u64,
+    evaluations: u64,
+    best_score: T,
+) {
+    #[cfg(feature = "log")]
+    log::info!(
+        "stopped ({:?}) after {} iterations and {} evaluations, best score: {}",
+        reason,
+        iterations,
+        evaluations,
+        best_score
+    );
+}
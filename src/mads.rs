@@ -0,0 +1,686 @@
+use std::iter;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DMatrix, DVector, RealField};
+
+use crate::constraints::{Constraint, SharedConstraint};
+use crate::error::NelderMeadError;
+use crate::logging;
+use crate::nelder_mead::{is_nan, total_cmp, Direction, NanHandling, NelderMead, StepSize};
+use crate::objective::ObjectiveFunction;
+use crate::observer::{Observer, SharedObserver};
+use crate::progress::Budget;
+use crate::random::Rng;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationCriterion, TerminationState};
+
+/// Mesh adaptive direct search (MADS), a pattern search whose poll
+/// directions become dense in the unit sphere, which guarantees
+/// convergence to a stationary point of nonsmooth and constrained
+/// problems.
+///
+/// Every trial point lies on a mesh around the incumbent, whose size
+/// `4^-l` shrinks four times faster than the poll size `2^-l`, both
+/// relative to `step`. Every iteration first runs the optional
+/// [`search`](MadsBuilder::search), a short Nelder-Mead search from the
+/// incumbent whose best point is rounded onto the mesh. Unless it
+/// improved, the poll tries the `2n` directions `±h_i`, `h_i` being the
+/// columns of a Householder matrix built from a random integer vector, so
+/// that they form an orthogonal positive basis reaching about as far as
+/// the poll size. The poll is opportunistic, stopping at the first
+/// improvement, and tries the directions closest to the last successful
+/// one first. A successful iteration coarsens the mesh, a failed one
+/// refines it.
+///
+/// Inequality [`Constraint`]s and the bounds are handled by the extreme
+/// barrier: the objective function is never evaluated at points violating
+/// them. From an infeasible starting point, the search first minimizes
+/// the largest violation until it reaches a feasible point, and the
+/// `best_score` of a run ending before that is infinite.
+///
+/// The run converges once the poll size is below `xtol` along every
+/// parameter. The [`Observer`] and the [`TerminationCriterion`] are given
+/// the state of the run after every iteration, in which the simplex is
+/// made of the incumbent and the last `n` feasible poll points. The same
+/// vertices are returned as the `final_simplex` of the
+/// [`OptimizationResult`].
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::mads::Mads;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+///
+/// // the l1 distance to (2, 2), kinked along both axes through it
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 2.0).abs() + (x[1] - 2.0).abs()
+/// }
+/// // x + 2y <= 2, the constrained optimum is (2, 0), on a kink
+/// let results = Mads::builder()
+///     .constraint(|x: &DVector<f64>| x[0] + 2.0 * x[1] - 2.0)
+///     .search(NelderMead::builder().build())
+///     .seed(7)
+///     .minimize(f, dvector![0.0, 0.0])?;
+///
+/// assert!((results.best_score - 2.0).abs() < 1e-6);
+/// assert!((&results.best_point - dvector![2.0, 0.0]).amax() < 1e-6);
+/// assert_eq!(results.constraint_violation, Some(0.0));
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct Mads<T = f64> {
+    pub(crate) step: StepSize<T>,
+    pub(crate) xtol: T,
+    pub(crate) search: Option<NelderMead<T>>,
+    pub(crate) search_evals: Option<u64>,
+    pub(crate) seed: Option<u64>,
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) bounds: Option<(DVector<T>, DVector<T>)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) constraints: Vec<SharedConstraint<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for Mads<T> {
+    fn default() -> Self {
+        Mads {
+            step: StepSize::Uniform(T::one()),
+            xtol: convert(1e-8),
+            search: None,
+            search_evals: None,
+            seed: None,
+            max_iter: 10_000,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            bounds: None,
+            constraints: Vec::new(),
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`Mads`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `step`                  | 1.0                  |
+/// | `xtol`                  | 1e-8                 |
+/// | `search`                | none                 |
+/// | `search_evals`          | `5 * (n + 1)`        |
+/// | `seed`                  | none                 |
+/// | `max_iter`              | 10000                |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `bounds`                | none                 |
+/// | `constraint`            | none                 |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct MadsBuilder<T = f64> {
+    config: Mads<T>,
+}
+
+impl<T: RealField + Copy> Default for MadsBuilder<T> {
+    fn default() -> Self {
+        MadsBuilder {
+            config: Mads::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> MadsBuilder<T> {
+    /// Initial poll size along every parameter, either a single value used
+    /// for every dimension or a [`StepSize`]. The mesh and the poll sizes
+    /// are relative to it, and the poll size may grow beyond it after
+    /// successful iterations.
+    pub fn step(mut self, step: impl Into<StepSize<T>>) -> Self {
+        self.config.step = step.into();
+        self
+    }
+
+    /// Poll size below which the search along a parameter is done. The run
+    /// converges once the poll size is below it along every parameter.
+    pub fn xtol(mut self, xtol: T) -> Self {
+        self.config.xtol = xtol;
+        self
+    }
+
+    /// Nelder-Mead solver run from the feasible incumbent at the start of
+    /// every iteration, with a simplex of the size of the poll, before
+    /// polling. Its best point is rounded onto the mesh, so the search
+    /// never weakens the convergence of the poll. Its direction, NaN
+    /// handling and budgets are taken from this solver, and so are the
+    /// bounds unless it has its own.
+    pub fn search(mut self, search: NelderMead<T>) -> Self {
+        self.config.search = Some(search);
+        self
+    }
+
+    /// Maximum number of evaluations of every
+    /// [`search`](MadsBuilder::search).
+    pub fn search_evals(mut self, search_evals: u64) -> Self {
+        self.config.search_evals = Some(search_evals);
+        self
+    }
+
+    /// Seed of the random poll directions, making the run reproducible.
+    /// Without a seed every run draws different directions.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Maximum number of iterations, each a search and a poll.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Lower and upper bounds of every parameter. The starting point is
+    /// clipped into them, and trial points outside of them are rejected
+    /// without being evaluated.
+    pub fn bounds(mut self, lower: DVector<T>, upper: DVector<T>) -> Self {
+        self.config.bounds = Some((lower, upper));
+        self
+    }
+
+    /// Adds the inequality constraint `g(x) <= 0`, see [`Constraint`],
+    /// enforced by the extreme barrier.
+    pub fn constraint<C>(mut self, constraint: C) -> Self
+    where
+        C: Constraint<T> + Send + Sync + 'static,
+    {
+        self.config
+            .constraints
+            .push(SharedConstraint(Arc::new(constraint)));
+        self
+    }
+
+    /// Additional stopping rule, checked after every iteration next to the
+    /// built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every iteration with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> Mads<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Mads::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Mads::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, x_start)
+    }
+}
+
+/// Point of a run with its largest constraint violation and its score,
+/// infinite when it violates a constraint.
+struct Trial<T> {
+    x: DVector<T>,
+    violation: T,
+    score: T,
+}
+
+impl<T: RealField + Copy> Trial<T> {
+    /// Whether this point is better than `other`: less infeasible when
+    /// `other` is infeasible, and feasible with a lower score otherwise.
+    fn improves_on(&self, other: &Trial<T>) -> bool {
+        if other.violation > T::zero() {
+            self.violation < other.violation
+        } else {
+            self.violation.is_zero() && self.score < other.score
+        }
+    }
+}
+
+/// Evaluations of a run, with the budgets they are bounded by.
+struct Run<'a, T, F> {
+    config: &'a Mads<T>,
+    obj_fn: F,
+    sign: T,
+    evaluations: u64,
+    start: Instant,
+}
+
+impl<T: RealField + Copy, F: FnMut(&DVector<T>) -> T> Run<'_, T, F> {
+    /// Trial at `x`, whose objective function is only evaluated when it
+    /// satisfies the constraints, or `None` when the evaluation budget or
+    /// the time ran out and `limited` is set.
+    fn trial(&mut self, x: DVector<T>, limited: bool) -> Result<Option<Trial<T>>, NelderMeadError> {
+        let violation = self.config.violation(&x);
+        if violation > T::zero() {
+            let score = convert(f64::INFINITY);
+            return Ok(Some(Trial {
+                x,
+                violation,
+                score,
+            }));
+        }
+        if limited && self.exhausted().is_some() {
+            return Ok(None);
+        }
+        self.evaluations += 1;
+        let value = (self.obj_fn)(&x);
+        let score = self.config.nan_handling.score(value, self.sign)?;
+        Ok(Some(Trial {
+            x,
+            violation,
+            score,
+        }))
+    }
+
+    /// Why the run has to stop before the next evaluation, if it does.
+    fn exhausted(&self) -> Option<TerminationReason> {
+        if self
+            .config
+            .max_fun_evals
+            .is_some_and(|limit| self.evaluations >= limit)
+        {
+            return Some(TerminationReason::MaxFunEvals);
+        }
+        if self
+            .config
+            .max_duration
+            .is_some_and(|limit| self.start.elapsed() >= limit)
+        {
+            return Some(TerminationReason::TimedOut);
+        }
+        None
+    }
+}
+
+impl<T: RealField + Copy> Mads<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> MadsBuilder<T> {
+        MadsBuilder::default()
+    }
+
+    /// Finds a local minimum (or maximum, depending on the configured
+    /// [`Direction`]) of the objective function satisfying the
+    /// constraints, starting from `x_start`.
+    ///
+    /// Fails if `x_start` is empty, if `xtol` is not positive and finite,
+    /// if the step or the bounds do not fit `x_start`, or if the objective
+    /// function returns NaN under [`NanHandling::Error`]. The search fails
+    /// like [`NelderMead::minimize`].
+    pub fn minimize<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let dim = x_start.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        if !(T::zero() < self.xtol && self.xtol.is_finite()) {
+            return Err(NelderMeadError::InvalidPatternSearch);
+        }
+        self.step.check_dim(dim)?;
+        if let Some((lower, upper)) = &self.bounds {
+            if lower.len() != dim
+                || upper.len() != dim
+                || lower
+                    .iter()
+                    .zip(upper.iter())
+                    .any(|(l, u)| l > u || is_nan(*l) || is_nan(*u))
+            {
+                return Err(NelderMeadError::InvalidBounds);
+            }
+        }
+
+        let mut run = Run {
+            config: self,
+            obj_fn,
+            sign: self.direction.sign(),
+            evaluations: 0,
+            start: Instant::now(),
+        };
+        let budget = Budget {
+            max_iter: self.max_iter,
+            max_fun_evals: self.max_fun_evals,
+            max_duration: self.max_duration,
+        };
+        let mut rng = Rng::seeded(self.seed);
+        let x_start = self.clip(x_start);
+        let steps = DVector::from_fn(dim, |i, _| self.step.offset(i, x_start[i]).abs());
+        let Some(mut incumbent) = run.trial(x_start, false)? else {
+            unreachable!("the first trial is not limited by the budgets");
+        };
+        let mut probes = vec![(incumbent.x.clone(), incumbent.score); dim];
+        let mut probed = 0;
+        // level `l` of the mesh size `min(1, 4^-l)` and the poll size `2^-l`
+        let mut level: i32 = 0;
+        let mut last_direction: Option<DVector<T>> = None;
+        let mut iterations = 0;
+
+        let termination_reason = 'run: loop {
+            if iterations >= self.max_iter {
+                break TerminationReason::MaxIter;
+            }
+            if let Some(reason) = run.exhausted() {
+                break reason;
+            }
+
+            let half: T = convert(0.5);
+            let poll = half.powi(level);
+            let mesh = poll.powi(2).min(T::one());
+            let mut success = None;
+            if let Some(search) = self
+                .search
+                .as_ref()
+                .filter(|_| incumbent.violation.is_zero())
+            {
+                if let Some(trial) =
+                    self.search(&mut run, search, &incumbent, &steps * mesh, &steps * poll)?
+                {
+                    if trial.improves_on(&incumbent) {
+                        success = Some(trial);
+                    }
+                }
+            }
+            if success.is_none() {
+                for direction in self.poll_directions(&mut rng, dim, level, last_direction.as_ref())
+                {
+                    let x = &incumbent.x + steps.component_mul(&direction) * mesh;
+                    if x == incumbent.x || !self.within_bounds(&x) {
+                        continue;
+                    }
+                    let Some(trial) = run.trial(x, true)? else {
+                        break 'run run.exhausted().unwrap_or(TerminationReason::MaxFunEvals);
+                    };
+                    if trial.violation.is_zero() {
+                        probes[probed % dim] = (trial.x.clone(), trial.score);
+                        probed += 1;
+                    }
+                    if trial.improves_on(&incumbent) {
+                        success = Some(trial);
+                        break;
+                    }
+                }
+            }
+            iterations += 1;
+            match success {
+                Some(trial) => {
+                    last_direction = Some(&trial.x - &incumbent.x);
+                    incumbent = trial;
+                    level -= 1;
+                }
+                None => level += 1,
+            }
+
+            let simplex = poll_simplex(&incumbent, &probes);
+            let state = TerminationState {
+                iterations,
+                evaluations: run.evaluations,
+                elapsed: run.start.elapsed(),
+                simplex: &simplex,
+                sign: run.sign,
+                last_move: None,
+                budget,
+            };
+            logging::log_iteration(&state);
+            if let Some(observer) = &self.observer {
+                observer.0.observe(&state);
+            }
+            if let Some(criterion) = &self.termination {
+                if criterion.0.should_stop(&state) {
+                    break TerminationReason::Criterion;
+                }
+            }
+            if steps
+                .iter()
+                .all(|step| *step * half.powi(level) < self.xtol)
+            {
+                break TerminationReason::Converged;
+            }
+        };
+
+        let mut result = OptimizationResult::from_simplex(
+            poll_simplex(&incumbent, &probes).to_pairs(T::one()),
+            run.sign,
+            iterations,
+            run.evaluations,
+            0,
+            termination_reason,
+            None,
+        );
+        if !self.constraints.is_empty() {
+            result.constraint_violation = Some(incumbent.violation);
+        }
+        Ok(result)
+    }
+
+    /// Same as [`Mads::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), x_start)
+    }
+
+    /// Nelder-Mead search from the feasible `incumbent` with a simplex of
+    /// the poll size `poll`, whose best point rounded onto the mesh of
+    /// size `mesh` is returned as a trial, or `None` when it rounds to the
+    /// incumbent, leaves the bounds or the budget ran out.
+    fn search<F>(
+        &self,
+        run: &mut Run<'_, T, F>,
+        search: &NelderMead<T>,
+        incumbent: &Trial<T>,
+        mesh: DVector<T>,
+        poll: DVector<T>,
+    ) -> Result<Option<Trial<T>>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let dim = incumbent.x.len();
+        let mut max_fun_evals = self.search_evals.unwrap_or(5 * (dim as u64 + 1));
+        if let Some(limit) = self.max_fun_evals {
+            max_fun_evals = max_fun_evals.min(limit.saturating_sub(run.evaluations));
+        }
+        if max_fun_evals == 0 {
+            return Ok(None);
+        }
+        let mut local = search.polishing(
+            self.direction,
+            self.nan_handling,
+            &self.bounds,
+            Some(max_fun_evals),
+            self.max_duration
+                .map(|limit| limit.saturating_sub(run.start.elapsed())),
+        );
+        local.step = StepSize::PerDim(poll);
+
+        // points violating a constraint are the worst, without evaluation
+        let worst = run.sign * convert(f64::INFINITY);
+        let mut evaluations = 0;
+        let obj_fn = &mut run.obj_fn;
+        let barrier = |x: &DVector<T>| {
+            if self.violation(x) > T::zero() {
+                return worst;
+            }
+            evaluations += 1;
+            obj_fn(x)
+        };
+        let found = local.minimize(barrier, incumbent.x.clone())?.best_point;
+        run.evaluations += evaluations;
+
+        let offset = (found - &incumbent.x).zip_map(&mesh, |d, size| {
+            if size.is_zero() {
+                T::zero()
+            } else {
+                (d / size).round() * size
+            }
+        });
+        let x = &incumbent.x + offset;
+        if x == incumbent.x || !self.within_bounds(&x) {
+            return Ok(None);
+        }
+        run.trial(x, true)
+    }
+
+    /// Poll directions at the mesh level `level`: the columns of the
+    /// Householder matrix `|q|^2 I - 2 q q^T` and their opposites, `q`
+    /// being a random integer vector of norm close to `2^(|level| / 2)`,
+    /// ordered from the closest to `last_direction`.
+    fn poll_directions(
+        &self,
+        rng: &mut Rng,
+        dim: usize,
+        level: i32,
+        last_direction: Option<&DVector<T>>,
+    ) -> Vec<DVector<T>> {
+        let mut v: DVector<T> = DVector::from_fn(dim, |_, _| convert(rng.normal()));
+        if v.norm().is_zero() {
+            v[0] = T::one();
+        }
+        let radius: T = convert(2f64.powf(f64::from(level.unsigned_abs()) / 2.0));
+        let mut q = (&v * (radius / v.norm())).map(|c| c.round());
+        if q.iter().all(|c| c.is_zero()) {
+            let i = v.iamax();
+            q[i] = v[i].signum();
+        }
+        let two: T = convert(2.0);
+        let householder = DMatrix::identity(dim, dim) * q.norm_squared() - &q * q.transpose() * two;
+        let mut directions: Vec<DVector<T>> = householder
+            .column_iter()
+            .flat_map(|column| [column.into_owned(), -column])
+            .collect();
+        if let Some(last) = last_direction {
+            let cosine = |d: &DVector<T>| d.dot(last) / d.norm();
+            directions.sort_by(|a, b| total_cmp(cosine(b), cosine(a)));
+        }
+        directions
+    }
+
+    /// Largest violation `max(0, g_i(x))` of the constraints at `x`.
+    fn violation(&self, x: &DVector<T>) -> T {
+        self.constraints
+            .iter()
+            .fold(T::zero(), |max, g| max.max(g.0.evaluate(x)))
+    }
+
+    /// Whether `x` lies within the bounds.
+    fn within_bounds(&self, x: &DVector<T>) -> bool {
+        match &self.bounds {
+            Some((lower, upper)) => x
+                .iter()
+                .zip(lower.iter().zip(upper.iter()))
+                .all(|(xi, (l, u))| l <= xi && xi <= u),
+            None => true,
+        }
+    }
+
+    /// Copy of `x` clipped into the bounds.
+    fn clip(&self, x: DVector<T>) -> DVector<T> {
+        match &self.bounds {
+            Some((lower, upper)) => x.sup(lower).inf(upper),
+            None => x,
+        }
+    }
+}
+
+/// Simplex of the incumbent and the last feasible poll points, ordered
+/// from the best one.
+fn poll_simplex<T: RealField + Copy>(
+    incumbent: &Trial<T>,
+    probes: &[(DVector<T>, T)],
+) -> Simplex<T> {
+    let probes = probes.iter().map(|(x, score)| (x.as_slice(), *score));
+    let mut simplex =
+        Simplex::from_slices(iter::once((incumbent.x.as_slice(), incumbent.score)).chain(probes));
+    simplex.sort();
+    simplex
+}
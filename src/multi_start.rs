@@ -0,0 +1,455 @@
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::nelder_mead::{is_nan, total_cmp, Direction, NanHandling, NelderMead};
+use crate::objective::ObjectiveFunction;
+use crate::random::Rng;
+use crate::result::OptimizationResult;
+use crate::sobol::Sobol;
+
+/// How the starting points of a [`MultiStart`] run are spread over the
+/// bounds.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Sampling {
+    /// Latin hypercube sample: the range of every parameter is split into
+    /// as many strata as there are starts, each holding exactly one start.
+    #[default]
+    LatinHypercube,
+    /// First points of a [`Sobol`] sequence, digitally shifted by the seed.
+    /// The sequence covers the bounds more evenly in several dimensions at
+    /// once, best with a power of two starts, for up to
+    /// [`Sobol::MAX_DIMENSION`] parameters.
+    Sobol,
+}
+
+/// Outcome of a [`MultiStart`] run.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct MultiStartResult<T = f64> {
+    /// Best local search, with the iterations, evaluations and restarts of
+    /// all of them.
+    pub result: OptimizationResult<T>,
+    /// Distinct local minima found with their scores, ordered from the best
+    /// one.
+    pub minima: Vec<(DVector<T>, T)>,
+}
+
+/// Multi-start driver, running a local Nelder-Mead search from every point
+/// of a Latin hypercube sample or of a Sobol sequence over the bounds.
+///
+/// The Latin hypercube splits the range of every parameter into as many
+/// strata as there are starts, and places exactly one starting point in
+/// every stratum of every parameter, spreading the starts more evenly than
+/// independent uniform draws. The [`Sampling::Sobol`] sequence spreads
+/// them evenly over several parameters at once. The sample is reproducible
+/// with [`MultiStartBuilder::seed`]. The local searches are bounded by the
+/// same bounds, and each is given an equal share of `max_fun_evals`. No
+/// search starts once `max_duration` has passed, except the first one.
+///
+/// The minima reached by the searches are ranked, and a minimum is dropped
+/// when it lies within `cluster_xtol` along every parameter of a better
+/// one and its score within `cluster_ftol` of it, relative to the score or
+/// absolutely below 1. With the `rayon` feature,
+/// `MultiStart::minimize_parallel` runs the searches in parallel.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::DVector;
+/// use nelder_mead_optimizer::multi_start::MultiStart;
+/// use nelder_mead_optimizer::test_functions::himmelblau;
+///
+/// let (lower, upper) = (DVector::from_element(2, -5.0), DVector::from_element(2, 5.0));
+/// let found = MultiStart::builder()
+///     .starts(30)
+///     .seed(1)
+///     .minimize(himmelblau, lower, upper)?;
+///
+/// // Himmelblau's function has four global minima
+/// assert_eq!(found.minima.len(), 4);
+/// assert!(found.minima.iter().all(|(_, score)| *score < 1e-4));
+/// assert_eq!(found.minima[0].1, found.result.best_score);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct MultiStart<T = f64> {
+    pub(crate) local: NelderMead<T>,
+    pub(crate) starts: Option<usize>,
+    pub(crate) sampling: Sampling,
+    pub(crate) seed: Option<u64>,
+    pub(crate) cluster_xtol: T,
+    pub(crate) cluster_ftol: T,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+}
+
+impl<T: RealField + Copy> Default for MultiStart<T> {
+    fn default() -> Self {
+        MultiStart {
+            local: NelderMead::default(),
+            starts: None,
+            sampling: Sampling::LatinHypercube,
+            seed: None,
+            cluster_xtol: convert(1e-2),
+            cluster_ftol: convert(1e-4),
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+        }
+    }
+}
+
+/// Builder of a [`MultiStart`] driver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `local`                 | `NelderMead` default |
+/// | `starts`                | 10 n                 |
+/// | `sampling`              | `LatinHypercube`     |
+/// | `seed`                  | random               |
+/// | `cluster_xtol`          | 1e-2                 |
+/// | `cluster_ftol`          | 1e-4                 |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+#[derive(Debug, Clone)]
+pub struct MultiStartBuilder<T = f64> {
+    config: MultiStart<T>,
+}
+
+impl<T: RealField + Copy> Default for MultiStartBuilder<T> {
+    fn default() -> Self {
+        MultiStartBuilder {
+            config: MultiStart::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> MultiStartBuilder<T> {
+    /// Nelder-Mead solver of the local searches. Its direction, NaN
+    /// handling and budgets are taken from this driver, and so are the
+    /// bounds unless it has its own.
+    pub fn local(mut self, local: NelderMead<T>) -> Self {
+        self.config.local = local;
+        self
+    }
+
+    /// Number of starting points, positive.
+    pub fn starts(mut self, starts: usize) -> Self {
+        self.config.starts = Some(starts);
+        self
+    }
+
+    /// How the starting points are spread over the bounds.
+    pub fn sampling(mut self, sampling: Sampling) -> Self {
+        self.config.sampling = sampling;
+        self
+    }
+
+    /// Seed of the sample, making the run reproducible. Without a seed
+    /// every run draws different starting points.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Distance along every parameter within which two minima can be the
+    /// same one.
+    pub fn cluster_xtol(mut self, cluster_xtol: T) -> Self {
+        self.config.cluster_xtol = cluster_xtol;
+        self
+    }
+
+    /// Difference of scores within which two minima can be the same one,
+    /// relative to the score, absolute below 1.
+    pub fn cluster_ftol(mut self, cluster_ftol: T) -> Self {
+        self.config.cluster_ftol = cluster_ftol;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times,
+    /// shared equally by the local searches. The run fails with
+    /// [`NelderMeadError::InvalidPopulation`] if the budget is below
+    /// `starts * (n + 1)`, the evaluations of the initial simplices.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Start no more searches once the run took this long, and stop the
+    /// running ones.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> MultiStart<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the driver, see
+    /// [`MultiStart::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<MultiStartResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, lower, upper)
+    }
+
+    /// Finishes the configuration and immediately runs the driver, see
+    /// [`MultiStart::minimize_parallel`].
+    #[cfg(feature = "rayon")]
+    pub fn minimize_parallel<F>(
+        self,
+        obj_fn: F,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<MultiStartResult<T>, NelderMeadError>
+    where
+        T: Send + Sync,
+        F: Fn(&DVector<T>) -> T + Sync,
+    {
+        self.build().minimize_parallel(obj_fn, lower, upper)
+    }
+
+    /// Finishes the configuration and immediately runs the driver, see
+    /// [`MultiStart::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<MultiStartResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, lower, upper)
+    }
+}
+
+impl<T: RealField + Copy> MultiStart<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> MultiStartBuilder<T> {
+        MultiStartBuilder::default()
+    }
+
+    /// Finds the minima (or maxima, depending on the configured
+    /// [`Direction`]) of the objective function within the bounds, running
+    /// the local searches one after the other.
+    ///
+    /// Fails if the bounds are empty, of different lengths, not finite or
+    /// crossed, if there are no starts or `max_fun_evals` cannot afford the
+    /// initial simplex of every one of them, if the Sobol sequence does not
+    /// support the dimension, if the cluster tolerances are not
+    /// positive and finite, or if a local search fails like
+    /// [`NelderMead::minimize`].
+    pub fn minimize<F>(
+        &self,
+        mut obj_fn: F,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<MultiStartResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let starts = self.starting_points(&lower, &upper)?;
+        let bounds = Some((lower, upper));
+        let start = Instant::now();
+        let mut runs = Vec::with_capacity(starts.len());
+        for x in starts {
+            if !runs.is_empty() && self.timed_out(start) {
+                break;
+            }
+            runs.push(self.local_search(&bounds, start).minimize(&mut obj_fn, x)?);
+        }
+        Ok(self.rank(runs))
+    }
+
+    /// Same as [`MultiStart::minimize`], but runs the local searches in
+    /// parallel on the rayon thread pool. The result is the same as with
+    /// [`MultiStart::minimize`] for the same seed, unless `max_duration`
+    /// cuts the run short.
+    #[cfg(feature = "rayon")]
+    pub fn minimize_parallel<F>(
+        &self,
+        obj_fn: F,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<MultiStartResult<T>, NelderMeadError>
+    where
+        T: Send + Sync,
+        F: Fn(&DVector<T>) -> T + Sync,
+    {
+        use rayon::prelude::*;
+
+        let starts = self.starting_points(&lower, &upper)?;
+        let bounds = Some((lower, upper));
+        let start = Instant::now();
+        let runs: Vec<_> = starts
+            .into_par_iter()
+            .enumerate()
+            .filter(|(k, _)| *k == 0 || !self.timed_out(start))
+            .map(|(_, x)| self.local_search(&bounds, start).minimize(&obj_fn, x))
+            .collect::<Result<_, _>>()?;
+        Ok(self.rank(runs))
+    }
+
+    /// Same as [`MultiStart::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        lower: DVector<T>,
+        upper: DVector<T>,
+    ) -> Result<MultiStartResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), lower, upper)
+    }
+
+    /// Sample of the bounds, after checking the configuration.
+    fn starting_points(&self, lower: &DVector<T>, upper: &DVector<T>) -> Result<Vec<DVector<T>>, NelderMeadError> {
+        let dim = lower.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        if upper.len() != dim
+            || lower
+                .iter()
+                .zip(upper.iter())
+                .any(|(l, u)| l > u || is_nan(*l) || is_nan(*u) || !l.is_finite() || !u.is_finite())
+        {
+            return Err(NelderMeadError::InvalidBounds);
+        }
+        let starts = self.starts.unwrap_or(10 * dim);
+        // every local search evaluates its whole initial simplex
+        let affordable = self.max_fun_evals.is_none_or(|limit| limit / (dim as u64 + 1) >= starts as u64);
+        if starts == 0 || !affordable {
+            return Err(NelderMeadError::InvalidPopulation);
+        }
+        if ![self.cluster_xtol, self.cluster_ftol].iter().all(|tol| T::zero() < *tol && tol.is_finite()) {
+            return Err(NelderMeadError::InvalidTolerances);
+        }
+        let mut rng = Rng::seeded(self.seed);
+        match self.sampling {
+            Sampling::LatinHypercube => Ok(latin_hypercube(&mut rng, lower, upper, starts)),
+            Sampling::Sobol => {
+                let sobol = Sobol::scrambled(dim, rng.next_u64())?;
+                let scale = |u: DVector<f64>| {
+                    DVector::from_fn(dim, |i, _| lower[i] + convert::<f64, T>(u[i]) * (upper[i] - lower[i]))
+                };
+                Ok(sobol.take(starts).map(scale).collect())
+            }
+        }
+    }
+
+    /// Local solver starting now, given its share of the budgets left since
+    /// `start`.
+    fn local_search(&self, bounds: &Option<(DVector<T>, DVector<T>)>, start: Instant) -> NelderMead<T> {
+        let starts = self.starts.unwrap_or(10 * bounds.as_ref().map_or(0, |(lower, _)| lower.len()));
+        self.local.polishing(
+            self.direction,
+            self.nan_handling,
+            bounds,
+            self.max_fun_evals.map(|limit| (limit / starts as u64).max(1)),
+            self.max_duration.map(|limit| limit.saturating_sub(start.elapsed())),
+        )
+    }
+
+    /// Whether `max_duration` has passed since `start`.
+    fn timed_out(&self, start: Instant) -> bool {
+        self.max_duration.is_some_and(|limit| start.elapsed() >= limit)
+    }
+
+    /// Result of the best of the runs, with the distinct minima reached by
+    /// all of them.
+    fn rank(&self, mut runs: Vec<OptimizationResult<T>>) -> MultiStartResult<T> {
+        let sign: T = self.direction.sign();
+        runs.sort_by(|a, b| total_cmp(sign * a.best_score, sign * b.best_score));
+        let mut minima: Vec<(DVector<T>, T)> = Vec::new();
+        for run in &runs {
+            let duplicate = minima.iter().any(|(x, score)| {
+                (x - &run.best_point).amax() <= self.cluster_xtol
+                    && (*score - run.best_score).abs() <= self.cluster_ftol * score.abs().max(T::one())
+            });
+            if !duplicate {
+                minima.push((run.best_point.clone(), run.best_score));
+            }
+        }
+        let iterations = runs.iter().map(|run| run.iterations).sum();
+        let evaluations = runs.iter().map(|run| run.evaluations).sum();
+        let restarts = runs.iter().map(|run| run.restarts).sum();
+        let mut result = runs.swap_remove(0);
+        result.iterations = iterations;
+        result.evaluations = evaluations;
+        result.restarts = restarts;
+        MultiStartResult { result, minima }
+    }
+}
+
+/// Latin hypercube sample of `count` points within the bounds: the range
+/// of every parameter is split into `count` strata, each holding the
+/// coordinate of exactly one point, uniformly within it.
+fn latin_hypercube<T: RealField + Copy>(
+    rng: &mut Rng,
+    lower: &DVector<T>,
+    upper: &DVector<T>,
+    count: usize,
+) -> Vec<DVector<T>> {
+    let mut points = vec![lower.clone(); count];
+    let mut strata: Vec<usize> = (0..count).collect();
+    for i in 0..lower.len() {
+        // Fisher-Yates shuffle of the strata
+        for k in (1..count).rev() {
+            strata.swap(k, rng.below(k + 1));
+        }
+        for (point, stratum) in points.iter_mut().zip(&strata) {
+            let u: T = convert((*stratum as f64 + rng.uniform()) / count as f64);
+            point[i] = lower[i] + u * (upper[i] - lower[i]);
+        }
+    }
+    points
+}
@@ -0,0 +1,71 @@
+use nalgebra::{DVector, RealField};
+use ndarray::{Array1, ArrayView1};
+
+use crate::builder::NelderMeadBuilder;
+use crate::nelder_mead::NelderMead;
+use crate::error::NelderMeadError;
+use crate::result::OptimizationResult;
+
+impl<T: RealField + Copy> NelderMead<T> {
+    /// Same as [`NelderMead::minimize`], but works with ndarray arrays.
+    ///
+    /// The objective receives a view borrowing the simplex vertex, so no
+    /// conversion or copy takes place on evaluation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ndarray::{array, ArrayView1};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn f(x: ArrayView1<f64>) -> f64 {
+    ///     x.mapv(|xi| (xi - 1.0).powi(2)).sum()
+    /// }
+    /// let results = NelderMead::builder()
+    ///     .minimize_ndarray(f, array![0.0, 0.0].view())?;
+    ///
+    /// assert!((results.best_point_array()[0] - 1.0).abs() < 1e-2);
+    ///
+    /// // any real type works, e.g. single precision
+    /// let results = NelderMead::<f32>::builder()
+    ///     .minimize_ndarray(|x: ArrayView1<f32>| (x[0] + 2.0).powi(2), array![0.0f32].view())?;
+    ///
+    /// assert!((results.best_point_array()[0] + 2.0).abs() < 1e-2);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn minimize_ndarray<F>(
+        &self,
+        mut obj_fn: F,
+        x_start: ArrayView1<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(ArrayView1<T>) -> T,
+    {
+        self.minimize(
+            |x: &DVector<T>| obj_fn(ArrayView1::from(x.as_slice())),
+            DVector::from_iterator(x_start.len(), x_start.iter().cloned()),
+        )
+    }
+}
+
+impl<T: RealField + Copy> NelderMeadBuilder<T> {
+    /// Finishes the configuration and immediately runs the optimizer,
+    /// see [`NelderMead::minimize_ndarray`].
+    pub fn minimize_ndarray<F>(
+        self,
+        obj_fn: F,
+        x_start: ArrayView1<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(ArrayView1<T>) -> T,
+    {
+        self.build().minimize_ndarray(obj_fn, x_start)
+    }
+}
+
+impl<T: RealField + Copy> OptimizationResult<T> {
+    /// Returns the best point as an ndarray array.
+    pub fn best_point_array(&self) -> Array1<T> {
+        Array1::from_iter(self.best_point.iter().cloned())
+    }
+}
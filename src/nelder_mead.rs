@@ -1,37 +1,1682 @@
-use nalgebra::DVector;
+use std::cmp::Ordering;
+use std::ops::IndexMut;
+use std::time::Duration;
 
-/// Finds a local minimum of provided objective function and returns 
-/// a tuple containing best parameter vector and best score.
-/// 
+use nalgebra::{convert, DMatrix, DVector, RealField};
+
+use crate::builder::NelderMeadBuilder;
+use crate::checkpoint::CheckpointSaver;
+use crate::coefficients::Coefficients;
+use crate::constraints::{AugmentedLagrangian, Penalty, PenaltySchedule, SharedConstraint};
+use crate::diagnostics::{self, SimplexHealth};
+use crate::error::NelderMeadError;
+use crate::event::{Event, SharedEventListener};
+use crate::interrupt;
+use crate::logging;
+use crate::noise::Replication;
+use crate::objective::{Batch, ByRef, Evaluator, FallibleScore, ObjectiveFunction, Seeded};
+#[cfg(feature = "rayon")]
+use crate::objective::Parallel;
+use crate::observer::{SharedCallback, SharedObserver};
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationState};
+use crate::transform::{self, ParameterTransform, SharedTransform};
+
+/// Whether the objective function should be minimized or maximized.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::{Direction, NelderMead};
+///
+/// fn log_likelihood(x: &DVector<f64>) -> f64 {
+///     -(x[0] - 2.0).powi(2) - (x[1] + 1.0).powi(2)
+/// }
+/// let results = NelderMead::builder()
+///     .direction(Direction::Maximize)
+///     .minimize(&log_likelihood, dvector![0.0, 0.0])?;
+///
+/// assert!((results.best_point[0] - 2.0).abs() < 1e-2);
+/// assert!(results.best_score <= 0.0);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    #[default]
+    Minimize,
+    Maximize,
+}
+
+impl Direction {
+    /// Factor turning an objective value into a score that is minimized.
+    pub(crate) fn sign<T: RealField + Copy>(self) -> T {
+        match self {
+            Direction::Minimize => T::one(),
+            Direction::Maximize => -T::one(),
+        }
+    }
+}
+
+/// Which contraction the simplex performs when the reflected point is
+/// not better than the second worst vertex.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::{ContractionMode, NelderMead};
+///
+/// fn rosenbrock(x: &DVector<f64>) -> f64 {
+///     (1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0].powi(2)).powi(2)
+/// }
+/// let results = NelderMead::builder()
+///     .contraction(ContractionMode::InsideOutside)
+///     .xatol(1e-8)
+///     .fatol(1e-8)
+///     .disable_no_improv_break()
+///     .minimize(&rosenbrock, dvector![-1.2, 1.0])?;
+///
+/// assert!(results.best_score < 1e-8);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContractionMode {
+    /// Always contract the worst vertex towards the centroid, accepting
+    /// the contracted point when it beats the worst vertex, as in the
+    /// reference Python implementation.
+    #[default]
+    InsideOnly,
+    /// The standard variant of Lagarias et al. (1998): when the reflected
+    /// point beats the worst vertex, contract it towards the centroid
+    /// instead (outside contraction) and accept the contracted point when
+    /// it is not worse than the reflected one. Otherwise contract inside.
+    InsideOutside,
+}
+
+/// Simplex algorithm moving the vertices, see
+/// [`NelderMeadBuilder::method`].
+///
+/// [`NelderMeadBuilder::method`]: crate::builder::NelderMeadBuilder::method
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SimplexMethod {
+    /// Nelder-Mead, replacing the worst vertex by its reflection,
+    /// expansion or contraction through the centroid of the other ones.
+    #[default]
+    NelderMead,
+    /// Torczon's multi-directional search (1989), reflecting every vertex
+    /// but the best one through the best one. The reflected simplex is
+    /// accepted, or expanded when the reflection improves on the best
+    /// vertex, and the simplex shrinks towards the best vertex otherwise.
+    MultiDirectional,
+}
+
+/// How the improvement of the best score is compared with
+/// `no_improve_thr` by the no-improvement criterion.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::{ImprovementMode, NelderMead};
+///
+/// // scores around 1e8, far beyond the scale of the default threshold
+/// fn f(x: &DVector<f64>) -> f64 {
+///     1e8 * (1.0 + (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2))
+/// }
+/// let absolute = NelderMead::builder()
+///     .minimize(&f, dvector![0.0, 0.0])?;
+/// let relative = NelderMead::builder()
+///     .improvement_mode(ImprovementMode::Relative)
+///     .minimize(&f, dvector![0.0, 0.0])?;
+///
+/// assert!(relative.iterations < absolute.iterations);
+/// assert!((relative.best_point[0] - 1.0).abs() < 1e-2);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ImprovementMode {
+    /// The best score improved when it decreased by more than
+    /// `no_improve_thr`.
+    #[default]
+    Absolute,
+    /// The best score `f_best` improved when it decreased by more than
+    /// `no_improve_thr * max(1, |f_best|)`, which suits objectives of
+    /// any magnitude.
+    Relative,
+}
+
+/// How the bounds set with [`NelderMeadBuilder::bounds`] are enforced:
+/// either trial points outside of them are brought back inside before
+/// they are evaluated, or the simplex moves in an unconstrained space that
+/// a transform maps inside of the bounds.
+///
+/// The initial simplex is built inside the bounds as well, the step of a
+/// coordinate is reversed when it would leave them.
+///
+/// With a transform, the simplex handed to the hooks, the events and the
+/// result are mapped back to the parameters, only a [`Checkpoint`] keeps
+/// the internal coordinates. Transforms behave much better than clipping
+/// when the optimum lies on or near a bound.
+///
+/// [`Checkpoint`]: crate::checkpoint::Checkpoint
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::{BoundsHandling, NelderMead};
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 2.0).powi(2) + (x[1] + 1.0).powi(2)
+/// }
+/// let results = NelderMead::builder()
+///     .bounds(dvector![0.0, 0.0], dvector![1.0, 1.0])
+///     .bounds_handling(BoundsHandling::Reflect)
+///     .minimize(&f, dvector![0.5, 0.5])?;
+///
+/// assert!((results.best_point[0] - 1.0).abs() < 1e-2);
+/// assert!(results.best_point[1].abs() < 1e-2);
+/// assert!(results.final_simplex.iter().all(|(x, _)| x.iter().all(|xi| (0.0..=1.0).contains(xi))));
+///
+/// let results = NelderMead::builder()
+///     .bounds(dvector![0.0, 0.0], dvector![1.0, 1.0])
+///     .bounds_handling(BoundsHandling::Sine)
+///     .minimize(&f, dvector![0.5, 0.5])?;
+///
+/// assert!((results.best_point[0] - 1.0).abs() < 1e-4);
+/// assert!(results.best_point[1].abs() < 1e-4);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BoundsHandling {
+    /// Move every coordinate outside of its bounds to the nearest one.
+    #[default]
+    Clip,
+    /// Mirror every coordinate outside of its bounds at the bound it
+    /// crossed, which keeps the simplex from collapsing onto the boundary.
+    Reflect,
+    /// Optimize the internal coordinate `y` of every parameter, mapped to
+    /// `lower + (upper - lower) * (sin(y) + 1) / 2` as in MATLAB's
+    /// `fminsearchbnd`. The bounds can be reached.
+    Sine,
+    /// Optimize the internal coordinate `y` of every parameter, mapped to
+    /// `lower + (upper - lower) / (1 + exp(-y))`. The bounds are only
+    /// approached, which suits parameters that are undefined on them.
+    Logit,
+}
+
+impl BoundsHandling {
+    /// Whether the bounds are enforced by a transform of the coordinates.
+    fn transforms(self) -> bool {
+        matches!(self, BoundsHandling::Sine | BoundsHandling::Logit)
+    }
+
+    /// Brings the coordinate `x` inside of `[lower, upper]`, clipping it
+    /// under a transform.
+    fn apply<T: RealField + Copy>(self, x: T, lower: T, upper: T) -> T {
+        if lower <= x && x <= upper {
+            return x;
+        }
+        match self {
+            BoundsHandling::Clip | BoundsHandling::Sine | BoundsHandling::Logit => {
+                x.max(lower).min(upper)
+            }
+            BoundsHandling::Reflect => {
+                let width = upper - lower;
+                if width.is_zero() {
+                    return lower;
+                }
+                // reflections at both bounds repeat with twice the width
+                let period = width + width;
+                let offset = x - lower;
+                let offset = offset - period * (offset / period).floor();
+                if offset > width {
+                    upper - (offset - width)
+                } else {
+                    lower + offset
+                }
+            }
+        }
+    }
+
+    /// Parameter within `[lower, upper]` of the internal coordinate `y`.
+    fn to_external<T: RealField + Copy>(self, y: T, lower: T, upper: T) -> T {
+        let width = upper - lower;
+        let x = match self {
+            BoundsHandling::Sine => {
+                let half: T = convert(0.5);
+                lower + width * (y.sin() + T::one()) * half
+            }
+            BoundsHandling::Logit => lower + width / (T::one() + (-y).exp()),
+            BoundsHandling::Clip | BoundsHandling::Reflect => return y,
+        };
+        // rounding must not step over a bound
+        x.max(lower).min(upper)
+    }
+
+    /// Internal coordinate of the parameter `x` within `[lower, upper]`.
+    /// Parameters on a bound are moved slightly inside under
+    /// [`BoundsHandling::Logit`], which never reaches them.
+    fn to_internal<T: RealField + Copy>(self, x: T, lower: T, upper: T) -> T {
+        let width = upper - lower;
+        if width.is_zero() && self.transforms() {
+            return T::zero();
+        }
+        match self {
+            BoundsHandling::Sine => {
+                let two: T = convert(2.0);
+                (two * (x - lower) / width - T::one()).max(-T::one()).min(T::one()).asin()
+            }
+            BoundsHandling::Logit => {
+                let margin = T::default_epsilon().sqrt();
+                let p = ((x - lower) / width).max(margin).min(T::one() - margin);
+                (p / (T::one() - p)).ln()
+            }
+            BoundsHandling::Clip | BoundsHandling::Reflect => x,
+        }
+    }
+}
+
+/// What to do when the objective function returns NaN, e.g. for a point
+/// outside of the domain where it is defined.
+///
+/// Infinite values are ordered like any other score, so returning
+/// infinity (negative infinity when maximizing) for infeasible points
+/// already pushes the simplex away from them under every policy.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::{NanHandling, NelderMead};
+///
+/// // negative log-likelihood of the normal distribution, NaN for sigma < 0
+/// fn nll(x: &DVector<f64>) -> f64 {
+///     let (mu, sigma) = (x[0], x[1]);
+///     [1.0, 3.0]
+///         .iter()
+///         .map(|xi| sigma.ln() + (xi - mu).powi(2) / (2.0 * sigma.powi(2)))
+///         .sum()
+/// }
+/// let results = NelderMead::builder()
+///     .step(0.5)
+///     .nan_handling(NanHandling::TreatAsWorst)
+///     .minimize(&nll, dvector![0.0, 0.2])?;
+///
+/// assert!((results.best_point[0] - 2.0).abs() < 1e-2);
+/// assert!((results.best_point[1] - 1.0).abs() < 1e-2);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NanHandling<T = f64> {
+    /// Fail with [`NelderMeadError::NanScore`].
+    #[default]
+    Error,
+    /// Consider the point worse than any other one.
+    TreatAsWorst,
+    /// Replace NaN with the given objective value.
+    PenaltyValue(T),
+}
+
+impl<T: RealField + Copy> NanHandling<T> {
+    /// Turns an objective value into a score that is minimized, applying
+    /// the policy if the value is NaN.
+    pub(crate) fn score(&self, value: T, sign: T) -> Result<T, NelderMeadError> {
+        if !is_nan(value) {
+            return Ok(sign * value);
+        }
+        match self {
+            NanHandling::Error => Err(NelderMeadError::NanScore),
+            NanHandling::TreatAsWorst => Ok(convert(f64::INFINITY)),
+            NanHandling::PenaltyValue(penalty) => Ok(sign * *penalty),
+        }
+    }
+}
+
+/// What to do when an objective function optimized with
+/// [`NelderMead::try_minimize`] fails to evaluate a point.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::error::NelderMeadError;
+/// use nelder_mead_optimizer::nelder_mead::{FailureHandling, NelderMead};
+///
+/// // simulation that cannot run with a non-positive rate
+/// fn simulate(x: &DVector<f64>) -> Result<f64, String> {
+///     if x[1] <= 0.0 {
+///         return Err(format!("invalid rate {}", x[1]));
+///     }
+///     Ok((x[0] - 1.0).powi(2) + (x[1] - 0.5).powi(2))
+/// }
+/// let results = NelderMead::builder()
+///     .step(0.5)
+///     .failure_handling(FailureHandling::TreatAsWorst)
+///     .try_minimize(simulate, dvector![0.0, 0.2])?;
+///
+/// assert!((results.best_point[1] - 0.5).abs() < 1e-2);
+///
+/// let results = NelderMead::builder()
+///     .step(0.5)
+///     .try_minimize(simulate, dvector![0.0, 0.2]);
+///
+/// assert!(matches!(results, Err(NelderMeadError::Objective(_))));
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FailureHandling {
+    /// Stop the run with [`NelderMeadError::Objective`].
+    #[default]
+    Abort,
+    /// Consider the point worse than any other one.
+    TreatAsWorst,
+    /// Evaluate the point again up to the given number of times, e.g. for
+    /// transient failures, and abort if it still fails. Retries do not
+    /// count towards `max_fun_evals`.
+    Retry(u32),
+}
+
+/// Perturbations applied to the starting point to build the initial simplex.
+///
+/// Vertex `i` of the initial simplex is `x_start` moved along the `i`-th
+/// coordinate by the step of that dimension.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::{NelderMead, StepSize};
+///
+/// // first parameter lives in [0, 1], the second one in [0, 1e6]
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 0.3).powi(2) + ((x[1] - 2.5e5) / 1e5).powi(2)
+/// }
+/// let results = NelderMead::builder()
+///     .step(StepSize::PerDim(dvector![0.05, 1e4]))
+///     .minimize(&f, dvector![0.5, 1e5])?;
+///
+/// assert!((results.best_point[1] - 2.5e5).abs() < 1e3);
+///
+/// // or let the step follow the magnitude of the starting point
+/// let results = NelderMead::builder()
+///     .step(StepSize::Relative(0.05))
+///     .minimize(&f, dvector![0.5, 1e5])?;
+///
+/// assert!((results.best_point[1] - 2.5e5).abs() < 1e3);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub enum StepSize<T = f64> {
+    /// The same step for every dimension.
+    Uniform(T),
+    /// A separate step for each dimension, must have the same length as
+    /// the starting point.
+    PerDim(DVector<T>),
+    /// A step proportional to the magnitude of each coordinate,
+    /// `factor * |x_i|`. Coordinates equal to zero are moved by
+    /// [`StepSize::ZERO_STEP`] instead. This is what MATLAB's `fminsearch`
+    /// does with a factor of 0.05.
+    Relative(T),
+}
+
+impl<T: RealField + Copy> StepSize<T> {
+    /// Absolute step used by [`StepSize::Relative`] for zero coordinates.
+    pub const ZERO_STEP: f64 = 0.00025;
+
+    /// Step applied to the `i`-th coordinate, whose starting value is `x_i`.
+    pub(crate) fn offset(&self, i: usize, x_i: T) -> T {
+        match self {
+            StepSize::Uniform(step) => *step,
+            StepSize::PerDim(steps) => steps[i],
+            StepSize::Relative(factor) => {
+                if x_i.is_zero() {
+                    convert(Self::ZERO_STEP)
+                } else {
+                    *factor * x_i.abs()
+                }
+            }
+        }
+    }
+
+    /// Checks that the step can be applied to a point of dimension `dim`.
+    pub(crate) fn check_dim(&self, dim: usize) -> Result<(), NelderMeadError> {
+        match self {
+            StepSize::PerDim(steps) if steps.len() != dim => {
+                Err(NelderMeadError::StepDimensionMismatch {
+                    expected: dim,
+                    found: steps.len(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the step is neither zero nor infinite nor NaN, with
+    /// which a simplex built around a point would be degenerate.
+    pub(crate) fn check_values(&self) -> Result<(), NelderMeadError> {
+        let valid = |step: &T| !step.is_zero() && step.is_finite();
+        let steps = match self {
+            StepSize::Uniform(step) | StepSize::Relative(step) => std::slice::from_ref(step),
+            StepSize::PerDim(steps) => steps.as_slice(),
+        };
+        if steps.iter().all(valid) {
+            Ok(())
+        } else {
+            Err(NelderMeadError::InvalidSimplex)
+        }
+    }
+}
+
+impl<T> From<T> for StepSize<T> {
+    fn from(step: T) -> Self {
+        StepSize::Uniform(step)
+    }
+}
+
+/// Restart of a collapsed simplex, configured with
+/// [`NelderMeadBuilder::restart_on_degeneracy`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct DegeneracyRestart<T> {
+    pub(crate) max_condition: T,
+    pub(crate) max_restarts: u64,
+}
+
+/// Oriented restart on stagnation, configured with
+/// [`NelderMeadBuilder::oriented_restart`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct OrientedRestart<T> {
+    pub(crate) alpha: T,
+    pub(crate) max_restarts: u64,
+}
+
+/// Jitter of the reflected point on a flat simplex, configured with
+/// [`NelderMeadBuilder::plateau_jitter`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct PlateauJitter<T> {
+    pub(crate) magnitude: T,
+    pub(crate) seed: u64,
+}
+
+/// Configured Nelder-Mead optimizer.
+///
+/// Instances are created with [`NelderMead::builder`], which starts from
+/// the defaults of the reference Python implementation and lets every
+/// parameter be overridden by name.
+///
+/// The optimizer is generic over the floating point type `T` of the
+/// parameters, scores and coefficients, which defaults to `f64`. Any
+/// [`RealField`] can be used, e.g. `f32` to halve the memory footprint.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     x[0].sin() * x[1].cos() * (1.0 / (x[2].abs() + 1.0))
+/// }
+/// let results = NelderMead::builder()
+///     .step(0.1)
+///     .max_iter(100)
+///     .minimize(&f, dvector![0.0, 0.0, 0.0])?;
+///
+/// assert!(results.best_score < -0.99);
+///
+/// fn g(x: &DVector<f32>) -> f32 {
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// }
+/// let results = NelderMead::builder()
+///     .minimize(&g, dvector![0.0f32, 0.0])?;
+///
+/// assert!(results.best_score < 1e-3);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct NelderMead<T = f64> {
+    pub(crate) step: StepSize<T>,
+    pub(crate) no_improve_thr: T,
+    pub(crate) improvement_mode: ImprovementMode,
+    pub(crate) no_improv_break: Option<u64>,
+    pub(crate) max_iter: u64,
+    pub(crate) coefficients: Coefficients<T>,
+    pub(crate) adaptive: bool,
+    pub(crate) contraction: ContractionMode,
+    pub(crate) method: SimplexMethod,
+    pub(crate) greedy_expansion: bool,
+    pub(crate) speculative: bool,
+    pub(crate) parallel_points: usize,
+    pub(crate) cache_resolution: Option<T>,
+    pub(crate) memory_efficient: bool,
+    pub(crate) xatol: Option<T>,
+    pub(crate) fatol: Option<T>,
+    pub(crate) target_value: Option<T>,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) callback: Option<SharedCallback<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) event_listener: Option<SharedEventListener<T>>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) failure_handling: FailureHandling,
+    pub(crate) bounds: Option<(DVector<T>, DVector<T>)>,
+    pub(crate) bounds_handling: BoundsHandling,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) parameter_transforms: Vec<Option<SharedTransform<T>>>,
+    pub(crate) unit_simplices: Vec<Vec<usize>>,
+    pub(crate) periods: Vec<Option<T>>,
+    pub(crate) auto_scale: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) scales: Option<DVector<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) constraints: Vec<SharedConstraint<T>>,
+    pub(crate) penalty: Penalty<T>,
+    pub(crate) penalty_schedule: Option<PenaltySchedule<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) equality_constraints: Vec<SharedConstraint<T>>,
+    pub(crate) augmented_lagrangian: AugmentedLagrangian<T>,
+    pub(crate) linear_equality: Option<(DMatrix<T>, DVector<T>)>,
+    pub(crate) record_history: bool,
+    pub(crate) record_timing: bool,
+    pub(crate) stop_on_interrupt: bool,
+    pub(crate) degeneracy_restart: Option<DegeneracyRestart<T>>,
+    pub(crate) oriented_restart: Option<OrientedRestart<T>>,
+    pub(crate) bfgs_polish: Option<u64>,
+    pub(crate) estimate_covariance: bool,
+    pub(crate) replication: Option<Replication<T>>,
+    pub(crate) reevaluation: Option<u64>,
+    pub(crate) noise_test: Option<T>,
+    pub(crate) plateau_jitter: Option<PlateauJitter<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) checkpoint: Option<CheckpointSaver<T>>,
+}
+
+impl<T: RealField + Copy> Default for NelderMead<T> {
+    fn default() -> Self {
+        NelderMead {
+            step: StepSize::Uniform(convert(0.1)),
+            no_improve_thr: convert(10e-6),
+            improvement_mode: ImprovementMode::Absolute,
+            no_improv_break: Some(10),
+            max_iter: 1000,
+            coefficients: Coefficients::default(),
+            adaptive: false,
+            contraction: ContractionMode::InsideOnly,
+            method: SimplexMethod::NelderMead,
+            greedy_expansion: false,
+            speculative: false,
+            parallel_points: 1,
+            cache_resolution: None,
+            memory_efficient: false,
+            xatol: None,
+            fatol: None,
+            target_value: None,
+            max_fun_evals: None,
+            max_duration: None,
+            termination: None,
+            observer: None,
+            callback: None,
+            event_listener: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            failure_handling: FailureHandling::Abort,
+            bounds: None,
+            bounds_handling: BoundsHandling::Clip,
+            parameter_transforms: Vec::new(),
+            unit_simplices: Vec::new(),
+            periods: Vec::new(),
+            auto_scale: false,
+            scales: None,
+            constraints: Vec::new(),
+            penalty: Penalty::default(),
+            penalty_schedule: None,
+            equality_constraints: Vec::new(),
+            augmented_lagrangian: AugmentedLagrangian::default(),
+            linear_equality: None,
+            record_history: false,
+            record_timing: false,
+            stop_on_interrupt: false,
+            degeneracy_restart: None,
+            oriented_restart: None,
+            bfgs_polish: None,
+            estimate_covariance: false,
+            replication: None,
+            reevaluation: None,
+            noise_test: None,
+            plateau_jitter: None,
+            checkpoint: None,
+        }
+    }
+}
+
+impl<T: RealField + Copy> NelderMead<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> NelderMeadBuilder<T> {
+        NelderMeadBuilder::default()
+    }
+
+    /// Returns an optimizer configured like
+    /// `scipy.optimize.minimize(method="Nelder-Mead")`: `alpha=1`,
+    /// `gamma=2`, `rho=0.5`, `sigma=0.5`, `xatol=fatol=1e-4`, and an
+    /// initial simplex built with a 5% relative step.
+    ///
+    /// The no-improvement criterion is disabled, as scipy has none. Scipy
+    /// also bounds the run by `200 * n` iterations and evaluations, which
+    /// [`minimize`] applies based on the dimension of the starting point.
+    pub fn scipy() -> Self {
+        NelderMead {
+            step: StepSize::Relative(convert(0.05)),
+            no_improv_break: None,
+            coefficients: Coefficients::default(),
+            xatol: Some(convert(1e-4)),
+            fatol: Some(convert(1e-4)),
+            ..NelderMead::default()
+        }
+    }
+
+    /// Copy of this solver polishing the result of a global solver, with
+    /// the direction and NaN handling of that solver and its bounds unless
+    /// this one has its own. Its budgets are capped to `max_fun_evals` and
+    /// `max_duration`, what remains of those of the global solver.
+    pub(crate) fn polishing(
+        &self,
+        direction: Direction,
+        nan_handling: NanHandling<T>,
+        bounds: &Option<(DVector<T>, DVector<T>)>,
+        max_fun_evals: Option<u64>,
+        max_duration: Option<Duration>,
+    ) -> NelderMead<T> {
+        let mut local = self.clone();
+        local.direction = direction;
+        local.nan_handling = nan_handling;
+        if local.bounds.is_none() {
+            local.bounds.clone_from(bounds);
+        }
+        if let Some(remaining) = max_fun_evals {
+            local.max_fun_evals = Some(local.max_fun_evals.map_or(remaining, |own| own.min(remaining)));
+        }
+        if let Some(remaining) = max_duration {
+            local.max_duration = Some(local.max_duration.map_or(remaining, |own| own.min(remaining)));
+        }
+        local
+    }
+
+    /// Coefficients for a problem of dimension `dim`, the adaptive ones
+    /// when enabled.
+    pub(crate) fn coefficients_for(&self, dim: usize) -> Coefficients<T> {
+        if self.adaptive {
+            Coefficients::adaptive(dim)
+        } else {
+            self.coefficients
+        }
+    }
+
+    /// Whether the best score `best` improved on `prev_best` for the
+    /// no-improvement criterion.
+    pub(crate) fn improved(&self, best: T, prev_best: T) -> bool {
+        let threshold = match self.improvement_mode {
+            ImprovementMode::Absolute => self.no_improve_thr,
+            ImprovementMode::Relative if prev_best.is_finite() => {
+                self.no_improve_thr * prev_best.abs().max(T::one())
+            }
+            ImprovementMode::Relative => self.no_improve_thr,
+        };
+        best < prev_best - threshold
+    }
+
+    /// Whether the expanded point scoring `escore` replaces the worst
+    /// vertex rather than the reflected point scoring `rscore`, `best`
+    /// being the score of the best vertex.
+    pub(crate) fn accept_expansion(&self, escore: T, rscore: T, best: T) -> bool {
+        if self.greedy_expansion {
+            escore < best
+        } else {
+            escore < rscore
+        }
+    }
+
+    /// Step following the reflection of one of the worst vertices, scoring
+    /// `score`, to a point scoring `rscore` under the multi-point variant,
+    /// `best` and `kept` being the scores of the best vertex and of the
+    /// worst vertex that is not replaced.
+    pub(crate) fn followup(&self, score: T, rscore: T, best: T, kept: T) -> Followup {
+        if rscore < best {
+            Followup::Expansion
+        } else if rscore < kept {
+            Followup::Reflection
+        } else if self.contraction == ContractionMode::InsideOutside && rscore < score {
+            Followup::OutsideContraction
+        } else {
+            Followup::Contraction
+        }
+    }
+
+    /// Vertices replacing all but the best one when the simplex, ordered
+    /// from the best vertex, has to be restarted after `restarts` earlier
+    /// restarts, `None` otherwise. `reference` carries the mean score and
+    /// the squared norm of the simplex gradient of the previous simplex,
+    /// for the sufficient decrease test of the oriented restart.
+    pub(crate) fn restart(
+        &self,
+        simplex: &Simplex<T>,
+        restarts: u64,
+        reference: &mut Option<(T, T)>,
+    ) -> Option<Vec<DVector<T>>> {
+        // oriented restart when the mean score did not decrease enough
+        if let Some(restart) = self.oriented_restart {
+            let count: T = convert(simplex.len() as f64);
+            let mean = simplex.scores().iter().fold(T::zero(), |sum, score| sum + *score) / count;
+            let stagnated = reference.is_some_and(|(prev_mean, prev_norm)| {
+                mean - prev_mean >= -restart.alpha * prev_norm
+            });
+            let gradient = diagnostics::simplex_gradient(simplex);
+            *reference = gradient.as_ref().map(|gradient| (mean, gradient.norm_squared()));
+            if let Some(gradient) = gradient.filter(|_| stagnated && restarts < restart.max_restarts) {
+                *reference = None;
+                let rebuilt = oriented_simplex(simplex, &gradient);
+                return Some(rebuilt.into_iter().map(|x| self.bounded(x)).collect());
+            }
+        }
+
+        // restart once the simplex has collapsed
+        if self.degeneracy_restart.is_some_and(|restart| {
+            restarts < restart.max_restarts
+                && SimplexHealth::new(simplex).is_degenerate(restart.max_condition)
+        }) {
+            *reference = None;
+            let rebuilt = self.initial_simplex(self.external(&simplex.point(0)))
+                .expect("step size and bounds are checked on construction");
+            return Some(rebuilt.into_iter().skip(1).map(|x| self.internal(x)).collect());
+        }
+        None
+    }
+
+    /// Checks that the bounds, if any, apply to a point of dimension `dim`,
+    /// that no lower bound exceeds its upper bound and that the bounds lie
+    /// in the domain of the parameter transforms, which must not be set
+    /// beyond dimension `dim`, and contain a full period of every periodic
+    /// parameter. Linear equality constraints are only eliminated by
+    /// [`NelderMead::minimize`] and [`NelderMead::minimize_from_simplex`],
+    /// every other solver rejects them. At most `dim` vertices are replaced
+    /// per iteration, so that at least one of them is kept.
+    pub(crate) fn check_domain(&self, dim: usize) -> Result<(), NelderMeadError> {
+        if self.linear_equality.is_some() {
+            return Err(NelderMeadError::InvalidLinearEquality);
+        }
+        if self.parallel_points == 0 || self.parallel_points > dim {
+            return Err(NelderMeadError::InvalidParallelPoints);
+        }
+        if self.cache_resolution.is_some_and(|resolution| !(resolution >= T::zero() && resolution.is_finite())) {
+            return Err(NelderMeadError::InvalidCacheResolution);
+        }
+        if self.noise_test.is_some_and(|critical| !(critical > T::zero() && critical.is_finite())) {
+            return Err(NelderMeadError::InvalidNoiseTest);
+        }
+        let jitter = self.plateau_jitter.map(|jitter| jitter.magnitude);
+        if jitter.is_some_and(|magnitude| !(magnitude > T::zero() && magnitude.is_finite())) {
+            return Err(NelderMeadError::InvalidPlateauJitter);
+        }
+        if self.parameter_transforms.iter().skip(dim).any(Option::is_some)
+            || self.periods.iter().skip(dim).any(Option::is_some)
+        {
+            return Err(NelderMeadError::InvalidTransform);
+        }
+        for (i, period) in self.periods.iter().enumerate() {
+            if let Some(period) = period {
+                if !(*period > T::zero() && period.is_finite()) || self.parameter_transform(i).is_some() {
+                    return Err(NelderMeadError::InvalidTransform);
+                }
+            }
+        }
+        let mut weights = vec![false; dim];
+        for group in &self.unit_simplices {
+            if group.len() < 2 {
+                return Err(NelderMeadError::InvalidTransform);
+            }
+            for &i in group {
+                if i >= dim || weights[i] || self.parameter_transform(i).is_some() || self.period(i).is_some() {
+                    return Err(NelderMeadError::InvalidTransform);
+                }
+                weights[i] = true;
+            }
+        }
+        match &self.bounds {
+            Some((lower, upper))
+                if lower.len() != dim
+                    || upper.len() != dim
+                    || lower.iter().zip(upper.iter()).enumerate().any(|(i, (l, u))| {
+                        l > u
+                            || is_nan(*l)
+                            || is_nan(*u)
+                            || !self.in_domain(i, *l)
+                            || !self.in_domain(i, *u)
+                            || (weights[i] && (*l > T::zero() || *u < T::one()))
+                            || self.period(i).is_some_and(|period| *l > T::zero() || *u < period)
+                    }) =>
+            {
+                Err(NelderMeadError::InvalidBounds)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the parameters `x` lie in the domain of their
+    /// transforms.
+    pub(crate) fn check_point(&self, x: &[T]) -> Result<(), NelderMeadError> {
+        if x.iter().enumerate().all(|(i, xi)| self.in_domain(i, *xi)) {
+            Ok(())
+        } else {
+            Err(NelderMeadError::InvalidTransform)
+        }
+    }
+
+    /// Transform of the `i`-th parameter, if any.
+    fn parameter_transform(&self, i: usize) -> Option<&(dyn ParameterTransform<T> + Send + Sync)> {
+        self.parameter_transforms.get(i).and_then(Option::as_ref).map(|t| &*t.0)
+    }
+
+    /// `i`-th parameter of the internal coordinate `y`.
+    fn forward(&self, i: usize, y: T) -> T {
+        self.parameter_transform(i).map_or(y, |t| t.forward(y))
+    }
+
+    /// Internal coordinate of the `i`-th parameter `x`.
+    fn inverse(&self, i: usize, x: T) -> T {
+        self.parameter_transform(i).map_or(x, |t| t.inverse(x))
+    }
+
+    /// Whether `x` lies in the domain of the transform of the `i`-th
+    /// parameter.
+    fn in_domain(&self, i: usize, x: T) -> bool {
+        self.parameter_transform(i).is_none_or(|t| t.contains(x))
+    }
+
+    /// Whether the `i`-th parameter is rescaled under automatic scaling,
+    /// i.e. is not already moved in transformed coordinates.
+    fn scalable(&self, i: usize) -> bool {
+        self.auto_scale
+            && self.parameter_transform(i).is_none()
+            && !self.is_weight(i)
+            && self.period(i).is_none()
+            && !(self.bounds.is_some() && self.bounds_handling.transforms())
+    }
+
+    /// Magnitude of the parameter `x_i` under automatic scaling, one
+    /// without it or for a parameter at zero.
+    fn magnitude(&self, x_i: T) -> T {
+        if self.auto_scale && !x_i.is_zero() && x_i.is_finite() {
+            x_i.abs()
+        } else {
+            T::one()
+        }
+    }
+
+    /// Fixes the scales of the internal coordinates to the magnitudes of
+    /// the starting parameters `x_start` under automatic scaling.
+    pub(crate) fn fit_scales(&mut self, x_start: &[T]) {
+        self.scales = None;
+        if self.auto_scale {
+            let scales = DVector::from_fn(x_start.len(), |i, _| {
+                if self.scalable(i) {
+                    self.magnitude(x_start[i])
+                } else {
+                    T::one()
+                }
+            });
+            self.scales = Some(scales);
+        }
+    }
+
+    /// Whether the `i`-th parameter is a weight of a unit simplex.
+    fn is_weight(&self, i: usize) -> bool {
+        self.unit_simplices.iter().any(|group| group.contains(&i))
+    }
+
+    /// Period of the `i`-th parameter, if it is periodic.
+    fn period(&self, i: usize) -> Option<T> {
+        self.periods.get(i).copied().flatten()
+    }
+
+    /// Shifts every vertex by the same multiple of the period of every
+    /// periodic parameter, so that the best vertex, ordered first, lies
+    /// within the first period. The shape of the simplex is kept, while
+    /// its internal coordinates cannot drift away. Returns whether the
+    /// vertices were shifted.
+    pub(crate) fn recenter<V>(&self, vertices: &mut [(V, T)]) -> bool
+    where
+        V: IndexMut<usize, Output = T>,
+    {
+        let mut shifted = false;
+        if vertices.is_empty() {
+            return shifted;
+        }
+        for i in 0..self.periods.len() {
+            if let Some(shift) = self.period_shift(i, vertices[0].0[i]) {
+                for (x, _) in vertices.iter_mut() {
+                    x[i] -= shift;
+                }
+                shifted = true;
+            }
+        }
+        shifted
+    }
+
+    /// Same as [`NelderMead::recenter`], for a simplex stored as a matrix.
+    pub(crate) fn recenter_simplex(&self, simplex: &mut Simplex<T>) -> bool {
+        let mut shifted = false;
+        if simplex.is_empty() {
+            return shifted;
+        }
+        for i in 0..self.periods.len() {
+            if let Some(shift) = self.period_shift(i, simplex.vertex(0)[i]) {
+                for j in 0..simplex.len() {
+                    simplex.vertex_mut(j)[i] -= shift;
+                }
+                shifted = true;
+            }
+        }
+        shifted
+    }
+
+    /// Multiple of the period of the `i`-th parameter moving its internal
+    /// coordinate `y` back within the first period, `None` if it already
+    /// lies there or the parameter is not periodic.
+    fn period_shift(&self, i: usize, y: T) -> Option<T> {
+        let period = self.period(i)?;
+        let shift = (y / period).floor() * period;
+        (!shift.is_zero() && shift.is_finite()).then_some(shift)
+    }
+
+    /// Bounds of the `i`-th internal coordinate before any transform of
+    /// the bounds handling, `None` without bounds, for a weight of a unit
+    /// simplex, which the softmax keeps within them, or for a periodic
+    /// parameter, which is wrapped within them.
+    fn internal_bounds(&self, i: usize) -> Option<(T, T)> {
+        if self.is_weight(i) || self.period(i).is_some() {
+            return None;
+        }
+        self.bounds.as_ref().map(|(lower, upper)| {
+            let scale = self.scales.as_ref().map_or(T::one(), |scales| scales[i]);
+            let lower = self.inverse(i, lower[i]) / scale;
+            let upper = self.inverse(i, upper[i]) / scale;
+            // a decreasing transform swaps the bounds
+            if lower <= upper {
+                (lower, upper)
+            } else {
+                (upper, lower)
+            }
+        })
+    }
+
+    /// Brings the parameters `x` inside of the bounds, if any, and the
+    /// weights onto their unit simplex. Periodic parameters are left as
+    /// they are, as they are wrapped within the bounds.
+    pub(crate) fn to_feasible(&self, x: &mut [T]) {
+        if let Some((lower, upper)) = &self.bounds {
+            for (i, xi) in x.iter_mut().enumerate() {
+                if self.period(i).is_some() {
+                    continue;
+                }
+                *xi = self.bounds_handling.apply(*xi, lower[i], upper[i]);
+            }
+        }
+        for group in &self.unit_simplices {
+            transform::normalize_weights(x, group);
+        }
+    }
+
+    /// Brings a trial point `x` in internal coordinates inside of the
+    /// bounds, unless they are enforced by a transform, under which every
+    /// point is feasible.
+    pub(crate) fn apply_bounds(&self, x: &mut [T]) {
+        if self.bounds.is_none() || self.bounds_handling.transforms() {
+            return;
+        }
+        for (i, xi) in x.iter_mut().enumerate() {
+            if let Some((lower, upper)) = self.internal_bounds(i) {
+                *xi = self.bounds_handling.apply(*xi, lower, upper);
+            }
+        }
+    }
+
+    /// Same as [`NelderMead::apply_bounds`], on an owned vector.
+    pub(crate) fn bounded(&self, mut x: DVector<T>) -> DVector<T> {
+        self.apply_bounds(x.as_mut_slice());
+        x
+    }
+
+    /// Same as [`NelderMead::to_feasible`], on an owned vector.
+    pub(crate) fn feasible(&self, mut x: DVector<T>) -> DVector<T> {
+        self.to_feasible(x.as_mut_slice());
+        x
+    }
+
+    /// Whether the simplex moves in internal coordinates, which a
+    /// transform maps to the parameters.
+    pub(crate) fn transforms(&self) -> bool {
+        (self.bounds.is_some() && self.bounds_handling.transforms())
+            || self.parameter_transforms.iter().any(Option::is_some)
+            || self.scales.is_some()
+            || !self.unit_simplices.is_empty()
+            || self.periods.iter().any(Option::is_some)
+    }
+
+    /// Maps the internal coordinates `x` to the parameters, in place. The
+    /// scale applies first, then the transform of the bounds handling and
+    /// the one of the parameter, and the softmax of every unit simplex.
+    /// Periodic parameters are wrapped within their first period.
+    pub(crate) fn to_external(&self, x: &mut [T]) {
+        if !self.transforms() {
+            return;
+        }
+        let bounded = self.bounds_handling.transforms();
+        for (i, xi) in x.iter_mut().enumerate() {
+            if let Some(scales) = &self.scales {
+                *xi *= scales[i];
+            }
+            match (&self.bounds, self.internal_bounds(i)) {
+                (Some((lower, upper)), Some((internal_lower, internal_upper))) if bounded => {
+                    let yi = self.bounds_handling.to_external(*xi, internal_lower, internal_upper);
+                    // rounding must not step over a bound
+                    *xi = self.forward(i, yi).max(lower[i]).min(upper[i]);
+                }
+                _ => *xi = self.forward(i, *xi),
+            }
+            if let Some(period) = self.period(i) {
+                *xi = transform::wrap(*xi, period);
+            }
+        }
+        for group in &self.unit_simplices {
+            transform::softmax(x, group);
+        }
+    }
+
+    /// Maps the parameters `x` to the internal coordinates, in place.
+    pub(crate) fn to_internal(&self, x: &mut [T]) {
+        if !self.transforms() {
+            return;
+        }
+        let bounded = self.bounds_handling.transforms();
+        for (i, xi) in x.iter_mut().enumerate() {
+            let yi = self.inverse(i, *xi);
+            *xi = match self.internal_bounds(i) {
+                Some((lower, upper)) if bounded => self.bounds_handling.to_internal(yi, lower, upper),
+                _ => yi,
+            };
+            if let Some(scales) = &self.scales {
+                *xi /= scales[i];
+            }
+        }
+        for group in &self.unit_simplices {
+            transform::log_weights(x, group);
+        }
+    }
+
+    /// Parameters of the internal coordinates `x`.
+    pub(crate) fn external(&self, x: &DVector<T>) -> DVector<T> {
+        let mut x = x.clone();
+        self.to_external(x.as_mut_slice());
+        x
+    }
+
+    /// Internal coordinates of the parameters `x`.
+    pub(crate) fn internal(&self, mut x: DVector<T>) -> DVector<T> {
+        self.to_internal(x.as_mut_slice());
+        x
+    }
+
+    /// Maps the vertices of a simplex in internal coordinates to the
+    /// parameters, in place.
+    pub(crate) fn to_external_simplex(&self, simplex: &mut [(DVector<T>, T)]) {
+        if self.transforms() {
+            for (x, _) in simplex.iter_mut() {
+                self.to_external(x.as_mut_slice());
+            }
+        }
+    }
+
+    /// Writes the vertices of a simplex in internal coordinates, mapped to
+    /// the parameters, to `external`, reusing its storage.
+    pub(crate) fn copy_to_external(&self, simplex: &Simplex<T>, external: &mut Simplex<T>) {
+        external.copy_from(simplex);
+        if self.transforms() {
+            for j in 0..external.len() {
+                self.to_external(external.vertex_mut(j));
+            }
+        }
+    }
+
+    /// Step applied to the `i`-th coordinate of the starting point, whose
+    /// value is `x_i`, to build the initial simplex. It is reversed when
+    /// it would leave the bounds or the domain of the parameter transform,
+    /// and shrunk when it fits neither way, e.g. in a box narrower than
+    /// the step. Under automatic scaling, the step applies relative to the
+    /// magnitude of the parameter.
+    pub(crate) fn initial_step(&self, i: usize, x_i: T) -> T {
+        let magnitude = self.magnitude(x_i);
+        let step = if magnitude == T::one() {
+            self.step.offset(i, x_i)
+        } else {
+            self.step.offset(i, x_i / magnitude) * magnitude
+        };
+        // periodic parameters are wrapped within the bounds
+        let bounds = self.bounds.as_ref().filter(|_| self.period(i).is_none());
+        let fits = |step: T| {
+            self.in_domain(i, x_i + step)
+                && bounds.is_none_or(|(lower, upper)| lower[i] <= x_i + step && x_i + step <= upper[i])
+        };
+        if fits(step) {
+            return step;
+        }
+        if fits(-step) {
+            return -step;
+        }
+        // halfway to the farther bound, so that the vertex stays strictly
+        // inside of the box, then halved until it lies in the domain
+        let half: T = convert(0.5);
+        let (above, below) = bounds.map_or((T::one(), T::zero()), |(lower, upper)| (upper[i] - x_i, x_i - lower[i]));
+        let mut step = if above >= below {
+            step.abs().min(half * above)
+        } else {
+            -step.abs().min(half * below)
+        };
+        for _ in 0..64 {
+            if fits(step) {
+                break;
+            }
+            step *= half;
+        }
+        step
+    }
+
+    /// Checks the convergence tolerances: the largest distance of any
+    /// vertex coordinate from the best vertex must be within `xatol` and
+    /// the largest score difference from the best score within `fatol`.
+    /// The distance of a periodic parameter is the shorter way around its
+    /// period. Vertices must be ordered from the best one.
+    pub(crate) fn has_converged<'a, I>(&self, mut vertices: I) -> bool
+    where
+        T: 'a,
+        I: Iterator<Item = (&'a [T], T)>,
+    {
+        if self.xatol.is_none() && self.fatol.is_none() {
+            return false;
+        }
+        let (x_best, f_best) = match vertices.next() {
+            Some(best) => best,
+            None => return false,
+        };
+        vertices.all(|(x, f)| {
+            let x_ok = self.xatol.is_none_or(|xatol| {
+                x.iter().zip(x_best.iter()).enumerate().all(|(i, (xi, bi))| {
+                    let distance = (*xi - *bi).abs();
+                    let distance = match self.period(i) {
+                        Some(period) => {
+                            let distance = transform::wrap(distance, period);
+                            distance.min(period - distance)
+                        }
+                        None => distance,
+                    };
+                    distance <= xatol
+                })
+            });
+            let f_ok = self.fatol.is_none_or(|fatol| (f - f_best).abs() <= fatol);
+            x_ok && f_ok
+        })
+    }
+
+    /// Reports the state of the run to the observer and the callback, then
+    /// checks the stopping rules evaluated at the start of every iteration,
+    /// once the simplex has been ordered.
+    pub(crate) fn check_stop(&self, state: &TerminationState<T>) -> Option<TerminationReason> {
+        let best = state.simplex.score(0);
+
+        logging::log_iteration(state);
+        if let Some(observer) = &self.observer {
+            observer.0.observe(state);
+        }
+
+        // break when the callback asks for it
+        if let Some(callback) = &self.callback {
+            if callback.0.call(state).is_break() {
+                return Some(TerminationReason::StoppedByCallback);
+            }
+        }
+
+        // break once a stop was requested
+        if self.stop_on_interrupt && interrupt::take_stop_request() {
+            return Some(TerminationReason::Interrupted);
+        }
+
+        // break once the target value is reached
+        if self.target_value.is_some_and(|target| best <= state.sign * target) {
+            return Some(TerminationReason::TargetReached);
+        }
+
+        // break when the simplex is within tolerances
+        if self.has_converged(state.simplex.iter()) {
+            return Some(TerminationReason::Converged);
+        }
+
+        // break when the termination criterion is met
+        if let Some(criterion) = &self.termination {
+            if criterion.0.should_stop(state) {
+                return Some(TerminationReason::Criterion);
+            }
+        }
+        None
+    }
+
+    /// Reports an event to the event listener, the event is only built
+    /// when a listener is configured.
+    pub(crate) fn emit(&self, event: impl FnOnce() -> Event<T>) {
+        if let Some(listener) = &self.event_listener {
+            let mut event = event();
+            if self.transforms() {
+                event.points_mut(|x| self.to_external(x.as_mut_slice()));
+            }
+            listener.0.on_event(&event);
+        }
+    }
+
+    /// Finds a local minimum (or maximum, depending on the configured
+    /// [`Direction`]) of provided objective function and returns
+    /// an [`OptimizationResult`] describing the best point and the run.
+    ///
+    /// Fails if the configuration does not fit the problem or the
+    /// objective function returns NaN under [`NanHandling::Error`], see
+    /// [`NelderMeadError`].
+    ///
+    /// # Arguments
+    ///
+    /// * `obj_fn` - function or closure to optimize, must return a scalar score and operate over
+    ///   a vector of the same dimensions as x_start
+    /// * `x_start` - initial position
+    pub fn minimize<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.minimize_with(obj_fn, x_start)
+    }
+
+    /// Same as [`NelderMead::minimize`], but evaluates the independent
+    /// points of a run, the vertices of the initial simplex and of every
+    /// shrink step, in parallel on the rayon thread pool. The result is the same as with
+    /// [`NelderMead::minimize`], only the wall-clock time of expensive
+    /// objectives shrinks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     x.iter().enumerate().map(|(i, xi)| (xi - i as f64).powi(2)).sum()
+    /// }
+    /// let optimizer = NelderMead::builder().build();
+    /// let x_start = DVector::zeros(8);
+    ///
+    /// let parallel = optimizer.minimize_parallel(f, x_start.clone())?;
+    ///
+    /// assert_eq!(parallel, optimizer.minimize(f, x_start)?);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn minimize_parallel<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        T: Send + Sync,
+        F: Fn(&DVector<T>) -> T + Sync,
+    {
+        self.minimize_with(Parallel(obj_fn), x_start)
+    }
+
+    /// Same as [`NelderMead::minimize`], but optimizes a batch objective,
+    /// taking several points at once and returning their values in the
+    /// same order, e.g. a vectorized or GPU implementation, or a simulator
+    /// amortizing its setup over many points. The vertices of the initial
+    /// simplex and of every shrink step, as well as the trial points under
+    /// [`NelderMeadBuilder::speculative_evaluation`], are evaluated with a
+    /// single call, the other points with a call on one point.
+    ///
+    /// Fails with [`NelderMeadError::InvalidBatch`] if the objective does
+    /// not return one value per point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// let mut calls = 0;
+    /// let batch = |xs: &[DVector<f64>]| -> Vec<f64> {
+    ///     calls += 1;
+    ///     xs.iter().map(|x| (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)).collect()
+    /// };
+    /// let results = NelderMead::builder()
+    ///     .speculative_evaluation(true)
+    ///     .minimize_batch(batch, dvector![0.0, 0.0])?;
+    ///
+    /// assert!(results.best_score < 1e-4);
+    /// assert!(calls < results.evaluations);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn minimize_batch<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&[DVector<T>]) -> Vec<T>,
+    {
+        let mut batch = Batch { obj_fn, mismatch: false };
+        let results = self.minimize_with(ByRef(&mut batch), x_start);
+        if batch.mismatch {
+            return Err(NelderMeadError::InvalidBatch);
+        }
+        results
+    }
+
+    /// Same as [`NelderMead::minimize`], but for a stochastic objective
+    /// taking the seed of its random numbers along with the point, e.g. a
+    /// simulation. The seeds are derived from `seed` and the index of the
+    /// evaluation of the point: the first evaluation of every point gets
+    /// the same seed, the second one, as with [`replicate`] or
+    /// [`reevaluate_best`], another seed shared by all points, and so on.
+    ///
+    /// With these common random numbers, the differences between points
+    /// reflect the points rather than the noise, so the simplex sees a
+    /// smooth landscape. Runs given the same seed are reproducible.
+    ///
+    /// [`replicate`]: crate::builder::NelderMeadBuilder::replicate
+    /// [`reevaluate_best`]: crate::builder::NelderMeadBuilder::reevaluate_best
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    /// use nelder_mead_optimizer::noise::Aggregate;
+    ///
+    /// // a simulation drawing its random numbers from `seed`
+    /// fn simulate(x: &DVector<f64>, seed: u64) -> f64 {
+    ///     let z = (seed >> 11) as f64 / (1u64 << 53) as f64;
+    ///     (1.0 + z) * ((x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)) + z
+    /// }
+    /// let optimizer = NelderMead::builder()
+    ///     .replicate(8, Aggregate::Mean)
+    ///     .build();
+    /// let results = optimizer.minimize_seeded(simulate, dvector![0.0, 0.0], 42)?;
+    ///
+    /// assert!((results.best_point[0] - 1.0).abs() < 1e-2);
+    /// assert!((results.best_point[1] + 1.0).abs() < 1e-2);
+    /// assert_eq!(results, optimizer.minimize_seeded(simulate, dvector![0.0, 0.0], 42)?);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn minimize_seeded<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+        seed: u64,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>, u64) -> T,
+    {
+        self.minimize_with(Seeded::new(obj_fn, seed), x_start)
+    }
+
+    /// Same as [`NelderMead::minimize`], with the objective evaluated by
+    /// `obj_fn`.
+    fn minimize_with<E>(
+        &self,
+        obj_fn: E,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        E: Evaluator<T>,
+    {
+        if self.linear_equality.is_some() {
+            return self.minimize_reduced(obj_fn, &x_start);
+        }
+        let simplex = self.initial_simplex(x_start)?;
+        self.minimize_penalized(obj_fn, simplex)
+    }
+
+    /// Builds the initial simplex around `x_start` with the configured
+    /// step.
+    pub(crate) fn initial_simplex(
+        &self,
+        x_start: DVector<T>,
+    ) -> Result<Vec<DVector<T>>, NelderMeadError> {
+        let dim = x_start.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        self.step.check_dim(dim)?;
+        self.step.check_values()?;
+        self.check_domain(dim)?;
+
+        let mut simplex = vec![self.feasible(x_start)];
+        for i in 0..dim {
+            let mut x = simplex[0].clone();
+            let step = self.initial_step(i, x[i]);
+            x[i] += step;
+            simplex.push(self.feasible(x));
+        }
+        Ok(simplex)
+    }
+
+    /// Same as [`NelderMead::minimize`], but starts from a complete
+    /// simplex instead of building one around a starting point, e.g. to
+    /// warm-start from the `final_simplex` of a previous run. The step
+    /// size is not used.
+    ///
+    /// Fails with [`NelderMeadError::InvalidSimplex`] if `simplex` does not
+    /// contain `n + 1` vertices of the same dimension `n >= 1`. Under
+    /// linear equality constraints, it must contain `k + 1` points, `k`
+    /// being the number of coordinates of the null space, which are
+    /// projected onto the constraints, e.g. the final simplex of a previous
+    /// run under the same constraints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+    /// }
+    /// let first = NelderMead::builder()
+    ///     .max_iter(10)
+    ///     .minimize(&f, dvector![0.0, 0.0])?;
+    /// let second = NelderMead::builder().minimize_from_simplex(
+    ///     &f,
+    ///     first.final_simplex.into_iter().map(|(x, _)| x).collect(),
+    /// )?;
+    ///
+    /// assert!(second.best_score <= first.best_score);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn minimize_from_simplex<F>(
+        &self,
+        obj_fn: F,
+        simplex: Vec<DVector<T>>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        if self.linear_equality.is_some() {
+            return self.minimize_from_simplex_reduced(obj_fn, &simplex);
+        }
+        self.minimize_penalized(obj_fn, simplex)
+    }
+
+    /// Same as [`NelderMead::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`] such as a struct carrying its own data.
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), x_start)
+    }
+
+    /// Same as [`NelderMead::minimize`], but optimizes an objective
+    /// function that can fail, returning a [`FallibleScore`] such as a
+    /// `Result` or an `Option`. Failed evaluations are handled according
+    /// to the configured [`FailureHandling`], NaN values according to the
+    /// configured [`NanHandling`].
+    pub fn try_minimize<F, R>(
+        &self,
+        mut obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> R,
+        R: FallibleScore<T>,
+    {
+        // failures are reported as NaN, so NaN values are handled here
+        let worst = self.direction.sign::<T>() * convert(f64::INFINITY);
+        let config = NelderMead {
+            nan_handling: NanHandling::Error,
+            ..self.clone()
+        };
+        let mut failure = None;
+        let results = config.minimize(
+            |x: &DVector<T>| {
+                let mut outcome = obj_fn(x).into_score();
+                if let FailureHandling::Retry(retries) = self.failure_handling {
+                    for _ in 0..retries {
+                        if outcome.is_ok() {
+                            break;
+                        }
+                        outcome = obj_fn(x).into_score();
+                    }
+                }
+                match outcome {
+                    Ok(value) if !is_nan(value) => value,
+                    Ok(value) => match self.nan_handling {
+                        NanHandling::Error => value,
+                        NanHandling::TreatAsWorst => worst,
+                        NanHandling::PenaltyValue(penalty) => penalty,
+                    },
+                    Err(_) if self.failure_handling == FailureHandling::TreatAsWorst => worst,
+                    Err(err) => {
+                        failure = Some(err);
+                        convert(f64::NAN)
+                    }
+                }
+            },
+            x_start,
+        );
+        match failure {
+            Some(err) => Err(NelderMeadError::Objective(err)),
+            None => results,
+        }
+    }
+
+    /// Same as [`NelderMead::minimize`], but works with plain slices
+    /// instead of nalgebra vectors. The best point of the returned result
+    /// can be read back with `best_point.as_slice()`.
+    pub fn minimize_slice<F>(
+        &self,
+        mut obj_fn: F,
+        x_start: &[T],
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&[T]) -> T,
+    {
+        self.minimize(
+            |x: &DVector<T>| obj_fn(x.as_slice()),
+            DVector::from_column_slice(x_start),
+        )
+    }
+}
+
+/// Finds a local minimum of provided objective function and returns
+/// an [`OptimizationResult`] containing best parameter vector, best score
+/// and statistics of the run.
+///
 /// It's a pure Rust implementation of the Nelder-Mead algorithm.
 /// Reference: <https://en.wikipedia.org/wiki/Nelder%E2%80%93Mead_method>
 ///
+/// This is a thin wrapper over [`NelderMead`], prefer configuring the
+/// algorithm through [`NelderMead::builder`] in new code.
+///
 /// # Arguments
 ///
-/// * `obj_fn` - function to optimize, must return a scalar score and operate over 
-///     a numpy array of the same dimensions as x_start
+/// * `obj_fn` - function or closure to optimize, must return a scalar score and operate over
+///   a numpy array of the same dimensions as x_start
 /// * `x_start` - initial position
 /// * `step` - look-around radius in initial step
 /// * `no_improve_thr` - threshold informing on no improvement
 /// * `no_improv_break` - break after no_improv_break iterations with an
-///     improvement lower than no_improv_thr 
+///   improvement lower than no_improv_thr
 /// * `max_iter` - always break after this number of iterations
 /// * `alpha` - reflection step parameter, usually equals 1.0
 /// * `gamma` - expansion step parameter, usually equals 2.0
-/// * `rho` - contraction step parameter, usually equals 0.5
+/// * `rho` - contraction step parameter, usually equals -0.5; like in the
+///   reference Python implementation a negative value contracts towards the
+///   worst point, which is the opposite sign of [`NelderMeadBuilder::rho`]
 /// * `sigma` - shrink step parameter, usually equals 0.5
 ///
 /// # Examples
 ///
 /// ```
 /// use nalgebra::{DVector, dvector};
-/// use nelder_mead::nelder_mead;
-/// 
+/// use nelder_mead_optimizer::nelder_mead::nelder_mead_algorithm;
+///
 /// fn f(x: &DVector<f64>) -> f64 {
-///     return x[0].sin() * x[1].cos() * (1.0 / (x[2].abs() + 1.0))
+///     x[0].sin() * x[1].cos() * (1.0 / (x[2].abs() + 1.0))
 /// }
-/// let results = nelder_mead(
-///     &f, 
+/// let results = nelder_mead_algorithm(
+///     &f,
 ///     dvector![0.0, 0.0, 0.0],
 ///     0.1,
 ///     10e-6,
@@ -41,127 +1686,215 @@ use nalgebra::DVector;
 ///     2.0,
 ///     -0.5,
 ///     0.5
-/// );
-/// 
+/// )?;
+///
 /// println!("{:?}", results);
-/// 
-/// assert_eq!(-0.9999447346002792, results.1);
-/// ```
-/// 
-pub fn nelder_mead_algorithm(
-    obj_fn: &dyn Fn(&DVector<f64>) -> f64,
-    x_start: DVector<f64>,
-    step: f64,
-    no_improve_thr: f64,
+///
+/// assert!(results.best_score < -0.99);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+///
+/// The objective is taken as `FnMut`, so it may mutate its own state
+/// between calls. Pass it by mutable reference to inspect that state
+/// once the optimization is done:
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::nelder_mead_algorithm;
+///
+/// let mut calls = 0;
+/// let mut last_point = DVector::<f64>::zeros(2);
+/// let mut f = |x: &DVector<f64>| {
+///     calls += 1;
+///     last_point.copy_from(x);
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// };
+/// let results = nelder_mead_algorithm(
+///     &mut f,
+///     dvector![0.0, 0.0],
+///     0.1,
+///     10e-6,
+///     10,
+///     100,
+///     1.0,
+///     2.0,
+///     -0.5,
+///     0.5
+/// )?;
+///
+/// assert_eq!(results.evaluations, calls);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+///
+#[allow(clippy::too_many_arguments)]
+pub fn nelder_mead_algorithm<T, F>(
+    obj_fn: F,
+    x_start: DVector<T>,
+    step: T,
+    no_improve_thr: T,
     no_improv_break: u64,
     max_iter: u64,
-    alpha: f64,
-    gamma: f64,
-    rho: f64,
-    sigma: f64
-) -> (DVector<f64>, f64) {
-
-    // init
-    let dim = x_start.len();
-    let mut prev_best = obj_fn(&x_start);
-    let mut no_improv = 0;
-    let mut res = vec![(x_start, prev_best)];
-
-    for i in 0..dim {
-        let mut x = res[0].0.clone();
-        x[i] += step;
-        let score = obj_fn(&x);
-        res.push((x, score));
-    }
-
-    // simplex iter
-    let mut iters = 0;
-    loop
-    {
-        // order
-        res.sort_by(|a, b| (a.1).partial_cmp(&b.1).unwrap());
-        let best = res[0].1.clone();
-
-        // break after max_iter
-        if iters >= max_iter {
-            return res[0].clone()
-        } 
-        iters += 1;
-
-        // break after no_improv_break iterations with no improvement
-        println!("Iter {}, best so far: {}", iters, best);
-        println!("Points:");
-        _print_points(&res);
-
-        if best < prev_best - no_improve_thr {
-            no_improv = 0;
-            prev_best = best;
-        } else {
-            no_improv += 1;
-        }
-
-        if no_improv >= no_improv_break {
-            return res[0].clone()
-        }
+    alpha: T,
+    gamma: T,
+    rho: T,
+    sigma: T
+) -> Result<OptimizationResult<T>, NelderMeadError>
+where
+    T: RealField + Copy,
+    F: FnMut(&DVector<T>) -> T,
+{
+    NelderMead::builder()
+        .step(step)
+        .no_improve_thr(no_improve_thr)
+        .no_improv_break(no_improv_break)
+        .max_iter(max_iter)
+        .alpha(alpha)
+        .gamma(gamma)
+        .rho(-rho)
+        .sigma(sigma)
+        .minimize(obj_fn, x_start)
+}
 
-        let last_idx = res.len()-1;
+/// Finds a local minimum of provided objective function with the same
+/// defaults as `scipy.optimize.minimize(method="Nelder-Mead")`, see
+/// [`NelderMead::scipy`].
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead;
+/// use nelder_mead_optimizer::result::TerminationReason;
+///
+/// fn rosenbrock(x: &DVector<f64>) -> f64 {
+///     (1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0].powi(2)).powi(2)
+/// }
+/// let results = nelder_mead::minimize(&rosenbrock, dvector![-1.2, 1.0])?;
+///
+/// assert_eq!(results.termination_reason, TerminationReason::Converged);
+/// assert!((results.best_point[0] - 1.0).abs() < 1e-3);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+pub fn minimize<T, F>(
+    obj_fn: F,
+    x_start: DVector<T>,
+) -> Result<OptimizationResult<T>, NelderMeadError>
+where
+    T: RealField + Copy,
+    F: FnMut(&DVector<T>) -> T,
+{
+    let max_iter = 200 * x_start.len() as u64;
+    NelderMead {
+        max_iter,
+        max_fun_evals: Some(max_iter),
+        ..NelderMead::scipy()
+    }
+    .minimize(obj_fn, x_start)
+}
 
-        // centroid
-        let mut x0 = DVector::<f64>::zeros(dim);
-        for tup in res[..last_idx].iter() {
-            for (i, c) in (tup.0).iter().enumerate() {
-                x0[i] += c / last_idx as f64;
-            }
-        }
+/// Finds a local minimum of provided objective function operating over
+/// plain slices, using the default parameters of [`NelderMead`].
+///
+/// # Examples
+///
+/// ```
+/// use nelder_mead_optimizer::nelder_mead::nelder_mead_slice;
+///
+/// fn f(x: &[f64]) -> f64 {
+///     x.iter().map(|xi| (xi - 1.0).powi(2)).sum()
+/// }
+/// let results = nelder_mead_slice(f, &[0.0, 0.0])?;
+///
+/// assert!(results.best_point.as_slice().iter().all(|xi| (xi - 1.0).abs() < 1e-2));
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+pub fn nelder_mead_slice<T, F>(
+    obj_fn: F,
+    x_start: &[T],
+) -> Result<OptimizationResult<T>, NelderMeadError>
+where
+    T: RealField + Copy,
+    F: FnMut(&[T]) -> T,
+{
+    NelderMead::default().minimize_slice(obj_fn, x_start)
+}
 
-        // reflection
-        let xr = &x0 + alpha*(&x0 - &(res[last_idx].0));
-        let rscore = obj_fn(&xr);
-        if (res[0].1 <= rscore) & (rscore < res[last_idx-1].1) {
-            res.remove(last_idx);
-            res.push((xr, rscore));
-            continue;
-        }
+/// Vertices of the oriented restart of Kelley (1999): the best vertex
+/// `x_0` is kept and `x_j = x_0 - s sign(g_j) e_j`, `g` being the simplex
+/// gradient and `s` half the shortest edge leaving `x_0`, so the new
+/// simplex points downhill.
+fn oriented_simplex<T: RealField + Copy>(simplex: &Simplex<T>, gradient: &DVector<T>) -> Vec<DVector<T>> {
+    let best = simplex.point(0);
+    let shortest = (1..simplex.len())
+        .map(|j| (simplex.column(j) - &best).norm())
+        .fold(None, |min: Option<T>, d| Some(min.map_or(d, |m| m.min(d))))
+        .unwrap_or_else(T::one);
+    let half: T = convert(0.5);
+    (0..best.len())
+        .map(|j| {
+            let mut x = best.clone();
+            let sign = if gradient[j] < T::zero() { -T::one() } else { T::one() };
+            x[j] -= sign * shortest * half;
+            x
+        })
+        .collect()
+}
 
-        // expansion
-        if rscore < res[0].1 {
-            let xe = &x0 + gamma*(&x0 - &(res[last_idx].0));
-            let escore = obj_fn(&xe);
-            if escore < rscore {
-                res.remove(last_idx);
-                res.push((xe, escore));
-                continue;
-            } else {
-                res.remove(last_idx);
-                res.push((xr, rscore));
-                continue;
-            }
-        }
+/// Step of one of the worst vertices under the multi-point variant, see
+/// [`NelderMeadBuilder::parallel_points`], once its reflected point is
+/// evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Followup {
+    /// The reflected point replaces the vertex.
+    Reflection,
+    /// The expanded point, or the reflected one if it does not improve
+    /// on it, replaces the vertex.
+    Expansion,
+    /// The vertex moved towards the centroid replaces it, if it improves
+    /// on it.
+    Contraction,
+    /// The reflected point moved towards the centroid replaces the
+    /// vertex, if it improves on the reflected point.
+    OutsideContraction,
+}
 
-        // contraction
-        let xc = &x0 + rho*(&x0 - &(res[last_idx].0));
-        let cscore = obj_fn(&xc);
-        if cscore < res[last_idx].1 {
-            res.remove(last_idx);
-            res.push((xc, cscore));
-            continue;
-        }
+/// Lowest of the scores of a batch of trial points, which is not empty.
+pub(crate) fn lowest<T: RealField + Copy>(scores: &[T]) -> T {
+    scores.iter().fold(scores[0], |min, score| min.min(*score))
+}
 
-        // reduction
-        let x1 = res[0].0.clone();
-        let mut nres: Vec<(DVector<f64>, f64)> = vec![];
-        for tup in res.iter() {
-            let redx = &x1 + sigma*(&tup.0 - &x1);
-            let score = obj_fn(&redx);
-            nres.push((redx, score)); 
-        }
-            
-        res = nres
+/// Updates the sum of the vertices of a simplex once the vertex `old` is
+/// replaced with `new`.
+pub(crate) fn replace_in_sum<T: RealField + Copy>(sum: &mut DVector<T>, old: &[T], new: &[T]) {
+    for ((s, o), x) in sum.iter_mut().zip(old.iter()).zip(new.iter()) {
+        *s += *x - *o;
     }
 }
 
-fn _print_points(rvec: &Vec<(DVector<f64>, f64)>) {
-    for v in rvec.iter(){
-        println!("{},{}", v.0[0], v.0[1]);
+/// Moves the last vertex of a simplex ordered from the best one, which
+/// has just replaced the worst vertex, up to its rank. It ranks after the
+/// vertices it ties with, as under a stable sort, and the others keep
+/// their order.
+pub(crate) fn rank_last<V, T: RealField + Copy>(vertices: &mut [(V, T)]) {
+    let Some(((_, score), ordered)) = vertices.split_last() else {
+        return;
+    };
+    let rank = ordered.partition_point(|(_, s)| total_cmp(*s, *score) != Ordering::Greater);
+    vertices[rank..].rotate_right(1);
+}
+
+/// Total order of scores in the manner of [`f64::total_cmp`]: negative
+/// zero comes before positive zero and NaN after every other value, so
+/// sorting never depends on how incomparable scores happen to be laid out.
+pub(crate) fn total_cmp<T: RealField + Copy>(a: T, b: T) -> Ordering {
+    match a.partial_cmp(&b) {
+        Some(Ordering::Equal) => b.is_sign_negative().cmp(&a.is_sign_negative()),
+        Some(ordering) => ordering,
+        None => is_nan(a).cmp(&is_nan(b)),
     }
-}
\ No newline at end of file
+}
+
+/// Whether `x` is NaN, the only value not comparable to itself.
+pub(crate) fn is_nan<T: PartialOrd>(x: T) -> bool {
+    x.partial_cmp(&x).is_none()
+}
@@ -0,0 +1,804 @@
+use std::time::Instant;
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::builder::NelderMeadBuilder;
+use crate::nelder_mead::{lowest, replace_in_sum, ContractionMode, Followup, NelderMead, SimplexMethod};
+use crate::error::NelderMeadError;
+use crate::event::Event;
+use crate::history::{History, HistoryEntry};
+use crate::logging;
+use crate::observer::Move;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::TerminationState;
+
+/// Simplex operation whose candidate points are being evaluated.
+#[derive(Debug, Clone)]
+enum Phase<T> {
+    /// Evaluation of the initial simplex.
+    Init,
+    /// Evaluation of the reflected point, `x0` being the centroid.
+    Reflection { x0: DVector<T> },
+    /// Evaluation of the reflected, expanded and contracted points at
+    /// once, under speculative evaluation.
+    Speculation { x0: DVector<T> },
+    /// Evaluation of the expanded point, once the reflected point `xr`
+    /// scored `rscore`.
+    Expansion { x0: DVector<T>, xr: DVector<T>, rscore: T },
+    /// Evaluation of the contracted point, `reflected` holding the
+    /// reflected point with its score for an outside contraction.
+    Contraction { x0: DVector<T>, reflected: Option<(DVector<T>, T)> },
+    /// Evaluation of the reflected points of the worst vertices, when
+    /// several of them are replaced, `x0` being the centroid of the other
+    /// ones.
+    MultiReflection { x0: DVector<T> },
+    /// Evaluation of the expanded and contracted points of the worst
+    /// vertices whose reflection, in `reflected` with its score, is not
+    /// accepted as is.
+    MultiTrial { x0: DVector<T>, reflected: Vec<(DVector<T>, T)> },
+    /// Evaluation of the reflections of every vertex but the best one
+    /// through the best one, under the multi-directional search.
+    DirectionalReflection,
+    /// Evaluation of the expansions of every vertex but the best one,
+    /// once their reflections, in `reflected` with their scores, improved
+    /// on the best vertex.
+    DirectionalExpansion { reflected: Vec<(DVector<T>, T)> },
+    /// Evaluation of the shrunk vertices, all but the best one, possibly
+    /// only the first ones when the evaluation budget does not allow all
+    /// of them.
+    Shrink,
+    /// Evaluation of the vertices of a rebuilt simplex, all but the best
+    /// one, possibly only the first ones when the evaluation budget does
+    /// not allow all of them.
+    Restart,
+    /// The run is over.
+    Done,
+}
+
+/// Expanded and contracted points evaluated along with the reflected one,
+/// told to the phases asking for them.
+#[derive(Debug, Clone)]
+struct Speculated<T> {
+    expanded: (DVector<T>, T),
+    inside: (DVector<T>, T),
+    outside: Option<(DVector<T>, T)>,
+}
+
+/// Nelder-Mead run where the objective function is evaluated by the
+/// caller.
+///
+/// Created with [`NelderMead::ask_tell`], it hands out the points to
+/// evaluate with [`ask`](NelderMeadAskTell::ask) and takes their
+/// objective values back with [`tell`](NelderMeadAskTell::tell), so the
+/// objective can be computed outside the optimizer: in another process,
+/// on a cluster or interactively. The points of a batch may be told in
+/// any order, and the next batch is ready once all of them were told. An
+/// empty batch means the run is over.
+///
+/// The run follows the same steps as [`NelderMead::minimize`]. The
+/// `max_fun_evals` budget is never exceeded, `max_duration` is checked
+/// before every batch.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// }
+/// let optimizer = NelderMead::builder().build();
+/// let mut ask_tell = optimizer.ask_tell(dvector![0.0, 0.0])?;
+/// loop {
+///     let points = ask_tell.ask();
+///     if points.is_empty() {
+///         break;
+///     }
+///     for x in points {
+///         // e.g. sent to a worker and collected later
+///         let value = f(&x);
+///         ask_tell.tell(&x, value)?;
+///     }
+/// }
+/// let results = ask_tell.result().unwrap();
+///
+/// assert_eq!(results, optimizer.minimize(&f, dvector![0.0, 0.0])?);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct NelderMeadAskTell<T = f64> {
+    config: NelderMead<T>,
+    start: Instant,
+    // scores are always minimized, maximization flips their sign
+    sign: T,
+    // vertices with their scores, ordered from the best one
+    res: Simplex<T>,
+    // the same vertices mapped to the parameters, when they are transformed
+    external: Simplex<T>,
+    // candidate points of the current batch with their scores once told
+    pending: Vec<(DVector<T>, Option<T>)>,
+    phase: Phase<T>,
+    evaluations: u64,
+    iters: u64,
+    restarts: u64,
+    // mean score and squared simplex gradient norm of the previous simplex
+    decrease_reference: Option<(T, T)>,
+    prev_best: T,
+    no_improv: u64,
+    last_move: Option<Move>,
+    termination_reason: Option<TerminationReason>,
+    history: Vec<HistoryEntry<T>>,
+    // running sum of the vertices, `None` until it is recomputed
+    sum: Option<DVector<T>>,
+    // whether vertices were replaced out of order since the last sort
+    unordered: bool,
+    // trial points of the iteration evaluated speculatively
+    speculated: Option<Speculated<T>>,
+}
+
+impl<T: RealField + Copy> NelderMeadAskTell<T> {
+    /// Validates the configuration and asks for the initial simplex.
+    pub(crate) fn new(
+        mut config: NelderMead<T>,
+        simplex: Vec<DVector<T>>,
+    ) -> Result<Self, NelderMeadError> {
+        let dim = simplex.len().saturating_sub(1);
+        if dim == 0 || simplex.iter().any(|x| x.len() != dim) {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        if config.degeneracy_restart.is_some() {
+            config.step.check_dim(dim)?;
+        }
+        config.check_domain(dim)?;
+        if config.replication.is_some() {
+            return Err(NelderMeadError::InvalidReplication);
+        }
+        config.coefficients = config.coefficients_for(dim);
+        config.coefficients.validate()?;
+
+        let sign = config.direction.sign();
+        let simplex = simplex
+            .into_iter()
+            .map(|x| {
+                let x = config.feasible(x);
+                config.check_point(x.as_slice())?;
+                Ok(x)
+            })
+            .collect::<Result<Vec<_>, NelderMeadError>>()?;
+        config.fit_scales(simplex[0].as_slice());
+        let pending = simplex.into_iter().map(|x| (config.internal(x), None)).collect();
+        Ok(NelderMeadAskTell {
+            config,
+            start: Instant::now(),
+            sign,
+            res: Simplex::default(),
+            external: Simplex::default(),
+            pending,
+            phase: Phase::Init,
+            evaluations: 0,
+            iters: 0,
+            restarts: 0,
+            decrease_reference: None,
+            prev_best: T::zero(),
+            no_improv: 0,
+            last_move: None,
+            termination_reason: None,
+            history: Vec::new(),
+            sum: None,
+            unordered: true,
+            speculated: None,
+        })
+    }
+
+    /// Points of the current batch that still have to be evaluated, empty
+    /// once the run is over.
+    pub fn ask(&self) -> Vec<DVector<T>> {
+        self.pending
+            .iter()
+            .filter(|(_, score)| score.is_none())
+            .map(|(x, _)| self.config.external(x))
+            .collect()
+    }
+
+    /// Records the objective value of `x`, one of the points returned by
+    /// [`ask`](Self::ask), to which the penalty of the violated
+    /// constraints is added. Once every point of the batch is told, the
+    /// simplex is updated and the next batch is prepared.
+    ///
+    /// Fails with [`NelderMeadError::UnexpectedPoint`] if `x` was not asked
+    /// for or was already told, and with [`NelderMeadError::NanScore`] if
+    /// `value` is NaN under [`NanHandling::Error`], in which case `x` stays
+    /// pending.
+    ///
+    /// [`NanHandling::Error`]: crate::nelder_mead::NanHandling::Error
+    pub fn tell(&mut self, x: &DVector<T>, value: T) -> Result<(), NelderMeadError> {
+        let score = self.config.nan_handling.score(value, self.sign)? + self.config.penalty_of(x);
+        let config = &self.config;
+        let slot = self.pending
+            .iter_mut()
+            .find(|(xi, score)| {
+                score.is_none()
+                    && if config.transforms() { config.external(xi) == *x } else { xi == x }
+            })
+            .ok_or(NelderMeadError::UnexpectedPoint)?;
+        slot.1 = Some(score);
+        self.evaluations += 1;
+
+        if self.pending.iter().all(|(_, score)| score.is_some()) {
+            let told: Vec<(DVector<T>, T)> = self.pending
+                .drain(..)
+                .map(|(x, score)| (x, score.unwrap_or_else(T::zero)))
+                .collect();
+            self.advance(told);
+        }
+        Ok(())
+    }
+
+    /// Snapshot of the run, `None` until the initial simplex is evaluated.
+    pub fn state(&self) -> Option<TerminationState<'_, T>> {
+        if self.res.is_empty() {
+            return None;
+        }
+        Some(TerminationState {
+            iterations: self.iters,
+            evaluations: self.evaluations,
+            elapsed: self.start.elapsed(),
+            simplex: self.simplex(),
+            sign: self.sign,
+            last_move: self.last_move,
+            budget: self.config.budget(),
+        })
+    }
+
+    /// Why the run stopped, `None` while it is still going.
+    pub fn termination_reason(&self) -> Option<TerminationReason> {
+        self.termination_reason
+    }
+
+    /// Returns the outcome of the run, `None` until the initial simplex is
+    /// evaluated. If the run is not over, the result holds the best point
+    /// found so far and the termination reason is
+    /// [`TerminationReason::StoppedByCaller`].
+    pub fn result(&self) -> Option<OptimizationResult<T>> {
+        if self.res.is_empty() {
+            return None;
+        }
+        let mut simplex = self.res.to_pairs(T::one());
+        self.config.to_external_simplex(&mut simplex);
+        let mut result = OptimizationResult::from_simplex(
+            simplex,
+            self.sign,
+            self.iters,
+            self.evaluations,
+            self.restarts,
+            self.termination_reason.unwrap_or(TerminationReason::StoppedByCaller),
+            self.config.record_history.then(|| History { entries: self.history.clone() }),
+        );
+        result.constraint_violation = self.config.violation(&result.best_point);
+        Some(result)
+    }
+
+    /// Updates the simplex with a fully evaluated batch.
+    fn advance(&mut self, told: Vec<(DVector<T>, T)>) {
+        let last_idx = self.res.len().saturating_sub(1);
+        match std::mem::replace(&mut self.phase, Phase::Done) {
+            Phase::Init => {
+                self.res = Simplex::from_pairs(&told);
+                self.prev_best = self.res.score(0);
+                self.begin_iteration();
+            }
+            Phase::Reflection { x0 } => {
+                let (xr, rscore) = told.into_iter().next().expect("one reflected point");
+                if (self.res.score(0) <= rscore) & (rscore < self.res.score(last_idx-1)) {
+                    self.config.emit(|| Event::Reflection {
+                        centroid: x0,
+                        replaced: self.res.point(last_idx),
+                        point: xr.clone(),
+                        score: self.sign * rscore,
+                    });
+                    self.replace_worst(xr, rscore);
+                    self.last_move = Some(Move::Reflection);
+                    self.begin_iteration();
+                } else if rscore < self.res.score(0) {
+                    // expansion
+                    let xe = &x0 + (&x0 - self.res.column(last_idx)) * self.config.coefficients.gamma;
+                    self.ask_for(Phase::Expansion { x0, xr, rscore }, vec![xe]);
+                } else if self.config.contraction == ContractionMode::InsideOutside
+                    && rscore < self.res.score(last_idx)
+                {
+                    // outside contraction
+                    let xc = &x0 + (&xr - &x0) * self.config.coefficients.rho;
+                    let reflected = Some((xr, rscore));
+                    self.ask_for(Phase::Contraction { x0, reflected }, vec![xc]);
+                } else {
+                    // contraction
+                    let xc = &x0 + (self.res.column(last_idx) - &x0) * self.config.coefficients.rho;
+                    self.ask_for(Phase::Contraction { x0, reflected: None }, vec![xc]);
+                }
+            }
+            Phase::Speculation { x0 } => {
+                let mut told = told.into_iter();
+                let mut next = || told.next().expect("one point per trial");
+                let reflected = next();
+                self.speculated = Some(Speculated {
+                    expanded: next(),
+                    inside: next(),
+                    outside: (self.config.contraction == ContractionMode::InsideOutside).then(next),
+                });
+                self.phase = Phase::Reflection { x0 };
+                self.advance(vec![reflected]);
+                self.speculated = None;
+            }
+            Phase::Expansion { x0, xr, rscore } => {
+                let (xe, escore) = told.into_iter().next().expect("one expanded point");
+                if self.config.accept_expansion(escore, rscore, self.res.score(0)) {
+                    self.config.emit(|| Event::Expansion {
+                        centroid: x0,
+                        replaced: self.res.point(last_idx),
+                        reflected: xr,
+                        point: xe.clone(),
+                        score: self.sign * escore,
+                    });
+                    self.replace_worst(xe, escore);
+                    self.last_move = Some(Move::Expansion);
+                } else {
+                    self.config.emit(|| Event::Reflection {
+                        centroid: x0,
+                        replaced: self.res.point(last_idx),
+                        point: xr.clone(),
+                        score: self.sign * rscore,
+                    });
+                    self.replace_worst(xr, rscore);
+                    self.last_move = Some(Move::Reflection);
+                }
+                self.begin_iteration();
+            }
+            Phase::Contraction { x0, reflected } => {
+                let (xc, cscore) = told.into_iter().next().expect("one contracted point");
+                match reflected {
+                    Some((xr, rscore)) if cscore <= rscore => {
+                        self.config.emit(|| Event::OutsideContraction {
+                            centroid: x0,
+                            replaced: self.res.point(last_idx),
+                            reflected: xr,
+                            point: xc.clone(),
+                            score: self.sign * cscore,
+                        });
+                        self.replace_worst(xc, cscore);
+                        self.last_move = Some(Move::OutsideContraction);
+                        self.begin_iteration();
+                        return;
+                    }
+                    None if cscore < self.res.score(last_idx) => {
+                        self.config.emit(|| Event::Contraction {
+                            centroid: x0,
+                            replaced: self.res.point(last_idx),
+                            point: xc.clone(),
+                            score: self.sign * cscore,
+                        });
+                        self.replace_worst(xc, cscore);
+                        self.last_move = Some(Move::Contraction);
+                        self.begin_iteration();
+                        return;
+                    }
+                    _ => {}
+                }
+
+                self.shrink();
+            }
+            Phase::MultiReflection { x0 } => {
+                let kept = self.res.len() - told.len();
+                let coefficients = self.config.coefficients;
+                let (best, kept_worst) = (self.res.score(0), self.res.score(kept-1));
+                let mut trials = Vec::new();
+                for (i, (xr, rscore)) in told.iter().enumerate() {
+                    let (x, score) = (self.res.column(kept + i), self.res.score(kept + i));
+                    match self.config.followup(score, *rscore, best, kept_worst) {
+                        Followup::Reflection => {}
+                        Followup::Expansion => trials.push(&x0 + (&x0 - x) * coefficients.gamma),
+                        Followup::Contraction => trials.push(&x0 + (x - &x0) * coefficients.rho),
+                        Followup::OutsideContraction => trials.push(&x0 + (xr - &x0) * coefficients.rho),
+                    }
+                }
+                let phase = Phase::MultiTrial { x0, reflected: told };
+                if trials.is_empty() {
+                    self.phase = phase;
+                    return self.advance(Vec::new());
+                }
+                if self.within_budget(trials.len()) < trials.len() {
+                    return self.finish(TerminationReason::MaxFunEvals);
+                }
+                self.ask_for(phase, trials);
+            }
+            Phase::MultiTrial { x0, reflected } => {
+                // the vertices that are kept are not touched, so the
+                // decisions do not depend on each other
+                let kept = self.res.len() - reflected.len();
+                let (best, kept_worst) = (self.res.score(0), self.res.score(kept-1));
+                let mut trials = told.into_iter();
+                let mut next = || trials.next().expect("one trial point each");
+                let mut improved = false;
+                for (i, (xr, rscore)) in reflected.into_iter().enumerate() {
+                    let j = kept + i;
+                    let vertex = match self.config.followup(self.res.score(j), rscore, best, kept_worst) {
+                        Followup::Reflection => {
+                            self.config.emit(|| Event::Reflection {
+                                centroid: x0.clone(),
+                                replaced: self.res.point(j),
+                                point: xr.clone(),
+                                score: self.sign * rscore,
+                            });
+                            (xr, rscore)
+                        }
+                        Followup::Expansion => {
+                            let (xe, escore) = next();
+                            if self.config.accept_expansion(escore, rscore, best) {
+                                self.config.emit(|| Event::Expansion {
+                                    centroid: x0.clone(),
+                                    replaced: self.res.point(j),
+                                    reflected: xr,
+                                    point: xe.clone(),
+                                    score: self.sign * escore,
+                                });
+                                (xe, escore)
+                            } else {
+                                self.config.emit(|| Event::Reflection {
+                                    centroid: x0.clone(),
+                                    replaced: self.res.point(j),
+                                    point: xr.clone(),
+                                    score: self.sign * rscore,
+                                });
+                                (xr, rscore)
+                            }
+                        }
+                        Followup::Contraction => {
+                            let (xc, cscore) = next();
+                            if cscore >= self.res.score(j) {
+                                continue;
+                            }
+                            self.config.emit(|| Event::Contraction {
+                                centroid: x0.clone(),
+                                replaced: self.res.point(j),
+                                point: xc.clone(),
+                                score: self.sign * cscore,
+                            });
+                            (xc, cscore)
+                        }
+                        Followup::OutsideContraction => {
+                            let (xc, cscore) = next();
+                            if cscore > rscore {
+                                continue;
+                            }
+                            self.config.emit(|| Event::OutsideContraction {
+                                centroid: x0.clone(),
+                                replaced: self.res.point(j),
+                                reflected: xr,
+                                point: xc.clone(),
+                                score: self.sign * cscore,
+                            });
+                            (xc, cscore)
+                        }
+                    };
+                    self.res.set(j, vertex.0.as_slice(), vertex.1);
+                    improved = true;
+                }
+                if !improved {
+                    return self.shrink();
+                }
+                self.sum = None;
+                self.unordered = true;
+                self.last_move = Some(Move::MultiPoint);
+                self.begin_iteration();
+            }
+            Phase::DirectionalReflection => {
+                let scores: Vec<T> = told.iter().map(|(_, score)| *score).collect();
+                if lowest(&scores) >= self.res.score(0) {
+                    return self.shrink();
+                }
+                // expansions, only asked for if the budget allows all of them
+                let count = told.len();
+                if self.within_budget(count) < count {
+                    return self.finish(TerminationReason::MaxFunEvals);
+                }
+                let best = self.res.point(0);
+                let gamma = self.config.coefficients.gamma;
+                let expanded = (1..=count).map(|j| &best + (&best - self.res.column(j)) * gamma).collect();
+                self.ask_for(Phase::DirectionalExpansion { reflected: told }, expanded);
+            }
+            Phase::DirectionalExpansion { reflected } => {
+                let rscores: Vec<T> = reflected.iter().map(|(_, score)| *score).collect();
+                let escores: Vec<T> = told.iter().map(|(_, score)| *score).collect();
+                let expand = self.config.accept_expansion(lowest(&escores), lowest(&rscores), self.res.score(0));
+                let best = self.res.point(0);
+                for (i, ((xr, rscore), (xe, escore))) in reflected.into_iter().zip(told).enumerate() {
+                    let j = i + 1;
+                    if expand {
+                        self.config.emit(|| Event::Expansion {
+                            centroid: best.clone(),
+                            replaced: self.res.point(j),
+                            reflected: xr,
+                            point: xe.clone(),
+                            score: self.sign * escore,
+                        });
+                        self.res.set(j, xe.as_slice(), escore);
+                    } else {
+                        self.config.emit(|| Event::Reflection {
+                            centroid: best.clone(),
+                            replaced: self.res.point(j),
+                            point: xr.clone(),
+                            score: self.sign * rscore,
+                        });
+                        self.res.set(j, xr.as_slice(), rscore);
+                    }
+                }
+                self.sum = None;
+                self.unordered = true;
+                self.last_move = Some(if expand { Move::Expansion } else { Move::Reflection });
+                self.begin_iteration();
+            }
+            Phase::Shrink => {
+                self.sum = None;
+                self.unordered = true;
+                let complete = told.len() == self.res.len() - 1;
+                let best = self.res.point(0);
+                for (i, (x, score)) in told.iter().enumerate() {
+                    self.res.set(i + 1, x.as_slice(), *score);
+                }
+                if complete {
+                    self.config.emit(|| Event::Shrink {
+                        best,
+                        vertices: self.res.to_pairs(self.sign),
+                    });
+                    self.last_move = Some(Move::Shrink);
+                    self.begin_iteration();
+                } else {
+                    self.sort();
+                    self.finish(TerminationReason::MaxFunEvals);
+                }
+            }
+            Phase::Restart => {
+                self.sum = None;
+                self.unordered = true;
+                let complete = told.len() == self.res.len() - 1;
+                for (i, (x, score)) in told.iter().enumerate() {
+                    self.res.set(i + 1, x.as_slice(), *score);
+                }
+                if complete {
+                    self.restarts += 1;
+                    self.no_improv = 0;
+                    self.config.emit(|| Event::Restart {
+                        vertices: self.res.to_pairs(self.sign),
+                    });
+                    self.last_move = Some(Move::Restart);
+                    self.begin_iteration();
+                } else {
+                    self.sort();
+                    self.finish(TerminationReason::MaxFunEvals);
+                }
+            }
+            Phase::Done => {}
+        }
+    }
+
+    /// Orders the simplex, checks the stopping rules and asks for the
+    /// reflected point of the next iteration.
+    fn begin_iteration(&mut self) {
+        self.sort();
+        let best = self.res.score(0);
+        if self.config.record_history {
+            let state = self.state().expect("initial simplex is evaluated");
+            let entry = HistoryEntry::new(&state, self.config.memory_efficient);
+            self.history.push(entry);
+        }
+        let state = self.state().expect("initial simplex is evaluated");
+        if let Some(reason) = self.config.check_stop(&state) {
+            return self.finish(reason);
+        }
+
+        // break after max_iter
+        if self.iters >= self.config.max_iter {
+            return self.finish(TerminationReason::MaxIter);
+        }
+        self.iters += 1;
+
+        // break after no_improv_break iterations with no improvement
+        if self.config.improved(best, self.prev_best) {
+            self.no_improv = 0;
+            self.prev_best = best;
+        } else {
+            self.no_improv += 1;
+        }
+
+        // restart once the simplex has collapsed or stagnates, limited to
+        // the remaining evaluation budget
+        let restart = self.config.restart(&self.res, self.restarts, &mut self.decrease_reference);
+        if let Some(mut rebuilt) = restart {
+            rebuilt.truncate(self.within_budget(rebuilt.len()));
+            return self.ask_for(Phase::Restart, rebuilt);
+        }
+
+        if self.config.no_improv_break.is_some_and(|limit| self.no_improv >= limit) {
+            return self.finish(TerminationReason::NoImprovement);
+        }
+
+        if self.config.method == SimplexMethod::MultiDirectional {
+            // reflections of every vertex but the best one through it, only
+            // asked for if the budget allows all of them
+            let count = self.res.len() - 1;
+            if self.within_budget(count) < count {
+                return self.finish(TerminationReason::MaxFunEvals);
+            }
+            let best = self.res.point(0);
+            let alpha = self.config.coefficients.alpha;
+            let reflected = (1..=count).map(|j| &best + (&best - self.res.column(j)) * alpha).collect();
+            return self.ask_for(Phase::DirectionalReflection, reflected);
+        }
+
+        let p = self.config.parallel_points;
+        if p > 1 {
+            // reflections of the worst vertices through the centroid of the
+            // other ones, only asked for if the budget allows all of them
+            let kept = self.res.len() - p;
+            let n: T = convert(kept as f64);
+            let x0 = self.res.partial_sum(kept) / n;
+            if self.within_budget(p) < p {
+                return self.finish(TerminationReason::MaxFunEvals);
+            }
+            let alpha = self.config.coefficients.alpha;
+            let reflected = (kept..self.res.len()).map(|j| &x0 + (&x0 - self.res.column(j)) * alpha).collect();
+            return self.ask_for(Phase::MultiReflection { x0 }, reflected);
+        }
+
+        let last_idx = self.res.len()-1;
+
+        // centroid, from the running sum of the vertices, which is also
+        // recomputed every n + 1 iterations so that its rounding errors do
+        // not outgrow a shrinking simplex
+        if self.iters.is_multiple_of(self.res.len() as u64) {
+            self.sum = None;
+        }
+        let sum = self.sum.get_or_insert_with(|| self.res.sum());
+        let n: T = convert(last_idx as f64);
+        let x0 = (&*sum - self.res.column(last_idx)) / n;
+
+        // reflection
+        let xr = &x0 + (&x0 - self.res.column(last_idx)) * self.config.coefficients.alpha;
+
+        // every trial point the iteration may need, asked for at once when
+        // speculative, as long as the budget allows all of them
+        let outside_mode = self.config.contraction == ContractionMode::InsideOutside;
+        let count = if outside_mode { 4 } else { 3 };
+        if self.config.speculative && self.within_budget(count) == count {
+            let coefficients = self.config.coefficients;
+            let worst = self.res.column(last_idx);
+            let xe = &x0 + (&x0 - worst) * coefficients.gamma;
+            let xc = &x0 + (worst - &x0) * coefficients.rho;
+            let mut points = vec![self.config.bounded(xr), xe, xc];
+            if outside_mode {
+                points.push(&x0 + (&points[0] - &x0) * coefficients.rho);
+            }
+            return self.ask_for(Phase::Speculation { x0 }, points);
+        }
+        self.ask_for(Phase::Reflection { x0 }, vec![xr]);
+    }
+
+    /// Asks for the vertices of the simplex shrunk towards the best one,
+    /// limited to the remaining evaluation budget.
+    fn shrink(&mut self) {
+        let x1 = self.res.point(0);
+        let count = self.within_budget(self.res.len() - 1);
+        let shrunk = (1..=count)
+            .map(|i| &x1 + (self.res.column(i) - &x1) * self.config.coefficients.sigma)
+            .collect();
+        self.ask_for(Phase::Shrink, shrunk);
+    }
+
+    /// Number of the `count` evaluations the remaining budget allows.
+    fn within_budget(&self, count: usize) -> usize {
+        let remaining = self.config.max_fun_evals
+            .map_or(u64::MAX, |limit| limit.saturating_sub(self.evaluations));
+        count.min(remaining.try_into().unwrap_or(usize::MAX))
+    }
+
+    /// Starts a new batch, unless the evaluation or time budget is spent.
+    /// A point evaluated speculatively is told right away instead.
+    fn ask_for(&mut self, phase: Phase<T>, points: Vec<DVector<T>>) {
+        if let Some(speculated) = self.speculated.take() {
+            let told = match &phase {
+                Phase::Expansion { .. } => Some(speculated.expanded),
+                Phase::Contraction { reflected: None, .. } => Some(speculated.inside),
+                Phase::Contraction { reflected: Some(_), .. } => speculated.outside,
+                _ => None,
+            };
+            if let Some(told) = told {
+                self.phase = phase;
+                return self.advance(vec![told]);
+            }
+        }
+        if self.config.max_fun_evals.is_some_and(|limit| self.evaluations >= limit) {
+            return self.finish(TerminationReason::MaxFunEvals);
+        }
+        if self.config.max_duration.is_some_and(|limit| self.start.elapsed() >= limit) {
+            return self.finish(TerminationReason::TimedOut);
+        }
+        self.phase = phase;
+        self.pending = points.into_iter().map(|x| (self.config.bounded(x), None)).collect();
+    }
+
+    /// Replaces the worst vertex with the accepted point `x` and moves it
+    /// to its rank.
+    fn replace_worst(&mut self, x: DVector<T>, score: T) {
+        let last_idx = self.res.len() - 1;
+        if let Some(sum) = &mut self.sum {
+            replace_in_sum(sum, self.res.vertex(last_idx), x.as_slice());
+        }
+        self.res.set(last_idx, x.as_slice(), score);
+        self.res.rank_last();
+    }
+
+    fn finish(&mut self, reason: TerminationReason) {
+        self.phase = Phase::Done;
+        self.pending.clear();
+        self.termination_reason = Some(reason);
+        let best_score = self.sign * self.res.score(0);
+        logging::log_termination(reason, self.iters, self.evaluations, best_score);
+    }
+
+    /// Orders the vertices from the best one. The sort is stable, so
+    /// vertices with equal scores keep their order and a new vertex ranks
+    /// after the ones it ties with, which keeps reruns reproducible. A
+    /// single replaced vertex is already moved to its rank, so the full
+    /// sort only follows the steps replacing several vertices.
+    fn sort(&mut self) {
+        if self.unordered {
+            self.res.sort();
+            self.unordered = false;
+        }
+        if self.config.recenter_simplex(&mut self.res) {
+            self.sum = None;
+        }
+        if self.config.transforms() {
+            self.config.copy_to_external(&self.res, &mut self.external);
+        }
+    }
+
+    /// Vertices of the simplex with their scores, mapped to the
+    /// parameters.
+    fn simplex(&self) -> &Simplex<T> {
+        if self.config.transforms() {
+            &self.external
+        } else {
+            &self.res
+        }
+    }
+}
+
+impl<T: RealField + Copy> NelderMead<T> {
+    /// Starts a run around `x_start` whose objective function is evaluated
+    /// by the caller, see [`NelderMeadAskTell`].
+    pub fn ask_tell(&self, x_start: DVector<T>) -> Result<NelderMeadAskTell<T>, NelderMeadError> {
+        let simplex = self.initial_simplex(x_start)?;
+        self.ask_tell_from_simplex(simplex)
+    }
+
+    /// Same as [`NelderMead::ask_tell`], but starts from a complete
+    /// simplex, see [`NelderMead::minimize_from_simplex`].
+    pub fn ask_tell_from_simplex(
+        &self,
+        simplex: Vec<DVector<T>>,
+    ) -> Result<NelderMeadAskTell<T>, NelderMeadError> {
+        NelderMeadAskTell::new(self.clone(), simplex)
+    }
+}
+
+impl<T: RealField + Copy> NelderMeadBuilder<T> {
+    /// Finishes the configuration and starts an ask-and-tell run, see
+    /// [`NelderMead::ask_tell`].
+    pub fn ask_tell(self, x_start: DVector<T>) -> Result<NelderMeadAskTell<T>, NelderMeadError> {
+        let config = self.build();
+        let simplex = config.initial_simplex(x_start)?;
+        NelderMeadAskTell::new(config, simplex)
+    }
+}
@@ -0,0 +1,1250 @@
+use std::ops::ControlFlow;
+use std::time::Instant;
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::builder::NelderMeadBuilder;
+use crate::cache::EvaluationCache;
+use crate::checkpoint::Checkpoint;
+use crate::nelder_mead::{lowest, replace_in_sum, ContractionMode, Followup, NelderMead, SimplexMethod};
+use crate::error::NelderMeadError;
+use crate::event::Event;
+use crate::history::{History, HistoryEntry};
+use crate::logging;
+use crate::objective::Evaluator;
+use crate::observer::Move;
+use crate::random::{split_mix, Rng};
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::TerminationState;
+use crate::timing::{time, Timer};
+
+/// Outcome of a single iteration, yielded by [`NelderMeadIter`].
+///
+/// Scores are reported in the direction of the objective, i.e. they are
+/// not negated when maximizing.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct IterationState<T = f64> {
+    /// Number of iterations completed so far, including this one.
+    pub iterations: u64,
+    /// Number of objective function evaluations so far.
+    pub evaluations: u64,
+    /// Best vertex of the simplex after this iteration.
+    pub best_point: DVector<T>,
+    /// Score of `best_point`.
+    pub best_score: T,
+    /// Operation performed by this iteration.
+    pub last_move: Move,
+}
+
+/// Nelder-Mead run driven one iteration at a time.
+///
+/// Created with [`NelderMead::iter`], it yields an [`IterationState`]
+/// for every iteration until one of the configured stopping rules is met,
+/// or an error if the objective function fails. The caller owns the whole
+/// state of the run, so it may interleave other work between iterations,
+/// inspect the simplex with [`NelderMeadIter::state`] or stop at any
+/// point and collect the best point found so far with
+/// [`NelderMeadIter::into_result`].
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::result::TerminationReason;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// }
+/// let optimizer = NelderMead::builder().build();
+/// let mut iter = optimizer.iter(&f, dvector![0.0, 0.0])?;
+/// for state in iter.by_ref().take(20) {
+///     let state = state?;
+///     assert!(state.best_score <= f(&dvector![0.0, 0.0]));
+/// }
+/// let results = iter.into_result();
+///
+/// assert_eq!(results.iterations, 20);
+/// assert_eq!(results.termination_reason, TerminationReason::StoppedByCaller);
+///
+/// // running the iterator to the end is the same as calling `minimize`
+/// let mut iter = optimizer.iter(&f, dvector![0.0, 0.0])?;
+/// for state in iter.by_ref() {
+///     state?;
+/// }
+/// assert_eq!(iter.into_result(), optimizer.minimize(&f, dvector![0.0, 0.0])?);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+pub struct NelderMeadIter<T, F> {
+    config: NelderMead<T>,
+    obj_fn: F,
+    start: Instant,
+    // scores are always minimized, maximization flips their sign
+    sign: T,
+    // vertices with their scores, ordered from the best one
+    res: Simplex<T>,
+    // the same vertices mapped to the parameters, when they are transformed
+    external: Simplex<T>,
+    evaluations: u64,
+    iters: u64,
+    restarts: u64,
+    // mean score and squared simplex gradient norm of the previous simplex
+    decrease_reference: Option<(T, T)>,
+    prev_best: T,
+    no_improv: u64,
+    last_move: Option<Move>,
+    termination_reason: Option<TerminationReason>,
+    history: Vec<HistoryEntry<T>>,
+    failed: bool,
+    // running sum of the vertices, `None` until it is recomputed
+    sum: Option<DVector<T>>,
+    // whether vertices were replaced out of order since the last sort
+    unordered: bool,
+    // scratch vectors of the iteration, reused so that it does not allocate
+    buffers: Buffers<T>,
+    // point passed to the objective, when the vertices are transformed
+    point: DVector<T>,
+    // objective values of the points evaluated so far, when enabled
+    cache: Option<EvaluationCache<T>>,
+    // durations of the objective calls and the iterations, when enabled
+    timer: Option<Timer>,
+}
+
+/// Centroid, trial points and shrunk vertices of an iteration. An
+/// accepted point is copied into the column of the vertex it replaces.
+struct Buffers<T> {
+    centroid: DVector<T>,
+    reflected: DVector<T>,
+    trial: DVector<T>,
+    shrunk: Vec<DVector<T>>,
+    // reflected, expanded, inside and outside contracted points, when
+    // they are evaluated speculatively, or the reflected points of the
+    // worst vertices, when several of them are replaced, or of all of
+    // them under the multi-directional search
+    candidates: Vec<DVector<T>>,
+    // expanded and contracted points of the worst vertices, when several
+    // of them are replaced, or the expanded points of all of them under
+    // the multi-directional search
+    trials: Vec<DVector<T>>,
+}
+
+impl<T: RealField + Copy> Buffers<T> {
+    /// Buffers of a simplex of dimension `dim`, with a single shrunk
+    /// vertex when `memory_efficient`.
+    fn new(dim: usize, memory_efficient: bool) -> Self {
+        Buffers {
+            centroid: DVector::zeros(dim),
+            reflected: DVector::zeros(dim),
+            trial: DVector::zeros(dim),
+            shrunk: vec![DVector::zeros(dim); if memory_efficient { dim.min(1) } else { dim }],
+            candidates: Vec::new(),
+            trials: Vec::new(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> Default for Buffers<T> {
+    fn default() -> Self {
+        Buffers::new(0, false)
+    }
+}
+
+/// Writes `origin + (origin - point) * t` to `out`, moving away from
+/// `point` through `origin`.
+fn beyond<T: RealField + Copy>(out: &mut DVector<T>, origin: &[T], point: &[T], t: T) {
+    for ((o, x0), w) in out.iter_mut().zip(origin.iter()).zip(point.iter()) {
+        *o = *x0 + (*x0 - *w) * t;
+    }
+}
+
+/// Writes `origin + (point - origin) * t` to `out`, moving from `origin`
+/// towards `point`.
+fn towards<T: RealField + Copy>(out: &mut DVector<T>, origin: &[T], point: &[T], t: T) {
+    for ((o, x0), w) in out.iter_mut().zip(origin.iter()).zip(point.iter()) {
+        *o = *x0 + (*w - *x0) * t;
+    }
+}
+
+impl<T, F> NelderMeadIter<T, F>
+where
+    T: RealField + Copy,
+    F: Evaluator<T>,
+{
+    /// Validates the configuration and evaluates the initial simplex.
+    pub(crate) fn new(
+        config: NelderMead<T>,
+        obj_fn: F,
+        simplex: Vec<DVector<T>>,
+    ) -> Result<Self, NelderMeadError> {
+        let cache = config.cache_resolution.map(EvaluationCache::new);
+        NelderMeadIter::with_cache(config, obj_fn, simplex, cache)
+    }
+
+    /// Same as [`NelderMeadIter::new`], looking points up in `cache`, that
+    /// of an earlier run on the same objective.
+    pub(crate) fn with_cache(
+        mut config: NelderMead<T>,
+        obj_fn: F,
+        simplex: Vec<DVector<T>>,
+        cache: Option<EvaluationCache<T>>,
+    ) -> Result<Self, NelderMeadError> {
+        let dim = simplex.len().saturating_sub(1);
+        if dim == 0 || simplex.iter().any(|x| x.len() != dim) {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        if config.degeneracy_restart.is_some() {
+            config.step.check_dim(dim)?;
+        }
+        config.check_domain(dim)?;
+        if config.replication.is_some() {
+            return Err(NelderMeadError::InvalidReplication);
+        }
+        config.coefficients = config.coefficients_for(dim);
+        config.coefficients.validate()?;
+        let simplex = simplex
+            .into_iter()
+            .map(|x| {
+                let x = config.feasible(x);
+                config.check_point(x.as_slice())?;
+                Ok(x)
+            })
+            .collect::<Result<Vec<_>, NelderMeadError>>()?;
+        config.fit_scales(simplex[0].as_slice());
+
+        let sign = config.direction.sign();
+        let memory_efficient = config.memory_efficient;
+        let timer = config.record_timing.then(Timer::new);
+        let mut iter = NelderMeadIter {
+            config,
+            obj_fn,
+            start: Instant::now(),
+            sign,
+            res: Simplex::default(),
+            external: Simplex::default(),
+            evaluations: 0,
+            iters: 0,
+            restarts: 0,
+            decrease_reference: None,
+            prev_best: T::zero(),
+            no_improv: 0,
+            last_move: None,
+            termination_reason: None,
+            history: Vec::new(),
+            failed: false,
+            sum: None,
+            unordered: true,
+            buffers: Buffers::new(dim, memory_efficient),
+            point: DVector::zeros(dim),
+            cache,
+            timer,
+        };
+
+        // init, the vertices are independent so they are evaluated at once
+        let vertices: Vec<DVector<T>> = simplex.into_iter().map(|x| iter.config.internal(x)).collect();
+        let scores = iter.evaluate_all(&vertices)?;
+        iter.res = Simplex::from_slices(vertices.iter().map(|x| x.as_slice()).zip(scores));
+        iter.prev_best = iter.res.score(0);
+        iter.sort();
+        Ok(iter)
+    }
+
+    /// Continues the run saved in `checkpoint`.
+    pub(crate) fn resume(
+        mut config: NelderMead<T>,
+        obj_fn: F,
+        checkpoint: Checkpoint<T>,
+    ) -> Result<Self, NelderMeadError> {
+        let dim = checkpoint.simplex.len().saturating_sub(1);
+        if dim == 0 || checkpoint.simplex.iter().any(|(x, _)| x.len() != dim) {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        if config.degeneracy_restart.is_some() {
+            config.step.check_dim(dim)?;
+        }
+        config.check_domain(dim)?;
+        if config.replication.is_some() {
+            return Err(NelderMeadError::InvalidReplication);
+        }
+        config.coefficients = config.coefficients_for(dim);
+        config.coefficients.validate()?;
+        config.scales = checkpoint.scales;
+
+        let sign = config.direction.sign();
+        let memory_efficient = config.memory_efficient;
+        let timer = config.record_timing.then(Timer::new);
+        let cache = config.cache_resolution.map(EvaluationCache::new);
+        let now = Instant::now();
+        let mut iter = NelderMeadIter {
+            config,
+            obj_fn,
+            start: now.checked_sub(checkpoint.elapsed).unwrap_or(now),
+            sign,
+            res: Simplex::from_slices(
+                checkpoint.simplex.iter().map(|(x, score)| (x.as_slice(), sign * *score)),
+            ),
+            external: Simplex::default(),
+            evaluations: checkpoint.evaluations,
+            iters: checkpoint.iterations,
+            restarts: checkpoint.restarts,
+            decrease_reference: checkpoint.decrease_reference
+                .map(|(mean, norm)| (sign * mean, norm)),
+            prev_best: sign * checkpoint.prev_best,
+            no_improv: checkpoint.no_improv,
+            last_move: checkpoint.last_move,
+            termination_reason: checkpoint.termination_reason,
+            history: checkpoint.history,
+            failed: false,
+            sum: checkpoint.vertex_sum,
+            unordered: true,
+            buffers: Buffers::new(dim, memory_efficient),
+            point: DVector::zeros(dim),
+            cache,
+            timer,
+        };
+        iter.sort();
+        Ok(iter)
+    }
+
+    /// Complete state of the run, from which it can be continued with
+    /// [`NelderMead::resume_from_checkpoint`].
+    pub fn checkpoint(&self) -> Checkpoint<T> {
+        Checkpoint {
+            simplex: self.res.to_pairs(self.sign),
+            iterations: self.iters,
+            evaluations: self.evaluations,
+            restarts: self.restarts,
+            decrease_reference: self.decrease_reference
+                .map(|(mean, norm)| (self.sign * mean, norm)),
+            elapsed: self.start.elapsed(),
+            prev_best: self.sign * self.prev_best,
+            no_improv: self.no_improv,
+            last_move: self.last_move,
+            termination_reason: self.termination_reason,
+            history: self.history.clone(),
+            scales: self.config.scales.clone(),
+            vertex_sum: self.sum.clone(),
+        }
+    }
+
+    /// Runs the remaining iterations and returns the outcome of the run.
+    pub fn run(mut self) -> Result<OptimizationResult<T>, NelderMeadError> {
+        while self.step()?.is_some() {}
+        Ok(self.into_result())
+    }
+
+    /// Same as [`NelderMeadIter::run`], also returning the cache for a
+    /// later run on the same objective.
+    pub(crate) fn run_with_cache(
+        mut self,
+    ) -> Result<(OptimizationResult<T>, Option<EvaluationCache<T>>), NelderMeadError> {
+        while self.step()?.is_some() {}
+        let cache = self.cache.take();
+        let mut result = self.into_result();
+        result.cache = cache.as_ref().map(|cache| cache.stats);
+        Ok((result, cache))
+    }
+
+    /// Snapshot of the run, with the simplex ordered from the best vertex.
+    pub fn state(&self) -> TerminationState<'_, T> {
+        TerminationState {
+            iterations: self.iters,
+            evaluations: self.evaluations,
+            elapsed: self.start.elapsed(),
+            simplex: self.simplex(),
+            sign: self.sign,
+            last_move: self.last_move,
+            budget: self.config.budget(),
+        }
+    }
+
+    /// Why the run stopped, `None` while it is still going.
+    pub fn termination_reason(&self) -> Option<TerminationReason> {
+        self.termination_reason
+    }
+
+    /// Returns the outcome of the run. If the iterator was not run to the
+    /// end, the result holds the best point found so far and the
+    /// termination reason is [`TerminationReason::StoppedByCaller`].
+    pub fn into_result(self) -> OptimizationResult<T> {
+        let mut simplex = self.res.to_pairs(T::one());
+        self.config.to_external_simplex(&mut simplex);
+        let mut result = OptimizationResult::from_simplex(
+            simplex,
+            self.sign,
+            self.iters,
+            self.evaluations,
+            self.restarts,
+            self.termination_reason.unwrap_or(TerminationReason::StoppedByCaller),
+            self.config.record_history.then_some(History { entries: self.history }),
+        );
+        result.constraint_violation = self.config.violation(&result.best_point);
+        result.cache = self.cache.map(|cache| cache.stats);
+        result.timing = self.timer.map(|timer| timer.stats());
+        result
+    }
+
+    /// Performs one iteration, returning the operation applied to the
+    /// simplex or `None` once the run is over.
+    pub(crate) fn step(&mut self) -> Result<Option<Move>, NelderMeadError> {
+        if self.failed || self.termination_reason.is_some() {
+            return Ok(None);
+        }
+        let iters = self.iters;
+        if let Some(timer) = &self.timer {
+            timer.start_iteration();
+        }
+        let flow = match self.advance() {
+            Ok(flow) => flow,
+            Err(err) => {
+                self.failed = true;
+                return Err(err);
+            }
+        };
+        // a shrink interrupted by the budget leaves the simplex unordered
+        self.sort();
+        if let Some(timer) = self.timer.as_ref().filter(|_| self.iters > iters) {
+            timer.end_iteration();
+        }
+        match flow {
+            ControlFlow::Continue(last_move) => {
+                self.last_move = Some(last_move);
+                if let Some(saver) = &self.config.checkpoint {
+                    if self.iters.is_multiple_of(saver.every) {
+                        if let Err(err) = (saver.save)(&self.checkpoint()) {
+                            self.failed = true;
+                            return Err(err);
+                        }
+                    }
+                }
+                Ok(Some(last_move))
+            }
+            ControlFlow::Break(reason) => {
+                self.termination_reason = Some(reason);
+                let best_score = self.sign * self.res.score(0);
+                logging::log_termination(reason, self.iters, self.evaluations, best_score);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Body of the simplex iteration: checks the stopping rules and moves
+    /// the simplex.
+    fn advance(&mut self) -> Result<ControlFlow<TerminationReason, Move>, NelderMeadError> {
+        // evaluate the best vertex again every so many iterations
+        let reevaluated = self.config.reevaluation.is_some_and(|every| {
+            every > 0 && self.iters > 0 && self.iters.is_multiple_of(every)
+        });
+        if reevaluated {
+            self.reevaluate_best()?;
+        }
+        if self.config.record_history {
+            let mut entry = HistoryEntry::new(&self.state(), self.config.memory_efficient);
+            entry.standard_error = self.obj_fn.standard_error(&self.config.external(&self.res.point(0)));
+            self.history.push(entry);
+        }
+        // with the noise-aware test, whether the vertices are within the
+        // noise of the objective, `None` when their noise is unknown
+        let indistinct = self
+            .config
+            .noise_test
+            .and_then(|critical| self.spread_significance().map(|t| t < critical));
+        if let Some(reason) = self.config.check_stop(&self.state()) {
+            let converged = matches!(
+                reason,
+                TerminationReason::Converged | TerminationReason::TargetReached | TerminationReason::Criterion
+            );
+            let premature = reason == TerminationReason::Converged && indistinct == Some(false);
+            if !premature && (!converged || self.stop_confirmed(reevaluated)?) {
+                return Ok(ControlFlow::Break(reason));
+            }
+        }
+        if indistinct == Some(true) && self.stop_confirmed(reevaluated)? {
+            return Ok(ControlFlow::Break(TerminationReason::Converged));
+        }
+        let best = self.res.score(0);
+
+        // break after max_iter
+        if self.iters >= self.config.max_iter {
+            return Ok(ControlFlow::Break(TerminationReason::MaxIter));
+        }
+        self.iters += 1;
+
+        // break after no_improv_break iterations with no improvement
+        if self.config.improved(best, self.prev_best) {
+            self.no_improv = 0;
+            self.prev_best = best;
+        } else {
+            self.no_improv += 1;
+        }
+
+        // restart once the simplex has collapsed or stagnates
+        let restart = self.config.restart(&self.res, self.restarts, &mut self.decrease_reference);
+        if let Some(rebuilt) = restart {
+            self.sum = None;
+            self.unordered = true;
+            for (i, x) in rebuilt.into_iter().enumerate() {
+                if let Some(reason) = self.out_of_budget() {
+                    return Ok(ControlFlow::Break(reason));
+                }
+                let score = self.evaluate(&x)?;
+                self.res.set(i + 1, x.as_slice(), score);
+            }
+            self.restarts += 1;
+            self.no_improv = 0;
+            self.config.emit(|| Event::Restart {
+                vertices: self.res.to_pairs(self.sign),
+            });
+            return Ok(ControlFlow::Continue(Move::Restart));
+        }
+
+        let stalled = self.config.no_improv_break.is_some_and(|limit| self.no_improv >= limit);
+        if stalled && indistinct != Some(false) {
+            if self.stop_confirmed(reevaluated)? {
+                return Ok(ControlFlow::Break(TerminationReason::NoImprovement));
+            }
+            self.no_improv = 0;
+        }
+
+        let mut buffers = std::mem::take(&mut self.buffers);
+        let flow = self.move_simplex(&mut buffers);
+        self.buffers = buffers;
+        flow
+    }
+
+    /// Whether the run may stop on one of its convergence rules. When
+    /// configured with `reevaluate_best`, only if the best vertex is still
+    /// the best one once evaluated again, unless it just was.
+    fn stop_confirmed(&mut self, reevaluated: bool) -> Result<bool, NelderMeadError> {
+        if self.config.reevaluation.is_none() || reevaluated {
+            return Ok(true);
+        }
+        Ok(!self.reevaluate_best()?)
+    }
+
+    /// Welch statistic of the difference between the scores of the worst
+    /// and the best vertex, `None` unless the standard errors of both are
+    /// known and not both zero.
+    fn spread_significance(&self) -> Option<T> {
+        let last_idx = self.res.len() - 1;
+        let error = |i| self.obj_fn.standard_error(&self.config.external(&self.res.point(i)));
+        let (best, worst) = (error(0)?, error(last_idx)?);
+        let pooled = (best * best + worst * worst).sqrt();
+        if pooled.is_zero() {
+            return None;
+        }
+        Some((self.res.score(last_idx) - self.res.score(0)) / pooled)
+    }
+
+    /// Evaluates the best vertex again, bypassing the cache, and ranks it
+    /// by its new score, which is also the new reference of the
+    /// no-improvement rule. Returns whether another vertex became the
+    /// best one, and does nothing once the budget is spent.
+    fn reevaluate_best(&mut self) -> Result<bool, NelderMeadError> {
+        if self.out_of_budget().is_some() {
+            return Ok(false);
+        }
+        let x = self.res.point(0);
+        let point = self.config.external(&x);
+        self.evaluations += 1;
+        let value = time(self.timer.as_ref(), || self.obj_fn.evaluate_point(&point));
+        let score = self.config.nan_handling.score(value, self.sign)? + self.config.penalty_of(&point);
+        self.res.set(0, x.as_slice(), score);
+        self.unordered = true;
+        self.sort();
+        self.prev_best = self.res.score(0);
+        Ok(self.res.vertex(0) != x.as_slice())
+    }
+
+    /// Generator of the jitter of the iteration, drawn from the seed and
+    /// the iteration, when jitter is enabled and all the vertices score
+    /// the same.
+    fn plateau_rng(&self) -> Option<Rng> {
+        let jitter = self.config.plateau_jitter?;
+        let flat = self.res.score(0) == self.res.score(self.res.len() - 1);
+        flat.then(|| Rng::new(split_mix(jitter.seed, self.iters)))
+    }
+
+    /// Perturbs the reflected point `xr` by normal deviates of the jitter
+    /// magnitude times the length of the reflection from the centroid
+    /// `x0`.
+    fn jitter(&self, xr: &mut DVector<T>, x0: &[T], rng: &mut Rng) {
+        let Some(jitter) = self.config.plateau_jitter else {
+            return;
+        };
+        let length = xr.iter().zip(x0).fold(T::zero(), |sum, (x, c)| sum + (*x - *c).powi(2)).sqrt();
+        for x in xr.iter_mut() {
+            *x += jitter.magnitude * length * convert(rng.normal());
+        }
+    }
+
+    /// Replaces the worst vertex or shrinks the simplex towards the best
+    /// one. Trial points are built in `buffers`, so nothing is allocated.
+    fn move_simplex(
+        &mut self,
+        buffers: &mut Buffers<T>,
+    ) -> Result<ControlFlow<TerminationReason, Move>, NelderMeadError> {
+        if self.config.method == SimplexMethod::MultiDirectional {
+            return self.move_all(buffers);
+        }
+        if self.config.parallel_points > 1 {
+            return self.move_worst(buffers, self.config.parallel_points);
+        }
+        let last_idx = self.res.len()-1;
+        let coefficients = self.config.coefficients;
+        let Buffers { centroid: x0, reflected: xr, trial, shrunk, candidates, .. } = buffers;
+
+        // on a plateau, the vertex reflected is drawn at random and the
+        // reflected point jittered, so that the simplex wanders across it
+        let mut plateau = self.plateau_rng();
+        if let Some(rng) = &mut plateau {
+            self.res.swap(rng.below(self.res.len()), last_idx);
+        }
+
+        // centroid, from the running sum of the vertices, which is also
+        // recomputed every n + 1 iterations so that its rounding errors do
+        // not outgrow a shrinking simplex
+        if self.iters.is_multiple_of(self.res.len() as u64) {
+            self.sum = None;
+        }
+        let sum = self.sum.get_or_insert_with(|| self.res.sum());
+        let n: T = convert(last_idx as f64);
+        for ((c, s), w) in x0.iter_mut().zip(sum.iter()).zip(self.res.vertex(last_idx)) {
+            *c = (*s - *w) / n;
+        }
+
+        // break once the evaluation or time budget is spent, this is
+        // checked before every evaluation so the budget is never exceeded
+        if let Some(reason) = self.out_of_budget() {
+            return Ok(ControlFlow::Break(reason));
+        }
+
+        // every trial point the iteration may need, evaluated at once when
+        // speculative, as long as the budget allows all of them
+        let outside_mode = self.config.contraction == ContractionMode::InsideOutside;
+        let count = if outside_mode { 4 } else { 3 };
+        let speculated = if self.config.speculative && self.within_budget(count) == count {
+            candidates.resize(count, DVector::zeros(last_idx));
+            let worst = self.res.vertex(last_idx);
+            beyond(&mut candidates[0], x0.as_slice(), worst, coefficients.alpha);
+            if let Some(rng) = &mut plateau {
+                self.jitter(&mut candidates[0], x0.as_slice(), rng);
+            }
+            beyond(&mut candidates[1], x0.as_slice(), worst, coefficients.gamma);
+            towards(&mut candidates[2], x0.as_slice(), worst, coefficients.rho);
+            for x in candidates[..3].iter_mut() {
+                self.config.apply_bounds(x.as_mut_slice());
+            }
+            if outside_mode {
+                let (reflected, outside) = candidates.split_at_mut(3);
+                towards(&mut outside[0], x0.as_slice(), reflected[0].as_slice(), coefficients.rho);
+                self.config.apply_bounds(outside[0].as_mut_slice());
+            }
+            let scores = self.evaluate_all(candidates)?;
+            std::mem::swap(xr, &mut candidates[0]);
+            Some(scores)
+        } else {
+            None
+        };
+
+        // reflection
+        let rscore = match &speculated {
+            Some(scores) => scores[0],
+            None => {
+                beyond(xr, x0.as_slice(), self.res.vertex(last_idx), coefficients.alpha);
+                if let Some(rng) = &mut plateau {
+                    self.jitter(xr, x0.as_slice(), rng);
+                }
+                self.config.apply_bounds(xr.as_mut_slice());
+                self.evaluate(xr)?
+            }
+        };
+        // and a point scoring the same as the vertices is accepted instead
+        // of shrinking the simplex
+        let drift = plateau.is_some() && rscore == self.res.score(last_idx);
+        if (self.res.score(0) <= rscore) & (rscore < self.res.score(last_idx-1)) || drift {
+            self.config.emit(|| Event::Reflection {
+                centroid: x0.clone(),
+                replaced: self.res.point(last_idx),
+                point: xr.clone(),
+                score: self.sign * rscore,
+            });
+            self.replace_worst(xr, rscore);
+            return Ok(ControlFlow::Continue(Move::Reflection));
+        }
+
+        // expansion
+        if rscore < self.res.score(0) {
+            let escore = match &speculated {
+                Some(scores) => {
+                    std::mem::swap(trial, &mut candidates[1]);
+                    scores[1]
+                }
+                None => {
+                    if let Some(reason) = self.out_of_budget() {
+                        return Ok(ControlFlow::Break(reason));
+                    }
+                    beyond(trial, x0.as_slice(), self.res.vertex(last_idx), coefficients.gamma);
+                    self.config.apply_bounds(trial.as_mut_slice());
+                    self.evaluate(trial)?
+                }
+            };
+            if self.config.accept_expansion(escore, rscore, self.res.score(0)) {
+                self.config.emit(|| Event::Expansion {
+                    centroid: x0.clone(),
+                    replaced: self.res.point(last_idx),
+                    reflected: xr.clone(),
+                    point: trial.clone(),
+                    score: self.sign * escore,
+                });
+                self.replace_worst(trial, escore);
+                return Ok(ControlFlow::Continue(Move::Expansion));
+            } else {
+                self.config.emit(|| Event::Reflection {
+                    centroid: x0.clone(),
+                    replaced: self.res.point(last_idx),
+                    point: xr.clone(),
+                    score: self.sign * rscore,
+                });
+                self.replace_worst(xr, rscore);
+                return Ok(ControlFlow::Continue(Move::Reflection));
+            }
+        }
+
+        // contraction
+        if speculated.is_none() {
+            if let Some(reason) = self.out_of_budget() {
+                return Ok(ControlFlow::Break(reason));
+            }
+        }
+        let outside = outside_mode && rscore < self.res.score(last_idx);
+        if outside {
+            let cscore = match &speculated {
+                Some(scores) => {
+                    std::mem::swap(trial, &mut candidates[3]);
+                    scores[3]
+                }
+                None => {
+                    towards(trial, x0.as_slice(), xr.as_slice(), coefficients.rho);
+                    self.config.apply_bounds(trial.as_mut_slice());
+                    self.evaluate(trial)?
+                }
+            };
+            if cscore <= rscore {
+                self.config.emit(|| Event::OutsideContraction {
+                    centroid: x0.clone(),
+                    replaced: self.res.point(last_idx),
+                    reflected: xr.clone(),
+                    point: trial.clone(),
+                    score: self.sign * cscore,
+                });
+                self.replace_worst(trial, cscore);
+                return Ok(ControlFlow::Continue(Move::OutsideContraction));
+            }
+        } else {
+            let cscore = match &speculated {
+                Some(scores) => {
+                    std::mem::swap(trial, &mut candidates[2]);
+                    scores[2]
+                }
+                None => {
+                    towards(trial, x0.as_slice(), self.res.vertex(last_idx), coefficients.rho);
+                    self.config.apply_bounds(trial.as_mut_slice());
+                    self.evaluate(trial)?
+                }
+            };
+            if cscore < self.res.score(last_idx) {
+                self.config.emit(|| Event::Contraction {
+                    centroid: x0.clone(),
+                    replaced: self.res.point(last_idx),
+                    point: trial.clone(),
+                    score: self.sign * cscore,
+                });
+                self.replace_worst(trial, cscore);
+                return Ok(ControlFlow::Continue(Move::Contraction));
+            }
+        }
+
+        self.shrink(shrunk)
+    }
+
+    /// Replaces the `p` worst vertices at once, each one by its
+    /// reflection, expansion or contraction through the centroid of the
+    /// other vertices, or shrinks the simplex when none of them improves.
+    /// The reflected points are evaluated as one batch, and so are the
+    /// expanded and contracted ones.
+    fn move_worst(
+        &mut self,
+        buffers: &mut Buffers<T>,
+        p: usize,
+    ) -> Result<ControlFlow<TerminationReason, Move>, NelderMeadError> {
+        let kept = self.res.len() - p;
+        let coefficients = self.config.coefficients;
+        let Buffers { centroid: x0, shrunk, candidates: reflected, trials, .. } = buffers;
+
+        // centroid of the vertices that are kept
+        let n: T = convert(kept as f64);
+        for (c, s) in x0.iter_mut().zip(self.res.partial_sum(kept).iter()) {
+            *c = *s / n;
+        }
+
+        // reflections, only evaluated if the budget allows all of them
+        if self.within_budget(p) < p {
+            return Ok(ControlFlow::Break(TerminationReason::MaxFunEvals));
+        }
+        if let Some(reason) = self.out_of_budget() {
+            return Ok(ControlFlow::Break(reason));
+        }
+        reflected.resize(p, DVector::zeros(x0.len()));
+        for (xr, (x, _)) in reflected.iter_mut().zip(self.res.iter().skip(kept)) {
+            beyond(xr, x0.as_slice(), x, coefficients.alpha);
+            self.config.apply_bounds(xr.as_mut_slice());
+        }
+        let rscores = self.evaluate_all(reflected)?;
+
+        // expansions and contractions of the vertices whose reflection is
+        // not accepted as is
+        let (best, kept_worst) = (self.res.score(0), self.res.score(kept-1));
+        let followups: Vec<Followup> = rscores
+            .iter()
+            .zip(&self.res.scores()[kept..])
+            .map(|(rscore, score)| self.config.followup(*score, *rscore, best, kept_worst))
+            .collect();
+        trials.resize(p, DVector::zeros(x0.len()));
+        let mut count = 0;
+        for ((followup, xr), (x, _)) in followups.iter().zip(reflected.iter()).zip(self.res.iter().skip(kept)) {
+            let trial = &mut trials[count];
+            match followup {
+                Followup::Reflection => continue,
+                Followup::Expansion => beyond(trial, x0.as_slice(), x, coefficients.gamma),
+                Followup::Contraction => towards(trial, x0.as_slice(), x, coefficients.rho),
+                Followup::OutsideContraction => towards(trial, x0.as_slice(), xr.as_slice(), coefficients.rho),
+            }
+            self.config.apply_bounds(trial.as_mut_slice());
+            count += 1;
+        }
+        let tscores = if count > 0 {
+            if self.within_budget(count) < count {
+                return Ok(ControlFlow::Break(TerminationReason::MaxFunEvals));
+            }
+            if let Some(reason) = self.out_of_budget() {
+                return Ok(ControlFlow::Break(reason));
+            }
+            self.evaluate_all(&trials[..count])?
+        } else {
+            Vec::new()
+        };
+
+        // replacements, the vertices that are kept are not touched, so
+        // the decisions do not depend on each other
+        let mut improved = false;
+        let mut t = 0;
+        for (i, followup) in followups.into_iter().enumerate() {
+            let j = kept + i;
+            let rscore = rscores[i];
+            let (xr, trial) = (&mut reflected[i], trials.get_mut(t));
+            let (point, score) = match followup {
+                Followup::Reflection => {
+                    self.config.emit(|| Event::Reflection {
+                        centroid: x0.clone(),
+                        replaced: self.res.point(j),
+                        point: xr.clone(),
+                        score: self.sign * rscore,
+                    });
+                    (xr, rscore)
+                }
+                Followup::Expansion => {
+                    let (xe, escore) = (trial.expect("one trial point each"), tscores[t]);
+                    t += 1;
+                    if self.config.accept_expansion(escore, rscore, best) {
+                        self.config.emit(|| Event::Expansion {
+                            centroid: x0.clone(),
+                            replaced: self.res.point(j),
+                            reflected: xr.clone(),
+                            point: xe.clone(),
+                            score: self.sign * escore,
+                        });
+                        (xe, escore)
+                    } else {
+                        self.config.emit(|| Event::Reflection {
+                            centroid: x0.clone(),
+                            replaced: self.res.point(j),
+                            point: xr.clone(),
+                            score: self.sign * rscore,
+                        });
+                        (xr, rscore)
+                    }
+                }
+                Followup::Contraction => {
+                    let (xc, cscore) = (trial.expect("one trial point each"), tscores[t]);
+                    t += 1;
+                    if cscore >= self.res.score(j) {
+                        continue;
+                    }
+                    self.config.emit(|| Event::Contraction {
+                        centroid: x0.clone(),
+                        replaced: self.res.point(j),
+                        point: xc.clone(),
+                        score: self.sign * cscore,
+                    });
+                    (xc, cscore)
+                }
+                Followup::OutsideContraction => {
+                    let (xc, cscore) = (trial.expect("one trial point each"), tscores[t]);
+                    t += 1;
+                    if cscore > rscore {
+                        continue;
+                    }
+                    self.config.emit(|| Event::OutsideContraction {
+                        centroid: x0.clone(),
+                        replaced: self.res.point(j),
+                        reflected: xr.clone(),
+                        point: xc.clone(),
+                        score: self.sign * cscore,
+                    });
+                    (xc, cscore)
+                }
+            };
+            self.res.set(j, point.as_slice(), score);
+            improved = true;
+        }
+        if !improved {
+            return self.shrink(shrunk);
+        }
+        self.sum = None;
+        self.unordered = true;
+        Ok(ControlFlow::Continue(Move::MultiPoint))
+    }
+
+    /// Step of the multi-directional search: reflects every vertex but the
+    /// best one through the best one, then expands them if the best
+    /// reflected point beats the best vertex, or shrinks the simplex
+    /// otherwise. The reflected points are evaluated as one batch, and so
+    /// are the expanded ones.
+    fn move_all(
+        &mut self,
+        buffers: &mut Buffers<T>,
+    ) -> Result<ControlFlow<TerminationReason, Move>, NelderMeadError> {
+        let count = self.res.len() - 1;
+        let coefficients = self.config.coefficients;
+        let Buffers { shrunk, candidates: reflected, trials: expanded, .. } = buffers;
+
+        // reflections, only evaluated if the budget allows all of them
+        if self.within_budget(count) < count {
+            return Ok(ControlFlow::Break(TerminationReason::MaxFunEvals));
+        }
+        if let Some(reason) = self.out_of_budget() {
+            return Ok(ControlFlow::Break(reason));
+        }
+        reflected.resize(count, DVector::zeros(self.res.dim()));
+        for (i, xr) in reflected.iter_mut().enumerate() {
+            beyond(xr, self.res.vertex(0), self.res.vertex(i + 1), coefficients.alpha);
+            self.config.apply_bounds(xr.as_mut_slice());
+        }
+        let rscores = self.evaluate_all(reflected)?;
+        let best = self.res.score(0);
+        let rbest = lowest(&rscores);
+        if rbest >= best {
+            return self.shrink(shrunk);
+        }
+
+        // expansions, likewise
+        if self.within_budget(count) < count {
+            return Ok(ControlFlow::Break(TerminationReason::MaxFunEvals));
+        }
+        if let Some(reason) = self.out_of_budget() {
+            return Ok(ControlFlow::Break(reason));
+        }
+        expanded.resize(count, DVector::zeros(self.res.dim()));
+        for (i, xe) in expanded.iter_mut().enumerate() {
+            beyond(xe, self.res.vertex(0), self.res.vertex(i + 1), coefficients.gamma);
+            self.config.apply_bounds(xe.as_mut_slice());
+        }
+        let escores = self.evaluate_all(expanded)?;
+        let expand = self.config.accept_expansion(lowest(&escores), rbest, best);
+
+        for i in 0..count {
+            let j = i + 1;
+            let (xr, rscore) = (&reflected[i], rscores[i]);
+            if expand {
+                self.config.emit(|| Event::Expansion {
+                    centroid: self.res.point(0),
+                    replaced: self.res.point(j),
+                    reflected: xr.clone(),
+                    point: expanded[i].clone(),
+                    score: self.sign * escores[i],
+                });
+                self.res.set(j, expanded[i].as_slice(), escores[i]);
+            } else {
+                self.config.emit(|| Event::Reflection {
+                    centroid: self.res.point(0),
+                    replaced: self.res.point(j),
+                    point: xr.clone(),
+                    score: self.sign * rscore,
+                });
+                self.res.set(j, xr.as_slice(), rscore);
+            }
+        }
+        self.sum = None;
+        self.unordered = true;
+        Ok(ControlFlow::Continue(if expand { Move::Expansion } else { Move::Reflection }))
+    }
+
+    /// Shrinks the simplex towards the best vertex, which is kept as is.
+    /// The other vertices are independent so they are evaluated at once,
+    /// as far as the remaining budget allows, or in batches of the size of
+    /// `shrunk`, a single vertex in the memory-efficient mode.
+    fn shrink(
+        &mut self,
+        shrunk: &mut [DVector<T>],
+    ) -> Result<ControlFlow<TerminationReason, Move>, NelderMeadError> {
+        if let Some(reason) = self.out_of_budget() {
+            return Ok(ControlFlow::Break(reason));
+        }
+        let last_idx = self.res.len()-1;
+        let sigma = self.config.coefficients.sigma;
+        self.sum = None;
+        self.unordered = true;
+        let count = self.within_budget(last_idx);
+        let size = shrunk.len();
+        for first in (1..=count).step_by(size) {
+            let batch = &mut shrunk[..size.min(count + 1 - first)];
+            for (i, x) in (first..).zip(batch.iter_mut()) {
+                towards(x, self.res.vertex(0), self.res.vertex(i), sigma);
+                self.config.apply_bounds(x.as_mut_slice());
+            }
+            let scores = self.evaluate_all(batch)?;
+            for (i, (x, score)) in (first..).zip(batch.iter().zip(scores)) {
+                self.res.set(i, x.as_slice(), score);
+            }
+        }
+        if count < last_idx {
+            return Ok(ControlFlow::Break(TerminationReason::MaxFunEvals));
+        }
+        self.config.emit(|| Event::Shrink {
+            best: self.res.point(0),
+            vertices: self.res.to_pairs(self.sign),
+        });
+        Ok(ControlFlow::Continue(Move::Shrink))
+    }
+
+    /// Copies the accepted point `x` over the worst vertex and moves it to
+    /// its rank.
+    fn replace_worst(&mut self, x: &DVector<T>, score: T) {
+        let last_idx = self.res.len() - 1;
+        if let Some(sum) = &mut self.sum {
+            replace_in_sum(sum, self.res.vertex(last_idx), x.as_slice());
+        }
+        self.res.set(last_idx, x.as_slice(), score);
+        self.res.rank_last();
+    }
+
+    /// Evaluates the objective, applying the NaN policy and the direction.
+    /// A point found in the cache is not evaluated again.
+    fn evaluate(&mut self, x: &DVector<T>) -> Result<T, NelderMeadError> {
+        let point = if self.config.transforms() {
+            self.point.copy_from(x);
+            self.config.to_external(self.point.as_mut_slice());
+            &self.point
+        } else {
+            x
+        };
+        let value = match self.cache.as_mut().and_then(|cache| cache.get(point.as_slice())) {
+            Some(value) => value,
+            None => {
+                self.evaluations += 1;
+                let value = time(self.timer.as_ref(), || self.obj_fn.evaluate_point(point));
+                if let Some(cache) = &mut self.cache {
+                    cache.insert(point.as_slice(), value);
+                }
+                value
+            }
+        };
+        Ok(self.config.nan_handling.score(value, self.sign)? + self.config.penalty_of(point))
+    }
+
+    /// Same as [`NelderMeadIter::evaluate`], for independent points
+    /// evaluated as one batch.
+    fn evaluate_all(&mut self, xs: &[DVector<T>]) -> Result<Vec<T>, NelderMeadError> {
+        let external: Vec<DVector<T>>;
+        let points = if self.config.transforms() {
+            external = xs.iter().map(|x| self.config.external(x)).collect();
+            &external
+        } else {
+            xs
+        };
+        let values = match &mut self.cache {
+            Some(cache) => {
+                // only the points missing from the cache are evaluated
+                let mut values: Vec<Option<T>> = points.iter().map(|x| cache.get(x.as_slice())).collect();
+                let missing: Vec<DVector<T>> = points
+                    .iter()
+                    .zip(&values)
+                    .filter(|(_, value)| value.is_none())
+                    .map(|(x, _)| x.clone())
+                    .collect();
+                self.evaluations += missing.len() as u64;
+                let evaluated = Self::call_objective(&mut self.obj_fn, self.timer.as_ref(), &missing);
+                if evaluated.len() != missing.len() {
+                    return Err(NelderMeadError::InvalidBatch);
+                }
+                let mut evaluated = missing.iter().zip(evaluated);
+                for value in values.iter_mut().filter(|value| value.is_none()) {
+                    let (x, v) = evaluated.next().expect("one value per missing point");
+                    cache.insert(x.as_slice(), v);
+                    *value = Some(v);
+                }
+                values.into_iter().flatten().collect()
+            }
+            None => {
+                self.evaluations += points.len() as u64;
+                Self::call_objective(&mut self.obj_fn, self.timer.as_ref(), points)
+            }
+        };
+        if values.len() != points.len() {
+            return Err(NelderMeadError::InvalidBatch);
+        }
+        values
+            .into_iter()
+            .zip(points)
+            .map(|(value, x)| Ok(self.config.nan_handling.score(value, self.sign)? + self.config.penalty_of(x)))
+            .collect()
+    }
+
+    /// Values of the objective at `points`, timed point by point unless
+    /// the objective evaluates them as one batch.
+    fn call_objective(obj_fn: &mut F, timer: Option<&Timer>, points: &[DVector<T>]) -> Vec<T> {
+        match timer {
+            Some(timer) if !obj_fn.evaluates_batches() => points
+                .iter()
+                .map(|x| timer.time(|| obj_fn.evaluate_point(x)))
+                .collect(),
+            timer => time(timer, || obj_fn.evaluate_points(points)),
+        }
+    }
+
+    /// Number of the `count` evaluations the remaining budget allows.
+    fn within_budget(&self, count: usize) -> usize {
+        let remaining = self.config.max_fun_evals
+            .map_or(u64::MAX, |limit| limit.saturating_sub(self.evaluations));
+        count.min(remaining.try_into().unwrap_or(usize::MAX))
+    }
+
+    /// Whether the evaluation or time budget is spent.
+    fn out_of_budget(&self) -> Option<TerminationReason> {
+        if self.config.max_fun_evals.is_some_and(|limit| self.evaluations >= limit) {
+            Some(TerminationReason::MaxFunEvals)
+        } else if self.config.max_duration.is_some_and(|limit| self.start.elapsed() >= limit) {
+            Some(TerminationReason::TimedOut)
+        } else {
+            None
+        }
+    }
+
+    /// Orders the vertices from the best one. The sort is stable, so
+    /// vertices with equal scores keep their order and a new vertex ranks
+    /// after the ones it ties with, which keeps reruns reproducible. A
+    /// single replaced vertex is already moved to its rank, so the full
+    /// sort only follows the steps replacing several vertices.
+    fn sort(&mut self) {
+        if self.unordered {
+            self.res.sort();
+            self.unordered = false;
+        }
+        if self.config.recenter_simplex(&mut self.res) {
+            self.sum = None;
+        }
+        if self.config.transforms() {
+            self.config.copy_to_external(&self.res, &mut self.external);
+        }
+    }
+
+    /// Vertices of the simplex with their scores, mapped to the
+    /// parameters.
+    fn simplex(&self) -> &Simplex<T> {
+        if self.config.transforms() {
+            &self.external
+        } else {
+            &self.res
+        }
+    }
+}
+
+impl<T, F> Iterator for NelderMeadIter<T, F>
+where
+    T: RealField + Copy,
+    F: Evaluator<T>,
+{
+    type Item = Result<IterationState<T>, NelderMeadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.step() {
+            Ok(Some(last_move)) => Some(Ok(IterationState {
+                iterations: self.iters,
+                evaluations: self.evaluations,
+                best_point: self.res.point(0),
+                best_score: self.sign * self.res.score(0),
+                last_move,
+            })),
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+impl<T: RealField + Copy> NelderMead<T> {
+    /// Same as [`NelderMead::minimize`], but returns an iterator performing
+    /// one iteration per call to `next`, see [`NelderMeadIter`].
+    ///
+    /// Fails if the configuration does not fit the problem or the
+    /// objective function returns NaN on the initial simplex.
+    pub fn iter<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<NelderMeadIter<T, F>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let simplex = self.initial_simplex(x_start)?;
+        self.iter_from_simplex(obj_fn, simplex)
+    }
+
+    /// Same as [`NelderMead::iter`], but starts from a complete simplex,
+    /// see [`NelderMead::minimize_from_simplex`].
+    pub fn iter_from_simplex<F>(
+        &self,
+        obj_fn: F,
+        simplex: Vec<DVector<T>>,
+    ) -> Result<NelderMeadIter<T, F>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        NelderMeadIter::new(self.clone(), obj_fn, simplex)
+    }
+}
+
+impl<T: RealField + Copy> NelderMeadBuilder<T> {
+    /// Finishes the configuration and returns an iterator over the run,
+    /// see [`NelderMead::iter`].
+    pub fn iter<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<NelderMeadIter<T, F>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let config = self.build();
+        let simplex = config.initial_simplex(x_start)?;
+        NelderMeadIter::new(config, obj_fn, simplex)
+    }
+}
@@ -0,0 +1,568 @@
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::time::Instant;
+
+use nalgebra::{convert, DVector, RealField, SVector};
+
+use crate::builder::NelderMeadBuilder;
+use crate::cache::EvaluationCache;
+use crate::nelder_mead::{lowest, rank_last, total_cmp, ContractionMode, NelderMead, SimplexMethod};
+use crate::error::NelderMeadError;
+use crate::event::Event;
+use crate::history::{History, HistoryEntry};
+use crate::interrupt;
+use crate::logging;
+use crate::observer::Move;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::TerminationState;
+use crate::timing::{time, Timer};
+
+impl<T: RealField + Copy> NelderMead<T> {
+    /// Same as [`NelderMead::minimize`], but operates on statically sized
+    /// vectors.
+    ///
+    /// Vertices live on the stack and the simplex storage is allocated
+    /// once up front, so the main loop performs no heap allocations. This
+    /// is considerably faster for problems with only a few parameters.
+    /// A configured termination criterion, observer, callback or event
+    /// listener, as well as `record_history`, gets a copy of the simplex
+    /// every iteration, which does allocate, and so does the evaluation
+    /// cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{SVector, vector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn f(x: &SVector<f64, 2>) -> f64 {
+    ///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+    /// }
+    /// let results = NelderMead::builder()
+    ///     .minimize_static(f, vector![0.0, 0.0])?;
+    ///
+    /// assert!(results.best_score < 1e-4);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn minimize_static<F, const N: usize>(
+        &self,
+        mut obj_fn: F,
+        x_start: SVector<T, N>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&SVector<T, N>) -> T,
+    {
+        if self.auto_scale && self.scales.is_none() {
+            self.check_domain(N)?;
+            let mut config = self.clone();
+            let mut x = x_start;
+            self.to_feasible(x.as_mut_slice());
+            config.fit_scales(x.as_slice());
+            return config.minimize_static(obj_fn, x_start);
+        }
+        let start = Instant::now();
+
+        // scores are always minimized, maximization flips their sign
+        let sign: T = self.direction.sign();
+        let evaluations = Cell::new(0);
+        let mut cache = self.cache_resolution.map(EvaluationCache::new);
+        let timer = self.record_timing.then(Timer::new);
+        let mut obj_fn = |x: &SVector<T, N>| -> Result<T, NelderMeadError> {
+            let mut x = *x;
+            self.to_external(x.as_mut_slice());
+            let value = match cache.as_mut().and_then(|cache| cache.get(x.as_slice())) {
+                Some(value) => value,
+                None => {
+                    evaluations.set(evaluations.get() + 1);
+                    let value = time(timer.as_ref(), || obj_fn(&x));
+                    if let Some(cache) = &mut cache {
+                        cache.insert(x.as_slice(), value);
+                    }
+                    value
+                }
+            };
+            let score = self.nan_handling.score(value, sign)?;
+            if !self.constrained() {
+                Ok(score)
+            } else {
+                Ok(score + self.penalty_of(&to_dynamic(&x)))
+            }
+        };
+        let bounded = |mut x: SVector<T, N>| {
+            self.apply_bounds(x.as_mut_slice());
+            x
+        };
+        let feasible = |mut x: SVector<T, N>| {
+            self.to_feasible(x.as_mut_slice());
+            x
+        };
+        let internal = |mut x: SVector<T, N>| {
+            self.to_internal(x.as_mut_slice());
+            x
+        };
+        let max_fun_evals = self.max_fun_evals.unwrap_or(u64::MAX);
+        let out_of_budget = || {
+            if evaluations.get() >= max_fun_evals {
+                Some(TerminationReason::MaxFunEvals)
+            } else if self.max_duration.is_some_and(|limit| start.elapsed() >= limit) {
+                Some(TerminationReason::TimedOut)
+            } else {
+                None
+            }
+        };
+
+        // init
+        if N == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        self.step.check_dim(N)?;
+        self.step.check_values()?;
+        self.check_domain(N)?;
+        if self.parallel_points > 1 {
+            return Err(NelderMeadError::InvalidParallelPoints);
+        }
+        if self.replication.is_some() {
+            return Err(NelderMeadError::InvalidReplication);
+        }
+        let coefficients = self.coefficients_for(N);
+        coefficients.validate()?;
+        let x_start = feasible(x_start);
+        self.check_point(x_start.as_slice())?;
+        let x_first = internal(x_start);
+        let mut prev_best = obj_fn(&x_first)?;
+        let mut no_improv = 0;
+        let mut restarts = 0;
+        let mut decrease_reference = None;
+        let mut last_move = None;
+        let mut history = Vec::new();
+        let mut res = Vec::with_capacity(N + 1);
+        res.push((x_first, prev_best));
+
+        for i in 0..N {
+            let mut x = x_start;
+            let step = self.initial_step(i, x[i]);
+            x[i] += step;
+            let x = internal(feasible(x));
+            let score = obj_fn(&x)?;
+            res.push((x, score));
+        }
+
+        let last_idx = N;
+        let n: T = convert(N as f64);
+
+        // simplex iter
+        let mut iters = 0;
+        // running sum of the vertices, `None` until it is recomputed
+        let mut sum: Option<SVector<T, N>> = None;
+        // whether vertices were replaced out of order since the last sort,
+        // a single replaced vertex is moved to its rank right away
+        let mut unordered = true;
+        // iterations whose duration was recorded
+        let mut timed_iters = 0;
+        let termination_reason = 'simplex: loop
+        {
+            // order
+            if unordered {
+                sort_by_score(&mut res);
+                unordered = false;
+            }
+            if self.recenter(&mut res) {
+                sum = None;
+            }
+            // an iteration ends once the simplex is ordered
+            if let Some(timer) = &timer {
+                if iters > timed_iters {
+                    timer.end_iteration();
+                    timed_iters = iters;
+                }
+                timer.start_iteration();
+            }
+            let best = res[0].1;
+
+            // the state handed to the logger, the history, the observer, the
+            // callback and the termination criterion needs a heap allocated
+            // copy of the simplex, mapped to the parameters
+            let hooked = logging::iteration_enabled()
+                || self.record_history
+                || self.observer.is_some()
+                || self.callback.is_some()
+                || self.termination.is_some()
+                || self.transforms();
+            let simplex: Option<Simplex<T>> = hooked.then(|| {
+                let mut simplex = Simplex::from_slices(res.iter().map(|(x, score)| (x.as_slice(), *score)));
+                if self.transforms() {
+                    for j in 0..simplex.len() {
+                        self.to_external(simplex.vertex_mut(j));
+                    }
+                }
+                simplex
+            });
+            let state = simplex.as_ref().map(|simplex| TerminationState {
+                iterations: iters,
+                evaluations: evaluations.get(),
+                elapsed: start.elapsed(),
+                simplex,
+                sign,
+                last_move,
+                budget: self.budget(),
+            });
+            if let Some(state) = &state {
+                logging::log_iteration(state);
+                if self.record_history {
+                    history.push(HistoryEntry::new(state, self.memory_efficient));
+                }
+            }
+            if let (Some(observer), Some(state)) = (&self.observer, &state) {
+                observer.0.observe(state);
+            }
+
+            // break when the callback asks for it
+            if let (Some(callback), Some(state)) = (&self.callback, &state) {
+                if callback.0.call(state).is_break() {
+                    break TerminationReason::StoppedByCallback;
+                }
+            }
+
+            // break once a stop was requested
+            if self.stop_on_interrupt && interrupt::take_stop_request() {
+                break TerminationReason::Interrupted;
+            }
+
+            // break once the target value is reached
+            if self.target_value.is_some_and(|target| best <= sign * target) {
+                break TerminationReason::TargetReached;
+            }
+
+            // break when the simplex is within tolerances
+            let converged = match &simplex {
+                Some(simplex) => self.has_converged(simplex.iter()),
+                None => self.has_converged(res.iter().map(|(x, f)| (x.as_slice(), *f))),
+            };
+            if converged {
+                break TerminationReason::Converged;
+            }
+
+            // break when the termination criterion is met
+            if let (Some(criterion), Some(state)) = (&self.termination, &state) {
+                if criterion.0.should_stop(state) {
+                    break TerminationReason::Criterion;
+                }
+            }
+
+            // break after max_iter
+            if iters >= self.max_iter {
+                break TerminationReason::MaxIter;
+            }
+            iters += 1;
+
+            // break after no_improv_break iterations with no improvement
+            if self.improved(best, prev_best) {
+                no_improv = 0;
+                prev_best = best;
+            } else {
+                no_improv += 1;
+            }
+
+            // restart once the simplex has collapsed or stagnates
+            let restart = if self.degeneracy_restart.is_some() || self.oriented_restart.is_some() {
+                let simplex = Simplex::from_slices(res.iter().map(|(x, score)| (x.as_slice(), *score)));
+                self.restart(&simplex, restarts, &mut decrease_reference)
+            } else {
+                None
+            };
+            if let Some(rebuilt) = restart {
+                sum = None;
+                unordered = true;
+                for (i, x) in rebuilt.iter().enumerate() {
+                    if let Some(reason) = out_of_budget() {
+                        break 'simplex reason;
+                    }
+                    let x = SVector::from_column_slice(x.as_slice());
+                    res[i + 1] = (x, obj_fn(&x)?);
+                }
+                restarts += 1;
+                no_improv = 0;
+                last_move = Some(Move::Restart);
+                self.emit(|| Event::Restart {
+                    vertices: res.iter().map(|(x, score)| (to_dynamic(x), sign * *score)).collect(),
+                });
+                continue;
+            }
+
+            if self.no_improv_break.is_some_and(|limit| no_improv >= limit) {
+                break TerminationReason::NoImprovement;
+            }
+
+            if self.method == SimplexMethod::MultiDirectional {
+                // reflections of every vertex but the best one through it,
+                // only evaluated if the budget allows all of them
+                if max_fun_evals.saturating_sub(evaluations.get()) < N as u64 {
+                    break TerminationReason::MaxFunEvals;
+                }
+                if let Some(reason) = out_of_budget() {
+                    break reason;
+                }
+                let x1 = res[0].0;
+                let mut reflected = [(x1, T::zero()); N];
+                for (r, (x, _)) in reflected.iter_mut().zip(&res[1..]) {
+                    let xr = bounded(x1 + (x1 - *x) * coefficients.alpha);
+                    *r = (xr, obj_fn(&xr)?);
+                }
+                let rbest = lowest(&reflected.map(|(_, score)| score));
+                if rbest < res[0].1 {
+                    // expansions, likewise
+                    if max_fun_evals.saturating_sub(evaluations.get()) < N as u64 {
+                        break TerminationReason::MaxFunEvals;
+                    }
+                    if let Some(reason) = out_of_budget() {
+                        break reason;
+                    }
+                    let mut expanded = reflected;
+                    for (e, (x, _)) in expanded.iter_mut().zip(&res[1..]) {
+                        let xe = bounded(x1 + (x1 - *x) * coefficients.gamma);
+                        *e = (xe, obj_fn(&xe)?);
+                    }
+                    let escores = expanded.map(|(_, score)| score);
+                    let expand = self.accept_expansion(lowest(&escores), rbest, res[0].1);
+                    for (j, ((xr, rscore), (xe, escore))) in (1..).zip(reflected.into_iter().zip(expanded)) {
+                        if expand {
+                            self.emit(|| Event::Expansion {
+                                centroid: to_dynamic(&x1),
+                                replaced: to_dynamic(&res[j].0),
+                                reflected: to_dynamic(&xr),
+                                point: to_dynamic(&xe),
+                                score: sign * escore,
+                            });
+                            res[j] = (xe, escore);
+                        } else {
+                            self.emit(|| Event::Reflection {
+                                centroid: to_dynamic(&x1),
+                                replaced: to_dynamic(&res[j].0),
+                                point: to_dynamic(&xr),
+                                score: sign * rscore,
+                            });
+                            res[j] = (xr, rscore);
+                        }
+                    }
+                    sum = None;
+                    unordered = true;
+                    last_move = Some(if expand { Move::Expansion } else { Move::Reflection });
+                    continue;
+                }
+            } else {
+                // centroid, from the running sum of the vertices, which is also
+                // recomputed every n + 1 iterations so that its rounding errors do
+                // not outgrow a shrinking simplex
+                if iters.is_multiple_of(N as u64 + 1) {
+                    sum = None;
+                }
+                let total = sum.get_or_insert_with(|| {
+                    let mut sum = SVector::<T, N>::zeros();
+                    for tup in res.iter() {
+                        sum += tup.0;
+                    }
+                    sum
+                });
+                let worst = res[last_idx].0;
+                let x0 = (*total - worst) / n;
+
+                // break once the evaluation or time budget is spent, this is
+                // checked before every evaluation so the budget is never exceeded
+                if let Some(reason) = out_of_budget() {
+                    break reason;
+                }
+
+                // reflection
+                let xr = bounded(x0 + (x0 - worst) * coefficients.alpha);
+                let rscore = obj_fn(&xr)?;
+                if (res[0].1 <= rscore) & (rscore < res[last_idx-1].1) {
+                    self.emit(|| Event::Reflection {
+                        centroid: to_dynamic(&x0),
+                        replaced: to_dynamic(&worst),
+                        point: to_dynamic(&xr),
+                        score: sign * rscore,
+                    });
+                    *total += xr - worst;
+                    res[last_idx] = (xr, rscore);
+                    rank_last(&mut res);
+                    last_move = Some(Move::Reflection);
+                    continue;
+                }
+
+                // expansion
+                if rscore < res[0].1 {
+                    if let Some(reason) = out_of_budget() {
+                        break reason;
+                    }
+                    let xe = bounded(x0 + (x0 - worst) * coefficients.gamma);
+                    let escore = obj_fn(&xe)?;
+                    if self.accept_expansion(escore, rscore, res[0].1) {
+                        self.emit(|| Event::Expansion {
+                            centroid: to_dynamic(&x0),
+                            replaced: to_dynamic(&worst),
+                            reflected: to_dynamic(&xr),
+                            point: to_dynamic(&xe),
+                            score: sign * escore,
+                        });
+                        *total += xe - worst;
+                        res[last_idx] = (xe, escore);
+                        rank_last(&mut res);
+                        last_move = Some(Move::Expansion);
+                    } else {
+                        self.emit(|| Event::Reflection {
+                            centroid: to_dynamic(&x0),
+                            replaced: to_dynamic(&worst),
+                            point: to_dynamic(&xr),
+                            score: sign * rscore,
+                        });
+                        *total += xr - worst;
+                        res[last_idx] = (xr, rscore);
+                        rank_last(&mut res);
+                        last_move = Some(Move::Reflection);
+                    }
+                    continue;
+                }
+
+                // contraction
+                if let Some(reason) = out_of_budget() {
+                    break reason;
+                }
+                let outside = self.contraction == ContractionMode::InsideOutside
+                    && rscore < res[last_idx].1;
+                if outside {
+                    let xc = bounded(x0 + (xr - x0) * coefficients.rho);
+                    let cscore = obj_fn(&xc)?;
+                    if cscore <= rscore {
+                        self.emit(|| Event::OutsideContraction {
+                            centroid: to_dynamic(&x0),
+                            replaced: to_dynamic(&worst),
+                            reflected: to_dynamic(&xr),
+                            point: to_dynamic(&xc),
+                            score: sign * cscore,
+                        });
+                        *total += xc - worst;
+                        res[last_idx] = (xc, cscore);
+                        rank_last(&mut res);
+                        last_move = Some(Move::OutsideContraction);
+                        continue;
+                    }
+                } else {
+                    let xc = bounded(x0 + (worst - x0) * coefficients.rho);
+                    let cscore = obj_fn(&xc)?;
+                    if cscore < res[last_idx].1 {
+                        self.emit(|| Event::Contraction {
+                            centroid: to_dynamic(&x0),
+                            replaced: to_dynamic(&worst),
+                            point: to_dynamic(&xc),
+                            score: sign * cscore,
+                        });
+                        *total += xc - worst;
+                        res[last_idx] = (xc, cscore);
+                        rank_last(&mut res);
+                        last_move = Some(Move::Contraction);
+                        continue;
+                    }
+                }
+            }
+
+            // reduction, the best vertex is kept as is
+            last_move = Some(Move::Shrink);
+            sum = None;
+            unordered = true;
+            let x1 = res[0].0;
+            for tup in res[1..].iter_mut() {
+                if let Some(reason) = out_of_budget() {
+                    break 'simplex reason;
+                }
+                tup.0 = bounded(x1 + (tup.0 - x1) * coefficients.sigma);
+                tup.1 = obj_fn(&tup.0)?;
+            }
+            self.emit(|| Event::Shrink {
+                best: to_dynamic(&x1),
+                vertices: res.iter().map(|(x, score)| (to_dynamic(x), sign * *score)).collect(),
+            });
+        };
+
+        // a shrink or restart interrupted by the budget leaves the simplex
+        // unordered
+        sort_by_score(&mut res);
+        self.recenter(&mut res);
+        if let Some(timer) = timer.as_ref().filter(|_| iters > timed_iters) {
+            timer.end_iteration();
+        }
+        let mut final_simplex: Vec<(DVector<T>, T)> = res
+            .iter()
+            .map(|(x, score)| (to_dynamic(x), sign * *score))
+            .collect();
+        self.to_external_simplex(&mut final_simplex);
+        logging::log_termination(
+            termination_reason,
+            iters,
+            evaluations.get(),
+            final_simplex[0].1,
+        );
+        Ok(OptimizationResult {
+            best_point: final_simplex[0].0.clone(),
+            best_score: final_simplex[0].1,
+            iterations: iters,
+            evaluations: evaluations.get(),
+            restarts,
+            termination_reason,
+            constraint_violation: self.violation(&final_simplex[0].0),
+            cache: cache.map(|cache| cache.stats),
+            timing: timer.map(|timer| timer.stats()),
+            hessian: None,
+            final_simplex,
+            history: self.record_history.then_some(History { entries: history }),
+        })
+    }
+}
+
+impl<T: RealField + Copy> NelderMeadBuilder<T> {
+    /// Finishes the configuration and immediately runs the optimizer,
+    /// see [`NelderMead::minimize_static`].
+    pub fn minimize_static<F, const N: usize>(
+        self,
+        obj_fn: F,
+        x_start: SVector<T, N>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&SVector<T, N>) -> T,
+    {
+        self.build().minimize_static(obj_fn, x_start)
+    }
+}
+
+/// Finds a local minimum of provided objective function operating over
+/// statically sized vectors, using the default parameters of
+/// [`NelderMead`]. See [`NelderMead::minimize_static`].
+pub fn nelder_mead_static<T, F, const N: usize>(
+    obj_fn: F,
+    x_start: SVector<T, N>,
+) -> Result<OptimizationResult<T>, NelderMeadError>
+where
+    T: RealField + Copy,
+    F: FnMut(&SVector<T, N>) -> T,
+{
+    NelderMead::default().minimize_static(obj_fn, x_start)
+}
+
+/// Stable insertion sort of the vertices by their score, in the same
+/// order as the dynamically sized solvers. The simplex is tiny, so this
+/// beats the general purpose sort and never allocates.
+fn sort_by_score<T: RealField + Copy, const N: usize>(res: &mut [(SVector<T, N>, T)]) {
+    for i in 1..res.len() {
+        let mut j = i;
+        while j > 0 && total_cmp(res[j - 1].1, res[j].1) == Ordering::Greater {
+            res.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+/// Copies a statically sized vector to the heap, for the hooks that
+/// operate on [`DVector`].
+fn to_dynamic<T: RealField + Copy, const N: usize>(x: &SVector<T, N>) -> DVector<T> {
+    DVector::from_column_slice(x.as_slice())
+}
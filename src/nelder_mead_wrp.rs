@@ -1,61 +1,226 @@
 use crate::nelder_mead::*;
+use crate::result::OptimizationResult;
+
+use std::cell::RefCell;
+use std::time::Duration;
 
 use nalgebra::DVector;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::wrap_pyfunction;
 use pyo3::types::PyFunction;
 
 
+/// Initial step accepted from Python, either a float or a list of floats
+/// with one step per dimension.
+#[derive(FromPyObject)]
+enum PyStepSize {
+    Uniform(f64),
+    PerDim(Vec<f64>),
+}
+
+impl From<PyStepSize> for StepSize {
+    fn from(step: PyStepSize) -> Self {
+        match step {
+            PyStepSize::Uniform(step) => StepSize::Uniform(step),
+            PyStepSize::PerDim(steps) => StepSize::PerDim(DVector::from(steps)),
+        }
+    }
+}
+
+/// NaN policy accepted from Python, either `"error"`, `"worst"` or a
+/// penalty value.
+#[derive(FromPyObject)]
+enum PyNanHandling {
+    PenaltyValue(f64),
+    Policy(String),
+}
+
+impl TryFrom<PyNanHandling> for NanHandling {
+    type Error = PyErr;
+
+    fn try_from(nan_handling: PyNanHandling) -> PyResult<Self> {
+        match nan_handling {
+            PyNanHandling::PenaltyValue(penalty) => Ok(NanHandling::PenaltyValue(penalty)),
+            PyNanHandling::Policy(policy) => match policy.as_str() {
+                "error" => Ok(NanHandling::Error),
+                "worst" => Ok(NanHandling::TreatAsWorst),
+                _ => Err(PyValueError::new_err(format!(
+                    "nan_handling must be 'error', 'worst' or a float, got '{}'",
+                    policy
+                ))),
+            },
+        }
+    }
+}
+
+/// Python view of [`OptimizationResult`].
+#[pyclass(name = "OptimizationResult", get_all)]
+struct PyOptimizationResult {
+    best_point: Vec<f64>,
+    best_score: f64,
+    iterations: u64,
+    evaluations: u64,
+    termination_reason: String,
+    final_simplex: Vec<(Vec<f64>, f64)>,
+}
+
+#[pymethods]
+impl PyOptimizationResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "OptimizationResult(best_point={:?}, best_score={}, iterations={}, \
+            evaluations={}, termination_reason='{}')",
+            self.best_point,
+            self.best_score,
+            self.iterations,
+            self.evaluations,
+            self.termination_reason
+        )
+    }
+}
+
+impl From<OptimizationResult> for PyOptimizationResult {
+    fn from(results: OptimizationResult) -> Self {
+        PyOptimizationResult {
+            best_point: results.best_point
+                .iter()
+                .cloned()
+                .collect(),
+            best_score: results.best_score,
+            iterations: results.iterations,
+            evaluations: results.evaluations,
+            termination_reason: format!("{:?}", results.termination_reason),
+            final_simplex: results.final_simplex
+                .iter()
+                .map(|(x, score)| (x.iter().cloned().collect(), *score))
+                .collect(),
+        }
+    }
+}
+
 #[pyfunction]
+#[pyo3(signature = (
+    obj_fn,
+    x_start,
+    step = PyStepSize::Uniform(0.1),
+    no_improve_thr = 10e-6,
+    no_improv_break = Some(10),
+    max_iter = 1000,
+    alpha = 1.0,
+    gamma = 2.0,
+    rho = -0.5,
+    sigma = 0.5,
+    maximize = false,
+    initial_simplex = None,
+    target_value = None,
+    max_fun_evals = None,
+    max_duration = None,
+    xatol = None,
+    fatol = None,
+    nan_handling = PyNanHandling::Policy("error".to_string()),
+    adaptive = false
+))]
+#[allow(clippy::too_many_arguments)]
 fn nelder_mead(
-    obj_fn: &PyFunction,
+    obj_fn: &Bound<'_, PyFunction>,
     x_start: Vec<f64>,
-    step: f64,
+    step: PyStepSize,
     no_improve_thr: f64,
-    no_improv_break: u64,
+    no_improv_break: Option<u64>,
     max_iter: u64,
     alpha: f64,
     gamma: f64,
     rho: f64,
-    sigma: f64
-) -> (Vec<f64>, f64) {
+    sigma: f64,
+    maximize: bool,
+    initial_simplex: Option<Vec<Vec<f64>>>,
+    target_value: Option<f64>,
+    max_fun_evals: Option<u64>,
+    max_duration: Option<f64>,
+    xatol: Option<f64>,
+    fatol: Option<f64>,
+    nan_handling: PyNanHandling,
+    adaptive: bool
+) -> PyResult<PyOptimizationResult> {
 
     let x0 = DVector::<f64>::from(x_start);
+    // an exception raised by obj_fn is re-raised once the run is over,
+    // meanwhile obj_fn is not called again and every point scores NaN
+    let py_err: RefCell<Option<PyErr>> = RefCell::new(None);
     let obj_fn_wrp = |x: &DVector<f64>| -> f64 {
+        if py_err.borrow().is_some() {
+            return f64::NAN;
+        }
         let v: Vec<f64> = x.iter()
             .cloned()
             .collect();
-        return obj_fn.call1((v,))
-                .unwrap()
-                .extract::<f64>()
-                .unwrap()
+        let score = obj_fn.call1((v,))
+            .and_then(|score| score.extract::<f64>());
+        match score {
+            Ok(score) => score,
+            Err(err) => {
+                *py_err.borrow_mut() = Some(err);
+                f64::NAN
+            }
+        }
     };
 
-    let results = nelder_mead_algorithm(
-        &obj_fn_wrp, 
-        x0,
-        step, 
-        no_improve_thr, 
-        no_improv_break, 
-        max_iter, 
-        alpha, 
-        gamma, 
-        rho, 
-        sigma
-    );
-
-    return (
-        results.0
-            .iter()
-            .cloned()
-            .collect(), 
-        results.1
-    )
+    let mut builder = NelderMead::builder()
+        .step(step)
+        .no_improve_thr(no_improve_thr)
+        .max_iter(max_iter)
+        .alpha(alpha)
+        .gamma(gamma)
+        // rho follows the sign convention of the reference implementation
+        .rho(-rho)
+        .sigma(sigma)
+        .adaptive(adaptive)
+        .direction(if maximize { Direction::Maximize } else { Direction::Minimize })
+        .nan_handling(nan_handling.try_into()?);
+    builder = match no_improv_break {
+        Some(no_improv_break) => builder.no_improv_break(no_improv_break),
+        None => builder.disable_no_improv_break(),
+    };
+    if let Some(xatol) = xatol {
+        builder = builder.xatol(xatol);
+    }
+    if let Some(fatol) = fatol {
+        builder = builder.fatol(fatol);
+    }
+    if let Some(target_value) = target_value {
+        builder = builder.target_value(target_value);
+    }
+    if let Some(max_fun_evals) = max_fun_evals {
+        builder = builder.max_fun_evals(max_fun_evals);
+    }
+    if let Some(max_duration) = max_duration {
+        let max_duration = Duration::try_from_secs_f64(max_duration)
+            .map_err(|err| PyValueError::new_err(format!("invalid max_duration: {err}")))?;
+        builder = builder.max_duration(max_duration);
+    }
+    let optimizer = builder.build();
+
+    let results = match initial_simplex {
+        Some(simplex) => optimizer.minimize_from_simplex(
+            obj_fn_wrp,
+            simplex.into_iter().map(DVector::from).collect(),
+        ),
+        None => optimizer.minimize(obj_fn_wrp, x0),
+    };
+
+    if let Some(err) = py_err.into_inner() {
+        return Err(err);
+    }
+    results
+        .map(PyOptimizationResult::from)
+        .map_err(|err| PyValueError::new_err(err.to_string()))
 }
 
 #[pymodule]
-fn nelder_mead_optimizer(py: Python, m: &PyModule) -> PyResult<()> {
-    m.add_function(wrap_pyfunction!(nelder_mead, m)?).unwrap();
+fn nelder_mead_optimizer(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(nelder_mead, m)?)?;
+    m.add_class::<PyOptimizationResult>()?;
 
     Ok(())
-}
\ No newline at end of file
+}
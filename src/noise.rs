@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::iter;
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::cache::point_key;
+use crate::error::NelderMeadError;
+use crate::nelder_mead::{is_nan, total_cmp};
+use crate::objective::Evaluator;
+
+/// How the replicated values of a noisy objective are combined into the
+/// value of a point, see [`NelderMeadBuilder::replicate`].
+///
+/// [`NelderMeadBuilder::replicate`]: crate::builder::NelderMeadBuilder::replicate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Aggregate {
+    /// Mean of the values, with the standard error `s / √k` of `k`
+    /// values of sample standard deviation `s`.
+    #[default]
+    Mean,
+    /// Median of the values, robust to the occasional outlier, with the
+    /// large-sample standard error `√(π/2) · s / √k` of normally
+    /// distributed noise.
+    Median,
+}
+
+impl Aggregate {
+    /// Value combining `values`, NaN if any of them is or if there are
+    /// none.
+    pub(crate) fn value<T: RealField + Copy>(self, values: &[T]) -> T {
+        if values.is_empty() || values.iter().any(|v| is_nan(*v)) {
+            return convert(f64::NAN);
+        }
+        let count: T = convert(values.len() as f64);
+        match self {
+            Aggregate::Mean => values.iter().fold(T::zero(), |sum, v| sum + *v) / count,
+            Aggregate::Median => {
+                let mut sorted = values.to_vec();
+                sorted.sort_by(|a, b| total_cmp(*a, *b));
+                let middle = sorted.len() / 2;
+                if sorted.len() % 2 == 1 {
+                    sorted[middle]
+                } else {
+                    (sorted[middle - 1] + sorted[middle]) * convert(0.5)
+                }
+            }
+        }
+    }
+
+    /// Standard error of the value combining `values`, `None` for less
+    /// than two values.
+    pub(crate) fn standard_error<T: RealField + Copy>(self, values: &[T]) -> Option<T> {
+        if values.len() < 2 {
+            return None;
+        }
+        let count: T = convert(values.len() as f64);
+        let mean = values.iter().fold(T::zero(), |sum, v| sum + *v) / count;
+        let squares = values.iter().fold(T::zero(), |sum, v| sum + (*v - mean).powi(2));
+        let error = (squares / (count - T::one()) / count).sqrt();
+        Some(match self {
+            Aggregate::Mean => error,
+            Aggregate::Median => error * T::frac_pi_2().sqrt(),
+        })
+    }
+}
+
+/// Replicated evaluations of a noisy objective, configured with
+/// [`NelderMeadBuilder::replicate`] or
+/// [`NelderMeadBuilder::replicate_adaptive`].
+///
+/// [`NelderMeadBuilder::replicate`]: crate::builder::NelderMeadBuilder::replicate
+/// [`NelderMeadBuilder::replicate_adaptive`]: crate::builder::NelderMeadBuilder::replicate_adaptive
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Replication<T> {
+    pub(crate) count: usize,
+    pub(crate) max_count: usize,
+    pub(crate) target_error: Option<T>,
+    pub(crate) aggregate: Aggregate,
+}
+
+impl<T: RealField + Copy> Replication<T> {
+    /// Checks that every point is evaluated at least once, that the
+    /// adaptive maximum is not below the minimum and that the target
+    /// standard error is positive and finite.
+    pub(crate) fn check(&self) -> Result<(), NelderMeadError> {
+        let target = self.target_error.is_none_or(|target| target > T::zero() && target.is_finite());
+        if self.count == 0 || self.max_count < self.count || !target {
+            return Err(NelderMeadError::InvalidReplication);
+        }
+        Ok(())
+    }
+}
+
+/// Objective evaluating every point several times and returning the
+/// aggregate of all the values drawn at that point so far, so that a
+/// point evaluated again gets a more precise value.
+pub(crate) struct Replicated<T, E> {
+    obj_fn: E,
+    replication: Replication<T>,
+    samples: HashMap<Vec<u64>, Vec<T>>,
+}
+
+impl<T: RealField + Copy, E: Evaluator<T>> Replicated<T, E> {
+    pub(crate) fn new(obj_fn: E, replication: Replication<T>) -> Self {
+        Replicated {
+            obj_fn,
+            replication,
+            samples: HashMap::new(),
+        }
+    }
+
+    /// Whether a point needs another value after `drawn` values in the
+    /// current evaluation, `values` being all those drawn at the point.
+    fn needs_more(&self, values: &[T], drawn: usize) -> bool {
+        let replication = &self.replication;
+        if drawn < replication.count {
+            return true;
+        }
+        replication.target_error.is_some_and(|target| {
+            drawn < replication.max_count
+                && replication
+                    .aggregate
+                    .standard_error(values)
+                    .is_none_or(|error| error > target)
+        })
+    }
+
+    fn values(&self, key: &[u64]) -> &[T] {
+        self.samples.get(key).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl<T: RealField + Copy, E: Evaluator<T>> Evaluator<T> for Replicated<T, E> {
+    fn evaluate_point(&mut self, x: &DVector<T>) -> T {
+        self.evaluate_points(std::slice::from_ref(x))
+            .pop()
+            .unwrap_or_else(|| convert(f64::NAN))
+    }
+
+    /// Draws the values of all points together: the fixed number of
+    /// replicates first, then one more value of every point whose
+    /// standard error is still above the target, until none is.
+    fn evaluate_points(&mut self, xs: &[DVector<T>]) -> Vec<T> {
+        let keys: Vec<Vec<u64>> = xs.iter().map(|x| point_key(x.as_slice(), T::zero())).collect();
+        let mut drawn = vec![0; xs.len()];
+        loop {
+            let needed: Vec<(usize, usize)> = (0..xs.len())
+                .filter(|&i| self.needs_more(self.values(&keys[i]), drawn[i]))
+                .map(|i| (i, self.replication.count.saturating_sub(drawn[i]).max(1)))
+                .collect();
+            if needed.is_empty() {
+                break;
+            }
+            let batch: Vec<DVector<T>> = needed
+                .iter()
+                .flat_map(|&(i, count)| iter::repeat_n(&xs[i], count).cloned())
+                .collect();
+            let values = self.obj_fn.evaluate_points(&batch);
+            if values.len() != batch.len() {
+                return Vec::new();
+            }
+            let mut values = values.into_iter();
+            for &(i, count) in &needed {
+                drawn[i] += count;
+                self.samples.entry(keys[i].clone()).or_default().extend(values.by_ref().take(count));
+            }
+        }
+        keys.iter()
+            .map(|key| self.replication.aggregate.value(self.values(key)))
+            .collect()
+    }
+
+    fn evaluates_batches(&self) -> bool {
+        self.obj_fn.evaluates_batches()
+    }
+
+    fn standard_error(&self, x: &DVector<T>) -> Option<T> {
+        let values = self.values(&point_key(x.as_slice(), T::zero()));
+        self.replication.aggregate.standard_error(values)
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::slice;
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::cache::point_key;
+use crate::random::split_mix;
+
+/// Objective function that can be optimized.
+///
+/// It is implemented for every closure and function taking a
+/// `&DVector<f64>` and returning its score, and can be implemented on
+/// custom types carrying the data the objective depends on (datasets,
+/// model configuration, ...). Since it is evaluated through a shared
+/// reference, the same objective can be reused across multiple runs.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::objective::ObjectiveFunction;
+///
+/// struct LeastSquares {
+///     xs: Vec<f64>,
+///     ys: Vec<f64>,
+/// }
+///
+/// impl ObjectiveFunction for LeastSquares {
+///     fn evaluate(&self, x: &DVector<f64>) -> f64 {
+///         self.xs.iter()
+///             .zip(self.ys.iter())
+///             .map(|(xi, yi)| (x[0] + x[1] * xi - yi).powi(2))
+///             .sum()
+///     }
+/// }
+///
+/// let problem = LeastSquares {
+///     xs: vec![0.0, 1.0, 2.0, 3.0],
+///     ys: vec![1.0, 3.0, 5.0, 7.0],
+/// };
+/// let coarse = NelderMead::builder()
+///     .step(1.0)
+///     .minimize_objective(&problem, dvector![0.0, 0.0])?;
+/// let fine = NelderMead::builder()
+///     .step(0.01)
+///     .minimize_objective(&problem, coarse.best_point)?;
+///
+/// assert!(fine.best_score < 1e-4);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+pub trait ObjectiveFunction<T = f64> {
+    /// Returns the score of the parameter vector `x`.
+    fn evaluate(&self, x: &DVector<T>) -> T;
+}
+
+impl<T, F> ObjectiveFunction<T> for F
+where
+    F: Fn(&DVector<T>) -> T,
+{
+    fn evaluate(&self, x: &DVector<T>) -> T {
+        self(x)
+    }
+}
+
+/// How the solver evaluates the objective: one point at a time, or a
+/// batch of independent points at once, such as the vertices of the
+/// initial simplex or of a shrink step.
+///
+/// It is implemented for every closure and function taking a
+/// `&DVector<f64>`, which evaluates a batch one point after the other,
+/// so it mostly shows up in the bounds of [`NelderMeadIter`]. With the
+/// `rayon` feature, `NelderMead::minimize_parallel` evaluates batches
+/// on all cores.
+///
+/// [`NelderMeadIter`]: crate::nelder_mead_iter::NelderMeadIter
+pub trait Evaluator<T = f64> {
+    /// Returns the value of the objective at `x`.
+    fn evaluate_point(&mut self, x: &DVector<T>) -> T;
+
+    /// Returns the values of the objective at every point of `xs`, in
+    /// the same order. A run fails with [`NelderMeadError::InvalidBatch`]
+    /// if their number differs from the number of points.
+    ///
+    /// [`NelderMeadError::InvalidBatch`]: crate::error::NelderMeadError::InvalidBatch
+    fn evaluate_points(&mut self, xs: &[DVector<T>]) -> Vec<T> {
+        xs.iter().map(|x| self.evaluate_point(x)).collect()
+    }
+
+    /// Whether [`evaluate_points`](Self::evaluate_points) evaluates the
+    /// points together, e.g. in parallel, rather than one after the other.
+    /// The durations recorded with `record_timing` are those of every
+    /// batch then, and of every point otherwise.
+    fn evaluates_batches(&self) -> bool {
+        false
+    }
+
+    /// Standard error of the value returned for `x`, when it aggregates
+    /// replicated evaluations of a noisy objective, see
+    /// [`NelderMeadBuilder::replicate`], and `None` otherwise.
+    ///
+    /// [`NelderMeadBuilder::replicate`]: crate::builder::NelderMeadBuilder::replicate
+    fn standard_error(&self, _x: &DVector<T>) -> Option<T> {
+        None
+    }
+}
+
+impl<T, F> Evaluator<T> for F
+where
+    F: FnMut(&DVector<T>) -> T,
+{
+    fn evaluate_point(&mut self, x: &DVector<T>) -> T {
+        self(x)
+    }
+}
+
+/// Objective evaluating batches of points in parallel with rayon.
+#[cfg(feature = "rayon")]
+pub(crate) struct Parallel<F>(pub(crate) F);
+
+#[cfg(feature = "rayon")]
+impl<T, F> Evaluator<T> for Parallel<F>
+where
+    T: Send + Sync,
+    F: Fn(&DVector<T>) -> T + Sync,
+{
+    fn evaluate_point(&mut self, x: &DVector<T>) -> T {
+        (self.0)(x)
+    }
+
+    fn evaluate_points(&mut self, xs: &[DVector<T>]) -> Vec<T> {
+        use rayon::prelude::*;
+
+        let obj_fn = &self.0;
+        xs.par_iter().map(obj_fn).collect()
+    }
+
+    fn evaluates_batches(&self) -> bool {
+        true
+    }
+}
+
+/// Objective evaluating every point as a batch, of a single point when
+/// the solver needs no more. `mismatch` records a single point for which
+/// it did not return exactly one value.
+pub(crate) struct Batch<F> {
+    pub(crate) obj_fn: F,
+    pub(crate) mismatch: bool,
+}
+
+impl<T, F> Evaluator<T> for Batch<F>
+where
+    T: RealField + Copy,
+    F: FnMut(&[DVector<T>]) -> Vec<T>,
+{
+    fn evaluate_point(&mut self, x: &DVector<T>) -> T {
+        match (self.obj_fn)(slice::from_ref(x))[..] {
+            [value] => value,
+            _ => {
+                self.mismatch = true;
+                convert(f64::NAN)
+            }
+        }
+    }
+
+    fn evaluate_points(&mut self, xs: &[DVector<T>]) -> Vec<T> {
+        (self.obj_fn)(xs)
+    }
+
+    fn evaluates_batches(&self) -> bool {
+        true
+    }
+}
+
+/// Objective taking the seed of its random numbers along with the point.
+/// The `k`-th evaluation of every point gets the `k`-th seed derived from
+/// `seed`, so that all points share their random numbers.
+pub(crate) struct Seeded<F> {
+    obj_fn: F,
+    seed: u64,
+    // number of evaluations of every point so far
+    counts: HashMap<Vec<u64>, u64>,
+}
+
+impl<F> Seeded<F> {
+    pub(crate) fn new(obj_fn: F, seed: u64) -> Self {
+        Seeded {
+            obj_fn,
+            seed,
+            counts: HashMap::new(),
+        }
+    }
+}
+
+impl<T, F> Evaluator<T> for Seeded<F>
+where
+    T: RealField + Copy,
+    F: FnMut(&DVector<T>, u64) -> T,
+{
+    fn evaluate_point(&mut self, x: &DVector<T>) -> T {
+        let count = self.counts.entry(point_key(x.as_slice(), T::zero())).or_default();
+        let seed = split_mix(self.seed, *count);
+        *count += 1;
+        (self.obj_fn)(x, seed)
+    }
+}
+
+/// Objective borrowed by one of several consecutive runs.
+pub(crate) struct ByRef<'a, E: ?Sized>(pub(crate) &'a mut E);
+
+impl<T, E: Evaluator<T> + ?Sized> Evaluator<T> for ByRef<'_, E> {
+    fn evaluate_point(&mut self, x: &DVector<T>) -> T {
+        self.0.evaluate_point(x)
+    }
+
+    fn evaluate_points(&mut self, xs: &[DVector<T>]) -> Vec<T> {
+        self.0.evaluate_points(xs)
+    }
+
+    fn evaluates_batches(&self) -> bool {
+        self.0.evaluates_batches()
+    }
+
+    fn standard_error(&self, x: &DVector<T>) -> Option<T> {
+        self.0.standard_error(x)
+    }
+}
+
+/// Outcome of an objective function that can fail, optimized with
+/// [`NelderMead::try_minimize`].
+///
+/// It is implemented for `Result<T, E>` with any displayable error and
+/// for `Option<T>`, so failed evaluations do not have to be encoded as
+/// magic values. How failures are handled is set with
+/// [`NelderMeadBuilder::failure_handling`].
+///
+/// [`NelderMead::try_minimize`]: crate::nelder_mead::NelderMead::try_minimize
+/// [`NelderMeadBuilder::failure_handling`]: crate::builder::NelderMeadBuilder::failure_handling
+pub trait FallibleScore<T = f64> {
+    /// Returns the score, or the description of the failure.
+    fn into_score(self) -> Result<T, String>;
+}
+
+impl<T, E: fmt::Display> FallibleScore<T> for Result<T, E> {
+    fn into_score(self) -> Result<T, String> {
+        self.map_err(|err| err.to_string())
+    }
+}
+
+impl<T> FallibleScore<T> for Option<T> {
+    fn into_score(self) -> Result<T, String> {
+        self.ok_or_else(|| "objective function returned no value".to_string())
+    }
+}
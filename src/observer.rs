@@ -0,0 +1,222 @@
+use std::fmt;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use nalgebra::RealField;
+
+use crate::termination::TerminationState;
+
+/// Simplex operation performed by an iteration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Move {
+    /// The worst vertex was replaced by its reflection.
+    Reflection,
+    /// The worst vertex was replaced by the expanded reflection.
+    Expansion,
+    /// The worst vertex was moved towards the centroid.
+    Contraction,
+    /// The worst vertex was replaced by the reflected point moved towards
+    /// the centroid, see [`ContractionMode::InsideOutside`].
+    ///
+    /// [`ContractionMode::InsideOutside`]: crate::nelder_mead::ContractionMode::InsideOutside
+    OutsideContraction,
+    /// Several of the worst vertices were replaced at once, each by its
+    /// reflection, expansion or contraction, see
+    /// [`NelderMeadBuilder::parallel_points`].
+    ///
+    /// [`NelderMeadBuilder::parallel_points`]: crate::builder::NelderMeadBuilder::parallel_points
+    MultiPoint,
+    /// Every vertex but the best one was moved towards the best one.
+    Shrink,
+    /// The simplex had collapsed or stagnated and was rebuilt around the
+    /// best vertex, see [`NelderMeadBuilder::restart_on_degeneracy`] and
+    /// [`NelderMeadBuilder::oriented_restart`].
+    ///
+    /// [`NelderMeadBuilder::restart_on_degeneracy`]: crate::builder::NelderMeadBuilder::restart_on_degeneracy
+    /// [`NelderMeadBuilder::oriented_restart`]: crate::builder::NelderMeadBuilder::oriented_restart
+    Restart,
+}
+
+/// Hook invoked with the state of the run once per iteration, e.g. to log
+/// progress, plot the simplex or feed a GUI.
+///
+/// The observer sees the ordered simplex at the start of every iteration,
+/// from the initial simplex to the final one, along with the [`Move`]
+/// that produced it. A run stopped by `max_fun_evals` or `max_duration`
+/// in the middle of an iteration skips the last report.
+///
+/// It is implemented for every closure taking a [`TerminationState`];
+/// closures collecting data need interior mutability since the observer
+/// is shared with the configuration.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Mutex;
+///
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::termination::TerminationState;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// }
+/// let scores = std::sync::Arc::new(Mutex::new(Vec::new()));
+/// let trace = scores.clone();
+/// let results = NelderMead::builder()
+///     .observer(move |state: &TerminationState| {
+///         trace.lock().unwrap().push(state.best_score());
+///     })
+///     .minimize(&f, dvector![0.0, 0.0])?;
+///
+/// let scores = scores.lock().unwrap();
+/// assert!(scores.windows(2).all(|w| w[1] <= w[0]));
+/// assert_eq!(*scores.last().unwrap(), results.best_score);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+pub trait Observer<T = f64> {
+    /// Called with the ordered simplex at the start of every iteration.
+    fn observe(&self, state: &TerminationState<T>);
+}
+
+impl<T, F> Observer<T> for F
+where
+    F: Fn(&TerminationState<T>),
+{
+    fn observe(&self, state: &TerminationState<T>) {
+        self(state)
+    }
+}
+
+/// Hook invoked with the state of the run once per iteration, right after
+/// the [`Observer`], that can abort the run early, e.g. when an external
+/// process signals convergence or a downstream metric is satisfied.
+///
+/// Returning [`ControlFlow::Break`] stops the run with
+/// [`TerminationReason::StoppedByCallback`], the result then holds the
+/// best point found so far. It is implemented for every closure taking a
+/// [`TerminationState`] and returning either a [`ControlFlow`] or a
+/// `bool`, `true` meaning stop.
+///
+/// [`TerminationReason::StoppedByCallback`]: crate::result::TerminationReason::StoppedByCallback
+///
+/// # Examples
+///
+/// ```
+/// use std::ops::ControlFlow;
+///
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::result::TerminationReason;
+/// use nelder_mead_optimizer::termination::TerminationState;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// }
+/// let results = NelderMead::builder()
+///     .callback(|state: &TerminationState| {
+///         if state.best_score() < 1e-2 {
+///             ControlFlow::Break(())
+///         } else {
+///             ControlFlow::Continue(())
+///         }
+///     })
+///     .minimize(&f, dvector![0.0, 0.0])?;
+///
+/// assert_eq!(results.termination_reason, TerminationReason::StoppedByCallback);
+/// assert!(results.best_score < 1e-2);
+///
+/// let results = NelderMead::builder()
+///     .callback(|state: &TerminationState| state.iterations() == 5)
+///     .minimize(&f, dvector![0.0, 0.0])?;
+///
+/// assert_eq!(results.iterations, 5);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+pub trait Callback<T = f64> {
+    /// Called with the ordered simplex at the start of every iteration,
+    /// [`ControlFlow::Break`] stops the run.
+    fn call(&self, state: &TerminationState<T>) -> ControlFlow<()>;
+}
+
+impl<T, F, R> Callback<T> for F
+where
+    F: Fn(&TerminationState<T>) -> R,
+    R: IntoControlFlow,
+{
+    fn call(&self, state: &TerminationState<T>) -> ControlFlow<()> {
+        self(state).into_control_flow()
+    }
+}
+
+/// Value a [`Callback`] closure may return.
+pub trait IntoControlFlow {
+    /// Whether the run goes on.
+    fn into_control_flow(self) -> ControlFlow<()>;
+}
+
+impl IntoControlFlow for ControlFlow<()> {
+    fn into_control_flow(self) -> ControlFlow<()> {
+        self
+    }
+}
+
+/// `true` stops the run.
+impl IntoControlFlow for bool {
+    fn into_control_flow(self) -> ControlFlow<()> {
+        if self {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+/// Prints the best score and the vertices of the simplex to stdout every
+/// iteration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PrintProgress;
+
+impl<T: RealField + Copy> Observer<T> for PrintProgress {
+    fn observe(&self, state: &TerminationState<T>) {
+        println!("Iter {}, best so far: {}", state.iterations(), state.best_score());
+        println!("Points:");
+        for (x, _) in state.vertices() {
+            let coords: Vec<String> = x.iter().map(|xi| xi.to_string()).collect();
+            println!("{}", coords.join(","));
+        }
+    }
+}
+
+/// Observer stored in the optimizer configuration.
+#[derive(Clone)]
+pub(crate) struct SharedObserver<T>(pub(crate) Arc<dyn Observer<T> + Send + Sync>);
+
+impl<T> fmt::Debug for SharedObserver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Observer")
+    }
+}
+
+impl<T> PartialEq for SharedObserver<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Callback stored in the optimizer configuration.
+#[derive(Clone)]
+pub(crate) struct SharedCallback<T>(pub(crate) Arc<dyn Callback<T> + Send + Sync>);
+
+impl<T> fmt::Debug for SharedCallback<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+impl<T> PartialEq for SharedCallback<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
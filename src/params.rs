@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::ops::Index;
+
+use nalgebra::{DVector, RealField};
+
+use crate::builder::NelderMeadBuilder;
+use crate::nelder_mead::NelderMead;
+use crate::error::NelderMeadError;
+use crate::result::OptimizationResult;
+
+/// Named view of a parameter vector passed to the objective function by
+/// [`NelderMead::minimize_named`].
+#[derive(Debug, Clone, Copy)]
+pub struct Params<'a, T = f64> {
+    names: &'a [String],
+    values: &'a DVector<T>,
+}
+
+impl<'a, T: RealField + Copy> Params<'a, T> {
+    /// Returns the value of the parameter called `name`, if there is one.
+    pub fn get(&self, name: &str) -> Option<T> {
+        self.names
+            .iter()
+            .position(|n| n == name)
+            .map(|i| self.values[i])
+    }
+
+    /// Names of the parameters, in the order they were declared.
+    pub fn names(&self) -> &'a [String] {
+        self.names
+    }
+
+    /// Underlying parameter vector.
+    pub fn values(&self) -> &'a DVector<T> {
+        self.values
+    }
+}
+
+impl<T: RealField + Copy> Index<&str> for Params<'_, T> {
+    type Output = T;
+
+    /// # Panics
+    ///
+    /// Panics if there is no parameter called `name`.
+    fn index(&self, name: &str) -> &T {
+        let i = self.names
+            .iter()
+            .position(|n| n == name)
+            .unwrap_or_else(|| panic!("unknown parameter '{}'", name));
+        &self.values[i]
+    }
+}
+
+/// Outcome of an optimization run over named parameters.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct NamedOptimizationResult<T = f64> {
+    /// Best value found for every parameter, by name.
+    pub best_params: HashMap<String, T>,
+    /// Result of the underlying run, with parameters in declaration order.
+    pub result: OptimizationResult<T>,
+}
+
+impl<T: RealField + Copy> NelderMead<T> {
+    /// Same as [`NelderMead::minimize`], but parameters are declared and
+    /// accessed by name instead of by position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    /// use nelder_mead_optimizer::params::Params;
+    ///
+    /// let results = NelderMead::builder().minimize_named(
+    ///     |p: &Params| (p["alpha"] - 0.5).powi(2) + (p["beta"] - 1.2).powi(2),
+    ///     &[("alpha", 0.0), ("beta", 0.0)],
+    /// )?;
+    ///
+    /// assert!((results.best_params["alpha"] - 0.5).abs() < 1e-2);
+    /// assert!((results.best_params["beta"] - 1.2).abs() < 1e-2);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn minimize_named<F>(
+        &self,
+        mut obj_fn: F,
+        x_start: &[(&str, T)],
+    ) -> Result<NamedOptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&Params<T>) -> T,
+    {
+        let names: Vec<String> = x_start
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        let x0 = DVector::from_iterator(x_start.len(), x_start.iter().map(|(_, v)| *v));
+
+        let result = self.minimize(
+            |x: &DVector<T>| obj_fn(&Params { names: &names, values: x }),
+            x0,
+        )?;
+
+        Ok(NamedOptimizationResult {
+            best_params: names
+                .into_iter()
+                .zip(result.best_point.iter().cloned())
+                .collect(),
+            result,
+        })
+    }
+}
+
+impl<T: RealField + Copy> NelderMeadBuilder<T> {
+    /// Finishes the configuration and immediately runs the optimizer,
+    /// see [`NelderMead::minimize_named`].
+    pub fn minimize_named<F>(
+        self,
+        obj_fn: F,
+        x_start: &[(&str, T)],
+    ) -> Result<NamedOptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&Params<T>) -> T,
+    {
+        self.build().minimize_named(obj_fn, x_start)
+    }
+}
@@ -0,0 +1,617 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::logging;
+use crate::nelder_mead::{is_nan, total_cmp, Direction, NanHandling, StepSize};
+#[cfg(feature = "rayon")]
+use crate::objective::Parallel;
+use crate::objective::{Batch, ByRef, Evaluator, ObjectiveFunction};
+use crate::observer::{Observer, SharedObserver};
+use crate::progress::Budget;
+use crate::random::Rng;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationCriterion, TerminationState};
+
+/// Particle swarm optimization, a population-based global optimizer in
+/// which every particle flies through the parameter space, pulled towards
+/// its own best point and the best point of the swarm.
+///
+/// Every iteration, the velocity of each particle becomes `w v + c1 r1
+/// (p - x) + c2 r2 (g - x)`, `w` being the inertia, `c1` and `c2` the
+/// cognitive and social weights, `p` the best point of the particle, `g`
+/// the best point of the swarm and `r1`, `r2` uniform random numbers drawn
+/// for every coordinate. The particles all move, then are evaluated
+/// together as one batch, so that [`ParticleSwarm::minimize_batch`] and
+/// `ParticleSwarm::minimize_parallel` evaluate the whole swarm with a
+/// single call. The initial swarm is drawn uniformly within the bounds, or
+/// within the step around `x_start` without bounds, and includes `x_start`
+/// itself. Particles reaching a bound stop there along that coordinate. The
+/// run is reproducible with [`ParticleSwarmBuilder::seed`].
+///
+/// The run converges once every particle is within `xtol` of the best
+/// point along every parameter, or once the scores of the best points of
+/// the particles lie within `ftol`, relative to the best score or
+/// absolutely below 1. Runs are reported as an [`OptimizationResult`],
+/// whose `final_simplex` holds the `n + 1` best points of the particles.
+/// The [`Observer`] and the [`TerminationCriterion`] are given the same
+/// points after every iteration. The initial swarm is always evaluated in
+/// full, the last iteration only as far as `max_fun_evals` allows.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::DVector;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::particle_swarm::ParticleSwarm;
+/// use nelder_mead_optimizer::test_functions::ackley;
+///
+/// let x_start = DVector::from_element(3, 2.5);
+/// let (lower, upper) = (DVector::from_element(3, -5.0), DVector::from_element(3, 5.0));
+/// let mut calls = 0;
+/// let swarm = ParticleSwarm::builder()
+///     .bounds(lower, upper)
+///     .swarm_size(30)
+///     .seed(11)
+///     .minimize_batch(
+///         |xs: &[DVector<f64>]| {
+///             calls += 1;
+///             xs.iter().map(ackley).collect()
+///         },
+///         x_start.clone(),
+///     )?;
+/// let plain = NelderMead::builder().minimize(ackley, x_start)?;
+///
+/// assert!(swarm.best_score < 1e-6);
+/// assert!(plain.best_score > 1.0);
+/// assert_eq!(calls, swarm.iterations + 1);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct ParticleSwarm<T = f64> {
+    pub(crate) step: StepSize<T>,
+    pub(crate) swarm_size: Option<usize>,
+    pub(crate) inertia: T,
+    pub(crate) cognitive: T,
+    pub(crate) social: T,
+    pub(crate) seed: Option<u64>,
+    pub(crate) xtol: T,
+    pub(crate) ftol: T,
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) bounds: Option<(DVector<T>, DVector<T>)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for ParticleSwarm<T> {
+    fn default() -> Self {
+        ParticleSwarm {
+            step: StepSize::Uniform(convert(1.0)),
+            swarm_size: None,
+            inertia: convert(0.7298),
+            cognitive: convert(1.49618),
+            social: convert(1.49618),
+            seed: None,
+            xtol: convert(1e-8),
+            ftol: convert(1e-10),
+            max_iter: 1000,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            bounds: None,
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`ParticleSwarm`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `step`                  | 1                    |
+/// | `swarm_size`            | 10 + 2 sqrt(n)       |
+/// | `inertia`               | 0.7298               |
+/// | `cognitive`             | 1.49618              |
+/// | `social`                | 1.49618              |
+/// | `seed`                  | random               |
+/// | `xtol`                  | 1e-8                 |
+/// | `ftol`                  | 1e-10                |
+/// | `max_iter`              | 1000                 |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `bounds`                | none                 |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct ParticleSwarmBuilder<T = f64> {
+    config: ParticleSwarm<T>,
+}
+
+impl<T: RealField + Copy> Default for ParticleSwarmBuilder<T> {
+    fn default() -> Self {
+        ParticleSwarmBuilder {
+            config: ParticleSwarm::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> ParticleSwarmBuilder<T> {
+    /// Half-width of the region around `x_start` the initial swarm is drawn
+    /// from, either a single value used for every dimension or a
+    /// [`StepSize`]. Ignored with bounds, within which the swarm is drawn
+    /// instead.
+    pub fn step(mut self, step: impl Into<StepSize<T>>) -> Self {
+        self.config.step = step.into();
+        self
+    }
+
+    /// Number of particles, at least `n + 1`. The default is raised to
+    /// `n + 1` in high dimensions.
+    pub fn swarm_size(mut self, swarm_size: usize) -> Self {
+        self.config.swarm_size = Some(swarm_size);
+        self
+    }
+
+    /// Share `w` of its velocity a particle keeps, within `[0, 1)`.
+    pub fn inertia(mut self, inertia: T) -> Self {
+        self.config.inertia = inertia;
+        self
+    }
+
+    /// Weight `c1` of the pull towards the best point of the particle, not
+    /// negative.
+    pub fn cognitive(mut self, cognitive: T) -> Self {
+        self.config.cognitive = cognitive;
+        self
+    }
+
+    /// Weight `c2` of the pull towards the best point of the swarm, not
+    /// negative.
+    pub fn social(mut self, social: T) -> Self {
+        self.config.social = social;
+        self
+    }
+
+    /// Seed of the random numbers, making the run reproducible. Without a
+    /// seed every run draws different numbers.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Distance of every particle from the best point below which the run
+    /// converges.
+    pub fn xtol(mut self, xtol: T) -> Self {
+        self.config.xtol = xtol;
+        self
+    }
+
+    /// Spread of the scores of the best points of the particles below
+    /// which the run converges, relative to the best score, absolute below
+    /// 1.
+    pub fn ftol(mut self, ftol: T) -> Self {
+        self.config.ftol = ftol;
+        self
+    }
+
+    /// Maximum number of iterations.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Lower and upper bounds of every parameter, within which the initial
+    /// swarm is drawn and the particles stay. The starting point is clipped
+    /// into them, and the velocities are limited to their width.
+    pub fn bounds(mut self, lower: DVector<T>, upper: DVector<T>) -> Self {
+        self.config.bounds = Some((lower, upper));
+        self
+    }
+
+    /// Additional stopping rule, checked after every iteration next to the
+    /// built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every iteration with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> ParticleSwarm<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`ParticleSwarm::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`ParticleSwarm::minimize_batch`].
+    pub fn minimize_batch<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&[DVector<T>]) -> Vec<T>,
+    {
+        self.build().minimize_batch(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`ParticleSwarm::minimize_parallel`].
+    #[cfg(feature = "rayon")]
+    pub fn minimize_parallel<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        T: Send + Sync,
+        F: Fn(&DVector<T>) -> T + Sync,
+    {
+        self.build().minimize_parallel(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`ParticleSwarm::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, x_start)
+    }
+}
+
+impl<T: RealField + Copy> ParticleSwarm<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> ParticleSwarmBuilder<T> {
+        ParticleSwarmBuilder::default()
+    }
+
+    /// Finds the minimum (or maximum, depending on the configured
+    /// [`Direction`]) of the objective function, within the bounds or
+    /// around `x_start`.
+    ///
+    /// Fails if `x_start` is empty, if the swarm has fewer than `n + 1`
+    /// particles, if the inertia is not within `[0, 1)` or a weight is
+    /// negative, if `xtol` or `ftol` is not positive and finite, if the
+    /// step or the bounds do not fit `x_start`, or if the objective function
+    /// returns NaN under [`NanHandling::Error`].
+    pub fn minimize<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.minimize_with(obj_fn, x_start)
+    }
+
+    /// Same as [`ParticleSwarm::minimize`], but optimizes a batch objective,
+    /// taking several points at once and returning their values in the
+    /// same order. The whole swarm is evaluated with a single call every
+    /// iteration.
+    ///
+    /// Fails with [`NelderMeadError::InvalidBatch`] if the objective does
+    /// not return one value per point.
+    pub fn minimize_batch<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&[DVector<T>]) -> Vec<T>,
+    {
+        let mut batch = Batch { obj_fn, mismatch: false };
+        self.minimize_with(ByRef(&mut batch), x_start)
+    }
+
+    /// Same as [`ParticleSwarm::minimize`], but evaluates the particles of
+    /// every iteration in parallel on the rayon thread pool. The result is
+    /// the same as with [`ParticleSwarm::minimize`] for the same seed.
+    #[cfg(feature = "rayon")]
+    pub fn minimize_parallel<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        T: Send + Sync,
+        F: Fn(&DVector<T>) -> T + Sync,
+    {
+        self.minimize_with(Parallel(obj_fn), x_start)
+    }
+
+    /// Same as [`ParticleSwarm::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), x_start)
+    }
+
+    /// Same as [`ParticleSwarm::minimize`], with the objective evaluated by
+    /// `evaluator`.
+    fn minimize_with<E>(
+        &self,
+        mut evaluator: E,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        E: Evaluator<T>,
+    {
+        let dim = x_start.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        let default_size = 10 + (2.0 * (dim as f64).sqrt()) as usize;
+        let size = self.swarm_size.unwrap_or(default_size.max(dim + 1));
+        if size <= dim {
+            return Err(NelderMeadError::InvalidPopulation);
+        }
+        let weights = T::zero() <= self.inertia
+            && self.inertia < T::one()
+            && [self.cognitive, self.social].iter().all(|w| T::zero() <= *w && w.is_finite());
+        if !weights {
+            return Err(NelderMeadError::InvalidParticleSwarm);
+        }
+        if ![self.xtol, self.ftol].iter().all(|tol| T::zero() < *tol && tol.is_finite()) {
+            return Err(NelderMeadError::InvalidTolerances);
+        }
+        self.step.check_dim(dim)?;
+        let mut x_start = x_start;
+        if let Some((lower, upper)) = &self.bounds {
+            if lower.len() != dim
+                || upper.len() != dim
+                || lower.iter().zip(upper.iter()).any(|(l, u)| l > u || is_nan(*l) || is_nan(*u))
+            {
+                return Err(NelderMeadError::InvalidBounds);
+            }
+            x_start = x_start.sup(lower).inf(upper);
+        }
+
+        let start = Instant::now();
+        let sign = self.direction.sign();
+        let mut rng = Rng::seeded(self.seed);
+        let mut positions = vec![x_start.clone()];
+        while positions.len() < size {
+            positions.push(DVector::from_fn(dim, |i, _| self.draw(&mut rng, &x_start, i)));
+        }
+        let half: T = convert(0.5);
+        let mut velocities: Vec<DVector<T>> = positions
+            .iter()
+            .map(|x| DVector::from_fn(dim, |i, _| (self.draw(&mut rng, &x_start, i) - x[i]) * half))
+            .collect();
+        let mut evaluations = 0;
+        let scores = self.evaluate(&mut evaluator, &positions, &mut evaluations, sign, false)?;
+        let mut personal: Vec<(DVector<T>, T)> = positions.iter().cloned().zip(scores).collect();
+        let mut global = best_index(&personal);
+        let mut iterations = 0;
+
+        let termination_reason = loop {
+            if iterations >= self.max_iter {
+                break TerminationReason::MaxIter;
+            }
+            if self.max_fun_evals.is_some_and(|limit| evaluations >= limit) {
+                break TerminationReason::MaxFunEvals;
+            }
+            if self.max_duration.is_some_and(|limit| start.elapsed() >= limit) {
+                break TerminationReason::TimedOut;
+            }
+
+            let best = personal[global].0.clone();
+            for (k, (x, v)) in positions.iter_mut().zip(&mut velocities).enumerate() {
+                for i in 0..dim {
+                    let r1: T = convert(rng.uniform());
+                    let r2: T = convert(rng.uniform());
+                    v[i] = self.inertia * v[i]
+                        + self.cognitive * r1 * (personal[k].0[i] - x[i])
+                        + self.social * r2 * (best[i] - x[i]);
+                    if let Some((lower, upper)) = &self.bounds {
+                        let width = upper[i] - lower[i];
+                        v[i] = v[i].max(-width).min(width);
+                    }
+                    x[i] += v[i];
+                    if let Some((lower, upper)) = &self.bounds {
+                        if x[i] < lower[i] || x[i] > upper[i] {
+                            x[i] = x[i].max(lower[i]).min(upper[i]);
+                            v[i] = T::zero();
+                        }
+                    }
+                }
+            }
+            let scores = self.evaluate(&mut evaluator, &positions, &mut evaluations, sign, true)?;
+            let evaluated = scores.len();
+            for (k, score) in scores.into_iter().enumerate() {
+                if score < personal[k].1 {
+                    personal[k] = (positions[k].clone(), score);
+                    if score < personal[global].1 {
+                        global = k;
+                    }
+                }
+            }
+            iterations += 1;
+            if evaluated < size {
+                break TerminationReason::MaxFunEvals;
+            }
+
+            let simplex = best_points(&personal, dim + 1);
+            let state = TerminationState {
+                iterations,
+                evaluations,
+                elapsed: start.elapsed(),
+                simplex: &simplex,
+                sign,
+                last_move: None,
+                budget: Budget {
+                    max_iter: self.max_iter,
+                    max_fun_evals: self.max_fun_evals,
+                    max_duration: self.max_duration,
+                },
+            };
+            logging::log_iteration(&state);
+            if let Some(observer) = &self.observer {
+                observer.0.observe(&state);
+            }
+            if let Some(criterion) = &self.termination {
+                if criterion.0.should_stop(&state) {
+                    break TerminationReason::Criterion;
+                }
+            }
+
+            let best = &personal[global];
+            let radius = positions.iter().fold(T::zero(), |max, x| max.max((x - &best.0).amax()));
+            let worst = personal.iter().fold(best.1, |max, (_, score)| max.max(*score));
+            if radius < self.xtol || worst - best.1 <= self.ftol * best.1.abs().max(T::one()) {
+                break TerminationReason::Converged;
+            }
+        };
+
+        Ok(OptimizationResult::from_simplex(
+            best_points(&personal, dim + 1).to_pairs(T::one()),
+            sign,
+            iterations,
+            evaluations,
+            0,
+            termination_reason,
+            None,
+        ))
+    }
+
+    /// Scores of `points`, evaluated as one batch. With `limited`, only the
+    /// points within the remaining evaluation budget are evaluated.
+    fn evaluate<E>(
+        &self,
+        evaluator: &mut E,
+        points: &[DVector<T>],
+        evaluations: &mut u64,
+        sign: T,
+        limited: bool,
+    ) -> Result<Vec<T>, NelderMeadError>
+    where
+        E: Evaluator<T>,
+    {
+        let count = match self.max_fun_evals {
+            Some(limit) if limited => points.len().min(limit.saturating_sub(*evaluations) as usize),
+            _ => points.len(),
+        };
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let values = evaluator.evaluate_points(&points[..count]);
+        if values.len() != count {
+            return Err(NelderMeadError::InvalidBatch);
+        }
+        *evaluations += count as u64;
+        values.into_iter().map(|value| self.nan_handling.score(value, sign)).collect()
+    }
+
+    /// Coordinate `i` of a random point, uniform within the bounds or
+    /// within the step around `x_start`.
+    fn draw(&self, rng: &mut Rng, x_start: &DVector<T>, i: usize) -> T {
+        let u: T = convert(rng.uniform());
+        match &self.bounds {
+            Some((lower, upper)) => lower[i] + u * (upper[i] - lower[i]),
+            None => {
+                let step = self.step.offset(i, x_start[i]);
+                x_start[i] + (u + u - T::one()) * step
+            }
+        }
+    }
+}
+
+/// Index of the best of the points.
+fn best_index<T: RealField + Copy>(points: &[(DVector<T>, T)]) -> usize {
+    points
+        .iter()
+        .enumerate()
+        .min_by(|a, b| total_cmp(a.1 .1, b.1 .1))
+        .map_or(0, |(index, _)| index)
+}
+
+/// Simplex of the `count` best of the points, ordered from the best one.
+fn best_points<T: RealField + Copy>(points: &[(DVector<T>, T)], count: usize) -> Simplex<T> {
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|a, b| total_cmp(points[*a].1, points[*b].1));
+    order.truncate(count);
+    Simplex::from_slices(order.iter().map(|&k| (points[k].0.as_slice(), points[k].1)))
+}
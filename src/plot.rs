@@ -0,0 +1,236 @@
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+
+use nalgebra::DVector;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+use crate::error::NelderMeadError;
+
+/// Renders the contour lines of a 2-parameter objective function together
+/// with the path of the simplex, to a PNG or an SVG file depending on the
+/// extension of the path.
+///
+/// The simplices are drawn as closed outlines, later ones darker, and the
+/// best vertex of every simplex is joined into a red path. The simplices
+/// are expected to be ordered from their best vertex, as the ones handed
+/// to an [`Observer`]. No text is drawn, so no font is needed.
+///
+/// [`Observer`]: crate::observer::Observer
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+///
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::plot::ContourPlot;
+/// use nelder_mead_optimizer::termination::TerminationState;
+///
+/// fn rosenbrock(x: &DVector<f64>) -> f64 {
+///     (1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0].powi(2)).powi(2)
+/// }
+/// let simplices = Arc::new(Mutex::new(Vec::new()));
+/// let trace = simplices.clone();
+/// NelderMead::builder()
+///     .step(0.5)
+///     .observer(move |state: &TerminationState| {
+///         trace.lock().unwrap().push(state.vertices().map(|(x, _)| x.clone_owned()).collect());
+///     })
+///     .minimize(&rosenbrock, dvector![-1.5, 2.0])?;
+///
+/// let path = std::env::temp_dir().join("nelder_mead_rosenbrock.svg");
+/// ContourPlot::new(-2.0..2.0, -1.0..3.0)
+///     .levels(20)
+///     .draw(rosenbrock, &simplices.lock().unwrap(), &path)?;
+///
+/// assert!(path.exists());
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContourPlot {
+    x_range: Range<f64>,
+    y_range: Range<f64>,
+    size: (u32, u32),
+    levels: usize,
+    resolution: usize,
+}
+
+impl ContourPlot {
+    /// Plot of the given region of the parameter space, 800x800 pixels
+    /// with 15 contour levels evaluated on a 200x200 grid.
+    pub fn new(x_range: Range<f64>, y_range: Range<f64>) -> Self {
+        ContourPlot {
+            x_range,
+            y_range,
+            size: (800, 800),
+            levels: 15,
+            resolution: 200,
+        }
+    }
+
+    /// Size of the image in pixels.
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    /// Number of contour lines. They are placed at evenly spaced quantiles
+    /// of the objective over the grid, so steep functions remain readable.
+    pub fn levels(mut self, levels: usize) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// Number of grid cells along each axis the objective is evaluated on.
+    pub fn resolution(mut self, resolution: usize) -> Self {
+        self.resolution = resolution.max(1);
+        self
+    }
+
+    /// Evaluates `obj_fn` over the grid and writes the plot to `path`, as
+    /// SVG when its extension is `svg` and as PNG otherwise.
+    ///
+    /// Fails with [`NelderMeadError::Plot`] if a vertex does not have two
+    /// coordinates or the image cannot be written.
+    pub fn draw<F, P>(
+        &self,
+        mut obj_fn: F,
+        simplices: &[Vec<DVector<f64>>],
+        path: P,
+    ) -> Result<(), NelderMeadError>
+    where
+        F: FnMut(&DVector<f64>) -> f64,
+        P: AsRef<Path>,
+    {
+        if simplices.iter().flatten().any(|x| x.len() != 2) {
+            return Err(NelderMeadError::Plot("vertices must have 2 coordinates".to_string()));
+        }
+
+        let n = self.resolution;
+        let grid_x = grid(&self.x_range, n);
+        let grid_y = grid(&self.y_range, n);
+        let values: Vec<Vec<f64>> = grid_x
+            .iter()
+            .map(|&x| grid_y.iter().map(|&y| obj_fn(&DVector::from_vec(vec![x, y]))).collect())
+            .collect();
+
+        let mut finite: Vec<f64> = values.iter().flatten().copied().filter(|v| v.is_finite()).collect();
+        finite.sort_by(f64::total_cmp);
+        let levels: Vec<f64> = if finite.is_empty() {
+            Vec::new()
+        } else {
+            (1..=self.levels)
+                .map(|k| finite[k * (finite.len() - 1) / (self.levels + 1)])
+                .collect()
+        };
+
+        let mut segments = Vec::new();
+        for (k, &level) in levels.iter().enumerate() {
+            let color = level_color(k, levels.len());
+            for i in 0..n {
+                for j in 0..n {
+                    let corners = [
+                        (grid_x[i], grid_y[j], values[i][j]),
+                        (grid_x[i + 1], grid_y[j], values[i + 1][j]),
+                        (grid_x[i + 1], grid_y[j + 1], values[i + 1][j + 1]),
+                        (grid_x[i], grid_y[j + 1], values[i][j + 1]),
+                    ];
+                    let crossings = cell_crossings(&corners, level);
+                    for pair in crossings.chunks_exact(2) {
+                        segments.push((pair[0], pair[1], color));
+                    }
+                }
+            }
+        }
+
+        let path = path.as_ref();
+        let svg = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+        if svg {
+            let root = SVGBackend::new(path, self.size).into_drawing_area();
+            self.render(root, &segments, simplices).map_err(plot_error)
+        } else {
+            let root = BitMapBackend::new(path, self.size).into_drawing_area();
+            self.render(root, &segments, simplices).map_err(plot_error)
+        }
+    }
+
+    /// Draws the contour segments and the simplices on `root`.
+    fn render<DB: DrawingBackend>(
+        &self,
+        root: DrawingArea<DB, Shift>,
+        segments: &[Segment],
+        simplices: &[Vec<DVector<f64>>],
+    ) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+            .margin(10)
+            .build_cartesian_2d(self.x_range.clone(), self.y_range.clone())?;
+
+        chart.draw_series(
+            segments
+                .iter()
+                .map(|&(a, b, color)| PathElement::new(vec![a, b], color)),
+        )?;
+
+        let count = simplices.len().max(1) as f64;
+        chart.draw_series(simplices.iter().enumerate().map(|(k, simplex)| {
+            let mut outline: Vec<(f64, f64)> = simplex.iter().map(|x| (x[0], x[1])).collect();
+            outline.extend(outline.first().copied());
+            PathElement::new(outline, BLACK.mix(0.15 + 0.85 * (k + 1) as f64 / count))
+        }))?;
+
+        let best: Vec<(f64, f64)> = simplices
+            .iter()
+            .filter_map(|simplex| simplex.first())
+            .map(|x| (x[0], x[1]))
+            .collect();
+        chart.draw_series(std::iter::once(PathElement::new(best.clone(), RED.stroke_width(2))))?;
+        chart.draw_series(best.last().map(|&x| Circle::new(x, 4, RED.filled())))?;
+
+        root.present()
+    }
+}
+
+/// Contour segment with its color.
+type Segment = ((f64, f64), (f64, f64), RGBColor);
+
+/// `n + 1` evenly spaced points spanning `range`.
+fn grid(range: &Range<f64>, n: usize) -> Vec<f64> {
+    (0..=n)
+        .map(|k| range.start + (range.end - range.start) * k as f64 / n as f64)
+        .collect()
+}
+
+/// Points where the contour line at `level` crosses the edges of a grid
+/// cell, given by its corners in counterclockwise order. Consecutive pairs
+/// form the segments of the line, cells with a non-finite corner are
+/// skipped.
+fn cell_crossings(corners: &[(f64, f64, f64); 4], level: f64) -> Vec<(f64, f64)> {
+    if corners.iter().any(|c| !c.2.is_finite()) {
+        return Vec::new();
+    }
+    let mut crossings = Vec::new();
+    for k in 0..4 {
+        let (x0, y0, v0) = corners[k];
+        let (x1, y1, v1) = corners[(k + 1) % 4];
+        if (v0 < level) != (v1 < level) {
+            let t = (level - v0) / (v1 - v0);
+            crossings.push((x0 + t * (x1 - x0), y0 + t * (y1 - y0)));
+        }
+    }
+    crossings
+}
+
+/// Color of the `k`-th of `count` contour levels, from blue for the lowest
+/// one to red for the highest.
+fn level_color(k: usize, count: usize) -> RGBColor {
+    let t = if count > 1 { k as f64 / (count - 1) as f64 } else { 0.0 };
+    RGBColor((255.0 * t) as u8, 64, (255.0 * (1.0 - t)) as u8)
+}
+
+fn plot_error<E: fmt::Display>(err: E) -> NelderMeadError {
+    NelderMeadError::Plot(err.to_string())
+}
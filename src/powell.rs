@@ -0,0 +1,703 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::logging;
+use crate::nelder_mead::{is_nan, Direction, NanHandling, StepSize};
+use crate::objective::ObjectiveFunction;
+use crate::observer::{Observer, SharedObserver};
+use crate::progress::Budget;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationCriterion, TerminationState};
+
+/// Ratio by which the bracket of a line search grows.
+const GOLDEN_RATIO: f64 = 1.618_033_988_749_895;
+/// Fraction of the bracket taken by a golden-section step of Brent's
+/// method.
+const GOLDEN_SECTION: f64 = 0.381_966_011_250_105;
+/// Limit on the growth steps of a bracket and on the steps of Brent's
+/// method, reached along directions on which the objective is unbounded.
+const MAX_LINE_STEPS: usize = 100;
+
+/// Powell's conjugate direction method, which minimizes the objective
+/// function by line searches along a set of directions updated as the run
+/// goes.
+///
+/// Every iteration minimizes along each direction in turn, starting from
+/// the coordinate axes scaled by the steps, with Brent's method. The
+/// overall move of the iteration then replaces the direction along which
+/// the objective decreased the most, unless that would make the directions
+/// nearly linearly dependent. On a quadratic the directions become
+/// conjugate, so on smooth objectives the method converges in far fewer
+/// evaluations than Nelder-Mead. Unlike the simplex of Nelder-Mead, it is
+/// easily misled by noise or discontinuities.
+///
+/// The run converges once an iteration decreased the objective by less than
+/// `ftol` relative to its value. The solver shares the configuration of
+/// [`NelderMead`] where it applies, so that switching algorithms only
+/// means switching builders. The [`Observer`] and the
+/// [`TerminationCriterion`] are given the state of the run after every
+/// iteration, in which the simplex is made of the last `n + 1` points the
+/// line searches reached. The same vertices are returned as the
+/// `final_simplex` of the [`OptimizationResult`].
+///
+/// [`NelderMead`]: crate::nelder_mead::NelderMead
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::DVector;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::powell::Powell;
+///
+/// // an ellipsoid whose axes span three orders of magnitude
+/// fn f(x: &DVector<f64>) -> f64 {
+///     let scale = |i: usize| 10f64.powi(i as i32 % 4);
+///     x.iter().enumerate().map(|(i, xi)| scale(i) * (xi - 1.0).powi(2)).sum()
+/// }
+/// let x_start = DVector::zeros(10);
+/// let powell = Powell::builder().minimize(f, x_start.clone())?;
+/// let plain = NelderMead::builder()
+///     .disable_no_improv_break()
+///     .xatol(1e-6)
+///     .fatol(1e-10)
+///     .max_iter(u64::MAX)
+///     .minimize(f, x_start)?;
+///
+/// assert!(powell.best_score < 1e-12);
+/// assert!(powell.evaluations * 5 < plain.evaluations);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct Powell<T = f64> {
+    pub(crate) step: StepSize<T>,
+    pub(crate) xtol: T,
+    pub(crate) ftol: T,
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) bounds: Option<(DVector<T>, DVector<T>)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for Powell<T> {
+    fn default() -> Self {
+        Powell {
+            step: StepSize::Uniform(convert(0.1)),
+            xtol: convert(1e-8),
+            ftol: convert(1e-12),
+            max_iter: 1000,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            bounds: None,
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`Powell`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `step`                  | 0.1                  |
+/// | `xtol`                  | 1e-8                 |
+/// | `ftol`                  | 1e-12                |
+/// | `max_iter`              | 1000                 |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `bounds`                | none                 |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct PowellBuilder<T = f64> {
+    config: Powell<T>,
+}
+
+impl<T: RealField + Copy> Default for PowellBuilder<T> {
+    fn default() -> Self {
+        PowellBuilder {
+            config: Powell::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> PowellBuilder<T> {
+    /// Length of the initial directions along every parameter, either a
+    /// single value used for every dimension or a [`StepSize`]. It is the
+    /// first step tried by the line searches along the coordinate axes.
+    pub fn step(mut self, step: impl Into<StepSize<T>>) -> Self {
+        self.config.step = step.into();
+        self
+    }
+
+    /// Accuracy of the line searches on the parameters.
+    pub fn xtol(mut self, xtol: T) -> Self {
+        self.config.xtol = xtol;
+        self
+    }
+
+    /// Relative decrease of the objective function per iteration below
+    /// which the run converges.
+    pub fn ftol(mut self, ftol: T) -> Self {
+        self.config.ftol = ftol;
+        self
+    }
+
+    /// Maximum number of iterations, each a line search along every
+    /// direction.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Lower and upper bounds of every parameter. The starting point is
+    /// clipped into them, and every line search is restricted to the part
+    /// of its line within them.
+    pub fn bounds(mut self, lower: DVector<T>, upper: DVector<T>) -> Self {
+        self.config.bounds = Some((lower, upper));
+        self
+    }
+
+    /// Additional stopping rule, checked after every iteration next to the
+    /// built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every iteration with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> Powell<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Powell::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Powell::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, x_start)
+    }
+}
+
+/// Evaluations of a run, with the budgets they are bounded by.
+struct Run<'a, T, F> {
+    config: &'a Powell<T>,
+    obj_fn: F,
+    sign: T,
+    evaluations: u64,
+    start: Instant,
+}
+
+impl<T: RealField + Copy, F: FnMut(&DVector<T>) -> T> Run<'_, T, F> {
+    /// Score of `x`, or `None` when the evaluation budget or the time ran
+    /// out.
+    fn score(&mut self, x: &DVector<T>) -> Result<Option<T>, NelderMeadError> {
+        if self.exhausted().is_some() {
+            return Ok(None);
+        }
+        self.evaluate(x).map(Some)
+    }
+
+    /// Score of `x`, regardless of the budgets.
+    fn evaluate(&mut self, x: &DVector<T>) -> Result<T, NelderMeadError> {
+        self.evaluations += 1;
+        let value = (self.obj_fn)(x);
+        self.config.nan_handling.score(value, self.sign)
+    }
+
+    /// Why the run has to stop before the next evaluation, if it does.
+    fn exhausted(&self) -> Option<TerminationReason> {
+        if self.config.max_fun_evals.is_some_and(|limit| self.evaluations >= limit) {
+            return Some(TerminationReason::MaxFunEvals);
+        }
+        if self.config.max_duration.is_some_and(|limit| self.start.elapsed() >= limit) {
+            return Some(TerminationReason::TimedOut);
+        }
+        None
+    }
+}
+
+/// Line `origin + t * direction` searched for a minimum, with `t` within
+/// `[lower, upper]` and the best point found so far.
+struct Line<'a, T> {
+    origin: &'a DVector<T>,
+    direction: &'a DVector<T>,
+    lower: T,
+    upper: T,
+    best: (T, T),
+}
+
+impl<T: RealField + Copy> Line<'_, T> {
+    /// Score at `t`, clamped into the line, or `None` when the budget ran
+    /// out.
+    fn score<F>(&mut self, run: &mut Run<'_, T, F>, t: T) -> Result<Option<(T, T)>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let t = t.max(self.lower).min(self.upper);
+        let mut point = self.origin + self.direction * t;
+        if let Some((lower, upper)) = &run.config.bounds {
+            point = point.sup(lower).inf(upper);
+        }
+        let Some(score) = run.score(&point)? else {
+            return Ok(None);
+        };
+        if score < self.best.1 {
+            self.best = (t, score);
+        }
+        Ok(Some((t, score)))
+    }
+}
+
+impl<T: RealField + Copy> Powell<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> PowellBuilder<T> {
+        PowellBuilder::default()
+    }
+
+    /// Finds a local minimum (or maximum, depending on the configured
+    /// [`Direction`]) of the objective function, starting from `x_start`.
+    ///
+    /// Fails if `x_start` is empty, if `xtol` or `ftol` is not positive and
+    /// finite, if the step or the bounds do not fit `x_start`, or if the
+    /// objective function returns NaN under [`NanHandling::Error`].
+    pub fn minimize<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let dim = x_start.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        if ![self.xtol, self.ftol].iter().all(|tol| T::zero() < *tol && tol.is_finite()) {
+            return Err(NelderMeadError::InvalidTolerances);
+        }
+        self.step.check_dim(dim)?;
+        let mut x = x_start;
+        if let Some((lower, upper)) = &self.bounds {
+            if lower.len() != dim
+                || upper.len() != dim
+                || lower.iter().zip(upper.iter()).any(|(l, u)| l > u || is_nan(*l) || is_nan(*u))
+            {
+                return Err(NelderMeadError::InvalidBounds);
+            }
+            x = x.sup(lower).inf(upper);
+        }
+
+        let mut run = Run {
+            config: self,
+            obj_fn,
+            sign: self.direction.sign(),
+            evaluations: 0,
+            start: Instant::now(),
+        };
+        let budget = Budget {
+            max_iter: self.max_iter,
+            max_fun_evals: self.max_fun_evals,
+            max_duration: self.max_duration,
+        };
+        let mut score = run.evaluate(&x)?;
+        let mut directions: Vec<DVector<T>> = (0..dim)
+            .map(|i| {
+                let mut direction = DVector::zeros(dim);
+                direction[i] = self.step.offset(i, x[i]);
+                direction
+            })
+            .collect();
+        // last `dim + 1` points reached by the line searches
+        let mut trail = vec![(x.clone(), score); dim + 1];
+        let mut iterations = 0;
+
+        let termination_reason = loop {
+            if iterations >= self.max_iter {
+                break TerminationReason::MaxIter;
+            }
+            if let Some(reason) = run.exhausted() {
+                break reason;
+            }
+
+            let (x_first, first) = (x.clone(), score);
+            let mut largest = (0, T::zero());
+            let mut completed = true;
+            for (i, direction) in directions.iter().enumerate() {
+                let before = score;
+                completed = self.line_search(&mut run, &mut x, &mut score, direction)?;
+                trail.rotate_left(1);
+                trail[dim] = (x.clone(), score);
+                if before - score > largest.1 {
+                    largest = (i, before - score);
+                }
+                if !completed {
+                    break;
+                }
+            }
+            iterations += 1;
+
+            let decrease = first - score;
+            let converged = decrease + decrease <= self.ftol * (first.abs() + score.abs()) + convert(1e-20);
+            if completed && !converged {
+                // replace the direction of the largest decrease with the
+                // move of the iteration, unless that loses a dimension
+                let moved = &x - &x_first;
+                let extrapolated = self.clip(&x + &moved);
+                match run.score(&extrapolated)? {
+                    Some(extrapolated) if extrapolated < first => {
+                        let two: T = convert(2.0);
+                        let curvature = two * (first - two * score + extrapolated) * (decrease - largest.1).powi(2);
+                        if curvature < largest.1 * (first - extrapolated).powi(2) {
+                            completed = self.line_search(&mut run, &mut x, &mut score, &moved)?;
+                            trail.rotate_left(1);
+                            trail[dim] = (x.clone(), score);
+                            directions.remove(largest.0);
+                            directions.push(moved);
+                        }
+                    }
+                    Some(_) => {}
+                    None => completed = false,
+                }
+            }
+            if !completed {
+                continue;
+            }
+
+            let simplex = trail_simplex(&trail);
+            let state = TerminationState {
+                iterations,
+                evaluations: run.evaluations,
+                elapsed: run.start.elapsed(),
+                simplex: &simplex,
+                sign: run.sign,
+                last_move: None,
+                budget,
+            };
+            logging::log_iteration(&state);
+            if let Some(observer) = &self.observer {
+                observer.0.observe(&state);
+            }
+            if let Some(criterion) = &self.termination {
+                if criterion.0.should_stop(&state) {
+                    break TerminationReason::Criterion;
+                }
+            }
+            if converged {
+                break TerminationReason::Converged;
+            }
+        };
+
+        Ok(OptimizationResult::from_simplex(
+            trail_simplex(&trail).to_pairs(T::one()),
+            run.sign,
+            iterations,
+            run.evaluations,
+            0,
+            termination_reason,
+            None,
+        ))
+    }
+
+    /// Same as [`Powell::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), x_start)
+    }
+
+    /// Moves `x`, whose score is `score`, to the minimum along `direction`:
+    /// brackets it by steps growing from `direction` itself, then narrows
+    /// the bracket with Brent's method. Returns `false` when the budget ran
+    /// out before the minimum was found, `x` then holding the best point
+    /// found so far.
+    fn line_search<F>(
+        &self,
+        run: &mut Run<'_, T, F>,
+        x: &mut DVector<T>,
+        score: &mut T,
+        direction: &DVector<T>,
+    ) -> Result<bool, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let (lower, upper) = self.line_bounds(x, direction);
+        let length = direction.norm();
+        if length.is_zero() || lower == upper {
+            return Ok(true);
+        }
+        let origin = x.clone();
+        let mut line = Line {
+            origin: &origin,
+            direction,
+            lower,
+            upper,
+            best: (T::zero(), *score),
+        };
+        let completed = self.bracket_and_minimize(run, &mut line, *score, self.xtol / length)?;
+        let (t, best) = line.best;
+        if !t.is_zero() {
+            *x = origin + direction * t;
+            if let Some((lower, upper)) = &self.bounds {
+                *x = x.sup(lower).inf(upper);
+            }
+            *score = best;
+        }
+        Ok(completed)
+    }
+
+    /// Brackets the minimum along `line`, whose score at `t = 0` is `score`,
+    /// and narrows the bracket down to `tol` with Brent's method.
+    fn bracket_and_minimize<F>(
+        &self,
+        run: &mut Run<'_, T, F>,
+        line: &mut Line<'_, T>,
+        score: T,
+        tol: T,
+    ) -> Result<bool, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let growth: T = convert(GOLDEN_RATIO);
+        // `a` and `b` are the last two points, `b` the best of them
+        let mut a = T::zero();
+        let first = if line.upper > T::zero() {
+            T::one().min(line.upper)
+        } else {
+            (-T::one()).max(line.lower)
+        };
+        let Some((mut b, mut fb)) = line.score(run, first)? else {
+            return Ok(false);
+        };
+        if fb >= score {
+            let opposite = (-b).max(line.lower).min(line.upper);
+            if opposite.is_zero() {
+                return self.brent(run, line, (a.min(b), a.max(b)), (a, score), tol);
+            }
+            let Some((c, fc)) = line.score(run, opposite)? else {
+                return Ok(false);
+            };
+            if fc >= score {
+                return self.brent(run, line, (c.min(b), c.max(b)), (a, score), tol);
+            }
+            (b, fb) = (c, fc);
+        }
+        for _ in 0..MAX_LINE_STEPS {
+            let next = (b + growth * (b - a)).max(line.lower).min(line.upper);
+            if next == b {
+                // the minimum lies at the end of the line, or just before
+                return self.brent(run, line, (a.min(b), a.max(b)), (b, fb), tol);
+            }
+            let Some((c, fc)) = line.score(run, next)? else {
+                return Ok(false);
+            };
+            if fc > fb {
+                return self.brent(run, line, (a.min(c), a.max(c)), (b, fb), tol);
+            }
+            a = b;
+            (b, fb) = (c, fc);
+        }
+        Ok(true)
+    }
+
+    /// Brent's method, narrowing the bracket `(low, high)` around `best`
+    /// by parabolic interpolation, falling back to golden-section steps.
+    fn brent<F>(
+        &self,
+        run: &mut Run<'_, T, F>,
+        line: &mut Line<'_, T>,
+        (mut low, mut high): (T, T),
+        (mut x, mut fx): (T, T),
+        tol: T,
+    ) -> Result<bool, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let section: T = convert(GOLDEN_SECTION);
+        let half: T = convert(0.5);
+        let sqrt_eps = T::default_epsilon().sqrt();
+        let (mut w, mut fw, mut v, mut fv) = (x, fx, x, fx);
+        // last two steps, the parabolic ones only taken while they shrink
+        let (mut step, mut previous) = (T::zero(), T::zero());
+        for _ in 0..MAX_LINE_STEPS {
+            let middle = half * (low + high);
+            let tol1 = sqrt_eps * x.abs() + tol;
+            let tol2 = tol1 + tol1;
+            if (x - middle).abs() <= tol2 - half * (high - low) {
+                break;
+            }
+            let mut parabolic = false;
+            if previous.abs() > tol1 {
+                let r = (x - w) * (fx - fv);
+                let mut q = (x - v) * (fx - fw);
+                let mut p = (x - v) * q - (x - w) * r;
+                q = (q - r) * convert(2.0);
+                if q > T::zero() {
+                    p = -p;
+                }
+                q = q.abs();
+                if p.abs() < (half * q * previous).abs() && p > q * (low - x) && p < q * (high - x) {
+                    previous = step;
+                    step = p / q;
+                    let u = x + step;
+                    if u - low < tol2 || high - u < tol2 {
+                        step = if middle >= x { tol1 } else { -tol1 };
+                    }
+                    parabolic = true;
+                }
+            }
+            if !parabolic {
+                previous = if x >= middle { low - x } else { high - x };
+                step = section * previous;
+            }
+            let u = if step.abs() >= tol1 {
+                x + step
+            } else if step > T::zero() {
+                x + tol1
+            } else {
+                x - tol1
+            };
+            let Some((u, fu)) = line.score(run, u)? else {
+                return Ok(false);
+            };
+            if fu <= fx {
+                if u >= x {
+                    low = x;
+                } else {
+                    high = x;
+                }
+                (v, fv, w, fw, x, fx) = (w, fw, x, fx, u, fu);
+            } else {
+                if u < x {
+                    low = u;
+                } else {
+                    high = u;
+                }
+                if fu <= fw || w == x {
+                    (v, fv, w, fw) = (w, fw, u, fu);
+                } else if fu <= fv || v == x || v == w {
+                    (v, fv) = (u, fu);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Range of `t` keeping `x + t * direction` within the bounds.
+    fn line_bounds(&self, x: &DVector<T>, direction: &DVector<T>) -> (T, T) {
+        let infinity: T = convert(f64::INFINITY);
+        let Some((lower, upper)) = &self.bounds else {
+            return (-infinity, infinity);
+        };
+        let (mut low, mut high) = (-infinity, infinity);
+        for i in 0..x.len() {
+            if direction[i].is_zero() {
+                continue;
+            }
+            let to_lower = (lower[i] - x[i]) / direction[i];
+            let to_upper = (upper[i] - x[i]) / direction[i];
+            low = low.max(to_lower.min(to_upper));
+            high = high.min(to_lower.max(to_upper));
+        }
+        (low.min(T::zero()), high.max(T::zero()))
+    }
+
+    /// Copy of `x` clipped into the bounds.
+    fn clip(&self, x: DVector<T>) -> DVector<T> {
+        match &self.bounds {
+            Some((lower, upper)) => x.sup(lower).inf(upper),
+            None => x,
+        }
+    }
+}
+
+/// Simplex of the points of the trail, ordered from the best one.
+fn trail_simplex<T: RealField + Copy>(trail: &[(DVector<T>, T)]) -> Simplex<T> {
+    let mut simplex = Simplex::from_slices(trail.iter().map(|(x, score)| (x.as_slice(), *score)));
+    simplex.sort();
+    simplex
+}
@@ -0,0 +1,254 @@
+use std::sync::Arc;
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::constraints::SharedConstraint;
+use crate::error::NelderMeadError;
+use crate::nelder_mead::{NelderMead, StepSize};
+use crate::result::OptimizationResult;
+
+/// Profile of the objective along one parameter, traced by
+/// [`NelderMead::profile_likelihood`], with the confidence interval it
+/// gives.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Profile<T = f64> {
+    /// Index of the profiled parameter.
+    pub parameter: usize,
+    /// Values at which the parameter was fixed, in increasing order,
+    /// including its fitted value.
+    pub values: Vec<T>,
+    /// Optimum of the objective over the other parameters at every value.
+    pub scores: Vec<T>,
+    /// Lower end of the interval, where the profile crosses the threshold
+    /// below the fitted value, `None` if it does not within the grid.
+    pub lower: Option<T>,
+    /// Upper end of the interval, where the profile crosses the threshold
+    /// above the fitted value, `None` if it does not within the grid.
+    pub upper: Option<T>,
+    /// Number of objective function evaluations spent on the profile.
+    pub evaluations: u64,
+}
+
+/// `y` with `value` inserted at index `i`.
+fn insert<T: RealField + Copy>(y: &DVector<T>, i: usize, value: T) -> DVector<T> {
+    y.clone().insert_row(i, value)
+}
+
+/// `options` set on single parameters, without that of the `i`-th one.
+fn without<V: Clone>(options: &[V], i: usize) -> Vec<V> {
+    let mut options = options.to_vec();
+    if i < options.len() {
+        options.remove(i);
+    }
+    options
+}
+
+/// Value at which the deviation from the fit, growing from `inside` to
+/// `outside` as pairs of a parameter value and its deviation, reaches
+/// `threshold`, by linear interpolation.
+fn crossing<T: RealField + Copy>(inside: (T, T), outside: (T, T), threshold: T) -> T {
+    let ((v0, d0), (v1, d1)) = (inside, outside);
+    if d1 <= d0 {
+        return v1;
+    }
+    v0 + (v1 - v0) * (threshold - d0) / (d1 - d0)
+}
+
+impl<T: RealField + Copy> NelderMead<T> {
+    /// Configuration of the runs over all the parameters but the `i`-th
+    /// one, fixed at `value`. Options set on single parameters apply to
+    /// the remaining ones, and the constraints are evaluated with the
+    /// fixed parameter put back. Only the optimum of the runs is used, so
+    /// they neither save checkpoints, polish, estimate the covariance nor
+    /// record their history. Fails with linear equality constraints or a
+    /// unit simplex containing the parameter.
+    fn fixing(&self, i: usize, value: T) -> Result<Self, NelderMeadError> {
+        if self.linear_equality.is_some() {
+            return Err(NelderMeadError::InvalidLinearEquality);
+        }
+        if self.unit_simplices.iter().any(|group| group.contains(&i)) {
+            return Err(NelderMeadError::InvalidTransform);
+        }
+        let fixed = |g: &SharedConstraint<T>| {
+            let g = Arc::clone(&g.0);
+            SharedConstraint(Arc::new(move |y: &DVector<T>| g.evaluate(&insert(y, i, value))))
+        };
+        Ok(NelderMead {
+            step: match &self.step {
+                StepSize::PerDim(steps) if i < steps.len() => StepSize::PerDim(steps.clone().remove_row(i)),
+                step => step.clone(),
+            },
+            bounds: self
+                .bounds
+                .as_ref()
+                .map(|(lower, upper)| (lower.clone().remove_row(i), upper.clone().remove_row(i))),
+            parameter_transforms: without(&self.parameter_transforms, i),
+            periods: without(&self.periods, i),
+            unit_simplices: self
+                .unit_simplices
+                .iter()
+                .map(|group| group.iter().map(|&j| if j > i { j - 1 } else { j }).collect())
+                .collect(),
+            scales: None,
+            constraints: self.constraints.iter().map(fixed).collect(),
+            equality_constraints: self.equality_constraints.iter().map(fixed).collect(),
+            checkpoint: None,
+            estimate_covariance: false,
+            bfgs_polish: None,
+            record_history: false,
+            ..self.clone()
+        })
+    }
+
+    /// Traces the profile of the objective along every parameter around
+    /// the fit `fit`, and the confidence intervals it gives.
+    ///
+    /// Every parameter is fixed in turn at `points` values on each side
+    /// of its fitted value, evenly spaced up to `widths[i]` away from it,
+    /// and the objective is optimized over the other parameters with this
+    /// configuration. The values are visited outwards from the fit, every
+    /// run starting from the optimum of the previous one, and each side
+    /// stops once the profile is more than `threshold` worse than the fit
+    /// or leaves the bounds. The ends of the interval are where the
+    /// profile crosses the threshold, interpolated linearly between the
+    /// grid values, so they need not be symmetric around the fit.
+    ///
+    /// For a negative log-likelihood, a threshold of `χ²₁(1 - α) / 2`
+    /// gives intervals of confidence `1 - α`, e.g. 1.92 for 95%. When
+    /// maximizing a log-likelihood, the threshold is the drop below its
+    /// maximum.
+    ///
+    /// The runs only keep their optimum: they do not save checkpoints, and
+    /// the BFGS polish, the covariance estimate and the history of the
+    /// configuration are skipped.
+    ///
+    /// Fails if `points` is zero, if `widths` does not have the dimension
+    /// of the fit or has a value that is not positive and finite, or if
+    /// `threshold` is not positive and finite, as well as for any of the
+    /// runs, see [`NelderMead::minimize`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// // negative log-likelihood of an exponential sample in its log rate,
+    /// // and of a normal sample of unit variance in its mean
+    /// let waits = [0.4, 1.9, 0.7, 3.1, 0.2, 1.1, 0.9, 2.4];
+    /// let heights = [1.2, 0.4, 2.1, 1.5, 0.9];
+    /// let nll = |x: &DVector<f64>| {
+    ///     let rate = x[0].exp();
+    ///     let exponential: f64 = waits.iter().map(|t| rate * t - x[0]).sum();
+    ///     let normal: f64 = heights.iter().map(|y| 0.5 * (y - x[1]).powi(2)).sum();
+    ///     exponential + normal
+    /// };
+    /// let optimizer = NelderMead::builder().xatol(1e-8).fatol(1e-10).build();
+    /// let fit = optimizer.minimize(nll, dvector![0.0, 0.0])?;
+    /// let profiles = optimizer.profile_likelihood(nll, &fit, &dvector![2.0, 2.0], 40, 1.92)?;
+    ///
+    /// // the interval of the mean is symmetric, that of the log rate is not
+    /// let mean = &profiles[1];
+    /// let (lower, upper) = (mean.lower.unwrap(), mean.upper.unwrap());
+    /// assert!((upper - lower - 2.0 * 1.96 / 5f64.sqrt()).abs() < 0.02);
+    /// let log_rate = &profiles[0];
+    /// let (lower, upper) = (log_rate.lower.unwrap(), log_rate.upper.unwrap());
+    /// assert!(fit.best_point[0] - lower > upper - fit.best_point[0]);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    ///
+    /// Estimating the covariance of the fit does not make the profile any
+    /// more expensive:
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// let f = |x: &DVector<f64>| (x[0] - 1.0).powi(2) + 2.0 * (x[1] + x[0]).powi(2);
+    /// let plain = NelderMead::builder().build();
+    /// let covariance = NelderMead::builder().estimate_covariance(true).build();
+    /// let fit = covariance.minimize(f, dvector![0.0, 0.0])?;
+    /// assert!(fit.hessian.is_some());
+    ///
+    /// let widths = dvector![1.0, 1.0];
+    /// let profiles = plain.profile_likelihood(f, &fit, &widths, 5, 0.5)?;
+    /// let with_covariance = covariance.profile_likelihood(f, &fit, &widths, 5, 0.5)?;
+    /// for (profile, with_covariance) in profiles.iter().zip(&with_covariance) {
+    ///     assert_eq!(profile.evaluations, with_covariance.evaluations);
+    /// }
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn profile_likelihood<F>(
+        &self,
+        mut obj_fn: F,
+        fit: &OptimizationResult<T>,
+        widths: &DVector<T>,
+        points: usize,
+        threshold: T,
+    ) -> Result<Vec<Profile<T>>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let dim = fit.best_point.len();
+        let positive = |v: T| v > T::zero() && v.is_finite();
+        if points == 0 || widths.len() != dim || !widths.iter().all(|w| positive(*w)) || !positive(threshold) {
+            return Err(NelderMeadError::InvalidProfile);
+        }
+        let sign: T = self.direction.sign();
+        let mut profiles = Vec::with_capacity(dim);
+        for i in 0..dim {
+            let fitted = fit.best_point[i];
+            let rest = fit.best_point.clone().remove_row(i);
+            let mut evaluations = 0;
+            let mut sides = [Vec::new(), Vec::new()];
+            let mut ends = [None, None];
+            for (side, direction) in [-T::one(), T::one()].into_iter().enumerate() {
+                let mut start = rest.clone();
+                let mut previous = (fitted, T::zero());
+                for k in 1..=points {
+                    let offset: T = convert::<f64, T>(k as f64 / points as f64) * widths[i];
+                    let value = fitted + direction * offset;
+                    if self.bounds.as_ref().is_some_and(|(lower, upper)| value < lower[i] || value > upper[i]) {
+                        break;
+                    }
+                    let score = if dim == 1 {
+                        evaluations += 1;
+                        obj_fn(&DVector::from_element(1, value))
+                    } else {
+                        let run = self
+                            .fixing(i, value)?
+                            .minimize(|y: &DVector<T>| obj_fn(&insert(y, i, value)), start)?;
+                        evaluations += run.evaluations;
+                        start = run.best_point;
+                        run.best_score
+                    };
+                    sides[side].push((value, score));
+                    let deviation = sign * (score - fit.best_score);
+                    if deviation > threshold {
+                        ends[side] = Some(crossing(previous, (value, deviation), threshold));
+                        break;
+                    }
+                    previous = (value, deviation);
+                }
+            }
+
+            let [below, above] = sides;
+            let (values, scores) = below
+                .into_iter()
+                .rev()
+                .chain(std::iter::once((fitted, fit.best_score)))
+                .chain(above)
+                .unzip();
+            profiles.push(Profile {
+                parameter: i,
+                values,
+                scores,
+                lower: ends[0],
+                upper: ends[1],
+                evaluations,
+            });
+        }
+        Ok(profiles)
+    }
+}
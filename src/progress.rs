@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use nalgebra::RealField;
+
+use crate::nelder_mead::NelderMead;
+
+/// Budgets a run is bounded by, as configured with `max_iter`,
+/// `max_fun_evals` and `max_duration`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Budget {
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+}
+
+impl<T: RealField + Copy> NelderMead<T> {
+    pub(crate) fn budget(&self) -> Budget {
+        Budget {
+            max_iter: self.max_iter,
+            max_fun_evals: self.max_fun_evals,
+            max_duration: self.max_duration,
+        }
+    }
+}
+
+/// Work done by a run compared with its configured budgets, returned by
+/// [`TerminationState::progress`].
+///
+/// The run may stop well before its budget is spent, e.g. once it has
+/// converged, so the completion estimates are upper bounds.
+///
+/// [`TerminationState::progress`]: crate::termination::TerminationState::progress
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::termination::TerminationState;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// }
+/// NelderMead::builder()
+///     .max_iter(50)
+///     .max_fun_evals(80)
+///     .disable_no_improv_break()
+///     .observer(|state: &TerminationState| {
+///         // e.g. `bar.set_position((1000.0 * progress.fraction()) as u64)`
+///         // with an indicatif progress bar of length 1000
+///         let progress = state.progress();
+///         assert!((0.0..=1.0).contains(&progress.fraction()));
+///     })
+///     .minimize(&f, dvector![0.0, 0.0])?;
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// Number of iterations completed so far.
+    pub iterations: u64,
+    /// Iteration limit of the run.
+    pub max_iter: u64,
+    /// Number of objective function evaluations so far.
+    pub evaluations: u64,
+    /// Evaluation budget of the run, if any.
+    pub max_fun_evals: Option<u64>,
+    /// Wall-clock time since the start of the run.
+    pub elapsed: Duration,
+    /// Time limit of the run, if any.
+    pub max_duration: Option<Duration>,
+}
+
+impl Progress {
+    pub(crate) fn new(
+        budget: Budget,
+        iterations: u64,
+        evaluations: u64,
+        elapsed: Duration,
+    ) -> Self {
+        Progress {
+            iterations,
+            max_iter: budget.max_iter,
+            evaluations,
+            max_fun_evals: budget.max_fun_evals,
+            elapsed,
+            max_duration: budget.max_duration,
+        }
+    }
+
+    /// Fraction of the budget spent, between 0 and 1, taking the budget
+    /// closest to being exhausted.
+    pub fn fraction(&self) -> f64 {
+        let iters = ratio(self.iterations as f64, self.max_iter as f64);
+        let evals = self.max_fun_evals
+            .map_or(0.0, |limit| ratio(self.evaluations as f64, limit as f64));
+        let time = self.max_duration
+            .map_or(0.0, |limit| ratio(self.elapsed.as_secs_f64(), limit.as_secs_f64()));
+        iters.max(evals).max(time)
+    }
+
+    /// Estimated time until the budget is spent, assuming the run keeps
+    /// its pace so far. `None` until some progress was made.
+    pub fn estimated_remaining(&self) -> Option<Duration> {
+        let fraction = self.fraction();
+        if fraction <= 0.0 {
+            return None;
+        }
+        let remaining = self.elapsed.as_secs_f64() * (1.0 - fraction) / fraction;
+        Some(Duration::from_secs_f64(remaining.max(0.0)))
+    }
+}
+
+/// `done / limit` clamped to `[0, 1]`, an exhausted zero limit counting as
+/// complete.
+fn ratio(done: f64, limit: f64) -> f64 {
+    if limit <= 0.0 {
+        return 1.0;
+    }
+    (done / limit).clamp(0.0, 1.0)
+}
@@ -0,0 +1,84 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seedable pseudo-random number generator of the stochastic solvers,
+/// xoshiro256++ with its state expanded from the seed by SplitMix64.
+///
+/// Runs given the same seed draw the same numbers on every platform.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: [u64; 4],
+    // second normal deviate of the last pair drawn
+    spare: Option<f64>,
+}
+
+impl Rng {
+    /// Generator seeded with `seed`.
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng {
+            state: [split_mix(seed, 0), split_mix(seed, 1), split_mix(seed, 2), split_mix(seed, 3)],
+            spare: None,
+        }
+    }
+
+    /// Generator seeded with `seed`, or from the randomly keyed hasher of
+    /// the standard library and the clock when there is none.
+    pub(crate) fn seeded(seed: Option<u64>) -> Self {
+        Rng::new(seed.unwrap_or_else(|| {
+            let mut hasher = RandomState::new().build_hasher();
+            let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos());
+            hasher.write_u128(nanos);
+            hasher.finish()
+        }))
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        let [s0, s1, s2, s3] = &mut self.state;
+        let result = s0.wrapping_add(*s3).rotate_left(23).wrapping_add(*s0);
+        let t = *s1 << 17;
+        *s2 ^= *s0;
+        *s3 ^= *s1;
+        *s1 ^= *s2;
+        *s0 ^= *s3;
+        *s2 ^= t;
+        *s3 = s3.rotate_left(45);
+        result
+    }
+
+    /// Uniform deviate within `[0, 1)`.
+    pub(crate) fn uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform index within `0..n`, with `n` positive.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        ((self.uniform() * n as f64) as usize).min(n - 1)
+    }
+
+    /// Standard normal deviate, drawn in pairs by the polar method.
+    pub(crate) fn normal(&mut self) -> f64 {
+        if let Some(spare) = self.spare.take() {
+            return spare;
+        }
+        loop {
+            let u = 2.0 * self.uniform() - 1.0;
+            let v = 2.0 * self.uniform() - 1.0;
+            let s = u * u + v * v;
+            if s > 0.0 && s < 1.0 {
+                let factor = (-2.0 * s.ln() / s).sqrt();
+                self.spare = Some(v * factor);
+                return u * factor;
+            }
+        }
+    }
+}
+
+/// Output of SplitMix64 at step `index` from `seed`, so that nearby seeds
+/// and indices give unrelated numbers.
+pub(crate) fn split_mix(seed: u64, index: u64) -> u64 {
+    let mut z = seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
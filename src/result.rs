@@ -0,0 +1,269 @@
+use nalgebra::{convert, DVector, RealField};
+
+use crate::cache::CacheStats;
+use crate::diagnostics::{simplex_gradient, Sensitivity, SimplexHealth};
+use crate::error::NelderMeadError;
+use crate::hessian::HessianEstimate;
+use crate::history::History;
+use crate::nelder_mead::StepSize;
+use crate::simplex::Simplex;
+use crate::timing::TimingStats;
+
+/// Reason for which the optimizer stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TerminationReason {
+    /// The `max_iter` limit was reached.
+    MaxIter,
+    /// The best score did not improve by more than `no_improve_thr`, see
+    /// [`ImprovementMode`], for `no_improv_break` consecutive iterations.
+    ///
+    /// [`ImprovementMode`]: crate::nelder_mead::ImprovementMode
+    NoImprovement,
+    /// The simplex satisfied the convergence tolerances.
+    Converged,
+    /// The best score reached the configured `target_value`.
+    TargetReached,
+    /// The `max_fun_evals` budget of objective evaluations was spent.
+    MaxFunEvals,
+    /// The `max_duration` time limit was reached.
+    TimedOut,
+    /// The configured termination criterion was met.
+    Criterion,
+    /// The configured callback returned `ControlFlow::Break`.
+    StoppedByCallback,
+    /// The run was driven by a [`NelderMeadIter`] whose result was taken
+    /// before the run was over.
+    ///
+    /// [`NelderMeadIter`]: crate::nelder_mead_iter::NelderMeadIter
+    StoppedByCaller,
+    /// A stop was requested with [`interrupt::request_stop`], e.g. on
+    /// Ctrl-C.
+    ///
+    /// [`interrupt::request_stop`]: crate::interrupt::request_stop
+    Interrupted,
+}
+
+/// Outcome of an optimization run.
+///
+/// With the `serde` feature it can be serialized, e.g. to persist runs and
+/// compare them later.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "serde")]
+/// # {
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::result::OptimizationResult;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// }
+/// let results = NelderMead::builder().minimize(&f, dvector![0.0, 0.0]).unwrap();
+/// let json = serde_json::to_string(&results).unwrap();
+/// let restored: OptimizationResult = serde_json::from_str(&json).unwrap();
+///
+/// assert_eq!(restored, results);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct OptimizationResult<T = f64> {
+    /// Best parameter vector found.
+    pub best_point: DVector<T>,
+    /// Objective value at `best_point`.
+    pub best_score: T,
+    /// Number of simplex iterations performed.
+    pub iterations: u64,
+    /// Number of objective function evaluations.
+    pub evaluations: u64,
+    /// Number of times the simplex was rebuilt, see
+    /// [`NelderMeadBuilder::restart_on_degeneracy`] and
+    /// [`NelderMeadBuilder::oriented_restart`].
+    ///
+    /// [`NelderMeadBuilder::restart_on_degeneracy`]: crate::builder::NelderMeadBuilder::restart_on_degeneracy
+    /// [`NelderMeadBuilder::oriented_restart`]: crate::builder::NelderMeadBuilder::oriented_restart
+    pub restarts: u64,
+    /// Why the optimizer stopped.
+    pub termination_reason: TerminationReason,
+    /// Largest violation of the constraints at `best_point`, `max(0, g_i(x))`
+    /// for the inequality and `|h_j(x)|` for the equality constraints,
+    /// `None` without constraints, see [`NelderMeadBuilder::constraint`] and
+    /// [`NelderMeadBuilder::equality_constraint`].
+    ///
+    /// [`NelderMeadBuilder::constraint`]: crate::builder::NelderMeadBuilder::constraint
+    /// [`NelderMeadBuilder::equality_constraint`]: crate::builder::NelderMeadBuilder::equality_constraint
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub constraint_violation: Option<T>,
+    /// Hits and misses of the evaluation cache, `None` unless enabled
+    /// with [`NelderMeadBuilder::cache_evaluations`].
+    ///
+    /// [`NelderMeadBuilder::cache_evaluations`]: crate::builder::NelderMeadBuilder::cache_evaluations
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cache: Option<CacheStats>,
+    /// Durations of the objective function calls and of the iterations,
+    /// `None` unless enabled with [`NelderMeadBuilder::record_timing`].
+    ///
+    /// [`NelderMeadBuilder::record_timing`]: crate::builder::NelderMeadBuilder::record_timing
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub timing: Option<TimingStats>,
+    /// Hessian of the objective at `best_point`, with the covariance and
+    /// standard errors of the parameters, `None` unless enabled with
+    /// [`NelderMeadBuilder::estimate_covariance`].
+    ///
+    /// [`NelderMeadBuilder::estimate_covariance`]: crate::builder::NelderMeadBuilder::estimate_covariance
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hessian: Option<HessianEstimate<T>>,
+    /// All vertices of the simplex at termination with their scores,
+    /// ordered from the best one. Useful to assess the spread of the
+    /// solution or to warm-start another run.
+    pub final_simplex: Vec<(DVector<T>, T)>,
+    /// State of every iteration, when enabled with `record_history`.
+    pub history: Option<History<T>>,
+}
+
+impl<T: RealField + Copy> OptimizationResult<T> {
+    /// Builds the result from the final simplex, ordered from the best
+    /// vertex, whose scores are multiplied by `sign` to undo maximization.
+    pub(crate) fn from_simplex(
+        simplex: Vec<(DVector<T>, T)>,
+        sign: T,
+        iterations: u64,
+        evaluations: u64,
+        restarts: u64,
+        termination_reason: TerminationReason,
+        history: Option<History<T>>,
+    ) -> Self {
+        let final_simplex: Vec<(DVector<T>, T)> = simplex
+            .into_iter()
+            .map(|(x, score)| (x, sign * score))
+            .collect();
+        OptimizationResult {
+            best_point: final_simplex[0].0.clone(),
+            best_score: final_simplex[0].1,
+            iterations,
+            evaluations,
+            restarts,
+            termination_reason,
+            constraint_violation: None,
+            cache: None,
+            timing: None,
+            hessian: None,
+            final_simplex,
+            history,
+        }
+    }
+
+    /// Volume, edge-length ratio and condition number of the final
+    /// simplex, see [`SimplexHealth`].
+    pub fn simplex_health(&self) -> SimplexHealth<T> {
+        SimplexHealth::new(&Simplex::from_pairs(&self.final_simplex))
+    }
+
+    /// Simplex gradient of the objective at the best vertex, the gradient
+    /// of the linear function through the scores of the final simplex,
+    /// `None` when the simplex is degenerate or was not kept.
+    ///
+    /// It costs no evaluation and approximates the gradient up to an
+    /// error of the order of the size of the simplex, so a norm that is
+    /// small next to the scale of the objective is a first-order sign of
+    /// optimality, while a large one tells that the simplex stalled away
+    /// from a stationary point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+    /// }
+    /// let converged = NelderMead::builder().minimize(&f, dvector![0.0, 0.0])?;
+    /// let stopped = NelderMead::builder().max_iter(2).minimize(&f, dvector![0.0, 0.0])?;
+    ///
+    /// assert!(converged.simplex_gradient().unwrap().norm() < 1e-3);
+    /// assert!(stopped.simplex_gradient().unwrap().norm() > 1.0);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn simplex_gradient(&self) -> Option<DVector<T>> {
+        let dim = self.best_point.len();
+        if self.final_simplex.len() != dim + 1 || dim == 0 {
+            return None;
+        }
+        simplex_gradient(&Simplex::from_pairs(&self.final_simplex))
+    }
+
+    /// Sensitivity table of the objective around `best_point`: every
+    /// parameter is moved in turn by `±δ`, `δ` being the offset of
+    /// `delta` for that parameter as for the initial step, see
+    /// [`StepSize`], and the objective is evaluated at both points and at
+    /// `best_point`, `2n + 1` evaluations in all. Fails if a per-parameter
+    /// `delta` does not have the dimension of `best_point`, or if `delta`
+    /// is zero or not finite.
+    ///
+    /// The slopes tell whether the point is stationary along every
+    /// parameter, and the curvatures how sharply the objective determines
+    /// each of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nalgebra::{DVector, dvector};
+    /// use nelder_mead_optimizer::nelder_mead::NelderMead;
+    ///
+    /// // the second parameter barely matters
+    /// fn f(x: &DVector<f64>) -> f64 {
+    ///     (x[0] - 1.0).powi(2) + 1e-4 * (x[1] + 1.0).powi(2)
+    /// }
+    /// let results = NelderMead::builder().minimize(&f, dvector![0.0, 0.0])?;
+    /// let table = results.sensitivity(f, 0.1)?;
+    ///
+    /// assert!(table[0].slope.abs() < 1e-2);
+    /// assert!((table[0].curvature - 2.0).abs() < 1e-6);
+    /// assert!(table[1].curvature < 1e-3);
+    /// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+    /// ```
+    pub fn sensitivity<F>(
+        &self,
+        mut obj_fn: F,
+        delta: impl Into<StepSize<T>>,
+    ) -> Result<Vec<Sensitivity<T>>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let delta = delta.into();
+        delta.check_dim(self.best_point.len())?;
+        delta.check_values()?;
+        let score = obj_fn(&self.best_point);
+        let two: T = convert(2.0);
+        let table = (0..self.best_point.len())
+            .map(|i| {
+                let delta = delta.offset(i, self.best_point[i]);
+                let mut point = self.best_point.clone();
+                point[i] = self.best_point[i] - delta;
+                let below = obj_fn(&point);
+                point[i] = self.best_point[i] + delta;
+                let above = obj_fn(&point);
+                Sensitivity {
+                    parameter: i,
+                    delta,
+                    below,
+                    above,
+                    slope: (above - below) / (two * delta),
+                    curvature: (above - two * score + below) / (delta * delta),
+                }
+            })
+            .collect();
+        Ok(table)
+    }
+}
@@ -0,0 +1,176 @@
+use std::cmp::Ordering;
+
+use nalgebra::{DMatrix, DVector, DVectorView, RealField};
+
+use crate::nelder_mead::total_cmp;
+
+/// Vertices of a simplex, one per column of a matrix, with their scores.
+///
+/// The vertices share one contiguous allocation, so the centroid is a
+/// column sum, a vertex is replaced by copying it into its column and a
+/// snapshot of the simplex clones a single matrix.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Simplex<T> {
+    vertices: DMatrix<T>,
+    scores: Vec<T>,
+}
+
+impl<T: RealField + Copy> Simplex<T> {
+    /// Simplex of the `(vertex, score)` pairs, in that order.
+    pub(crate) fn from_slices<'a>(pairs: impl IntoIterator<Item = (&'a [T], T)>) -> Self {
+        let mut data = Vec::new();
+        let mut scores = Vec::new();
+        for (x, score) in pairs {
+            data.extend_from_slice(x);
+            scores.push(score);
+        }
+        let dim = data.len().checked_div(scores.len()).unwrap_or(0);
+        Simplex {
+            vertices: DMatrix::from_vec(dim, scores.len(), data),
+            scores,
+        }
+    }
+
+    /// Simplex of the `(vertex, score)` pairs, in that order.
+    pub(crate) fn from_pairs(pairs: &[(DVector<T>, T)]) -> Self {
+        Simplex::from_slices(pairs.iter().map(|(x, score)| (x.as_slice(), *score)))
+    }
+
+    /// Number of vertices.
+    pub(crate) fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Whether the simplex has no vertex, e.g. before it is evaluated.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// Dimension of the vertices.
+    pub(crate) fn dim(&self) -> usize {
+        self.vertices.nrows()
+    }
+
+    /// Coordinates of the `i`-th vertex.
+    pub(crate) fn vertex(&self, i: usize) -> &[T] {
+        let dim = self.dim();
+        &self.vertices.as_slice()[i * dim..(i + 1) * dim]
+    }
+
+    /// Mutable coordinates of the `i`-th vertex.
+    pub(crate) fn vertex_mut(&mut self, i: usize) -> &mut [T] {
+        let dim = self.dim();
+        &mut self.vertices.as_mut_slice()[i * dim..(i + 1) * dim]
+    }
+
+    /// The `i`-th vertex as a column view.
+    pub(crate) fn column(&self, i: usize) -> DVectorView<'_, T> {
+        self.vertices.column(i)
+    }
+
+    /// Copy of the `i`-th vertex.
+    pub(crate) fn point(&self, i: usize) -> DVector<T> {
+        self.vertices.column(i).into_owned()
+    }
+
+    /// Score of the `i`-th vertex.
+    pub(crate) fn score(&self, i: usize) -> T {
+        self.scores[i]
+    }
+
+    /// Scores of the vertices, in their order.
+    pub(crate) fn scores(&self) -> &[T] {
+        &self.scores
+    }
+
+    /// Vertices with their scores, in their order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&[T], T)> + '_ {
+        self.vertices.as_slice().chunks_exact(self.dim().max(1)).zip(self.scores.iter().copied())
+    }
+
+    /// Replaces the `i`-th vertex with `x` scoring `score`.
+    pub(crate) fn set(&mut self, i: usize, x: &[T], score: T) {
+        self.vertex_mut(i).copy_from_slice(x);
+        self.scores[i] = score;
+    }
+
+    /// Sum of the vertices, from which the centroid of all but the worst
+    /// vertex follows in `O(n)`.
+    pub(crate) fn sum(&self) -> DVector<T> {
+        self.vertices.column_sum()
+    }
+
+    /// Sum of the first `count` vertices.
+    pub(crate) fn partial_sum(&self, count: usize) -> DVector<T> {
+        self.vertices.columns(0, count).column_sum()
+    }
+
+    /// Vertices with their scores multiplied by `sign`, as pairs.
+    pub(crate) fn to_pairs(&self, sign: T) -> Vec<(DVector<T>, T)> {
+        (0..self.len()).map(|i| (self.point(i), sign * self.scores[i])).collect()
+    }
+
+    /// Overwrites the simplex with `other`, reusing its storage when the
+    /// shapes match.
+    pub(crate) fn copy_from(&mut self, other: &Simplex<T>) {
+        if self.vertices.shape() == other.vertices.shape() {
+            self.vertices.copy_from(&other.vertices);
+            self.scores.copy_from_slice(&other.scores);
+        } else {
+            self.clone_from(other);
+        }
+    }
+
+    /// Orders the vertices from the best one. The sort is stable, so
+    /// vertices with equal scores keep their order. The columns are
+    /// swapped in place, following the cycles of the permutation, so the
+    /// matrix is not copied.
+    pub(crate) fn sort(&mut self) {
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        order.sort_by(|a, b| total_cmp(self.scores[*a], self.scores[*b]));
+        let mut placed = vec![false; order.len()];
+        for start in 0..order.len() {
+            // the `i`-th vertex of the cycle receives the `order[i]`-th one
+            let mut i = start;
+            while !placed[i] {
+                placed[i] = true;
+                let source = order[i];
+                if source == start {
+                    break;
+                }
+                self.vertices.swap_columns(i, source);
+                self.scores.swap(i, source);
+                i = source;
+            }
+        }
+    }
+
+    /// Moves the last vertex of a simplex ordered from the best one, which
+    /// has just replaced the worst vertex, up to its rank. It ranks after
+    /// the vertices it ties with, as under a stable sort, and the others
+    /// keep their order.
+    pub(crate) fn rank_last(&mut self) {
+        let Some((score, ordered)) = self.scores.split_last() else {
+            return;
+        };
+        let rank = ordered.partition_point(|s| total_cmp(*s, *score) != Ordering::Greater);
+        let dim = self.dim();
+        self.scores[rank..].rotate_right(1);
+        self.vertices.as_mut_slice()[rank * dim..].rotate_right(dim);
+    }
+
+    /// Swaps the vertices `i` and `j`, with their scores.
+    pub(crate) fn swap(&mut self, i: usize, j: usize) {
+        self.vertices.swap_columns(i, j);
+        self.scores.swap(i, j);
+    }
+}
+
+impl<T: RealField + Copy> Default for Simplex<T> {
+    fn default() -> Self {
+        Simplex {
+            vertices: DMatrix::from_vec(0, 0, Vec::new()),
+            scores: Vec::new(),
+        }
+    }
+}
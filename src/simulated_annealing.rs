@@ -0,0 +1,573 @@
+use std::collections::VecDeque;
+use std::iter;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::logging;
+use crate::nelder_mead::{is_nan, Direction, NanHandling, NelderMead, StepSize};
+use crate::objective::ObjectiveFunction;
+use crate::observer::{Observer, SharedObserver};
+use crate::progress::Budget;
+use crate::random::Rng;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationCriterion, TerminationState};
+
+/// How the temperature of a [`SimulatedAnnealing`] run decreases with the
+/// iterations `k`, from the initial temperature `t0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CoolingSchedule<T = f64> {
+    /// `t0 a^k`, with the rate `a` within `(0, 1)`.
+    Exponential(T),
+    /// `t0 (1 - k / max_iter)`, reaching zero after the last iteration.
+    Linear,
+    /// `t0 ln 2 / ln(k + 2)`, the slow schedule of Boltzmann annealing.
+    Logarithmic,
+    /// `t0 / (k + 1)`, the schedule of fast annealing.
+    Fast,
+}
+
+impl<T: RealField + Copy> CoolingSchedule<T> {
+    /// Temperature of the iteration `k`.
+    pub(crate) fn temperature(&self, t0: T, k: u64, max_iter: u64) -> T {
+        let k_t: T = convert(k as f64);
+        match self {
+            CoolingSchedule::Exponential(rate) => t0 * rate.powf(k_t),
+            CoolingSchedule::Linear => {
+                t0 * (T::one() - k_t / convert(max_iter.max(1) as f64)).max(T::zero())
+            }
+            CoolingSchedule::Logarithmic => {
+                let two: T = convert(2.0);
+                t0 * two.ln() / (k_t + two).ln()
+            }
+            CoolingSchedule::Fast => t0 / (k_t + T::one()),
+        }
+    }
+}
+
+/// Simulated annealing, a stochastic global optimizer for rugged objective
+/// functions with many local minima, whose best point is polished by a
+/// local Nelder-Mead search.
+///
+/// Every iteration runs a chain of `chain_length` moves at the temperature
+/// given by the [`CoolingSchedule`]. A move draws a neighbour of the
+/// current point, normally distributed around it with the step as the
+/// standard deviation, shrunk by the square root of the ratio of the
+/// temperature to the initial one. The neighbour becomes the current point
+/// when it scores better, and otherwise with the Metropolis probability
+/// `exp(-d / t)`, `d` being how much worse it scores and `t` the
+/// temperature, so that the run climbs out of local minima while it is hot.
+/// The temperature is in units of the objective function, the initial one
+/// should be of the order of the differences between its local minima.
+/// Neighbours beyond the bounds are clipped into them. The run is
+/// reproducible with [`SimulatedAnnealingBuilder::seed`].
+///
+/// The run converges once the temperature falls below `min_temperature`.
+/// The best point found is then refined by the Nelder-Mead solver of
+/// [`polish`](SimulatedAnnealingBuilder::polish), within the bounds and the
+/// remaining budget. Runs are reported as an [`OptimizationResult`],
+/// whose `iterations` are the temperatures followed by the iterations of
+/// the polishing, and whose `final_simplex` holds the best point and the
+/// last `n` points sampled, or the final simplex of the polishing when it
+/// improved on them. The [`Observer`] and the [`TerminationCriterion`] are
+/// given the same points after every iteration.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::DVector;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::simulated_annealing::{CoolingSchedule, SimulatedAnnealing};
+/// use nelder_mead_optimizer::test_functions::rastrigin;
+///
+/// let x_start = DVector::from_element(3, 3.0);
+/// let (lower, upper) = (DVector::from_element(3, -5.12), DVector::from_element(3, 5.12));
+/// let annealing = SimulatedAnnealing::builder()
+///     .bounds(lower, upper)
+///     .cooling(CoolingSchedule::Exponential(0.98))
+///     .seed(2)
+///     .polish(
+///         NelderMead::builder()
+///             .disable_no_improv_break()
+///             .xatol(1e-10)
+///             .fatol(1e-14)
+///             .build(),
+///     )
+///     .minimize(rastrigin, x_start.clone())?;
+/// let plain = NelderMead::builder().minimize(rastrigin, x_start)?;
+///
+/// assert!(annealing.best_score < 1e-10);
+/// assert!(plain.best_score > 1.0);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct SimulatedAnnealing<T = f64> {
+    pub(crate) step: StepSize<T>,
+    pub(crate) initial_temperature: T,
+    pub(crate) min_temperature: Option<T>,
+    pub(crate) cooling: CoolingSchedule<T>,
+    pub(crate) chain_length: Option<usize>,
+    pub(crate) seed: Option<u64>,
+    pub(crate) polish: Option<NelderMead<T>>,
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) bounds: Option<(DVector<T>, DVector<T>)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for SimulatedAnnealing<T> {
+    fn default() -> Self {
+        SimulatedAnnealing {
+            step: StepSize::Uniform(convert(1.0)),
+            initial_temperature: T::one(),
+            min_temperature: None,
+            cooling: CoolingSchedule::Exponential(convert(0.95)),
+            chain_length: None,
+            seed: None,
+            polish: Some(NelderMead::default()),
+            max_iter: 1000,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            bounds: None,
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`SimulatedAnnealing`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `step`                  | 1                    |
+/// | `initial_temperature`   | 1                    |
+/// | `min_temperature`       | 1e-3 t0              |
+/// | `cooling`               | `Exponential(0.95)`  |
+/// | `chain_length`          | 10 n                 |
+/// | `seed`                  | random               |
+/// | `polish`                | `NelderMead` default |
+/// | `max_iter`              | 1000                 |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `bounds`                | none                 |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct SimulatedAnnealingBuilder<T = f64> {
+    config: SimulatedAnnealing<T>,
+}
+
+impl<T: RealField + Copy> Default for SimulatedAnnealingBuilder<T> {
+    fn default() -> Self {
+        SimulatedAnnealingBuilder {
+            config: SimulatedAnnealing::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> SimulatedAnnealingBuilder<T> {
+    /// Standard deviation of the moves at the initial temperature, either a
+    /// single value used for every dimension or a [`StepSize`].
+    pub fn step(mut self, step: impl Into<StepSize<T>>) -> Self {
+        self.config.step = step.into();
+        self
+    }
+
+    /// Temperature of the first iteration, positive and finite.
+    pub fn initial_temperature(mut self, initial_temperature: T) -> Self {
+        self.config.initial_temperature = initial_temperature;
+        self
+    }
+
+    /// Temperature below which the run converges, positive and below the
+    /// initial one. The polishing takes over from there.
+    pub fn min_temperature(mut self, min_temperature: T) -> Self {
+        self.config.min_temperature = Some(min_temperature);
+        self
+    }
+
+    /// How the temperature decreases with the iterations.
+    pub fn cooling(mut self, cooling: CoolingSchedule<T>) -> Self {
+        self.config.cooling = cooling;
+        self
+    }
+
+    /// Number of moves at every temperature, positive.
+    pub fn chain_length(mut self, chain_length: usize) -> Self {
+        self.config.chain_length = Some(chain_length);
+        self
+    }
+
+    /// Seed of the random numbers, making the run reproducible. Without a
+    /// seed every run draws different numbers.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Nelder-Mead solver refining the best point once the run converged or
+    /// ran out of iterations. Its direction, NaN handling and budgets are
+    /// taken from this solver, and so are the bounds unless it has its own.
+    pub fn polish(mut self, polish: NelderMead<T>) -> Self {
+        self.config.polish = Some(polish);
+        self
+    }
+
+    /// Report the best point found without polishing it.
+    pub fn disable_polish(mut self) -> Self {
+        self.config.polish = None;
+        self
+    }
+
+    /// Maximum number of iterations, i.e. of temperatures.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times,
+    /// polishing included.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long, polishing included.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Lower and upper bounds of every parameter. The starting point and
+    /// the neighbours are clipped into them.
+    pub fn bounds(mut self, lower: DVector<T>, upper: DVector<T>) -> Self {
+        self.config.bounds = Some((lower, upper));
+        self
+    }
+
+    /// Additional stopping rule, checked after every iteration next to the
+    /// built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every iteration with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> SimulatedAnnealing<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`SimulatedAnnealing::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`SimulatedAnnealing::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, x_start)
+    }
+}
+
+/// Evaluations of a run, with the budgets they are bounded by, and the
+/// points reported in its state.
+struct Run<'a, T, F> {
+    config: &'a SimulatedAnnealing<T>,
+    obj_fn: F,
+    sign: T,
+    evaluations: u64,
+    start: Instant,
+    // best point found, and the last `n` other points sampled
+    best: (DVector<T>, T),
+    recent: VecDeque<(DVector<T>, T)>,
+}
+
+impl<T: RealField + Copy, F: FnMut(&DVector<T>) -> T> Run<'_, T, F> {
+    /// Score of `x`, or `None` when the evaluation budget or the time ran
+    /// out.
+    fn score(&mut self, x: &DVector<T>) -> Result<Option<T>, NelderMeadError> {
+        if self.exhausted().is_some() {
+            return Ok(None);
+        }
+        let score = self.evaluate(x)?;
+        let mut point = (x.clone(), score);
+        if score < self.best.1 {
+            // the previous best point becomes the latest one sampled
+            point = std::mem::replace(&mut self.best, point);
+        }
+        self.recent.pop_front();
+        self.recent.push_back(point);
+        Ok(Some(score))
+    }
+
+    /// Score of `x`, regardless of the budgets.
+    fn evaluate(&mut self, x: &DVector<T>) -> Result<T, NelderMeadError> {
+        self.evaluations += 1;
+        let value = (self.obj_fn)(x);
+        self.config.nan_handling.score(value, self.sign)
+    }
+
+    /// Why the run has to stop before the next evaluation, if it does.
+    fn exhausted(&self) -> Option<TerminationReason> {
+        if self.config.max_fun_evals.is_some_and(|limit| self.evaluations >= limit) {
+            return Some(TerminationReason::MaxFunEvals);
+        }
+        if self.config.max_duration.is_some_and(|limit| self.start.elapsed() >= limit) {
+            return Some(TerminationReason::TimedOut);
+        }
+        None
+    }
+
+    /// Simplex of the best point and the last points sampled, ordered from
+    /// the best one.
+    fn simplex(&self) -> Simplex<T> {
+        let points = iter::once(&self.best).chain(&self.recent);
+        let mut simplex = Simplex::from_slices(points.map(|(x, score)| (x.as_slice(), *score)));
+        simplex.sort();
+        simplex
+    }
+}
+
+impl<T: RealField + Copy> SimulatedAnnealing<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> SimulatedAnnealingBuilder<T> {
+        SimulatedAnnealingBuilder::default()
+    }
+
+    /// Finds the minimum (or maximum, depending on the configured
+    /// [`Direction`]) of the objective function, starting from `x_start`.
+    ///
+    /// Fails if `x_start` is empty, if the initial temperature is not
+    /// positive and finite, the minimum temperature not positive and below
+    /// it, the exponential cooling rate not within `(0, 1)` or the chain
+    /// empty, if the step or the bounds do not fit `x_start`, or if the
+    /// objective function returns NaN under [`NanHandling::Error`]. The
+    /// polishing fails like [`NelderMead::minimize`].
+    pub fn minimize<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let dim = x_start.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        let t0 = self.initial_temperature;
+        let min_temperature = self.min_temperature.unwrap_or(t0 * convert(1e-3));
+        let chain_length = self.chain_length.unwrap_or(10 * dim);
+        let valid = T::zero() < t0
+            && t0.is_finite()
+            && T::zero() < min_temperature
+            && min_temperature < t0
+            && chain_length > 0
+            && match self.cooling {
+                CoolingSchedule::Exponential(rate) => T::zero() < rate && rate < T::one(),
+                _ => true,
+            };
+        if !valid {
+            return Err(NelderMeadError::InvalidAnnealing);
+        }
+        self.step.check_dim(dim)?;
+        let mut x_start = x_start;
+        if let Some((lower, upper)) = &self.bounds {
+            if lower.len() != dim
+                || upper.len() != dim
+                || lower.iter().zip(upper.iter()).any(|(l, u)| l > u || is_nan(*l) || is_nan(*u))
+            {
+                return Err(NelderMeadError::InvalidBounds);
+            }
+            x_start = x_start.sup(lower).inf(upper);
+        }
+
+        let mut run = Run {
+            config: self,
+            obj_fn,
+            sign: self.direction.sign(),
+            evaluations: 0,
+            start: Instant::now(),
+            best: (x_start.clone(), T::zero()),
+            recent: VecDeque::new(),
+        };
+        let score = run.evaluate(&x_start)?;
+        run.best.1 = score;
+        run.recent = iter::repeat_n((x_start.clone(), score), dim).collect();
+        let mut rng = Rng::seeded(self.seed);
+        let mut current = (x_start, score);
+        let mut iterations = 0;
+
+        let termination_reason = 'run: loop {
+            if iterations >= self.max_iter {
+                break TerminationReason::MaxIter;
+            }
+            if let Some(reason) = run.exhausted() {
+                break reason;
+            }
+            let temperature = self.cooling.temperature(t0, iterations, self.max_iter);
+            if temperature < min_temperature {
+                break TerminationReason::Converged;
+            }
+
+            let scale = (temperature / t0).sqrt();
+            for _ in 0..chain_length {
+                let neighbour = DVector::from_fn(dim, |i, _| {
+                    let deviate: T = convert(rng.normal());
+                    current.0[i] + self.step.offset(i, current.0[i]) * scale * deviate
+                });
+                let neighbour = self.clip(neighbour);
+                let Some(score) = run.score(&neighbour)? else {
+                    break 'run run.exhausted().unwrap_or(TerminationReason::MaxFunEvals);
+                };
+                let worsening = score - current.1;
+                let u: T = convert(rng.uniform());
+                if worsening <= T::zero() || u < (-worsening / temperature).exp() {
+                    current = (neighbour, score);
+                }
+            }
+            iterations += 1;
+
+            let simplex = run.simplex();
+            let state = TerminationState {
+                iterations,
+                evaluations: run.evaluations,
+                elapsed: run.start.elapsed(),
+                simplex: &simplex,
+                sign: run.sign,
+                last_move: None,
+                budget: Budget {
+                    max_iter: self.max_iter,
+                    max_fun_evals: self.max_fun_evals,
+                    max_duration: self.max_duration,
+                },
+            };
+            logging::log_iteration(&state);
+            if let Some(observer) = &self.observer {
+                observer.0.observe(&state);
+            }
+            if let Some(criterion) = &self.termination {
+                if criterion.0.should_stop(&state) {
+                    break TerminationReason::Criterion;
+                }
+            }
+        };
+
+        let mut final_simplex = run.simplex().to_pairs(run.sign);
+        let best = final_simplex[0].clone();
+        let polishing = !matches!(
+            termination_reason,
+            TerminationReason::MaxFunEvals | TerminationReason::TimedOut
+        );
+        if let Some(polish) = self.polish.as_ref().filter(|_| polishing) {
+            let local = polish.polishing(
+                self.direction,
+                self.nan_handling,
+                &self.bounds,
+                self.max_fun_evals.map(|limit| limit.saturating_sub(run.evaluations)),
+                self.max_duration.map(|limit| limit.saturating_sub(run.start.elapsed())),
+            );
+            let polished = local.minimize(&mut run.obj_fn, best.0)?;
+            run.evaluations += polished.evaluations;
+            iterations += polished.iterations;
+            if run.sign * polished.best_score < run.sign * best.1 {
+                final_simplex = polished.final_simplex;
+            }
+        }
+
+        Ok(OptimizationResult::from_simplex(
+            final_simplex,
+            T::one(),
+            iterations,
+            run.evaluations,
+            0,
+            termination_reason,
+            None,
+        ))
+    }
+
+    /// Same as [`SimulatedAnnealing::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), x_start)
+    }
+
+    /// Copy of `x` clipped into the bounds.
+    fn clip(&self, x: DVector<T>) -> DVector<T> {
+        match &self.bounds {
+            Some((lower, upper)) => x.sup(lower).inf(upper),
+            None => x,
+        }
+    }
+}
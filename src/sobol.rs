@@ -0,0 +1,174 @@
+use nalgebra::DVector;
+
+use crate::error::NelderMeadError;
+use crate::random::Rng;
+
+/// Number of bits of every coordinate, and the base-2 logarithm of the
+/// number of points of the sequence.
+const BITS: usize = 32;
+
+/// Degree `s`, coefficients `a` of the primitive polynomial and initial
+/// direction numbers `m` of every dimension after the first, from the
+/// `new-joe-kuo-6.21201` table of S. Joe and F. Y. Kuo.
+const DIRECTION_NUMBERS: [(u32, u32, &[u32]); Sobol::MAX_DIMENSION - 1] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+    (6, 19, &[1, 1, 1, 15, 7, 5]),
+    (6, 22, &[1, 3, 1, 15, 13, 25]),
+    (6, 25, &[1, 1, 5, 5, 19, 61]),
+    (7, 1, &[1, 3, 7, 11, 23, 15, 103]),
+    (7, 4, &[1, 3, 7, 13, 13, 15, 69]),
+    (7, 7, &[1, 1, 3, 13, 7, 35, 63]),
+    (7, 8, &[1, 3, 5, 9, 1, 25, 53]),
+    (7, 14, &[1, 3, 1, 13, 9, 35, 107]),
+    (7, 19, &[1, 3, 1, 5, 27, 61, 31]),
+    (7, 21, &[1, 1, 5, 11, 19, 41, 61]),
+    (7, 28, &[1, 3, 5, 3, 3, 13, 69]),
+    (7, 31, &[1, 1, 7, 13, 1, 19, 1]),
+    (7, 32, &[1, 3, 7, 5, 13, 19, 59]),
+    (7, 37, &[1, 1, 3, 9, 25, 29, 41]),
+    (7, 41, &[1, 3, 5, 13, 23, 1, 55]),
+    (7, 42, &[1, 3, 7, 3, 13, 59, 17]),
+    (7, 50, &[1, 3, 1, 3, 5, 53, 69]),
+    (7, 55, &[1, 1, 5, 5, 23, 33, 13]),
+    (7, 56, &[1, 1, 7, 7, 1, 61, 123]),
+    (7, 59, &[1, 1, 7, 9, 13, 61, 49]),
+    (7, 62, &[1, 3, 3, 5, 3, 55, 33]),
+    (8, 14, &[1, 3, 1, 15, 31, 13, 49, 245]),
+    (8, 21, &[1, 3, 5, 15, 31, 59, 63, 97]),
+    (8, 22, &[1, 3, 1, 11, 11, 11, 77, 249]),
+];
+
+/// Sobol sequence, a quasi-random low-discrepancy sequence of points in
+/// the unit cube `[0, 1)^n`.
+///
+/// Its first `2^k` points split every axis into `2^k` equal intervals
+/// holding one point each, and cover the cube far more evenly than random
+/// draws, so that start points taken from it leave no region of the search
+/// box unexplored. Points are generated in Gray code order with the
+/// direction numbers of Joe and Kuo, for up to [`Sobol::MAX_DIMENSION`]
+/// dimensions. [`Sobol::scrambled`] applies a random digital shift to the
+/// sequence, which keeps its stratification while moving its first point
+/// off the origin, and makes it reproducible from a seed.
+///
+/// # Examples
+///
+/// ```
+/// use nelder_mead_optimizer::sobol::Sobol;
+///
+/// let points: Vec<_> = Sobol::new(2)?.take(4).collect();
+///
+/// assert_eq!(points[1].as_slice(), &[0.5, 0.5]);
+/// assert_eq!(points[2].as_slice(), &[0.75, 0.25]);
+/// assert_eq!(points[3].as_slice(), &[0.25, 0.75]);
+///
+/// // one point in every quarter of both axes
+/// let shifted: Vec<_> = Sobol::scrambled(2, 7)?.take(4).collect();
+/// for axis in 0..2 {
+///     let mut quarters: Vec<_> = shifted.iter().map(|x| (x[axis] * 4.0) as usize).collect();
+///     quarters.sort();
+///     assert_eq!(quarters, [0, 1, 2, 3]);
+/// }
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sobol {
+    directions: Vec<[u32; BITS]>,
+    // coordinates of the next point, as binary fractions
+    state: Vec<u32>,
+    shift: Vec<u32>,
+    // index of the next point, `None` once the sequence is exhausted
+    index: Option<u32>,
+}
+
+impl Sobol {
+    /// Largest dimension supported.
+    pub const MAX_DIMENSION: usize = 40;
+
+    /// Sequence in `dim` dimensions, starting at the origin.
+    ///
+    /// Fails with [`NelderMeadError::InvalidSampling`] if `dim` is zero or
+    /// above [`Sobol::MAX_DIMENSION`].
+    pub fn new(dim: usize) -> Result<Self, NelderMeadError> {
+        if dim == 0 || dim > Sobol::MAX_DIMENSION {
+            return Err(NelderMeadError::InvalidSampling);
+        }
+        let mut directions = Vec::with_capacity(dim);
+        directions.push(std::array::from_fn(|k| 1 << (BITS - 1 - k)));
+        for &(degree, coefficients, initial) in &DIRECTION_NUMBERS[..dim - 1] {
+            let s = degree as usize;
+            let mut v = [0u32; BITS];
+            for k in 0..BITS {
+                v[k] = if k < s {
+                    initial[k] << (BITS - 1 - k)
+                } else {
+                    let mut value = v[k - s] ^ (v[k - s] >> s);
+                    for j in 1..s {
+                        if (coefficients >> (s - 1 - j)) & 1 == 1 {
+                            value ^= v[k - j];
+                        }
+                    }
+                    value
+                };
+            }
+            directions.push(v);
+        }
+        Ok(Sobol {
+            directions,
+            state: vec![0; dim],
+            shift: vec![0; dim],
+            index: Some(0),
+        })
+    }
+
+    /// Sequence in `dim` dimensions, digitally shifted by random bits drawn
+    /// from `seed`.
+    ///
+    /// Fails like [`Sobol::new`].
+    pub fn scrambled(dim: usize, seed: u64) -> Result<Self, NelderMeadError> {
+        let mut sobol = Sobol::new(dim)?;
+        let mut rng = Rng::new(seed);
+        sobol.shift = (0..dim).map(|_| (rng.next_u64() >> BITS) as u32).collect();
+        Ok(sobol)
+    }
+
+    /// Dimension of the points.
+    pub fn dimension(&self) -> usize {
+        self.directions.len()
+    }
+}
+
+impl Iterator for Sobol {
+    type Item = DVector<f64>;
+
+    fn next(&mut self) -> Option<DVector<f64>> {
+        let index = self.index?;
+        let scale = (1u64 << BITS) as f64;
+        let point = DVector::from_fn(self.dimension(), |i, _| {
+            f64::from(self.state[i] ^ self.shift[i]) / scale
+        });
+        // the next point flips the direction of the lowest zero bit of the
+        // index, which runs past the last bit after the last point
+        let bit = index.trailing_ones() as usize;
+        self.index = index.checked_add(1);
+        if bit < BITS {
+            for (x, v) in self.state.iter_mut().zip(&self.directions) {
+                *x ^= v[bit];
+            }
+        }
+        Some(point)
+    }
+}
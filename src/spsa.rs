@@ -0,0 +1,601 @@
+use std::collections::VecDeque;
+use std::iter;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::error::NelderMeadError;
+use crate::logging;
+use crate::nelder_mead::{is_nan, Direction, NanHandling};
+#[cfg(feature = "rayon")]
+use crate::objective::Parallel;
+use crate::objective::{Batch, ByRef, Evaluator, ObjectiveFunction};
+use crate::observer::{Observer, SharedObserver};
+use crate::progress::Budget;
+use crate::random::Rng;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::simplex::Simplex;
+use crate::termination::{SharedCriterion, TerminationCriterion, TerminationState};
+
+/// Simultaneous perturbation stochastic approximation (SPSA), a gradient
+/// descent on noisy objectives whose gradient is estimated from two
+/// evaluations whatever the number of parameters, for problems with
+/// hundreds of them where a simplex no longer makes progress.
+///
+/// Every iteration `k` perturbs the current point `x` along a random
+/// direction `Δ` of `±1` entries, evaluates `x + c_k Δ` and `x - c_k Δ`,
+/// and estimates every partial derivative `g_i` by the difference of both
+/// scores divided by `2 c_k Δ_i`. The point then moves to `x - a_k g`,
+/// clipped into the bounds. The gains decrease as `a_k = a / (k + 1 +
+/// A)^α` and `c_k = c / (k + 1)^γ`, with Spall's exponents `α = 0.602`
+/// and `γ = 0.101` by default and the stability constant `A` a tenth of
+/// `max_iter`. Averaging several perturbations per iteration, see
+/// [`SpsaBuilder::averaging`], smooths the gradient estimate. The
+/// perturbation `c` should be of the order of the noise of the objective
+/// in units of the parameters, and `a` is calibrated from the first
+/// gradient estimate so that the first move has the size of
+/// [`step`](SpsaBuilder::step), unless set with
+/// [`gain`](SpsaBuilder::gain). The run is reproducible with
+/// [`SpsaBuilder::seed`].
+///
+/// The perturbed points of every iteration are evaluated as one batch, see
+/// [`Spsa::minimize_batch`] and `Spsa::minimize_parallel`. The run goes
+/// on until its budgets are spent or the termination criterion stops it.
+/// It is reported as an [`OptimizationResult`] whose `best_point` is the
+/// last point, scored by the mean of the scores of its perturbed points,
+/// and whose `final_simplex` holds it followed by the `n` previous points,
+/// most recent first. The [`Observer`] and the [`TerminationCriterion`]
+/// are given the same points after every iteration.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::DVector;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::spsa::Spsa;
+///
+/// // a quadratic in 100 parameters with uniform noise in [-0.05, 0.05)
+/// // from a linear congruential generator
+/// fn noisy() -> impl FnMut(&DVector<f64>) -> f64 {
+///     let mut state = 7u64;
+///     move |x| {
+///         state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+///         let noise = (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+///         let f: f64 = x.iter().enumerate().map(|(i, xi)| (xi - i as f64 / 100.0).powi(2)).sum();
+///         f + 0.1 * noise
+///     }
+/// }
+/// let x_start = DVector::from_element(100, 1.0);
+/// let results = Spsa::builder()
+///     .seed(3)
+///     .max_iter(2000)
+///     .minimize(noisy(), x_start.clone())?;
+/// let plain = NelderMead::builder()
+///     .max_fun_evals(results.evaluations)
+///     .minimize(noisy(), x_start)?;
+///
+/// assert_eq!(results.evaluations, 2 * 2001);
+/// assert!(results.best_score < 0.5);
+/// assert!(plain.best_score > 30.0);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct Spsa<T = f64> {
+    pub(crate) step: T,
+    pub(crate) gain: Option<T>,
+    pub(crate) perturbation: T,
+    pub(crate) stability: Option<T>,
+    pub(crate) alpha: T,
+    pub(crate) gamma: T,
+    pub(crate) averaging: usize,
+    pub(crate) seed: Option<u64>,
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) bounds: Option<(DVector<T>, DVector<T>)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) termination: Option<SharedCriterion<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) observer: Option<SharedObserver<T>>,
+}
+
+impl<T: RealField + Copy> Default for Spsa<T> {
+    fn default() -> Self {
+        Spsa {
+            step: convert(0.1),
+            gain: None,
+            perturbation: convert(0.1),
+            stability: None,
+            alpha: convert(0.602),
+            gamma: convert(0.101),
+            averaging: 1,
+            seed: None,
+            max_iter: 1000,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            bounds: None,
+            termination: None,
+            observer: None,
+        }
+    }
+}
+
+/// Builder of a [`Spsa`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `step`                  | 0.1                  |
+/// | `gain`                  | calibrated           |
+/// | `perturbation`          | 0.1                  |
+/// | `stability`             | 0.1 `max_iter`       |
+/// | `exponents`             | `(0.602, 0.101)`     |
+/// | `averaging`             | 1                    |
+/// | `seed`                  | random               |
+/// | `max_iter`              | 1000                 |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `bounds`                | none                 |
+/// | `termination`           | none                 |
+/// | `observer`              | none                 |
+#[derive(Debug, Clone)]
+pub struct SpsaBuilder<T = f64> {
+    config: Spsa<T>,
+}
+
+impl<T: RealField + Copy> Default for SpsaBuilder<T> {
+    fn default() -> Self {
+        SpsaBuilder {
+            config: Spsa::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> SpsaBuilder<T> {
+    /// Size of the largest coordinate change of the first move, which the
+    /// gain `a` is calibrated to, positive and finite.
+    pub fn step(mut self, step: T) -> Self {
+        self.config.step = step;
+        self
+    }
+
+    /// Gain `a` of the moves, positive and finite, instead of the one
+    /// calibrated from the step.
+    pub fn gain(mut self, gain: T) -> Self {
+        self.config.gain = Some(gain);
+        self
+    }
+
+    /// Size `c` of the first perturbations, positive and finite.
+    pub fn perturbation(mut self, perturbation: T) -> Self {
+        self.config.perturbation = perturbation;
+        self
+    }
+
+    /// Stability constant `A` delaying the decrease of the gain of the
+    /// moves, non-negative and finite.
+    pub fn stability(mut self, stability: T) -> Self {
+        self.config.stability = Some(stability);
+        self
+    }
+
+    /// Exponents `α` and `γ` of the decrease of the gains of the moves and
+    /// of the perturbations, positive. Asymptotically optimal values are
+    /// `1` and `1/6`.
+    pub fn exponents(mut self, alpha: T, gamma: T) -> Self {
+        self.config.alpha = alpha;
+        self.config.gamma = gamma;
+        self
+    }
+
+    /// Number of perturbations averaged into the gradient estimate of
+    /// every iteration, positive, each costing two evaluations.
+    pub fn averaging(mut self, averaging: usize) -> Self {
+        self.config.averaging = averaging;
+        self
+    }
+
+    /// Seed of the random numbers, making the run reproducible. Without a
+    /// seed every run draws different numbers.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Maximum number of iterations, i.e. of moves.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Lower and upper bounds of every parameter. The starting point, the
+    /// perturbed points and the moves are clipped into them.
+    pub fn bounds(mut self, lower: DVector<T>, upper: DVector<T>) -> Self {
+        self.config.bounds = Some((lower, upper));
+        self
+    }
+
+    /// Additional stopping rule, checked after every iteration next to the
+    /// built-in ones. See [`TerminationCriterion`].
+    pub fn termination<C>(mut self, criterion: C) -> Self
+    where
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        self.config.termination = Some(SharedCriterion(Arc::new(criterion)));
+        self
+    }
+
+    /// Hook invoked after every iteration with the state of the run, see
+    /// [`Observer`].
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<T> + Send + Sync + 'static,
+    {
+        self.config.observer = Some(SharedObserver(Arc::new(observer)));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> Spsa<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Spsa::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Spsa::minimize_batch`].
+    pub fn minimize_batch<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&[DVector<T>]) -> Vec<T>,
+    {
+        self.build().minimize_batch(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Spsa::minimize_parallel`].
+    #[cfg(feature = "rayon")]
+    pub fn minimize_parallel<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        T: Send + Sync,
+        F: Fn(&DVector<T>) -> T + Sync,
+    {
+        self.build().minimize_parallel(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Spsa::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, x_start)
+    }
+}
+
+impl<T: RealField + Copy> Spsa<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> SpsaBuilder<T> {
+        SpsaBuilder::default()
+    }
+
+    /// Finds a minimum (or maximum, depending on the configured
+    /// [`Direction`]) of the objective function, starting from `x_start`.
+    ///
+    /// Fails if `x_start` is empty, if the step, the gain or the
+    /// perturbation is not positive and finite, the stability constant
+    /// negative or infinite, an exponent not positive or no perturbation
+    /// averaged, if the bounds do not fit `x_start`, or if the objective
+    /// function returns NaN under [`NanHandling::Error`].
+    pub fn minimize<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.minimize_with(obj_fn, x_start)
+    }
+
+    /// Same as [`Spsa::minimize`], but optimizes a batch objective, taking
+    /// several points at once and returning their values in the same
+    /// order. The perturbed points of every iteration are evaluated with a
+    /// single call.
+    ///
+    /// Fails with [`NelderMeadError::InvalidBatch`] if the objective does
+    /// not return one value per point.
+    pub fn minimize_batch<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&[DVector<T>]) -> Vec<T>,
+    {
+        let mut batch = Batch { obj_fn, mismatch: false };
+        self.minimize_with(ByRef(&mut batch), x_start)
+    }
+
+    /// Same as [`Spsa::minimize`], but evaluates the perturbed points of
+    /// every iteration in parallel on the rayon thread pool. The result is
+    /// the same as with [`Spsa::minimize`] for the same seed.
+    #[cfg(feature = "rayon")]
+    pub fn minimize_parallel<F>(
+        &self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        T: Send + Sync,
+        F: Fn(&DVector<T>) -> T + Sync,
+    {
+        self.minimize_with(Parallel(obj_fn), x_start)
+    }
+
+    /// Same as [`Spsa::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), x_start)
+    }
+
+    /// Same as [`Spsa::minimize`], with the objective evaluated by
+    /// `evaluator`.
+    fn minimize_with<E>(
+        &self,
+        mut evaluator: E,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        E: Evaluator<T>,
+    {
+        let dim = x_start.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        let stability = self.stability.unwrap_or(convert(0.1 * self.max_iter as f64));
+        let positive = |value: T| T::zero() < value && value.is_finite();
+        let valid = positive(self.step)
+            && self.gain.is_none_or(positive)
+            && positive(self.perturbation)
+            && T::zero() <= stability
+            && stability.is_finite()
+            && positive(self.alpha)
+            && positive(self.gamma)
+            && self.averaging > 0;
+        if !valid {
+            return Err(NelderMeadError::InvalidSpsa);
+        }
+        let mut x = x_start;
+        if let Some((lower, upper)) = &self.bounds {
+            if lower.len() != dim
+                || upper.len() != dim
+                || lower.iter().zip(upper.iter()).any(|(l, u)| l > u || is_nan(*l) || is_nan(*u))
+            {
+                return Err(NelderMeadError::InvalidBounds);
+            }
+            x = x.sup(lower).inf(upper);
+        }
+
+        let start = Instant::now();
+        let sign = self.direction.sign();
+        let mut rng = Rng::seeded(self.seed);
+        let mut evaluations = 0;
+        let mut gain = self.gain;
+        // last point with its estimated score, and the points before it
+        let mut current = None;
+        let mut recent = VecDeque::new();
+        let mut iterations = 0;
+
+        let termination_reason = loop {
+            if self.max_duration.is_some_and(|limit| start.elapsed() >= limit) {
+                break TerminationReason::TimedOut;
+            }
+            let k: T = convert(iterations as f64);
+            let c = self.perturbation / (k + T::one()).powf(self.gamma);
+            let mut points = Vec::with_capacity(2 * self.averaging);
+            for _ in 0..self.averaging {
+                let delta = DVector::from_fn(dim, |_, _| if rng.uniform() < 0.5 { -T::one() } else { T::one() });
+                points.push(self.clip(&x + &delta * c));
+                points.push(self.clip(&x - &delta * c));
+            }
+            let scores = self.evaluate(&mut evaluator, &points, &mut evaluations, sign, true)?;
+            if scores.len() < points.len() {
+                break TerminationReason::MaxFunEvals;
+            }
+
+            // the clipped points may lie closer than `2 c` along some
+            // coordinates, which the differences are divided by
+            let count: T = convert(points.len() as f64);
+            let score = scores.iter().fold(T::zero(), |sum, score| sum + *score) / count;
+            let mut gradient = DVector::zeros(dim);
+            for (pair, values) in points.chunks(2).zip(scores.chunks(2)) {
+                for i in 0..dim {
+                    let width = pair[0][i] - pair[1][i];
+                    if !width.is_zero() {
+                        gradient[i] += (values[0] - values[1]) / width;
+                    }
+                }
+            }
+            gradient /= convert::<f64, T>(self.averaging as f64);
+            if let Some(previous) = current.replace((x.clone(), score)) {
+                recent.push_front(previous);
+                recent.truncate(dim);
+            }
+
+            let simplex = self.simplex(current.as_ref(), &recent, dim);
+            let state = TerminationState {
+                iterations,
+                evaluations,
+                elapsed: start.elapsed(),
+                simplex: &simplex,
+                sign,
+                last_move: None,
+                budget: Budget {
+                    max_iter: self.max_iter,
+                    max_fun_evals: self.max_fun_evals,
+                    max_duration: self.max_duration,
+                },
+            };
+            logging::log_iteration(&state);
+            if let Some(observer) = &self.observer {
+                observer.0.observe(&state);
+            }
+            if let Some(criterion) = &self.termination {
+                if criterion.0.should_stop(&state) {
+                    break TerminationReason::Criterion;
+                }
+            }
+            if iterations >= self.max_iter {
+                break TerminationReason::MaxIter;
+            }
+
+            let decay = (stability + T::one()).powf(self.alpha);
+            let a = *gain.get_or_insert_with(|| {
+                let magnitude = gradient.amax();
+                let magnitude = if positive(magnitude) { magnitude } else { T::one() };
+                self.step * decay / magnitude
+            });
+            x = self.clip(&x - gradient * (a / (k + T::one() + stability).powf(self.alpha)));
+            iterations += 1;
+        };
+
+        // a run stopped before its first gradient still scores its point
+        let current = match current {
+            Some(current) => current,
+            None => {
+                let scores = self.evaluate(&mut evaluator, std::slice::from_ref(&x), &mut evaluations, sign, false)?;
+                (x, scores[0])
+            }
+        };
+        let simplex = self.simplex(Some(&current), &recent, dim);
+        Ok(OptimizationResult::from_simplex(
+            simplex.to_pairs(T::one()),
+            sign,
+            iterations,
+            evaluations,
+            0,
+            termination_reason,
+            None,
+        ))
+    }
+
+    /// Scores of `points`, evaluated as one batch. With `limited`, only the
+    /// points within the remaining evaluation budget are evaluated.
+    fn evaluate<E>(
+        &self,
+        evaluator: &mut E,
+        points: &[DVector<T>],
+        evaluations: &mut u64,
+        sign: T,
+        limited: bool,
+    ) -> Result<Vec<T>, NelderMeadError>
+    where
+        E: Evaluator<T>,
+    {
+        let count = match self.max_fun_evals {
+            Some(limit) if limited => points.len().min(limit.saturating_sub(*evaluations) as usize),
+            _ => points.len(),
+        };
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let values = evaluator.evaluate_points(&points[..count]);
+        if values.len() != count {
+            return Err(NelderMeadError::InvalidBatch);
+        }
+        *evaluations += count as u64;
+        values.into_iter().map(|value| self.nan_handling.score(value, sign)).collect()
+    }
+
+    /// Simplex of the last point followed by the points before it, most
+    /// recent first, the earliest one repeated up to `n + 1` points.
+    fn simplex(
+        &self,
+        current: Option<&(DVector<T>, T)>,
+        recent: &VecDeque<(DVector<T>, T)>,
+        dim: usize,
+    ) -> Simplex<T> {
+        let points: Vec<&(DVector<T>, T)> = current.into_iter().chain(recent).collect();
+        let earliest = points[points.len() - 1];
+        let padded = points.into_iter().chain(iter::repeat(earliest)).take(dim + 1);
+        Simplex::from_slices(padded.map(|(x, score)| (x.as_slice(), *score)))
+    }
+
+    /// Copy of `x` clipped into the bounds.
+    fn clip(&self, x: DVector<T>) -> DVector<T> {
+        match &self.bounds {
+            Some((lower, upper)) => x.sup(lower).inf(upper),
+            None => x,
+        }
+    }
+}
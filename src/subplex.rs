@@ -0,0 +1,489 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::coefficients::Coefficients;
+use crate::error::NelderMeadError;
+use crate::nelder_mead::{is_nan, ContractionMode, Direction, NanHandling, NelderMead, StepSize};
+use crate::objective::ObjectiveFunction;
+use crate::result::{OptimizationResult, TerminationReason};
+use crate::termination::{SharedCriterion, SimplexSize};
+
+/// Rowan's Subplex, which runs Nelder-Mead on a sequence of
+/// low-dimensional subspaces of the parameters.
+///
+/// Every cycle splits the parameters into subspaces of `min..=max`
+/// coordinates, grouping those which moved the most in the previous cycle,
+/// and minimizes along each subspace in turn with a Nelder-Mead simplex,
+/// the other coordinates held fixed. A subspace search stops once its
+/// simplex shrank by the factor `psi`. The steps of the next cycle follow
+/// the progress of the previous one, scaled within `[omega, 1 / omega]`.
+/// Plain Nelder-Mead degrades quickly above 10 to 15 parameters, as its
+/// simplex collapses or stalls, while the small simplices of Subplex stay
+/// well shaped in any dimension.
+///
+/// The run converges once the last cycle moved every coordinate, and its
+/// next step, by less than `xtol` relative to the coordinate, or absolutely
+/// below 1. Runs are reported as an [`OptimizationResult`], whose
+/// `iterations` are the cycles and whose `final_simplex` holds the vertices
+/// of the last subspace search, the other coordinates filled in.
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::DVector;
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::subplex::Subplex;
+/// use nelder_mead_optimizer::test_functions::ackley;
+///
+/// let x_start = DVector::from_element(30, 0.3);
+/// let subplex = Subplex::builder()
+///     .xtol(1e-8)
+///     .max_fun_evals(20_000)
+///     .minimize(ackley, x_start.clone())?;
+/// let plain = NelderMead::builder()
+///     .disable_no_improv_break()
+///     .max_iter(u64::MAX)
+///     .max_fun_evals(20_000)
+///     .minimize(ackley, x_start)?;
+///
+/// assert!(subplex.best_score < 1e-6);
+/// assert!(subplex.evaluations < 5_000);
+/// assert!(plain.best_score > 0.1);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: nalgebra::Scalar + serde::Serialize",
+        deserialize = "T: nalgebra::Scalar + serde::Deserialize<'de>"
+    ))
+)]
+pub struct Subplex<T = f64> {
+    pub(crate) step: StepSize<T>,
+    pub(crate) xtol: T,
+    pub(crate) psi: T,
+    pub(crate) omega: T,
+    pub(crate) min_subspace: usize,
+    pub(crate) max_subspace: usize,
+    pub(crate) coefficients: Coefficients<T>,
+    pub(crate) max_iter: u64,
+    pub(crate) max_fun_evals: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) direction: Direction,
+    pub(crate) nan_handling: NanHandling<T>,
+    pub(crate) bounds: Option<(DVector<T>, DVector<T>)>,
+}
+
+impl<T: RealField + Copy> Default for Subplex<T> {
+    fn default() -> Self {
+        Subplex {
+            step: StepSize::Uniform(convert(0.1)),
+            xtol: convert(1e-6),
+            psi: convert(0.25),
+            omega: convert(0.1),
+            min_subspace: 2,
+            max_subspace: 5,
+            coefficients: Coefficients::default(),
+            max_iter: 1000,
+            max_fun_evals: None,
+            max_duration: None,
+            direction: Direction::Minimize,
+            nan_handling: NanHandling::Error,
+            bounds: None,
+        }
+    }
+}
+
+/// Builder of a [`Subplex`] solver.
+///
+/// Defaults:
+///
+/// | Option                  | Default              |
+/// |-------------------------|----------------------|
+/// | `step`                  | 0.1                  |
+/// | `xtol`                  | 1e-6                 |
+/// | `psi`                   | 0.25                 |
+/// | `omega`                 | 0.1                  |
+/// | `subspace_sizes`        | 2 to 5               |
+/// | `coefficients`          | 1, 2, 0.5, 0.5       |
+/// | `max_iter`              | 1000                 |
+/// | `max_fun_evals`         | none                 |
+/// | `max_duration`          | none                 |
+/// | `direction`             | `Minimize`           |
+/// | `nan_handling`          | `Error`              |
+/// | `bounds`                | none                 |
+#[derive(Debug, Clone)]
+pub struct SubplexBuilder<T = f64> {
+    config: Subplex<T>,
+}
+
+impl<T: RealField + Copy> Default for SubplexBuilder<T> {
+    fn default() -> Self {
+        SubplexBuilder {
+            config: Subplex::default(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> SubplexBuilder<T> {
+    /// Initial step along every parameter, either a single value used for
+    /// every dimension or a [`StepSize`]. The steps of the later cycles
+    /// follow the progress of the run.
+    pub fn step(mut self, step: impl Into<StepSize<T>>) -> Self {
+        self.config.step = step.into();
+        self
+    }
+
+    /// Relative accuracy required on the parameters, absolute for those
+    /// below 1 in magnitude.
+    pub fn xtol(mut self, xtol: T) -> Self {
+        self.config.xtol = xtol;
+        self
+    }
+
+    /// Factor by which the simplex of a subspace shrinks before its search
+    /// stops, within `(0, 1)`. It also scales the steps after a cycle on a
+    /// single subspace.
+    pub fn psi(mut self, psi: T) -> Self {
+        self.config.psi = psi;
+        self
+    }
+
+    /// Bound on the rescaling of the steps between cycles, which stays
+    /// within `[omega, 1 / omega]`, with `omega` within `(0, 1)`.
+    pub fn omega(mut self, omega: T) -> Self {
+        self.config.omega = omega;
+        self
+    }
+
+    /// Smallest and largest number of parameters of a subspace, with
+    /// `1 <= min <= max`. Both are capped by the dimension of the problem,
+    /// and the last subspace of a cycle is smaller than `min` when the
+    /// parameters cannot be split otherwise.
+    pub fn subspace_sizes(mut self, min: usize, max: usize) -> Self {
+        self.config.min_subspace = min;
+        self.config.max_subspace = max;
+        self
+    }
+
+    /// Coefficients of the Nelder-Mead simplex searching the subspaces.
+    pub fn coefficients(mut self, coefficients: Coefficients<T>) -> Self {
+        self.config.coefficients = coefficients;
+        self
+    }
+
+    /// Maximum number of cycles over all the subspaces.
+    pub fn max_iter(mut self, max_iter: u64) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Stop once the objective function was evaluated this many times.
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.config.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Stop once the run took this long.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether to minimize or maximize the objective function.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// What to do when the objective function returns NaN.
+    pub fn nan_handling(mut self, nan_handling: NanHandling<T>) -> Self {
+        self.config.nan_handling = nan_handling;
+        self
+    }
+
+    /// Lower and upper bounds of every parameter. The starting point is
+    /// clipped into them, and the points of the subspace searches are
+    /// clipped like under [`BoundsHandling::Clip`].
+    ///
+    /// [`BoundsHandling::Clip`]: crate::nelder_mead::BoundsHandling::Clip
+    pub fn bounds(mut self, lower: DVector<T>, upper: DVector<T>) -> Self {
+        self.config.bounds = Some((lower, upper));
+        self
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> Subplex<T> {
+        self.config
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Subplex::minimize`].
+    pub fn minimize<F>(
+        self,
+        obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        self.build().minimize(obj_fn, x_start)
+    }
+
+    /// Finishes the configuration and immediately runs the solver, see
+    /// [`Subplex::minimize_objective`].
+    pub fn minimize_objective<O>(
+        self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.build().minimize_objective(objective, x_start)
+    }
+}
+
+impl<T: RealField + Copy> Subplex<T> {
+    /// Returns a builder initialised with the default parameters.
+    pub fn builder() -> SubplexBuilder<T> {
+        SubplexBuilder::default()
+    }
+
+    /// Finds a local minimum (or maximum, depending on the configured
+    /// [`Direction`]) of the objective function, starting from `x_start`.
+    ///
+    /// Fails if `x_start` is empty, if the subspace sizes or the factors
+    /// `psi` and `omega` are out of their ranges, if the step or the bounds
+    /// do not fit `x_start`, if the coefficients are invalid, or if the
+    /// objective function returns NaN under [`NanHandling::Error`].
+    pub fn minimize<F>(
+        &self,
+        mut obj_fn: F,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let dim = x_start.len();
+        if dim == 0 {
+            return Err(NelderMeadError::InvalidSimplex);
+        }
+        let factors = [self.psi, self.omega].iter().all(|f| T::zero() < *f && *f < T::one());
+        if !factors || self.min_subspace == 0 || self.min_subspace > self.max_subspace {
+            return Err(NelderMeadError::InvalidSubplex);
+        }
+        self.step.check_dim(dim)?;
+        self.coefficients.validate()?;
+        let mut x = x_start;
+        if let Some((lower, upper)) = &self.bounds {
+            if lower.len() != dim
+                || upper.len() != dim
+                || lower.iter().zip(upper.iter()).any(|(l, u)| l > u || is_nan(*l) || is_nan(*u))
+            {
+                return Err(NelderMeadError::InvalidBounds);
+            }
+            for i in 0..dim {
+                x[i] = x[i].max(lower[i]).min(upper[i]);
+            }
+        }
+
+        let start = Instant::now();
+        let sign = self.direction.sign();
+        let mut value = obj_fn(&x);
+        self.nan_handling.score(value, sign)?;
+        let mut evaluations = 1;
+        let mut steps = DVector::from_fn(dim, |i, _| self.step.offset(i, x[i]));
+        // the first cycle groups the parameters by their initial steps
+        let mut moves = steps.clone();
+        let mut final_simplex = vec![(x.clone(), value)];
+        let mut iterations = 0;
+
+        let termination_reason = loop {
+            if iterations >= self.max_iter {
+                break TerminationReason::MaxIter;
+            }
+            if self.max_fun_evals.is_some_and(|limit| evaluations >= limit) {
+                break TerminationReason::MaxFunEvals;
+            }
+            if self.max_duration.is_some_and(|limit| start.elapsed() >= limit) {
+                break TerminationReason::TimedOut;
+            }
+
+            let previous = x.clone();
+            let subspaces = self.subspaces(&moves);
+            let mut stopped = None;
+            for subspace in &subspaces {
+                let result = self.search(&mut obj_fn, &x, value, subspace, &steps, &mut evaluations, start)?;
+                final_simplex = result
+                    .final_simplex
+                    .into_iter()
+                    .map(|(z, score)| (lift(&x, subspace, &z), score))
+                    .collect();
+                for (k, &i) in subspace.iter().enumerate() {
+                    x[i] = result.best_point[k];
+                }
+                value = result.best_score;
+                if let reason @ (TerminationReason::MaxFunEvals | TerminationReason::TimedOut) =
+                    result.termination_reason
+                {
+                    stopped = Some(reason);
+                    break;
+                }
+            }
+            iterations += 1;
+            if let Some(reason) = stopped {
+                break reason;
+            }
+
+            moves = &x - &previous;
+            let scale = if subspaces.len() > 1 {
+                let ratio = moves.lp_norm(1) / steps.lp_norm(1);
+                ratio.max(self.omega).min(T::one() / self.omega)
+            } else {
+                self.psi
+            };
+            for i in 0..dim {
+                steps[i] = if moves[i].is_zero() {
+                    -steps[i] * scale
+                } else if moves[i] > T::zero() {
+                    steps[i].abs() * scale
+                } else {
+                    -steps[i].abs() * scale
+                };
+            }
+            let converged = (0..dim).all(|i| {
+                moves[i].abs().max(steps[i].abs() * self.psi) <= self.xtol * x[i].abs().max(T::one())
+            });
+            if converged {
+                break TerminationReason::Converged;
+            }
+        };
+
+        Ok(OptimizationResult::from_simplex(
+            final_simplex,
+            T::one(),
+            iterations,
+            evaluations,
+            0,
+            termination_reason,
+            None,
+        ))
+    }
+
+    /// Same as [`Subplex::minimize`], but optimizes a reusable
+    /// [`ObjectiveFunction`].
+    pub fn minimize_objective<O>(
+        &self,
+        objective: &O,
+        x_start: DVector<T>,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        O: ObjectiveFunction<T> + ?Sized,
+    {
+        self.minimize(|x: &DVector<T>| objective.evaluate(x), x_start)
+    }
+
+    /// Splits the parameters into subspaces, from those which moved the
+    /// most: each subspace takes the number of the remaining parameters
+    /// which best separates their moves from the moves of the rest.
+    fn subspaces(&self, moves: &DVector<T>) -> Vec<Vec<usize>> {
+        let dim = moves.len();
+        let (min, max) = (self.min_subspace.min(dim), self.max_subspace.min(dim));
+        let mut order: Vec<usize> = (0..dim).collect();
+        order.sort_by(|&i, &j| moves[j].abs().partial_cmp(&moves[i].abs()).unwrap_or(Ordering::Equal));
+
+        let mut subspaces = Vec::new();
+        let mut rest = &order[..];
+        while !rest.is_empty() {
+            let remaining = rest.len();
+            let total = rest.iter().fold(T::zero(), |sum, &i| sum + moves[i].abs());
+            let mut head = T::zero();
+            let mut best: Option<(usize, T)> = None;
+            for (k, &i) in rest.iter().enumerate().take(max) {
+                head += moves[i].abs();
+                let size = k + 1;
+                if size < min || (size < remaining && remaining - size < min) {
+                    continue;
+                }
+                let mut goodness = head / convert(size as f64);
+                if size < remaining {
+                    goodness -= (total - head) / convert((remaining - size) as f64);
+                }
+                if best.is_none_or(|(_, best)| goodness > best) {
+                    best = Some((size, goodness));
+                }
+            }
+            let size = best.map_or(max.min(remaining), |(size, _)| size);
+            subspaces.push(rest[..size].to_vec());
+            rest = &rest[size..];
+        }
+        subspaces
+    }
+
+    /// Runs Nelder-Mead along the coordinates of `subspace` from `x`, at
+    /// which the objective function is `value`, within the remaining
+    /// budget.
+    #[allow(clippy::too_many_arguments)]
+    fn search<F>(
+        &self,
+        obj_fn: &mut F,
+        x: &DVector<T>,
+        value: T,
+        subspace: &[usize],
+        steps: &DVector<T>,
+        evaluations: &mut u64,
+        start: Instant,
+    ) -> Result<OptimizationResult<T>, NelderMeadError>
+    where
+        F: FnMut(&DVector<T>) -> T,
+    {
+        let project = |v: &DVector<T>| DVector::from_fn(subspace.len(), |k, _| v[subspace[k]]);
+        let size = subspace.iter().fold(T::zero(), |max, &i| max.max(steps[i].abs()));
+        let z_start = project(x);
+        let inner = NelderMead {
+            step: StepSize::PerDim(project(steps)),
+            no_improv_break: None,
+            max_iter: u64::MAX,
+            coefficients: self.coefficients,
+            contraction: ContractionMode::InsideOutside,
+            termination: Some(SharedCriterion(Arc::new(SimplexSize(self.psi * size)))),
+            // the first vertex is the current point, whose value is known
+            max_fun_evals: self.max_fun_evals.map(|limit| limit.saturating_sub(*evaluations) + 1),
+            max_duration: self.max_duration.map(|limit| limit.saturating_sub(start.elapsed())),
+            direction: self.direction,
+            nan_handling: self.nan_handling,
+            bounds: self.bounds.as_ref().map(|(lower, upper)| (project(lower), project(upper))),
+            ..NelderMead::default()
+        };
+        let mut known = Some(z_start.clone());
+        let mut point = x.clone();
+        inner.minimize(
+            |z: &DVector<T>| {
+                if known.take().is_some_and(|known| &known == z) {
+                    return value;
+                }
+                for (k, &i) in subspace.iter().enumerate() {
+                    point[i] = z[k];
+                }
+                *evaluations += 1;
+                obj_fn(&point)
+            },
+            z_start,
+        )
+    }
+}
+
+/// Copy of `x` with the coordinates of `subspace` taken from `z`.
+fn lift<T: RealField + Copy>(x: &DVector<T>, subspace: &[usize], z: &DVector<T>) -> DVector<T> {
+    let mut point = x.clone();
+    for (k, &i) in subspace.iter().enumerate() {
+        point[i] = z[k];
+    }
+    point
+}
@@ -0,0 +1,261 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use nalgebra::{DVectorView, RealField};
+
+use crate::diagnostics::{self, SimplexHealth};
+use crate::observer::Move;
+use crate::progress::{Budget, Progress};
+use crate::simplex::Simplex;
+
+/// Snapshot of a run handed to a [`TerminationCriterion`] and to an
+/// [`Observer`] at the start of every iteration, once the simplex has been
+/// ordered.
+///
+/// [`Observer`]: crate::observer::Observer
+///
+/// Scores are reported in the direction of the objective, i.e. they are
+/// not negated when maximizing.
+#[derive(Debug, Clone, Copy)]
+pub struct TerminationState<'a, T = f64> {
+    pub(crate) iterations: u64,
+    pub(crate) evaluations: u64,
+    pub(crate) elapsed: Duration,
+    pub(crate) simplex: &'a Simplex<T>,
+    pub(crate) sign: T,
+    pub(crate) last_move: Option<Move>,
+    pub(crate) budget: Budget,
+}
+
+impl<'a, T: RealField + Copy> TerminationState<'a, T> {
+    /// Number of iterations completed so far.
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// Number of objective function evaluations so far.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Wall-clock time since the start of the run.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Operation performed by the previous iteration, `None` for the
+    /// initial simplex.
+    pub fn last_move(&self) -> Option<Move> {
+        self.last_move
+    }
+
+    /// Work done so far compared with the configured budgets, see
+    /// [`Progress`].
+    pub fn progress(&self) -> Progress {
+        Progress::new(self.budget, self.iterations, self.evaluations, self.elapsed)
+    }
+
+    /// Best vertex of the simplex, a view into the matrix holding the
+    /// vertices.
+    pub fn best_point(&self) -> DVectorView<'a, T> {
+        self.simplex.column(0)
+    }
+
+    /// Score of the best vertex.
+    pub fn best_score(&self) -> T {
+        self.sign * self.simplex.score(0)
+    }
+
+    /// Vertices of the simplex with their scores, from the best one.
+    pub fn vertices(&self) -> impl Iterator<Item = (DVectorView<'a, T>, T)> + '_ {
+        let simplex = self.simplex;
+        (0..simplex.len()).map(move |i| (simplex.column(i), self.sign * simplex.score(i)))
+    }
+
+    /// Largest distance of any vertex coordinate from the best vertex.
+    pub fn simplex_size(&self) -> T {
+        let best = self.simplex.vertex(0);
+        self.simplex
+            .iter()
+            .skip(1)
+            .map(|(x, _)| x.iter().zip(best).fold(T::zero(), |max, (xi, bi)| max.max((*xi - *bi).abs())))
+            .fold(T::zero(), |a, b| a.max(b))
+    }
+
+    /// Volume of the simplex, `|det(x_1 - x_0, ..., x_n - x_0)| / n!`.
+    /// It drops to zero when the simplex degenerates into a subspace.
+    pub fn simplex_volume(&self) -> T {
+        diagnostics::volume(&diagnostics::edge_matrix(self.simplex))
+    }
+
+    /// Volume, edge-length ratio and condition number of the simplex, see
+    /// [`SimplexHealth`].
+    pub fn health(&self) -> SimplexHealth<T> {
+        SimplexHealth::new(self.simplex)
+    }
+
+    /// Largest difference of any vertex score from the best score.
+    pub fn score_spread(&self) -> T {
+        let best = self.simplex.score(0);
+        self.simplex.scores()[1..]
+            .iter()
+            .map(|score| (*score - best).abs())
+            .fold(T::zero(), |a, b| a.max(b))
+    }
+}
+
+/// Rule deciding when an optimization run should stop.
+///
+/// It is implemented for the built-in criteria of this module and for
+/// every closure taking a [`TerminationState`] and returning `true` to
+/// stop. Criteria are combined with [`TerminationCriterion::or`] and
+/// [`TerminationCriterion::and`]. When the configured criterion is met the
+/// run ends with [`TerminationReason::Criterion`].
+///
+/// [`TerminationReason::Criterion`]: crate::result::TerminationReason::Criterion
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::result::TerminationReason;
+/// use nelder_mead_optimizer::termination::*;
+///
+/// fn f(x: &DVector<f64>) -> f64 {
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// }
+/// let criterion = SimplexSize(1e-6)
+///     .and(ScoreSpread(1e-10))
+///     .or(MaxEvaluations(500))
+///     .or(|state: &TerminationState| state.best_score() < 1e-12);
+/// let results = NelderMead::builder()
+///     .disable_no_improv_break()
+///     .termination(criterion)
+///     .minimize(&f, dvector![0.0, 0.0])?;
+///
+/// assert_eq!(results.termination_reason, TerminationReason::Criterion);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+pub trait TerminationCriterion<T = f64> {
+    /// Returns `true` when the run should stop.
+    fn should_stop(&self, state: &TerminationState<T>) -> bool;
+
+    /// Combines two criteria, stopping when either of them is met.
+    fn or<C>(self, other: C) -> Any<T>
+    where
+        Self: Sized + Send + Sync + 'static,
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        Any(vec![Box::new(self), Box::new(other)])
+    }
+
+    /// Combines two criteria, stopping when both of them are met.
+    fn and<C>(self, other: C) -> All<T>
+    where
+        Self: Sized + Send + Sync + 'static,
+        C: TerminationCriterion<T> + Send + Sync + 'static,
+    {
+        All(vec![Box::new(self), Box::new(other)])
+    }
+}
+
+impl<T, F> TerminationCriterion<T> for F
+where
+    F: Fn(&TerminationState<T>) -> bool,
+{
+    fn should_stop(&self, state: &TerminationState<T>) -> bool {
+        self(state)
+    }
+}
+
+/// Boxed criterion, as stored by the combinators.
+pub type BoxedCriterion<T = f64> = Box<dyn TerminationCriterion<T> + Send + Sync>;
+
+/// Stops when any of the criteria is met.
+pub struct Any<T = f64>(pub Vec<BoxedCriterion<T>>);
+
+impl<T> TerminationCriterion<T> for Any<T> {
+    fn should_stop(&self, state: &TerminationState<T>) -> bool {
+        self.0.iter().any(|c| c.should_stop(state))
+    }
+}
+
+/// Stops when all of the criteria are met.
+pub struct All<T = f64>(pub Vec<BoxedCriterion<T>>);
+
+impl<T> TerminationCriterion<T> for All<T> {
+    fn should_stop(&self, state: &TerminationState<T>) -> bool {
+        self.0.iter().all(|c| c.should_stop(state))
+    }
+}
+
+/// Stops after the given number of iterations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaxIterations(pub u64);
+
+impl<T> TerminationCriterion<T> for MaxIterations {
+    fn should_stop(&self, state: &TerminationState<T>) -> bool {
+        state.iterations >= self.0
+    }
+}
+
+/// Stops once the objective function was evaluated the given number of
+/// times. Unlike `max_fun_evals` it is only checked between iterations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaxEvaluations(pub u64);
+
+impl<T> TerminationCriterion<T> for MaxEvaluations {
+    fn should_stop(&self, state: &TerminationState<T>) -> bool {
+        state.evaluations >= self.0
+    }
+}
+
+/// Stops once the given wall-clock time has elapsed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaxDuration(pub Duration);
+
+impl<T> TerminationCriterion<T> for MaxDuration {
+    fn should_stop(&self, state: &TerminationState<T>) -> bool {
+        state.elapsed >= self.0
+    }
+}
+
+/// Stops once every vertex coordinate is within the given distance of
+/// the best vertex, see [`TerminationState::simplex_size`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimplexSize<T = f64>(pub T);
+
+impl<T: RealField + Copy> TerminationCriterion<T> for SimplexSize<T> {
+    fn should_stop(&self, state: &TerminationState<T>) -> bool {
+        state.simplex_size() <= self.0
+    }
+}
+
+/// Stops once every vertex score is within the given distance of the
+/// best score, see [`TerminationState::score_spread`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreSpread<T = f64>(pub T);
+
+impl<T: RealField + Copy> TerminationCriterion<T> for ScoreSpread<T> {
+    fn should_stop(&self, state: &TerminationState<T>) -> bool {
+        state.score_spread() <= self.0
+    }
+}
+
+/// Criterion stored in the optimizer configuration.
+#[derive(Clone)]
+pub(crate) struct SharedCriterion<T>(pub(crate) Arc<dyn TerminationCriterion<T> + Send + Sync>);
+
+impl<T> fmt::Debug for SharedCriterion<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TerminationCriterion")
+    }
+}
+
+impl<T> PartialEq for SharedCriterion<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
@@ -0,0 +1,224 @@
+use nalgebra::{convert, DVector, RealField};
+
+/// Standard problem of unconstrained optimization with a known global
+/// minimum, to check a configuration on or to benchmark it with.
+///
+/// Every function has a minimum value of zero. [`TestFunction::Himmelblau`]
+/// and [`TestFunction::Beale`] take two parameters, the other ones any
+/// number of them.
+///
+/// # Examples
+///
+/// ```
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::test_functions::TestFunction;
+///
+/// let optimizer = NelderMead::builder()
+///     .xatol(1e-8)
+///     .fatol(1e-10)
+///     .disable_no_improv_break()
+///     .build();
+/// for function in TestFunction::ALL {
+///     let dim = function.dimension().unwrap_or(2);
+///     let results = optimizer.minimize(|x| function.evaluate(x), function.start(dim))?;
+///
+///     assert!(results.best_score - function.minimum::<f64>() < 1e-8, "{}", function.name());
+///     assert!(function.distance_to_minimizer(&results.best_point) < 1e-6);
+/// }
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TestFunction {
+    /// Sum of the squared parameters, see [`sphere`].
+    Sphere,
+    /// Curved valley of [`rosenbrock`].
+    Rosenbrock,
+    /// Bowl covered with local minima at integer points, see [`rastrigin`].
+    Rastrigin,
+    /// Nearly flat plateau around a narrow hole, see [`ackley`].
+    Ackley,
+    /// Four global minima of [`himmelblau`], with two parameters.
+    Himmelblau,
+    /// Flat valleys of [`beale`], with two parameters.
+    Beale,
+}
+
+impl TestFunction {
+    /// Every test function, in the order of their declaration.
+    pub const ALL: [TestFunction; 6] = [
+        TestFunction::Sphere,
+        TestFunction::Rosenbrock,
+        TestFunction::Rastrigin,
+        TestFunction::Ackley,
+        TestFunction::Himmelblau,
+        TestFunction::Beale,
+    ];
+
+    /// Name of the function, e.g. to label a benchmark.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TestFunction::Sphere => "sphere",
+            TestFunction::Rosenbrock => "rosenbrock",
+            TestFunction::Rastrigin => "rastrigin",
+            TestFunction::Ackley => "ackley",
+            TestFunction::Himmelblau => "himmelblau",
+            TestFunction::Beale => "beale",
+        }
+    }
+
+    /// Number of parameters the function takes, `None` if it takes any
+    /// number of them.
+    pub fn dimension(&self) -> Option<usize> {
+        match self {
+            TestFunction::Himmelblau | TestFunction::Beale => Some(2),
+            _ => None,
+        }
+    }
+
+    /// Value of the function at `x`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the function takes a fixed number of parameters and `x`
+    /// has another length.
+    pub fn evaluate<T: RealField + Copy>(&self, x: &DVector<T>) -> T {
+        match self {
+            TestFunction::Sphere => sphere(x),
+            TestFunction::Rosenbrock => rosenbrock(x),
+            TestFunction::Rastrigin => rastrigin(x),
+            TestFunction::Ackley => ackley(x),
+            TestFunction::Himmelblau => himmelblau(x),
+            TestFunction::Beale => beale(x),
+        }
+    }
+
+    /// Global minimum value of the function.
+    pub fn minimum<T: RealField + Copy>(&self) -> T {
+        T::zero()
+    }
+
+    /// Points of `dim` parameters at which the function reaches its global
+    /// minimum. The minimizers of [`TestFunction::Himmelblau`] other than
+    /// `(3, 2)` are only known numerically, to about 15 digits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the function takes a fixed number of parameters other
+    /// than `dim`.
+    pub fn minimizers<T: RealField + Copy>(&self, dim: usize) -> Vec<DVector<T>> {
+        self.check_dim(dim);
+        let point = |x: &[f64]| DVector::from_iterator(x.len(), x.iter().map(|xi| convert(*xi)));
+        match self {
+            TestFunction::Sphere | TestFunction::Rastrigin | TestFunction::Ackley => {
+                vec![DVector::zeros(dim)]
+            }
+            TestFunction::Rosenbrock => vec![DVector::from_element(dim, T::one())],
+            TestFunction::Himmelblau => vec![
+                point(&[3.0, 2.0]),
+                point(&[-2.805118086952745, 3.131312518250573]),
+                point(&[-3.779310253377747, -3.28318599128617]),
+                point(&[3.584428340330492, -1.848126526964404]),
+            ],
+            TestFunction::Beale => vec![point(&[3.0, 0.5])],
+        }
+    }
+
+    /// Euclidean distance from `x` to the closest global minimizer.
+    pub fn distance_to_minimizer<T: RealField + Copy>(&self, x: &DVector<T>) -> T {
+        self.minimizers(x.len())
+            .iter()
+            .map(|minimizer| (x - minimizer).norm())
+            .fold(T::max_value().unwrap_or_else(T::one), |a, b| a.min(b))
+    }
+
+    /// Customary starting point of `dim` parameters, away from the global
+    /// minimizers: `(-1.2, 1, -1.2, 1, ...)` for [`TestFunction::Rosenbrock`],
+    /// `(1, 1)` for [`TestFunction::Beale`] and the origin for
+    /// [`TestFunction::Himmelblau`]. The starting points of the functions
+    /// with local minima lie in the basin of the global one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the function takes a fixed number of parameters other
+    /// than `dim`.
+    pub fn start<T: RealField + Copy>(&self, dim: usize) -> DVector<T> {
+        self.check_dim(dim);
+        match self {
+            TestFunction::Sphere => DVector::from_element(dim, T::one()),
+            TestFunction::Rosenbrock => DVector::from_fn(dim, |i, _| {
+                if i % 2 == 0 { convert(-1.2) } else { T::one() }
+            }),
+            TestFunction::Rastrigin | TestFunction::Ackley => DVector::from_element(dim, convert(0.3)),
+            TestFunction::Himmelblau => DVector::zeros(dim),
+            TestFunction::Beale => DVector::from_element(dim, T::one()),
+        }
+    }
+
+    fn check_dim(&self, dim: usize) {
+        if let Some(expected) = self.dimension() {
+            assert_eq!(dim, expected, "{} takes {} parameters", self.name(), expected);
+        }
+    }
+}
+
+/// Sum of the squared parameters, `0` at the origin.
+pub fn sphere<T: RealField + Copy>(x: &DVector<T>) -> T {
+    x.iter().fold(T::zero(), |sum, xi| sum + *xi * *xi)
+}
+
+/// `sum (1 - x_i)^2 + 100 (x_{i+1} - x_i^2)^2`, `0` at `(1, ..., 1)`. The
+/// minimizer lies at the end of a long curved valley, hard to follow for
+/// methods that do not adapt the shape of their steps.
+pub fn rosenbrock<T: RealField + Copy>(x: &DVector<T>) -> T {
+    let hundred: T = convert(100.0);
+    x.as_slice().windows(2).fold(T::zero(), |sum, pair| {
+        let (a, b) = (pair[0], pair[1]);
+        sum + (T::one() - a).powi(2) + hundred * (b - a * a).powi(2)
+    })
+}
+
+/// `10 n + sum x_i^2 - 10 cos(2 pi x_i)`, `0` at the origin, with a local
+/// minimum near every point of integer coordinates.
+pub fn rastrigin<T: RealField + Copy>(x: &DVector<T>) -> T {
+    let ten: T = convert(10.0);
+    x.iter().fold(ten * convert(x.len() as f64), |sum, xi| {
+        sum + *xi * *xi - ten * (T::two_pi() * *xi).cos()
+    })
+}
+
+/// `-20 exp(-0.2 sqrt(mean x_i^2)) - exp(mean cos(2 pi x_i)) + 20 + e`, `0`
+/// at the origin, with a nearly flat outer region and many local minima.
+pub fn ackley<T: RealField + Copy>(x: &DVector<T>) -> T {
+    let n: T = convert(x.len() as f64);
+    let twenty: T = convert(20.0);
+    let squares = sphere(x) / n;
+    let cosines = x.iter().fold(T::zero(), |sum, xi| sum + (T::two_pi() * *xi).cos()) / n;
+    -twenty * (convert::<f64, T>(-0.2) * squares.sqrt()).exp() - cosines.exp() + twenty + T::e()
+}
+
+/// `(x^2 + y - 11)^2 + (x + y^2 - 7)^2`, `0` at four points, see
+/// [`TestFunction::minimizers`].
+///
+/// # Panics
+///
+/// Panics if `x` does not have two parameters.
+pub fn himmelblau<T: RealField + Copy>(x: &DVector<T>) -> T {
+    assert_eq!(x.len(), 2, "himmelblau takes 2 parameters");
+    let (a, b) = (x[0], x[1]);
+    (a * a + b - convert(11.0)).powi(2) + (a + b * b - convert(7.0)).powi(2)
+}
+
+/// `(1.5 - x + x y)^2 + (2.25 - x + x y^2)^2 + (2.625 - x + x y^3)^2`, `0`
+/// at `(3, 0.5)`.
+///
+/// # Panics
+///
+/// Panics if `x` does not have two parameters.
+pub fn beale<T: RealField + Copy>(x: &DVector<T>) -> T {
+    assert_eq!(x.len(), 2, "beale takes 2 parameters");
+    let (a, b) = (x[0], x[1]);
+    (convert::<f64, T>(1.5) - a + a * b).powi(2)
+        + (convert::<f64, T>(2.25) - a + a * b * b).powi(2)
+        + (convert::<f64, T>(2.625) - a + a * b * b * b).powi(2)
+}
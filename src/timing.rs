@@ -0,0 +1,181 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+/// Count, total, shortest and longest of a series of durations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DurationStats {
+    /// Number of recorded durations.
+    pub count: u64,
+    /// Sum of the recorded durations.
+    pub total: Duration,
+    /// Shortest recorded duration, zero if none was recorded.
+    pub min: Duration,
+    /// Longest recorded duration.
+    pub max: Duration,
+}
+
+impl DurationStats {
+    /// Mean of the recorded durations, zero if none was recorded.
+    pub fn mean(&self) -> Duration {
+        match u32::try_from(self.count) {
+            Ok(0) => Duration::ZERO,
+            Ok(count) => self.total / count,
+            Err(_) => Duration::from_secs_f64(self.total.as_secs_f64() / self.count as f64),
+        }
+    }
+
+    pub(crate) fn record(&mut self, duration: Duration) {
+        self.min = if self.count == 0 { duration } else { self.min.min(duration) };
+        self.max = self.max.max(duration);
+        self.total += duration;
+        self.count += 1;
+    }
+
+    /// Adds the durations recorded in `other`.
+    pub(crate) fn merge(&mut self, other: &DurationStats) {
+        if other.count == 0 {
+            return;
+        }
+        self.min = if self.count == 0 { other.min } else { self.min.min(other.min) };
+        self.max = self.max.max(other.max);
+        self.total += other.total;
+        self.count += other.count;
+    }
+}
+
+/// Time spent in the objective function and in the optimizer itself, as
+/// recorded with [`NelderMeadBuilder::record_timing`].
+///
+/// A run whose `bookkeeping` is small next to its `evaluations` is bound
+/// by the objective, and benefits from evaluating points in parallel, see
+/// `NelderMead::minimize_parallel` and
+/// [`NelderMeadBuilder::parallel_points`]. A run dominated by bookkeeping
+/// has a cheap objective, for which the options checking the simplex
+/// every iteration, such as `restart_on_degeneracy`, are the cost to cut.
+///
+/// [`NelderMeadBuilder::record_timing`]: crate::builder::NelderMeadBuilder::record_timing
+/// [`NelderMeadBuilder::parallel_points`]: crate::builder::NelderMeadBuilder::parallel_points
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+///
+/// fn slow(x: &DVector<f64>) -> f64 {
+///     std::thread::sleep(Duration::from_millis(1));
+///     (x[0] - 1.0).powi(2) + (x[1] + 1.0).powi(2)
+/// }
+/// let results = NelderMead::builder()
+///     .record_timing(true)
+///     .minimize(&slow, dvector![0.0, 0.0])?;
+/// let timing = results.timing.unwrap();
+///
+/// assert_eq!(timing.evaluations.count, results.evaluations);
+/// assert_eq!(timing.iterations.count, results.iterations);
+/// assert!(timing.evaluations.min >= Duration::from_millis(1));
+/// assert!(timing.objective_share() > 0.5);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimingStats {
+    /// Duration of every evaluation of the objective function. The
+    /// batches of [`NelderMead::minimize_batch`] and
+    /// `NelderMead::minimize_parallel` are timed as a whole, one
+    /// duration per batch.
+    ///
+    /// [`NelderMead::minimize_batch`]: crate::nelder_mead::NelderMead::minimize_batch
+    pub evaluations: DurationStats,
+    /// Duration of every iteration.
+    pub iterations: DurationStats,
+    /// Time every iteration spent outside of the objective function,
+    /// updating the simplex and checking the stopping rules, including
+    /// the observer, the callback and the termination criterion.
+    pub bookkeeping: DurationStats,
+}
+
+impl TimingStats {
+    /// Share of the time spent in the objective function, out of the time
+    /// spent in it and in the bookkeeping of the iterations, zero if
+    /// nothing was recorded.
+    pub fn objective_share(&self) -> f64 {
+        let objective = self.evaluations.total.as_secs_f64();
+        let total = objective + self.bookkeeping.total.as_secs_f64();
+        if total > 0.0 { objective / total } else { 0.0 }
+    }
+
+    /// Adds the durations recorded in `other`, e.g. by an earlier round
+    /// of the same run.
+    pub(crate) fn merge(&mut self, other: &TimingStats) {
+        self.evaluations.merge(&other.evaluations);
+        self.iterations.merge(&other.iterations);
+        self.bookkeeping.merge(&other.bookkeeping);
+    }
+}
+
+/// Records the durations of a run as it goes. It only needs a shared
+/// reference, so that the objective function wrapped in a closure and the
+/// loop of the iterations can both hold it.
+#[derive(Debug)]
+pub(crate) struct Timer {
+    stats: Cell<TimingStats>,
+    // start of the current iteration
+    iteration: Cell<Instant>,
+    // time spent in the objective function during the current iteration
+    evaluating: Cell<Duration>,
+}
+
+impl Timer {
+    pub(crate) fn new() -> Self {
+        Timer {
+            stats: Cell::new(TimingStats::default()),
+            iteration: Cell::new(Instant::now()),
+            evaluating: Cell::new(Duration::ZERO),
+        }
+    }
+
+    /// Calls the objective function through `evaluate`, recording how
+    /// long it takes.
+    pub(crate) fn time<R>(&self, evaluate: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let value = evaluate();
+        let elapsed = start.elapsed();
+        let mut stats = self.stats.get();
+        stats.evaluations.record(elapsed);
+        self.stats.set(stats);
+        self.evaluating.set(self.evaluating.get() + elapsed);
+        value
+    }
+
+    /// Starts an iteration, discarding the time spent since the previous
+    /// one ended, e.g. in the caller of a step.
+    pub(crate) fn start_iteration(&self) {
+        self.iteration.set(Instant::now());
+        self.evaluating.set(Duration::ZERO);
+    }
+
+    /// Records the iteration started last.
+    pub(crate) fn end_iteration(&self) {
+        let elapsed = self.iteration.get().elapsed();
+        let mut stats = self.stats.get();
+        stats.iterations.record(elapsed);
+        stats.bookkeeping.record(elapsed.saturating_sub(self.evaluating.get()));
+        self.stats.set(stats);
+    }
+
+    pub(crate) fn stats(&self) -> TimingStats {
+        self.stats.get()
+    }
+}
+
+/// Calls the objective function through `evaluate`, timed by `timer` if
+/// there is one.
+pub(crate) fn time<R>(timer: Option<&Timer>, evaluate: impl FnOnce() -> R) -> R {
+    match timer {
+        Some(timer) => timer.time(evaluate),
+        None => evaluate(),
+    }
+}
@@ -0,0 +1,219 @@
+use std::fmt;
+use std::sync::Arc;
+
+use nalgebra::{convert, RealField};
+
+/// Reparameterization of a single parameter, set with
+/// [`NelderMeadBuilder::transform`].
+///
+/// The simplex moves in the internal coordinate of the parameter while the
+/// objective function receives, and the result reports, the parameter
+/// itself. Bounds set on a transformed parameter apply to the parameter
+/// and must lie within the domain of the transform. Other
+/// reparameterizations can be plugged in with a [`ParameterTransform`].
+///
+/// [`NelderMeadBuilder::transform`]: crate::builder::NelderMeadBuilder::transform
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::transform::Transform;
+///
+/// // undefined for a non-positive variance, minimal at (0.5, 1)
+/// fn f(x: &DVector<f64>) -> f64 {
+///     x[0] - 0.5 * x[0].ln() + (x[1] - 1.0).powi(2)
+/// }
+/// let results = NelderMead::builder()
+///     .transform(0, Transform::Log)
+///     .xatol(1e-8)
+///     .fatol(1e-12)
+///     .minimize(&f, dvector![0.001, 0.0])?;
+///
+/// assert!((results.best_point[0] - 0.5).abs() < 1e-4);
+/// assert!((results.best_point[1] - 1.0).abs() < 1e-4);
+/// assert!(results.final_simplex.iter().all(|(x, _)| x[0] > 0.0));
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Transform {
+    /// Optimize the parameter itself.
+    #[default]
+    Identity,
+    /// Optimize `ln(x)`, for strictly positive parameters such as
+    /// variances or rates.
+    Log,
+}
+
+impl<T: RealField + Copy> ParameterTransform<T> for Transform {
+    fn forward(&self, y: T) -> T {
+        match self {
+            Transform::Identity => y,
+            Transform::Log => y.exp(),
+        }
+    }
+
+    fn inverse(&self, x: T) -> T {
+        match self {
+            Transform::Identity => x,
+            Transform::Log => x.ln(),
+        }
+    }
+
+    fn contains(&self, x: T) -> bool {
+        match self {
+            Transform::Identity => true,
+            Transform::Log => x > T::zero(),
+        }
+    }
+}
+
+/// Bijection between the internal coordinate of a parameter, moved by the
+/// simplex, and the parameter itself, set with
+/// [`NelderMeadBuilder::parameter_transform`].
+///
+/// It must be strictly monotone and continuous, so that bounds on the
+/// parameter map to bounds on its internal coordinate. It is implemented
+/// for every pair of closures `(forward, inverse)` and can be implemented
+/// on custom types, e.g. to restrict the domain of the parameter.
+///
+/// [`NelderMeadBuilder::parameter_transform`]: crate::builder::NelderMeadBuilder::parameter_transform
+///
+/// # Examples
+///
+/// ```
+/// use nalgebra::{DVector, dvector};
+/// use nelder_mead_optimizer::nelder_mead::NelderMead;
+/// use nelder_mead_optimizer::transform::ParameterTransform;
+///
+/// struct Softplus;
+///
+/// impl ParameterTransform for Softplus {
+///     fn forward(&self, y: f64) -> f64 {
+///         y.exp().ln_1p()
+///     }
+///
+///     fn inverse(&self, x: f64) -> f64 {
+///         x.exp_m1().ln()
+///     }
+///
+///     fn contains(&self, x: f64) -> bool {
+///         x > 0.0
+///     }
+/// }
+///
+/// // a variance and a probability, minimal at (0.5, 0.3)
+/// fn f(x: &DVector<f64>) -> f64 {
+///     x[0] - 0.5 * x[0].ln() - 3.0 * x[1].ln() - 7.0 * (1.0 - x[1]).ln()
+/// }
+/// let logit = (|y: f64| 1.0 / (1.0 + (-y).exp()), |x: f64| (x / (1.0 - x)).ln());
+/// let results = NelderMead::builder()
+///     .parameter_transform(0, Softplus)
+///     .parameter_transform(1, logit)
+///     .xatol(1e-8)
+///     .fatol(1e-12)
+///     .minimize(&f, dvector![1.0, 0.5])?;
+///
+/// assert!((results.best_point[0] - 0.5).abs() < 1e-4);
+/// assert!((results.best_point[1] - 0.3).abs() < 1e-4);
+/// # Ok::<(), nelder_mead_optimizer::error::NelderMeadError>(())
+/// ```
+pub trait ParameterTransform<T = f64> {
+    /// Parameter of the internal coordinate `y`.
+    fn forward(&self, y: T) -> T;
+
+    /// Internal coordinate of the parameter `x`.
+    fn inverse(&self, x: T) -> T;
+
+    /// Whether the parameter `x` lies in the domain of the transform,
+    /// i.e. is the image of an internal coordinate.
+    fn contains(&self, x: T) -> bool {
+        true
+    }
+}
+
+impl<T, F, G> ParameterTransform<T> for (F, G)
+where
+    F: Fn(T) -> T,
+    G: Fn(T) -> T,
+{
+    fn forward(&self, y: T) -> T {
+        (self.0)(y)
+    }
+
+    fn inverse(&self, x: T) -> T {
+        (self.1)(x)
+    }
+}
+
+/// Wraps `x` within the first period `[0, period)`.
+pub(crate) fn wrap<T: RealField + Copy>(x: T, period: T) -> T {
+    let wrapped = x - (x / period).floor() * period;
+    // rounding may land on the period itself
+    if wrapped >= period {
+        T::zero()
+    } else {
+        wrapped
+    }
+}
+
+/// Maps the internal coordinates at `indices` of `x` to weights summing
+/// to one with a softmax, in place.
+pub(crate) fn softmax<T: RealField + Copy>(x: &mut [T], indices: &[usize]) {
+    let max = match indices.iter().map(|&i| x[i]).reduce(|a, b| a.max(b)) {
+        Some(max) => max,
+        None => return,
+    };
+    let mut sum = T::zero();
+    for &i in indices {
+        // shifting by the largest coordinate keeps the exponentials finite
+        x[i] = (x[i] - max).exp();
+        sum += x[i];
+    }
+    for &i in indices {
+        x[i] /= sum;
+    }
+}
+
+/// Maps the weights at `indices` of `x` to internal coordinates whose
+/// softmax they are, in place. Weights at zero are moved slightly inside,
+/// as a softmax never reaches them.
+pub(crate) fn log_weights<T: RealField + Copy>(x: &mut [T], indices: &[usize]) {
+    let margin = T::default_epsilon().sqrt();
+    for &i in indices {
+        x[i] = x[i].max(margin).ln();
+    }
+}
+
+/// Brings the weights at `indices` of `x` onto the unit simplex: negative
+/// weights are set to zero and the others normalized to sum to one,
+/// equal weights are used when none is positive.
+pub(crate) fn normalize_weights<T: RealField + Copy>(x: &mut [T], indices: &[usize]) {
+    let mut sum = T::zero();
+    for &i in indices {
+        x[i] = x[i].max(T::zero());
+        sum += x[i];
+    }
+    let equal = T::one() / convert(indices.len() as f64);
+    for &i in indices {
+        x[i] = if sum > T::zero() && sum.is_finite() { x[i] / sum } else { equal };
+    }
+}
+
+/// Parameter transform stored in the optimizer configuration.
+#[derive(Clone)]
+pub(crate) struct SharedTransform<T>(pub(crate) Arc<dyn ParameterTransform<T> + Send + Sync>);
+
+impl<T> fmt::Debug for SharedTransform<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ParameterTransform")
+    }
+}
+
+impl<T> PartialEq for SharedTransform<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}